@@ -1,10 +1,438 @@
+use cosmwasm_std::Uint128;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::ContractError;
+use crate::utils::calculate_blackjack_total;
+
+// ─────────────────────────────────────────────────────────────
+// 21 点（Blackjack）数据类型
+//
+// 规则：
+//   - 使用 1-8 副牌组成的牌靴（按庄规 decks），开局时用本局种子洗好
+//   - 玩家、庄家各发 2 张，庄家第 1 张为暗牌、第 2 张为明牌
+//   - 点数：A=1 或 11，2-10=面值，J/Q/K=10
+//   - 天然 21 点（前两张为 A + 10 点牌）按庄规赔 3:2（2.5× 返还）或 6:5（2.2× 返还）
+//   - 庄家看暗牌（peek）：明牌为 10 点牌时开局即看，明牌为 A 时先给玩家买保险的机会，
+//     开局后的第一个操作时再看。庄家天然 21 点立即结算，玩家同为天然 21 点时平局退还本金，
+//     否则输掉下注（保险按 2:1 赔付）
+//   - 普通赢 2× 返还，平局退还本金
+//   - 庄家只按自己的牌补牌：小于 17 点要牌，软 17 点按庄规要牌或停牌，不看玩家的牌
+//
+// 玩家操作（作用于当前这手牌，Stand 后轮到下一手，全部结束后庄家补牌比牌）：
+//   - Hit：要一张牌，21 点及以上不能再要
+//   - DoubleDown：前两张牌时附带与该手相同的下注加倍，只再发一张牌并结束该手（分牌后能否加倍按庄规）
+//   - Split：前两张点数相同时附带与该手相同的下注分成两手，各补一张牌；最多分到 4 手，
+//     A 分牌后各只补一张牌并直接结束，不能再分；分牌得到的 A + 10 点牌按普通 21 点计
+//   - Insurance：庄家明牌为 A 且尚未看暗牌时，附带开局下注的一半买保险，庄家天然 21 点赔 2:1
+//   - Surrender：庄规允许时，第一个决定时投降（看暗牌之后的 late surrender），退还一半下注
+// ─────────────────────────────────────────────────────────────
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BlackjackAction {
     Start,
     Hit,
     Stand,
+    DoubleDown,
+    Split,
+    Insurance,
+    Surrender,
+}
+
+impl BlackjackAction {
+    /// 事件属性中的操作名
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlackjackAction::Start => "play_blackjack_start",
+            BlackjackAction::Hit => "blackjack_hit",
+            BlackjackAction::Stand => "blackjack_stand",
+            BlackjackAction::DoubleDown => "blackjack_double_down",
+            BlackjackAction::Split => "blackjack_split",
+            BlackjackAction::Insurance => "blackjack_insurance",
+            BlackjackAction::Surrender => "blackjack_surrender",
+        }
+    }
+}
+
+/// 牌靴默认副数
+pub const DEFAULT_SHOE_DECKS: u8 = 6;
+
+/// 牌靴最多副数
+pub const MAX_SHOE_DECKS: u8 = 8;
+
+/// 开局时天然 21 点最高 2.5× 返还，预留赔付按整数倍向上取 3×（加倍 / 分牌 / 保险后再补足）
+pub const MAX_PAYOUT_MULTIPLIER: u128 = 3;
+
+/// 分牌后最多的手数
+pub const MAX_HANDS: usize = 4;
+
+/// 天然 21 点的赔率
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlackjackPayout {
+    /// 3:2，2.5× 返还
+    #[default]
+    ThreeToTwo,
+    /// 6:5，2.2× 返还
+    SixToFive,
+}
+
+impl BlackjackPayout {
+    /// 天然 21 点的返还（含本金）
+    pub fn natural_payout(&self, bet: u128) -> u128 {
+        match self {
+            BlackjackPayout::ThreeToTwo => bet + bet * 3 / 2,
+            BlackjackPayout::SixToFive => bet + bet * 6 / 5,
+        }
+    }
+}
+
+/// 21 点庄规：每个部署一套，开局时随牌局保存，进行中的牌局不受之后的修改影响
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackRules {
+    /// 庄家软 17 点是否要牌（H17），否则停牌（S17）
+    pub dealer_hits_soft_17: bool,
+    /// 牌靴副数（1-8）
+    pub decks: u8,
+    /// 分牌后能否加倍
+    pub double_after_split: bool,
+    /// 是否允许投降
+    pub surrender_allowed: bool,
+    /// 天然 21 点的赔率
+    pub blackjack_payout: BlackjackPayout,
+}
+
+impl Default for BlackjackRules {
+    fn default() -> Self {
+        BlackjackRules {
+            dealer_hits_soft_17: false,
+            decks: DEFAULT_SHOE_DECKS,
+            double_after_split: true,
+            surrender_allowed: true,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+        }
+    }
+}
+
+impl BlackjackRules {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if !(1..=MAX_SHOE_DECKS).contains(&self.decks) {
+            return Err(ContractError::InvalidShoeDecks { max: MAX_SHOE_DECKS });
+        }
+        Ok(())
+    }
+
+    /// 庄家是否继续要牌：小于 17 点要牌，软 17 点按庄规
+    pub fn dealer_hits(&self, dealer_cards: &[Card]) -> bool {
+        let total = hand_total(dealer_cards);
+        total < 17 || (total == 17 && self.dealer_hits_soft_17 && is_soft(dealer_cards))
+    }
+}
+
+/// 花色
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Copy)]
+pub enum Suit {
+    Spades,   // 黑桃 ♠
+    Hearts,   // 红心 ♥
+    Diamonds, // 方块 ♦
+    Clubs,    // 梅花 ♣
+}
+
+impl Suit {
+    pub fn from_u8(v: u8) -> Self {
+        match v % 4 {
+            0 => Suit::Spades,
+            1 => Suit::Hearts,
+            2 => Suit::Diamonds,
+            _ => Suit::Clubs,
+        }
+    }
+
+    /// 红色花色：红心、方块
+    pub fn is_red(&self) -> bool {
+        matches!(self, Suit::Hearts | Suit::Diamonds)
+    }
+}
+
+/// 21 点的牌：rank 1=A, 2-10, 11=J, 12=Q, 13=K
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Copy)]
+pub struct Card {
+    pub rank: u8, // 1..=13
+    pub suit: Suit,
+}
+
+impl Card {
+    /// 从 0..=51 的牌 ID 构造
+    pub fn from_id(id: u8) -> Self {
+        Card {
+            rank: id / 4 + 1,
+            suit: Suit::from_u8(id % 4),
+        }
+    }
+
+    /// 牌值：A=1（计算总点数时可当 11），2-10=面值，J/Q/K=10
+    pub fn value(&self) -> u32 {
+        u32::from(self.rank.min(10))
+    }
+
+    pub fn is_ace(&self) -> bool {
+        self.rank == 1
+    }
+}
+
+/// 手牌总点数，自动处理 A 的 1 / 11
+pub fn hand_total(cards: &[Card]) -> u32 {
+    let values: Vec<u32> = cards.iter().map(Card::value).collect();
+    calculate_blackjack_total(&values)
+}
+
+/// 天然 21 点：前两张牌即为 21 点
+pub fn is_natural(cards: &[Card]) -> bool {
+    cards.len() == 2 && hand_total(cards) == 21
+}
+
+/// 软牌：有 A 按 11 点计
+pub fn is_soft(cards: &[Card]) -> bool {
+    hand_total(cards) != cards.iter().map(Card::value).sum::<u32>()
+}
+
+/// 保险的返还（含本金）：2:1 赔付
+pub fn insurance_payout(insurance: u128) -> u128 {
+    insurance * 3
+}
+
+/// 玩家的一手牌
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackHand {
+    pub cards: Vec<Card>,
+    pub bet: Uint128,      // 该手的下注（加倍后为两倍）
+    pub doubled: bool,     // 已加倍
+    pub split: bool,       // 由分牌得到
+    pub stood: bool,       // 该手已结束（停牌 / 加倍 / A 分牌 / 投降）
+    pub surrendered: bool, // 已投降
+}
+
+impl BlackjackHand {
+    pub fn new(cards: Vec<Card>, bet: Uint128) -> Self {
+        BlackjackHand {
+            cards,
+            bet,
+            doubled: false,
+            split: false,
+            stood: false,
+            surrendered: false,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        hand_total(&self.cards)
+    }
+
+    /// 天然 21 点：开局的两张牌即为 21 点（分牌得到的不算）
+    pub fn is_natural(&self) -> bool {
+        !self.split && is_natural(&self.cards)
+    }
+
+    /// 可以分牌：前两张点数相同
+    pub fn is_pair(&self) -> bool {
+        self.cards.len() == 2 && self.cards[0].rank == self.cards[1].rank
+    }
+
+    /// 最坏情况下该手的赔付（含本金、抽水前）
+    pub fn max_payout(&self, rules: &BlackjackRules) -> u128 {
+        if self.is_natural() {
+            rules.blackjack_payout.natural_payout(self.bet.u128())
+        } else {
+            self.bet.u128() * 2
+        }
+    }
+
+    /// 比牌结果与应付金额（含本金、抽水前）
+    pub fn settle(&self, dealer_cards: &[Card], rules: &BlackjackRules) -> (&'static str, u128) {
+        let bet = self.bet.u128();
+        if self.surrendered {
+            return ("surrender", bet / 2);
+        }
+        let user_total = self.total();
+        let dealer_total = hand_total(dealer_cards);
+        if user_total > 21 {
+            return ("player_busted", 0);
+        }
+        match (self.is_natural(), is_natural(dealer_cards)) {
+            (true, true) => ("draw", bet),
+            (true, false) => ("blackjack", rules.blackjack_payout.natural_payout(bet)),
+            (false, true) => ("dealer_blackjack", 0),
+            _ if dealer_total > 21 => ("dealer_busted", bet * 2),
+            _ if user_total > dealer_total => ("player_win", bet * 2),
+            _ if user_total < dealer_total => ("dealer_win", 0),
+            _ => ("draw", bet),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// 边注（开局时随主注一起下，发完初始牌即结算，与主注输赢无关）
+//
+//   - Perfect Pairs：玩家前两张牌成对。完美对子（同点同花色）25:1、
+//     同色对子（同点同颜色不同花色）12:1、混色对子（同点不同颜色）6:1
+//   - 21+3：玩家前两张牌 + 庄家明牌组成三张牌。同花三条 100:1、同花顺 40:1、
+//     三条 30:1、顺子 10:1、同花 5:1；A 可作 A-2-3 或 Q-K-A
+//
+// 单副牌靴时不可能出现完美对子和同花三条，副数越少庄家优势越高（见 README）。
+// ─────────────────────────────────────────────────────────────
+
+/// 边注下注额，未下的边注为 0
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct BlackjackSideBets {
+    #[serde(default)]
+    pub perfect_pairs: Uint128,
+    #[serde(default)]
+    pub twenty_one_plus_three: Uint128,
+}
+
+impl BlackjackSideBets {
+    /// 边注下注总额
+    pub fn total(&self) -> u128 {
+        self.perfect_pairs.u128() + self.twenty_one_plus_three.u128()
+    }
+
+    /// 边注最坏情况下的赔付（含本金、抽水前）
+    pub fn max_payout(&self) -> u128 {
+        self.perfect_pairs.u128() * PerfectPairsHand::Perfect.multiplier()
+            + self.twenty_one_plus_three.u128() * ThreeCardHand::SuitedTrips.multiplier()
+    }
+
+    /// 按玩家前两张牌与庄家明牌结算已下的边注
+    pub fn settle(&self, user_cards: &[Card], upcard: &Card) -> Vec<SideBetResult> {
+        let mut results = vec![];
+        if !self.perfect_pairs.is_zero() {
+            let hand = perfect_pairs(&user_cards[0], &user_cards[1]);
+            results.push(SideBetResult {
+                side_bet: "perfect_pairs",
+                result: hand.name(),
+                bet: self.perfect_pairs.u128(),
+                gross: self.perfect_pairs.u128() * hand.multiplier(),
+            });
+        }
+        if !self.twenty_one_plus_three.is_zero() {
+            let hand = evaluate_3card(&[user_cards[0], user_cards[1], *upcard]);
+            results.push(SideBetResult {
+                side_bet: "twenty_one_plus_three",
+                result: hand.name(),
+                bet: self.twenty_one_plus_three.u128(),
+                gross: self.twenty_one_plus_three.u128() * hand.multiplier(),
+            });
+        }
+        results
+    }
+}
+
+/// 单个边注的结算结果
+#[derive(Clone, Debug, PartialEq)]
+pub struct SideBetResult {
+    pub side_bet: &'static str, // 边注名
+    pub result: &'static str,   // 牌型
+    pub bet: u128,
+    pub gross: u128,            // 应付金额（含本金、抽水前）
+}
+
+/// Perfect Pairs 牌型
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PerfectPairsHand {
+    Nothing,
+    Mixed,    // 混色对子
+    Coloured, // 同色对子
+    Perfect,  // 完美对子
+}
+
+impl PerfectPairsHand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PerfectPairsHand::Nothing => "no_pair",
+            PerfectPairsHand::Mixed => "mixed_pair",
+            PerfectPairsHand::Coloured => "coloured_pair",
+            PerfectPairsHand::Perfect => "perfect_pair",
+        }
+    }
+
+    /// 返还倍数（含本金）
+    pub fn multiplier(&self) -> u128 {
+        match self {
+            PerfectPairsHand::Nothing => 0,
+            PerfectPairsHand::Mixed => 7,
+            PerfectPairsHand::Coloured => 13,
+            PerfectPairsHand::Perfect => 26,
+        }
+    }
+}
+
+/// 21+3 牌型
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThreeCardHand {
+    Nothing,
+    Flush,         // 同花
+    Straight,      // 顺子
+    Trips,         // 三条
+    StraightFlush, // 同花顺
+    SuitedTrips,   // 同花三条
+}
+
+impl ThreeCardHand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThreeCardHand::Nothing => "nothing",
+            ThreeCardHand::Flush => "flush",
+            ThreeCardHand::Straight => "straight",
+            ThreeCardHand::Trips => "trips",
+            ThreeCardHand::StraightFlush => "straight_flush",
+            ThreeCardHand::SuitedTrips => "suited_trips",
+        }
+    }
+
+    /// 返还倍数（含本金）
+    pub fn multiplier(&self) -> u128 {
+        match self {
+            ThreeCardHand::Nothing => 0,
+            ThreeCardHand::Flush => 6,
+            ThreeCardHand::Straight => 11,
+            ThreeCardHand::Trips => 31,
+            ThreeCardHand::StraightFlush => 41,
+            ThreeCardHand::SuitedTrips => 101,
+        }
+    }
+}
+
+/// Perfect Pairs：玩家前两张牌的对子类型
+pub fn perfect_pairs(first: &Card, second: &Card) -> PerfectPairsHand {
+    if first.rank != second.rank {
+        PerfectPairsHand::Nothing
+    } else if first.suit == second.suit {
+        PerfectPairsHand::Perfect
+    } else if first.suit.is_red() == second.suit.is_red() {
+        PerfectPairsHand::Coloured
+    } else {
+        PerfectPairsHand::Mixed
+    }
+}
+
+/// 对 3 张牌进行 21+3 牌型评估（同花三条 > 同花顺 > 三条 > 顺子 > 同花）
+pub fn evaluate_3card(cards: &[Card; 3]) -> ThreeCardHand {
+    let mut ranks: Vec<u8> = cards.iter().map(|c| c.rank).collect();
+    ranks.sort_unstable();
+
+    let is_flush = cards[0].suit == cards[1].suit && cards[1].suit == cards[2].suit;
+    let is_trips = ranks[0] == ranks[2];
+    // A 可作 1（A-2-3）或 14（Q-K-A）
+    let is_straight =
+        (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1) || ranks == [1, 12, 13];
+
+    match (is_flush, is_trips, is_straight) {
+        (true, true, _) => ThreeCardHand::SuitedTrips,
+        (true, _, true) => ThreeCardHand::StraightFlush,
+        (_, true, _) => ThreeCardHand::Trips,
+        (_, _, true) => ThreeCardHand::Straight,
+        (true, _, _) => ThreeCardHand::Flush,
+        _ => ThreeCardHand::Nothing,
+    }
 }
@@ -161,7 +161,7 @@ pub fn evaluate_bull_hand(cards: &[BullCard; 5]) -> BullHandRank {
         for j in (i + 1)..5 {
             for k in (j + 1)..5 {
                 let three_sum = points[i] as u16 + points[j] as u16 + points[k] as u16;
-                if three_sum % 10 == 0 {
+                if three_sum.is_multiple_of(10) {
                     // 有牛！剩余 2 张的点数和的个位
                     let remaining_sum: u16 = (0..5)
                         .filter(|&idx| idx != i && idx != j && idx != k)
@@ -0,0 +1,85 @@
+use crate::utils::RngStream;
+
+// ─────────────────────────────────────────────────────────────
+// 牌组 / 牌靴（Shoe）
+//
+// 所有纸牌游戏共用的洗牌逻辑：由一个随机数流对整副牌做 Fisher–Yates 洗牌，
+// 得到均匀分布的排列。
+//
+// card_id 约定（各游戏的 Card::from_id 都基于它）：
+//   0..=51 ：id / 4 为点数序号（A 在前或在后由各游戏决定），id % 4 为花色
+//   52, 53 ：大小王（仅在开启 jokers 时出现）
+// 多副牌的牌靴中同一个 card_id 会出现多次。
+// ─────────────────────────────────────────────────────────────
+
+/// 一副牌（不含王）的张数
+pub const CARDS_PER_DECK: u8 = 52;
+
+/// 大小王的 card_id
+pub const JOKER_IDS: [u8; 2] = [52, 53];
+
+/// 牌靴配置：几副牌、是否带大小王
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShoeSpec {
+    pub decks: u8,
+    pub jokers: bool,
+}
+
+impl ShoeSpec {
+    /// 单副 52 张牌（不含王）
+    pub const SINGLE_DECK: ShoeSpec = ShoeSpec {
+        decks: 1,
+        jokers: false,
+    };
+
+    /// 多副牌组成的牌靴（不含王）
+    pub const fn decks(decks: u8) -> Self {
+        ShoeSpec {
+            decks,
+            jokers: false,
+        }
+    }
+
+    /// 牌靴总张数
+    pub fn size(&self) -> usize {
+        let per_deck = CARDS_PER_DECK as usize + if self.jokers { JOKER_IDS.len() } else { 0 };
+        per_deck * self.decks as usize
+    }
+
+    /// 未洗牌的牌靴：逐副排列 0..=51（以及大小王）
+    pub fn cards(&self) -> Vec<u8> {
+        let mut cards = Vec::with_capacity(self.size());
+        for _ in 0..self.decks {
+            cards.extend(0..CARDS_PER_DECK);
+            if self.jokers {
+                cards.extend(JOKER_IDS);
+            }
+        }
+        cards
+    }
+
+    /// 洗好的整副牌靴
+    pub fn shuffle(&self, rng: &mut RngStream) -> Vec<u8> {
+        let mut cards = self.cards();
+        rng.shuffle(&mut cards);
+        cards
+    }
+
+    /// 只洗出牌靴顶部的 `count` 张（前向 Fisher–Yates），
+    /// 与整副洗牌后取前 `count` 张同分布，适合大牌靴只用少量牌的游戏
+    pub fn deal(&self, rng: &mut RngStream, count: usize) -> Vec<u8> {
+        let mut cards = self.cards();
+        let count = count.min(cards.len());
+        for i in 0..count {
+            let j = i + rng.below((cards.len() - i) as u64) as usize;
+            cards.swap(i, j);
+        }
+        cards.truncate(count);
+        cards
+    }
+}
+
+/// 用本局种子洗一副 52 张的牌，domain 区分不同游戏
+pub fn shuffled_deck(seed: &[u8], domain: &[u8]) -> Vec<u8> {
+    ShoeSpec::SINGLE_DECK.shuffle(&mut RngStream::new(seed, domain))
+}
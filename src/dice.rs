@@ -1,3 +1,4 @@
+use crate::error::ContractError;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -27,3 +28,17 @@ pub enum DiceGameMode {
         end: u8,   // 结束点（包含）
     },
 }
+
+/// 猜范围的赔付倍数（含本金）：宽度 2 → 3 倍，宽度 3 → 2 倍
+pub fn range_multiplier(start: u8, end: u8) -> Result<u128, ContractError> {
+    if start > end || !(1..=6).contains(&start) || !(1..=6).contains(&end) {
+        return Err(ContractError::InvalidRange { reason: "Invalid range" });
+    }
+    match end - start + 1 {
+        2 => Ok(3),
+        3 => Ok(2),
+        _ => Err(ContractError::InvalidRange {
+            reason: "Range width must be 2 or 3",
+        }),
+    }
+}
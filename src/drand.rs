@@ -0,0 +1,98 @@
+use cosmwasm_std::HexBinary;
+use drand_verify::{derive_randomness, G2PubkeyRfc, Pubkey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+// ─────────────────────────────────────────────────────────────
+// drand 随机信标
+//
+// 单局游戏下注时锁定一个「未来」的 drand 轮次（下注时任何人都不知道其随机数），
+// 该轮次发布后任何人都可以把签名提交到合约，合约用 BLS 公钥验签后保存随机数，
+// 再用它结算锁定该轮次的下注。
+//
+// 默认使用 League of Entropy quicknet（bls-unchained-g1-rfc9380，3 秒一轮）：
+//   https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/info
+// ─────────────────────────────────────────────────────────────
+
+/// quicknet 公钥（G2，96 字节）
+pub const QUICKNET_PUBKEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+/// quicknet 创世时间（秒）
+pub const QUICKNET_GENESIS_TIME: u64 = 1_692_803_367;
+/// quicknet 出块间隔（秒）
+pub const QUICKNET_PERIOD: u64 = 3;
+
+/// 目标轮次至少晚于区块时间的秒数（区块时间可能落后于真实时间）
+pub const BEACON_SAFETY_MARGIN_SECS: u64 = 10;
+
+/// drand 网络配置
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DrandConfig {
+    /// 网络公钥（G2 压缩点，96 字节）
+    pub pubkey: HexBinary,
+    /// 第 1 轮的发布时间（秒）
+    pub genesis_time: u64,
+    /// 出块间隔（秒）
+    pub period: u64,
+}
+
+impl DrandConfig {
+    /// League of Entropy quicknet
+    pub fn quicknet() -> Self {
+        DrandConfig {
+            pubkey: HexBinary::from_hex(QUICKNET_PUBKEY).unwrap(),
+            genesis_time: QUICKNET_GENESIS_TIME,
+            period: QUICKNET_PERIOD,
+        }
+    }
+
+    /// 校验配置：公钥必须是合法的 G2 点，出块间隔不能为 0
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.period == 0 {
+            return Err(ContractError::InvalidDrandConfig {
+                reason: "Drand period must be greater than 0".to_string(),
+            });
+        }
+        G2PubkeyRfc::from_variable(self.pubkey.as_slice())
+            .map_err(invalid_pubkey)?;
+        Ok(())
+    }
+
+    /// 在 `time_secs` 之后才发布的第一个轮次
+    ///
+    /// 第 r 轮发布时间 = genesis_time + (r - 1) * period
+    pub fn round_after(&self, time_secs: u64) -> u64 {
+        if time_secs < self.genesis_time {
+            return 1;
+        }
+        (time_secs - self.genesis_time) / self.period + 2
+    }
+
+    /// 验证某轮次的签名，成功时返回该轮随机数 sha256(signature)
+    pub fn verify(&self, round: u64, signature: &[u8]) -> Result<[u8; 32], ContractError> {
+        let pubkey = G2PubkeyRfc::from_variable(self.pubkey.as_slice())
+            .map_err(invalid_pubkey)?;
+        let valid = pubkey
+            .verify(round, b"", signature)
+            .map_err(|_| ContractError::InvalidBeaconSignature { round })?;
+        if !valid {
+            return Err(ContractError::InvalidBeaconSignature { round });
+        }
+        Ok(derive_randomness(signature))
+    }
+}
+
+/// 公钥不是合法的 G2 点
+fn invalid_pubkey(err: impl std::fmt::Display) -> ContractError {
+    ContractError::InvalidDrandConfig {
+        reason: format!("Invalid drand pubkey: {}", err),
+    }
+}
+
+/// 某玩家在某轮次上等待开奖的下注
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingBeaconBets {
+    pub round: u64,
+    pub bet_ids: Vec<u64>,
+}
@@ -0,0 +1,260 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+use crate::game_config::GameKind;
+
+// ─────────────────────────────────────────────────────────────
+// 合约错误
+//
+// 所有入口返回 ContractError，前端按变体区分错误原因，不再匹配错误文本。
+// 存储、序列化等底层错误包装为 Std。
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    // ── 权限 ──────────────────────────────────────────────────
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Caller is not the pending owner")]
+    NotPendingOwner,
+
+    #[error("No pending ownership transfer")]
+    NoPendingOwnershipTransfer,
+
+    #[error("Contract {address} is not allowed to play")]
+    ContractNotAllowed { address: String },
+
+    // ── 熔断 ──────────────────────────────────────────────────
+    #[error("Contract is paused")]
+    Paused,
+
+    #[error("Game {game} is disabled")]
+    GameDisabled { game: GameKind },
+
+    #[error("Game {game} is disabled; only the owner can re-enable it with set_game_enabled")]
+    GameReenableRequiresOwner { game: GameKind },
+
+    // ── 下注 ──────────────────────────────────────────────────
+    #[error("Message is not a bet")]
+    NotABet,
+
+    #[error("Bet must be between {min} and {max} {denom} for {game}")]
+    InvalidBet {
+        game: GameKind,
+        min: u128,
+        max: u128,
+        denom: String,
+    },
+
+    #[error(
+        "Bet exceeds the current max bet of {max_bet} {denom} \
+         ({multiplier}x max payout, {max_payout_bps} bps of free bankroll)"
+    )]
+    BetExceedsMaxPayout {
+        max_bet: u128,
+        denom: String,
+        multiplier: u128,
+        max_payout_bps: u16,
+    },
+
+    #[error("Total stake exceeds the max bet of {max} {denom} for {game}")]
+    TotalStakeExceedsMaxBet {
+        game: GameKind,
+        max: u128,
+        denom: String,
+    },
+
+    #[error("Guess must be between {min} and {max}")]
+    InvalidGuess { min: u8, max: u8 },
+
+    #[error("{reason}")]
+    InvalidRange { reason: &'static str },
+
+    #[error("{reason}")]
+    InvalidPicks { reason: &'static str },
+
+    #[error("{reason}")]
+    InvalidSicBoBet { reason: &'static str },
+
+    #[error("Client seed must not exceed {max} characters")]
+    ClientSeedTooLong { max: usize },
+
+    #[error("Unsupported denom: {denom}")]
+    UnsupportedDenom { denom: String },
+
+    #[error("Unsupported CW20 token: {token}")]
+    UnsupportedCw20 { token: String },
+
+    #[error("Bet must be placed in a single denom")]
+    MultipleDenoms,
+
+    #[error("{reason}")]
+    InvalidFunds { reason: &'static str },
+
+    #[error("Must attach funds to {action}")]
+    NoFunds { action: &'static str },
+
+    #[error("Only Play* messages can be {context}")]
+    NotAPlayMessage { context: &'static str },
+
+    // ── 资金 ──────────────────────────────────────────────────
+    #[error("Insufficient free bankroll: {available} {denom} available, {required} required")]
+    InsufficientBankroll {
+        denom: String,
+        available: u128,
+        required: u128,
+    },
+
+    #[error("Insufficient contract balance: {held} {denom} held, {required} required")]
+    InsufficientContractBalance {
+        denom: String,
+        held: u128,
+        required: u128,
+    },
+
+    #[error("Contract has insufficient funds for payout")]
+    InsufficientPayoutFunds,
+
+    #[error("Insufficient locked funds")]
+    InsufficientLockedFunds,
+
+    #[error("Contract must have at least {required} {denom} locked")]
+    MinimumLockNotMet { required: u128, denom: String },
+
+    #[error("Received amount {received} < required minimum lock amount {required}")]
+    InitialLockTooLow { received: u128, required: u128 },
+
+    #[error("Insufficient balance: {available} {denom} available")]
+    InsufficientBalance { available: u128, denom: String },
+
+    #[error("Nothing to withdraw")]
+    NothingToWithdraw,
+
+    // ── 多步游戏 ──────────────────────────────────────────────
+    #[error("No active {game} game")]
+    GameNotFound { game: GameKind },
+
+    #[error("Game already finished")]
+    GameFinished,
+
+    #[error("The {game} game is still in progress")]
+    GameNotFinished { game: GameKind },
+
+    #[error("You already have an active {game} game. Fold or Showdown first.")]
+    GameInProgress { game: GameKind },
+
+    #[error("{game} Start must be placed as a bet and settled after reveal")]
+    StartRequiresBet { game: GameKind },
+
+    #[error("{reason}")]
+    InvalidAction { reason: &'static str },
+
+    #[error("Must attach exactly the {action} amount in {denom} funds")]
+    FundsMismatch { action: &'static str, denom: String },
+
+    #[error("Minimum raise is {min} {denom}")]
+    RaiseTooSmall { min: u128, denom: String },
+
+    #[error("All-in amount must be at least {min} {denom}")]
+    AllInTooSmall { min: u128, denom: String },
+
+    #[error("{game} is not a multi-step game")]
+    NotAMultiStepGame { game: GameKind },
+
+    #[error("{game} game does not expire until block {height} and time {time}")]
+    GameNotExpired { game: GameKind, height: u64, time: u64 },
+
+    // ── 承诺-揭示 / drand ─────────────────────────────────────
+    #[error("No active server seed commitment")]
+    NoActiveCommitment,
+
+    #[error("An active commitment exists; reveal it to rotate to a new one")]
+    ActiveCommitmentExists,
+
+    #[error("Seed hash must be a 64-character hex sha256 digest")]
+    InvalidSeedHash,
+
+    #[error("Server seed does not match commitment")]
+    SeedMismatch,
+
+    #[error("Server seed not revealed yet")]
+    SeedNotRevealed,
+
+    #[error("Server seed already revealed, settle the bet instead")]
+    SeedAlreadyRevealed,
+
+    #[error("Bet already settled")]
+    BetAlreadySettled,
+
+    #[error("Bet can be refunded from block {height}")]
+    RefundNotAvailable { height: u64 },
+
+    #[error("Drand bets cannot be refunded, submit the beacon and settle instead")]
+    DrandBetNotRefundable,
+
+    #[error("Drand beacon is not configured")]
+    DrandNotConfigured,
+
+    #[error("Drand round {round} not submitted yet")]
+    BeaconNotSubmitted { round: u64 },
+
+    #[error("Drand round {round} already submitted")]
+    BeaconAlreadySubmitted { round: u64 },
+
+    #[error("Invalid drand signature for round {round}")]
+    InvalidBeaconSignature { round: u64 },
+
+    #[error("{reason}")]
+    InvalidDrandConfig { reason: String },
+
+    // ── 管理配置 ──────────────────────────────────────────────
+    #[error("House edge must not exceed 1000 bps (10%)")]
+    InvalidHouseEdge,
+
+    #[error("Max payout must be between 1 and {max} bps")]
+    InvalidMaxPayout { max: u16 },
+
+    #[error("Min bet must be greater than 0 and not exceed max bet")]
+    InvalidBetRange,
+
+    #[error("Blackjack shoe must have between 1 and {max} decks")]
+    InvalidShoeDecks { max: u8 },
+
+    #[error("Each blackjack side bet must not exceed the main bet of {main_bet} {denom}")]
+    SideBetExceedsBet { main_bet: u128, denom: String },
+
+    #[error("{reason}")]
+    InvalidDenomConfig { reason: String },
+
+    #[error("Invalid amount")]
+    InvalidAmount,
+
+    #[error("Game timeout must be at least 1 block and 1 second")]
+    InvalidGameTimeout,
+
+    #[error("Admin action {id} not found")]
+    AdminActionNotFound { id: u64 },
+
+    #[error("Admin action {id} is not ready until block {height} and time {time}")]
+    AdminActionNotReady { id: u64, height: u64, time: u64 },
+
+    // ── 迁移 ──────────────────────────────────────────────────
+    #[error("Cannot migrate from contract {contract}")]
+    WrongContract { contract: String },
+
+    #[error("Cannot migrate from version {from} to older version {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Cannot migrate from state version {from} to older state version {to}")]
+    CannotDowngradeState { from: u32, to: u32 },
+
+    #[error("No migration from state version {version}")]
+    NoMigrationPath { version: u32 },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidVersion { version: String },
+}
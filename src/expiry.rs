@@ -0,0 +1,60 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+// ─────────────────────────────────────────────────────────────
+// 多步游戏超时
+//
+// 21 点、奥马哈、德州开局后由玩家继续操作，玩家一直不停牌 / 摊牌时，
+// 牌局与其预留的赔付会永远占用资金。开局时记录区块高度与时间，
+// 超过配置的区块数与秒数（都满足）后任何人都可以调用 ExpireGame 代为结算：
+//   - 21 点：按玩家当前手牌停牌，庄家补牌后比牌
+//   - 奥马哈 / 德州：已跟齐注额时自动摊牌，否则按弃牌处理
+// 结算后删除牌局记录。
+// ─────────────────────────────────────────────────────────────
+
+/// 默认超时区块数（约 1 天，按 6 秒出块）
+pub const DEFAULT_TIMEOUT_BLOCKS: u64 = 14_400;
+
+/// 默认超时秒数（1 天）
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 86_400;
+
+/// 多步游戏超时：开局后区块数与秒数都满足才算超时
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GameTimeout {
+    pub timeout_blocks: u64,
+    pub timeout_seconds: u64,
+}
+
+impl Default for GameTimeout {
+    fn default() -> Self {
+        GameTimeout {
+            timeout_blocks: DEFAULT_TIMEOUT_BLOCKS,
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+        }
+    }
+}
+
+impl GameTimeout {
+    /// 超时为 0 会让刚开局的牌局立即可被他人结算
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.timeout_blocks == 0 || self.timeout_seconds == 0 {
+            return Err(ContractError::InvalidGameTimeout);
+        }
+        Ok(())
+    }
+
+    /// 开局于 (start_height, start_time) 的牌局最早可被结算的区块高度与时间
+    pub fn expires_at(&self, start_height: u64, start_time: u64) -> (u64, u64) {
+        (
+            start_height.saturating_add(self.timeout_blocks),
+            start_time.saturating_add(self.timeout_seconds),
+        )
+    }
+
+    pub fn is_expired(&self, start_height: u64, start_time: u64, height: u64, time: u64) -> bool {
+        let (expiry_height, expiry_time) = self.expires_at(start_height, start_time);
+        height >= expiry_height && time >= expiry_time
+    }
+}
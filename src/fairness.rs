@@ -0,0 +1,184 @@
+use cosmwasm_std::{Addr, HexBinary, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::baccarat::BaccaratResult;
+use crate::bullfight::BullCard;
+use crate::coin::CoinSide;
+use crate::keno::KenoResult;
+use crate::msg::ExecuteMsg;
+use crate::roulette::RouletteResult;
+use crate::sangong::SanGongCard;
+use crate::scratch::ScratchSymbol;
+use crate::sicbo::SicBoResult;
+use crate::slot::Symbol;
+use crate::state::{default_denom, Bettor, FundsSource};
+use crate::{blackjack, omaha, texas};
+
+// ─────────────────────────────────────────────────────────────
+// 承诺-揭示（Commit–Reveal）随机数
+//
+// 流程：
+//   1. 庄家提交服务端种子的哈希：seed_hash = sha256(server_seed)
+//   2. 玩家下注时附带自己的客户端种子（client_seed），下注进入待开奖状态，
+//      并记录下注时生效的承诺 ID
+//   3. 庄家揭示 server_seed（合约校验哈希一致），同时提交下一轮承诺
+//   4. 任何人都可以结算已揭示承诺下的下注，
+//      本局随机种子 = sha256("{server_seed}:{client_seed}:{player}:{nonce}:{bet_id}")
+//      其中 nonce 为该玩家的第几笔下注（从 1 开始）
+//
+// 配置了 drand 信标时，单局游戏改为锁定未来的 drand 轮次（见 drand.rs），
+// 结算时用该轮随机数（十六进制）代替 server_seed 推导本局种子。
+//
+// 庄家在下注前已锁定种子，无法针对玩家调整；玩家下注时不知道种子，无法预测结果。
+// 揭示后的种子永久保存，旧下注可随时复算验证。
+// ─────────────────────────────────────────────────────────────
+
+/// 庄家未在该区块数内揭示种子时，玩家可申请退还下注
+pub const REVEAL_TIMEOUT_BLOCKS: u64 = 1_000;
+
+/// 服务端种子承诺
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SeedCommitment {
+    /// 承诺 ID（从 1 开始递增）
+    pub id: u64,
+    /// sha256(server_seed) 的十六进制
+    pub seed_hash: String,
+    /// 揭示后的服务端种子（未揭示时为 None）
+    pub server_seed: Option<String>,
+    /// 提交承诺时的区块高度
+    pub committed_height: u64,
+    /// 揭示种子时的区块高度
+    pub revealed_height: Option<u64>,
+}
+
+/// 下注的随机数来源
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BetEntropy {
+    /// 服务端种子承诺（承诺-揭示）
+    Commitment { id: u64 },
+    /// drand 信标轮次
+    Drand { round: u64 },
+}
+
+/// 下注记录（待开奖或已结算）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BetRecord {
+    pub id: u64,
+    pub player: Addr,
+    /// 原始下注消息（Play* 系列）
+    pub game: ExecuteMsg,
+    pub amount: Uint128,
+    /// 下注币种
+    #[serde(default = "default_denom")]
+    pub denom: String,
+    pub client_seed: String,
+    /// 该玩家的第几笔下注（从 1 开始）
+    pub nonce: u64,
+    /// 随机数来源：下注时生效的承诺，或锁定的 drand 轮次
+    pub entropy: BetEntropy,
+    pub placed_height: u64,
+    /// 下注资金来源，赔付和退款按同一来源返还
+    #[serde(default)]
+    pub source: FundsSource,
+    /// 多步游戏预留的最坏情况赔付，开局后转入牌局状态
+    #[serde(default)]
+    pub reserved: Uint128,
+    /// 下注时生效的 21 点庄规（含牌靴副数），开局与复算都按这份庄规（其他玩法为空）
+    #[serde(default)]
+    pub blackjack_rules: Option<blackjack::BlackjackRules>,
+    /// 是否已结算（含退款）
+    pub settled: bool,
+}
+
+impl BetRecord {
+    /// 下注方：赔付和退款按下注时的币种与资金来源返还
+    pub fn bettor(&self) -> Bettor {
+        Bettor {
+            player: self.player.clone(),
+            denom: self.denom.clone(),
+            source: self.source,
+        }
+    }
+}
+
+/// 由本局种子复算出的开奖结果（与结算时使用完全相同的推导）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOutcome {
+    War { user_rand: u32, contract_rand: u32 },
+    /// reels[col][row]
+    Slot { reels: Vec<Vec<Symbol>> },
+    GuessNumber { correct_number: u32 },
+    /// shoe 为洗好的牌靴，开局 4 张牌之后按顺序继续发出；dealer_cards[0] 为暗牌。
+    /// user_hands 为玩家的各手牌（分牌后多手），复算时只有开局的一手
+    Blackjack {
+        shoe: Vec<u8>,
+        user_hands: Vec<Vec<blackjack::Card>>,
+        dealer_cards: Vec<blackjack::Card>,
+    },
+    CoinFlip { result: CoinSide },
+    Dice { roll: u32 },
+    Baccarat(BaccaratResult),
+    Roulette(RouletteResult),
+    /// deck 为洗牌后的完整牌序，community_cards 为全部 5 张公共牌
+    Omaha {
+        deck: Vec<u8>,
+        player_hand: Vec<omaha::Card>,
+        dealer_hand: Vec<omaha::Card>,
+        community_cards: Vec<omaha::Card>,
+    },
+    Texas {
+        deck: Vec<u8>,
+        player_hand: Vec<texas::Card>,
+        dealer_hand: Vec<texas::Card>,
+        community_cards: Vec<texas::Card>,
+    },
+    SanGong { player_cards: Vec<SanGongCard>, dealer_cards: Vec<SanGongCard> },
+    SicBo(SicBoResult),
+    Keno(KenoResult),
+    /// 3×3 格子，按行排列
+    ScratchCard { grid: Vec<ScratchSymbol> },
+    BullFight { player_cards: Vec<BullCard>, dealer_cards: Vec<BullCard> },
+}
+
+/// 开奖复算结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyOutcomeResponse {
+    pub bet_id: u64,
+    pub player: Addr,
+    pub game: ExecuteMsg,
+    pub entropy: BetEntropy,
+    /// 揭示的服务端种子，或 drand 轮次随机数（十六进制）
+    pub entropy_value: String,
+    pub client_seed: String,
+    pub nonce: u64,
+    /// 本局随机种子 sha256("{entropy_value}:{client_seed}:{player}:{nonce}:{bet_id}")
+    pub seed: HexBinary,
+    pub settled: bool,
+    pub outcome: GameOutcome,
+}
+
+/// 计算服务端种子的承诺哈希（十六进制）
+pub fn hash_server_seed(server_seed: &str) -> String {
+    hex::encode(Sha256::digest(server_seed.as_bytes()))
+}
+
+/// 校验承诺哈希格式：32 字节的十六进制字符串
+pub fn is_valid_seed_hash(seed_hash: &str) -> bool {
+    seed_hash.len() == 64 && seed_hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// 由熵（服务端种子或 drand 随机数十六进制）、客户端种子、玩家地址、玩家下注序号和下注 ID 推导本局随机种子
+pub fn derive_bet_seed(
+    entropy: &str,
+    client_seed: &str,
+    player: &Addr,
+    nonce: u64,
+    bet_id: u64,
+) -> [u8; 32] {
+    let input = format!("{}:{}:{}:{}:{}", entropy, client_seed, player, nonce, bet_id);
+    Sha256::digest(input.as_bytes()).into()
+}
@@ -0,0 +1,223 @@
+use cosmwasm_std::{StdError, StdResult, Uint128};
+use cw_storage_plus::{Key, KeyDeserialize, PrimaryKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+use crate::msg::ExecuteMsg;
+use crate::scratch::{bet_range as scratch_bet_range, ScratchCardType};
+use crate::slot::SlotMode;
+
+// ─────────────────────────────────────────────────────────────
+// 各玩法配置
+//
+// 每个玩法（老虎机按模式、刮刮乐按卡面区分）一条配置：是否开放、下注上下限、
+// 抽水覆盖、赔率表版本，以及奥马哈 / 德州的最低加注额。金额以 uatom 计，
+// 使用时按下注币种的 bet_scale 换算。
+//
+// 未单独保存配置的玩法使用 default_config 中的默认值。
+// ─────────────────────────────────────────────────────────────
+
+/// 玩法种类（GAME_CONFIG 的键）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GameKind {
+    War,
+    SlotBasic,
+    SlotAdvanced,
+    SlotMega,
+    GuessNumber,
+    Blackjack,
+    CoinFlip,
+    Dice,
+    Baccarat,
+    Roulette,
+    Omaha,
+    Texas,
+    SanGong,
+    SicBo,
+    Keno,
+    ScratchClassic,
+    ScratchPremium,
+    ScratchDeluxe,
+    BullFight,
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 19] = [
+        GameKind::War,
+        GameKind::SlotBasic,
+        GameKind::SlotAdvanced,
+        GameKind::SlotMega,
+        GameKind::GuessNumber,
+        GameKind::Blackjack,
+        GameKind::CoinFlip,
+        GameKind::Dice,
+        GameKind::Baccarat,
+        GameKind::Roulette,
+        GameKind::Omaha,
+        GameKind::Texas,
+        GameKind::SanGong,
+        GameKind::SicBo,
+        GameKind::Keno,
+        GameKind::ScratchClassic,
+        GameKind::ScratchPremium,
+        GameKind::ScratchDeluxe,
+        GameKind::BullFight,
+    ];
+
+    /// 存储键（与 JSON 序列化名一致）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameKind::War => "war",
+            GameKind::SlotBasic => "slot_basic",
+            GameKind::SlotAdvanced => "slot_advanced",
+            GameKind::SlotMega => "slot_mega",
+            GameKind::GuessNumber => "guess_number",
+            GameKind::Blackjack => "blackjack",
+            GameKind::CoinFlip => "coin_flip",
+            GameKind::Dice => "dice",
+            GameKind::Baccarat => "baccarat",
+            GameKind::Roulette => "roulette",
+            GameKind::Omaha => "omaha",
+            GameKind::Texas => "texas",
+            GameKind::SanGong => "san_gong",
+            GameKind::SicBo => "sic_bo",
+            GameKind::Keno => "keno",
+            GameKind::ScratchClassic => "scratch_classic",
+            GameKind::ScratchPremium => "scratch_premium",
+            GameKind::ScratchDeluxe => "scratch_deluxe",
+            GameKind::BullFight => "bull_fight",
+        }
+    }
+
+    /// 老虎机各模式对应的玩法
+    pub fn slot(mode: &SlotMode) -> GameKind {
+        match mode {
+            SlotMode::Basic => GameKind::SlotBasic,
+            SlotMode::Advanced => GameKind::SlotAdvanced,
+            SlotMode::Mega => GameKind::SlotMega,
+        }
+    }
+
+    /// 刮刮乐各卡面对应的玩法
+    pub fn scratch(card_type: &ScratchCardType) -> GameKind {
+        match card_type {
+            ScratchCardType::Classic => GameKind::ScratchClassic,
+            ScratchCardType::Premium => GameKind::ScratchPremium,
+            ScratchCardType::Deluxe => GameKind::ScratchDeluxe,
+        }
+    }
+
+    /// 下注消息对应的玩法
+    pub fn from_msg(msg: &ExecuteMsg) -> Result<GameKind, ContractError> {
+        let kind = match msg {
+            ExecuteMsg::PlayWar { .. } => GameKind::War,
+            ExecuteMsg::PlaySlot { mode, .. } => GameKind::slot(mode),
+            ExecuteMsg::GuessNumber { .. } => GameKind::GuessNumber,
+            ExecuteMsg::PlayBlackjack { .. } => GameKind::Blackjack,
+            ExecuteMsg::PlayCoinFlip { .. } => GameKind::CoinFlip,
+            ExecuteMsg::PlayDice { .. } => GameKind::Dice,
+            ExecuteMsg::PlayBaccarat { .. } => GameKind::Baccarat,
+            ExecuteMsg::PlayRoulette { .. } => GameKind::Roulette,
+            ExecuteMsg::PlayOmaha { .. } => GameKind::Omaha,
+            ExecuteMsg::PlayTexas { .. } => GameKind::Texas,
+            ExecuteMsg::PlaySanGong { .. } => GameKind::SanGong,
+            ExecuteMsg::PlaySicBo { .. } => GameKind::SicBo,
+            ExecuteMsg::PlayKeno { .. } => GameKind::Keno,
+            ExecuteMsg::PlayScratchCard { card_type, .. } => GameKind::scratch(card_type),
+            ExecuteMsg::PlayBullFight { .. } => GameKind::BullFight,
+            _ => return Err(ContractError::NotABet),
+        };
+        Ok(kind)
+    }
+
+    /// 默认配置（即原先写死在合约中的限额）
+    pub fn default_config(&self) -> GameConfig {
+        let (min_bet, max_bet) = match self {
+            GameKind::SlotAdvanced => (200_000, 10_000_000),
+            GameKind::SlotMega => (500_000, 10_000_000),
+            GameKind::Omaha | GameKind::Texas => (100_000, 5_000_000),
+            GameKind::ScratchClassic => scratch_bet_range(&ScratchCardType::Classic),
+            GameKind::ScratchPremium => scratch_bet_range(&ScratchCardType::Premium),
+            GameKind::ScratchDeluxe => scratch_bet_range(&ScratchCardType::Deluxe),
+            _ => (100_000, 10_000_000),
+        };
+        let min_raise = match self {
+            GameKind::Omaha | GameKind::Texas => 50_000,
+            _ => 0,
+        };
+        GameConfig {
+            enabled: true,
+            min_bet: Uint128::new(min_bet),
+            max_bet: Uint128::new(max_bet),
+            house_edge_bps: None,
+            paytable_version: 1,
+            min_raise: Uint128::new(min_raise),
+        }
+    }
+}
+
+impl std::fmt::Display for GameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'a> PrimaryKey<'a> for GameKind {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key<'_>> {
+        vec![Key::Ref(self.as_str().as_bytes())]
+    }
+}
+
+impl KeyDeserialize for GameKind {
+    type Output = GameKind;
+
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        GameKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().as_bytes() == value.as_slice())
+            .ok_or_else(|| StdError::generic_err("Invalid game kind key"))
+    }
+}
+
+/// 单个玩法的配置
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GameConfig {
+    /// 是否开放下注（关闭后进行中的多步牌局仍可继续）
+    pub enabled: bool,
+    /// 下注下限（uatom，按 bet_scale 换算）
+    pub min_bet: Uint128,
+    /// 下注上限（uatom，按 bet_scale 换算）
+    pub max_bet: Uint128,
+    /// 覆盖全局抽水（基点，最高 1000），为空时使用全局抽水
+    pub house_edge_bps: Option<u16>,
+    /// 赔率表版本，修改赔率时递增，便于链下核对
+    pub paytable_version: u32,
+    /// 最低加注额（uatom，仅奥马哈 / 德州使用）
+    #[serde(default)]
+    pub min_raise: Uint128,
+}
+
+impl GameConfig {
+    /// 校验配置：下限大于 0 且不高于上限，抽水覆盖不超过 10%
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.min_bet.is_zero() || self.min_bet > self.max_bet {
+            return Err(ContractError::InvalidBetRange);
+        }
+        if self.house_edge_bps.is_some_and(|bps| bps > 1000) {
+            return Err(ContractError::InvalidHouseEdge);
+        }
+        Ok(())
+    }
+
+    /// 该玩法实际生效的抽水
+    pub fn house_edge(&self, global_house_edge_bps: u16) -> u16 {
+        self.house_edge_bps.unwrap_or(global_house_edge_bps)
+    }
+}
@@ -1,5 +1,6 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use crate::error::ContractError;
 
 // ─────────────────────────────────────────────────────────────
 // 基诺（Keno）数据类型
@@ -38,19 +39,25 @@ pub struct KenoResult {
 }
 
 /// 验证玩家选号是否合法
-pub fn validate_picks(picks: &[u8]) -> Result<(), &'static str> {
+pub fn validate_picks(picks: &[u8]) -> Result<(), ContractError> {
     if picks.is_empty() || picks.len() > 10 {
-        return Err("Must pick between 1 and 10 numbers");
+        return Err(ContractError::InvalidPicks {
+            reason: "Must pick between 1 and 10 numbers",
+        });
     }
 
     // 检查号码范围和重复
     let mut seen = [false; 81]; // index 0 unused, 1-80
     for &n in picks {
-        if n < 1 || n > 80 {
-            return Err("Numbers must be between 1 and 80");
+        if !(1..=80).contains(&n) {
+            return Err(ContractError::InvalidPicks {
+                reason: "Numbers must be between 1 and 80",
+            });
         }
         if seen[n as usize] {
-            return Err("Duplicate numbers are not allowed");
+            return Err(ContractError::InvalidPicks {
+                reason: "Duplicate numbers are not allowed",
+            });
         }
         seen[n as usize] = true;
     }
@@ -131,3 +138,11 @@ pub fn keno_payout_multiplier(pick_count: u8, hit_count: u8) -> u128 {
     }
 }
 
+/// 选 `pick_count` 个号码时可能的最高赔率倍数（含本金）
+pub fn max_payout_multiplier(pick_count: u8) -> u128 {
+    (0..=pick_count)
+        .map(|hits| keno_payout_multiplier(pick_count, hits))
+        .max()
+        .unwrap_or(0)
+}
+
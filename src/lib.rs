@@ -2,28 +2,54 @@ mod baccarat;
 mod blackjack;
 mod bullfight;
 mod coin;
+mod deck;
 mod dice;
+mod drand;
+mod error;
+mod expiry;
+mod fairness;
+mod game_config;
 mod keno;
+mod migrate;
 mod msg;
 mod omaha;
+mod risk;
 mod roulette;
 mod sangong;
 mod scratch;
+mod settlement;
 mod sicbo;
 mod slot;
 mod state;
 mod texas;
+mod timelock;
 mod utils;
 
-use crate::baccarat::BaccaratBet;
-use crate::blackjack::BlackjackAction;
+use crate::baccarat::{BaccaratBet, BaccaratResult};
+use crate::blackjack::{
+    hand_total, insurance_payout, is_natural, BlackjackAction, BlackjackHand, BlackjackRules,
+    BlackjackSideBets, Card as BlackjackCard, MAX_PAYOUT_MULTIPLIER as BLACKJACK_MAX_MULTIPLIER,
+};
 use crate::bullfight::{
     bull_hand_type_name, bull_payout_multiplier, evaluate_bull_hand, BullCard,
 };
 use crate::coin::CoinSide;
-use crate::dice::{DiceGameMode, DiceGuessSize};
-use crate::keno::{calculate_hits, keno_payout_multiplier, validate_picks};
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::deck::{shuffled_deck, ShoeSpec};
+use crate::dice::{range_multiplier as dice_range_multiplier, DiceGameMode, DiceGuessSize};
+use crate::drand::{PendingBeaconBets, BEACON_SAFETY_MARGIN_SECS};
+use crate::error::ContractError;
+use crate::expiry::GameTimeout;
+use crate::fairness::{
+    derive_bet_seed, hash_server_seed, is_valid_seed_hash, BetEntropy, BetRecord, GameOutcome,
+    SeedCommitment, VerifyOutcomeResponse, REVEAL_TIMEOUT_BLOCKS,
+};
+use crate::game_config::{GameConfig, GameKind};
+use crate::keno::{calculate_hits, keno_payout_multiplier, validate_picks, KenoResult};
+use crate::migrate::{
+    parse_version, BLACKJACK_STATE_V1, CONTRACT_NAME, CONTRACT_VERSION, CURRENT_STATE_VERSION,
+    LEGACY_STATE_VERSION, STATE_V1,
+};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::omaha::{
     best_omaha_hand_rank, hand_rank_name, Card, OmahaAction, OmahaState, OmahaStateResponse,
     OmahaStage,
@@ -33,144 +59,350 @@ use crate::sangong::{
     evaluate_sangong_hand, hand_type_name, payout_multiplier as sangong_payout_multiplier,
     SanGongCard, SanGongHandType,
 };
-use crate::scratch::{
-    bet_range as scratch_bet_range, evaluate_scratch_card, ScratchCardType, ScratchSymbol,
+use crate::risk::{
+    default_max_payout_bps, dynamic_max_bet, max_payout, max_payout_multiplier,
+    MAX_PAYOUT_BPS_CAP, MULTI_STEP_MAX_MULTIPLIER,
 };
+use crate::scratch::{evaluate_scratch_card, ScratchCardType, ScratchSymbol};
+use crate::settlement::{Settlement, REFUND_OUTCOME};
 use crate::sicbo::{calculate_sicbo_payout, validate_bet, SicBoBetType, SicBoResult};
 use crate::slot::{evaluate_advanced, evaluate_basic, evaluate_mega, Symbol, SlotMode};
 use crate::state::{
-    BlackjackState, BlackjackStateResponse, HouseEdgeResponse, LockedAmountResponse, State,
-    BLACKJACK_STATE, OMAHA_STATE, STATE, TEXAS_STATE,
+    AddressRolesResponse, BetLimitsResponse, Bettor, BlackjackState, BlackjackStateResponse,
+    CallerGuardResponse, CircuitBreakerResponse, ContractVersionResponse, DenomConfig,
+    ExposureResponse, FundsSource, GameConfigResponse, GameConfigsResponse, HouseEdgeResponse,
+    LockedAmountResponse, OwnershipResponse, PendingAdminActionsResponse, PlayerBalanceResponse,
+    PlayerNonceResponse, Role, RoleMembersResponse, State, ADMIN_ACTIONS, ADMIN_ACTION_COUNT,
+    ALLOWED_CONTRACTS, DEFAULT_DENOM, EOA_ONLY, ACTIVE_COMMITMENT, BETS, BET_COUNT,
+    BLACKJACK_STATE, COMMITMENT_COUNT, DRAND_BEACONS, DRAND_CONFIG, GAME_CONFIG, OMAHA_STATE,
+    PENDING_BEACON_BETS, PLAYER_BALANCES, PLAYER_NONCES, ROLE_MEMBERS, SEED_COMMITMENTS, STATE,
+    STATE_VERSION, TEXAS_STATE, TIMELOCK, GAME_TIMEOUT, BLACKJACK_RULES, PLAYER_BALANCE_TOTALS,
 };
+use crate::timelock::{AdminAction, PendingAdminAction, TimelockConfig};
+use crate::utils::RngStream;
 use crate::texas::{
     best_texas_hand_rank, TexasAction, TexasState, TexasStateResponse, TexasStage,
 };
 use cosmwasm_std::{
-    entry_point, to_json_binary, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Uint128,
+    entry_point, from_json, to_json_binary, Addr, Api, Binary, Coin, CosmosMsg, Deps, DepsMut,
+    Env, Event, HexBinary, MessageInfo, Order, Response, StdResult, Storage, Uint128,
 };
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, Cw20ReceiveMsg};
 
 #[entry_point]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
-    // 初始化需要的最少锁仓金额
-    let required_minimum_lock_coin_amount = 10_000_000_000;
+) -> Result<Response, ContractError> {
+    // 可下注币种：默认仅 uatom
+    let denoms = msg.denoms.unwrap_or_else(|| vec![DenomConfig::uatom()]);
+    validate_denoms(deps.api, &denoms)?;
+
+    // 附带的资金全部计入锁仓，只接受可下注的原生币种
+    let mut locked_amounts: Vec<Coin> = vec![];
+    for coin in info.funds.iter().filter(|c| !c.amount.is_zero()) {
+        if !denoms.iter().any(|d| d.denom == coin.denom && !d.cw20) {
+            return Err(ContractError::UnsupportedDenom {
+                denom: coin.denom.clone(),
+            });
+        }
+        locked_amounts.push(coin.clone());
+    }
 
-    let received = info
-        .funds
+    // 初始化需要的最少锁仓金额（按主币种计算）
+    let primary = &denoms[0];
+    let required_minimum_lock_coin_amount = primary.scale(10_000_000_000);
+    let received = locked_amounts
         .iter()
-        .find(|c| c.denom == "uatom")
+        .find(|c| c.denom == primary.denom)
         .map(|c| c.amount)
         .unwrap_or_else(Uint128::zero);
 
     if received.u128() < required_minimum_lock_coin_amount {
-        return Err(StdError::generic_err(format!(
-            "Received amount {} < required minimum lock amount {}",
-            received, required_minimum_lock_coin_amount
-        )));
+        return Err(ContractError::InitialLockTooLow {
+            received: received.u128(),
+            required: required_minimum_lock_coin_amount,
+        });
     }
 
     // 验证抽水比例：最高 1000 基点 = 10%
     let house_edge_bps = msg.house_edge_bps.unwrap_or(0);
     if house_edge_bps > 1000 {
-        return Err(StdError::generic_err(
-            "House edge must not exceed 1000 bps (10%)",
-        ));
+        return Err(ContractError::InvalidHouseEdge);
     }
 
+    // 单注最多可赢取可用资金的比例，默认 100%
+    let max_payout_bps = msg.max_payout_bps.unwrap_or_else(default_max_payout_bps);
+    validate_max_payout_bps(max_payout_bps)?;
+
     // 存储初始状态
     let state = State {
         owner: info.sender.clone(),
-        locked_amount: received.u128(),
+        locked_amounts,
         house_edge_bps,
+        denoms,
+        reserved_amounts: vec![],
+        max_payout_bps,
+        paused: false,
+        pending_owner: None,
     };
 
     STATE.save(deps.storage, &state)?;
+    STATE_VERSION.save(deps.storage, &CURRENT_STATE_VERSION)?;
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::new()
+    let mut response = Response::new()
         .add_attribute("action", "init")
         .add_attribute("owner", info.sender)
         .add_attribute("locked_amount", received.to_string())
-        .add_attribute("house_edge_bps", house_edge_bps.to_string()))
+        .add_attribute("house_edge_bps", house_edge_bps.to_string());
+
+    // 可选：初始化时直接提交第一个服务端种子承诺
+    if let Some(seed_hash) = msg.server_seed_hash {
+        let commitment_id = save_commitment(deps.storage, seed_hash, env.block.height)?;
+        response = response.add_attribute("commitment_id", commitment_id.to_string());
+    }
+
+    // 默认仅允许外部账户下注
+    let eoa_only = msg.eoa_only.unwrap_or(true);
+    EOA_ONLY.save(deps.storage, &eoa_only)?;
+    response = response.add_attribute("eoa_only", eoa_only.to_string());
+
+    // 可选：配置 drand 信标，单局游戏改用 drand 轮次开奖
+    if let Some(drand) = msg.drand {
+        drand.validate()?;
+        DRAND_CONFIG.save(deps.storage, &drand)?;
+        response = response.add_attribute("drand_pubkey", drand.pubkey.to_hex());
+    }
+
+    // 可选：管理操作时间锁
+    if let Some(timelock) = msg.timelock {
+        TIMELOCK.save(deps.storage, &timelock)?;
+        response = response
+            .add_attribute("timelock_delay_blocks", timelock.delay_blocks.to_string())
+            .add_attribute("timelock_delay_seconds", timelock.delay_seconds.to_string());
+    }
+
+    // 可选：多步游戏超时（未设置时使用默认值）
+    if let Some(timeout) = msg.game_timeout {
+        timeout.validate()?;
+        GAME_TIMEOUT.save(deps.storage, &timeout)?;
+        response = response
+            .add_attribute("game_timeout_blocks", timeout.timeout_blocks.to_string())
+            .add_attribute("game_timeout_seconds", timeout.timeout_seconds.to_string());
+    }
+
+    // 可选：21 点庄规（未设置时使用默认庄规）
+    if let Some(rules) = msg.blackjack_rules {
+        rules.validate()?;
+        BLACKJACK_RULES.save(deps.storage, &rules)?;
+        response = response.add_attribute("blackjack_decks", rules.decks.to_string());
+    }
+
+    Ok(response)
 }
 
 /// 处理执行逻辑
 /// 目前支持比大小的游戏逻辑未来会继续扩展
 /// 包括合约管理员提取锁仓代币的逻辑
 #[entry_point]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
-    // 检查合约是否至少有 100,000,000 uatom 锁仓
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // 检查合约主币种是否至少有 100,000,000 uatom（按 bet_scale 换算）锁仓
     let state = STATE.load(deps.storage)?;
-    if state.locked_amount < 100_000_000 {
-        return Err(StdError::generic_err(
-            "Contract must have at least 100,000,000 uatom locked",
-        ));
+    let primary = state.primary_denom();
+    let minimum_locked = primary.scale(100_000_000);
+    if state.locked(&primary.denom) < minimum_locked {
+        return Err(ContractError::MinimumLockNotMet {
+            required: minimum_locked,
+            denom: primary.denom.clone(),
+        });
+    }
+    // CW20 代币只能经由代币合约 Send 进入，附带的原生资金不能冒充
+    if info.funds.iter().any(|c| state.denom_config(&c.denom).is_ok_and(|d| d.cw20)) {
+        return Err(ContractError::InvalidFunds {
+            reason: "CW20 tokens must be sent through the token contract",
+        });
     }
+    // 游戏消息检查调用方：合约可以在同一交易内看到结果并在输掉时回滚
+    if msg.is_play() {
+        ensure_caller_allowed(deps.as_ref(), &info.sender)?;
+    }
+    check_circuit_breaker(deps.storage, &msg)?;
     match msg {
-        ExecuteMsg::PlayWar {} => play_war(deps, env, info),
-        ExecuteMsg::PlaySlot { mode } => play_slot(deps, env, info, mode),
-        ExecuteMsg::GuessNumber { guess } => play_guess_number(deps, env, info, guess),
-        ExecuteMsg::PlayBlackjack { action } => match action {
-            BlackjackAction::Start => play_blackjack_start(deps, env, info),
-            BlackjackAction::Hit => play_blackjack_hit(deps, env, info),
-            BlackjackAction::Stand => play_blackjack_stand(deps, env, info),
-        },
-        ExecuteMsg::PlayCoinFlip { choice } => play_coin_flip(deps, env, info, choice),
-        ExecuteMsg::PlayDice { mode } => match mode {
-            DiceGameMode::GuessSize { guess_big } => {
-                play_dice_guess_size(deps, env, info, guess_big)
-            }
-            DiceGameMode::ExactNumber { guess_number } => {
-                play_dice_exact_number(deps, env, info, guess_number)
-            }
-            DiceGameMode::RangeBet { start, end } => {
-                play_dice_range_bet(deps, env, info, start, end)
-            }
-        },
-        ExecuteMsg::PlayBaccarat { bet_choice } => play_baccarat(deps, env, info, bet_choice),
-        ExecuteMsg::PlayRoulette { bet_type } => play_roulette(deps, env, info, bet_type),
-        ExecuteMsg::PlayOmaha { action } => play_omaha(deps, env, info, action),
-        ExecuteMsg::PlayTexas { action } => play_texas(deps, env, info, action),
-        ExecuteMsg::PlaySanGong {} => play_sangong(deps, env, info),
-        ExecuteMsg::PlaySicBo { bet_type } => play_sicbo(deps, env, info, bet_type),
-        ExecuteMsg::PlayKeno { picks } => play_keno(deps, env, info, picks),
-        ExecuteMsg::PlayScratchCard { card_type } => play_scratch_card(deps, env, info, card_type),
-        ExecuteMsg::PlayBullFight {} => play_bullfight(deps, env, info),
-        ExecuteMsg::Withdraw { amount } => withdraw_funds(deps, info, amount),
+        ExecuteMsg::PlayFromBalance {
+            amount,
+            denom,
+            game,
+        } => play_from_balance(deps, env, info, amount, denom, *game),
+        ExecuteMsg::Deposit {} => deposit(deps, info),
+        ExecuteMsg::WithdrawBalance { amount, denom } => {
+            withdraw_balance(deps, info, amount, denom)
+        }
+        ExecuteMsg::Withdraw { amount, denom } => {
+            propose_admin_action(deps, &env, info, AdminAction::Withdraw { amount, denom })
+        }
+        ExecuteMsg::AddLiquidity {} => add_liquidity(deps, info),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateHouseEdge { new_house_edge_bps } => {
-            update_house_edge(deps, info, new_house_edge_bps)
+            let action = AdminAction::UpdateHouseEdge { new_house_edge_bps };
+            propose_admin_action(deps, &env, info, action)
+        }
+        ExecuteMsg::UpdateMaxPayout { max_payout_bps } => {
+            update_max_payout(deps, info, max_payout_bps)
+        }
+        ExecuteMsg::UpdateGameConfig { game, config } => {
+            propose_admin_action(deps, &env, info, AdminAction::UpdateGameConfig { game, config })
+        }
+        ExecuteMsg::CommitServerSeed { seed_hash } => {
+            commit_server_seed(deps, env, info, seed_hash)
+        }
+        ExecuteMsg::RevealServerSeed {
+            server_seed,
+            next_seed_hash,
+        } => reveal_server_seed(deps, env, info, server_seed, next_seed_hash),
+        ExecuteMsg::SettleBet { bet_id } => settle_bet(deps, env, bet_id),
+        ExecuteMsg::RefundBet { bet_id } => refund_bet(deps, env, info, bet_id),
+        ExecuteMsg::SubmitBeacon { round, signature } => submit_beacon(deps, round, signature),
+        ExecuteMsg::SetEoaOnly { enabled } => set_eoa_only(deps, info, enabled),
+        ExecuteMsg::AllowContract { address } => {
+            update_allowed_contract(deps, info, address, true)
+        }
+        ExecuteMsg::DisallowContract { address } => {
+            update_allowed_contract(deps, info, address, false)
+        }
+        ExecuteMsg::Pause {} => set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => set_paused(deps, info, false),
+        ExecuteMsg::SetGameEnabled { game, enabled } => {
+            set_game_enabled(deps, info, game, enabled)
+        }
+        ExecuteMsg::GrantRole { role, address } => update_role(deps, info, role, address, true),
+        ExecuteMsg::RevokeRole { role, address } => {
+            update_role(deps, info, role, address, false)
+        }
+        ExecuteMsg::TransferOwnership { new_owner } => transfer_ownership(deps, info, new_owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+        ExecuteMsg::CancelOwnershipTransfer {} => cancel_ownership_transfer(deps, info),
+        ExecuteMsg::UpdateTimelock {
+            delay_blocks,
+            delay_seconds,
+        } => {
+            let config = TimelockConfig {
+                delay_blocks,
+                delay_seconds,
+            };
+            propose_admin_action(deps, &env, info, AdminAction::UpdateTimelock { config })
+        }
+        ExecuteMsg::ExecuteAdminAction { id } => execute_admin_action(deps, env, info, id),
+        ExecuteMsg::CancelAdminAction { id } => cancel_admin_action(deps, info, id),
+        ExecuteMsg::UpdateGameTimeout {
+            timeout_blocks,
+            timeout_seconds,
+        } => {
+            let timeout = GameTimeout {
+                timeout_blocks,
+                timeout_seconds,
+            };
+            propose_admin_action(deps, &env, info, AdminAction::UpdateGameTimeout { timeout })
+        }
+        ExecuteMsg::UpdateBlackjackRules { rules } => {
+            propose_admin_action(deps, &env, info, AdminAction::UpdateBlackjackRules { rules })
+        }
+        ExecuteMsg::ExpireGame { game, player } => expire_game(deps, env, info, game, player),
+        play => execute_play(deps, env, info, play, FundsSource::Attached),
+    }
+}
+
+/// 执行游戏消息，source 为本次下注的资金来源
+fn execute_play(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+    source: FundsSource,
+) -> Result<Response, ContractError> {
+    match msg {
+        // 多步游戏的后续操作使用开局时已确定的牌，直接执行
+        ExecuteMsg::PlayBlackjack { action, side_bets, .. }
+            if !matches!(action, BlackjackAction::Start) =>
+        {
+            if side_bets.is_some() {
+                return Err(ContractError::InvalidAction {
+                    reason: "Side bets can only be placed when starting a game",
+                });
+            }
+            play_blackjack(deps, &env, info, action)
         }
+        ExecuteMsg::PlayOmaha { action, .. } if !matches!(action, OmahaAction::Start) => {
+            play_omaha(deps, &env, info, action)
+        }
+        ExecuteMsg::PlayTexas { action, .. } if !matches!(action, TexasAction::Start) => {
+            play_texas(deps, &env, info, action)
+        }
+        // 其余 Play* 消息：先下注，等待庄家揭示种子后结算
+        play => place_bet(deps, env, info, play, source),
     }
 }
 
 /// 处理查询逻辑
 /// 查询当前合约还有多少锁仓代币
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetLockedAmount {} => {
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    let binary = match msg {
+        QueryMsg::GetLockedAmount { denom } => {
             let state = STATE.load(deps.storage)?;
+            let denom = state.denom_or_primary(denom)?.denom.clone();
             let resp = LockedAmountResponse {
-                locked_amount: Uint128::from(state.locked_amount),
+                locked_amount: Uint128::from(state.locked(&denom)),
+                denom,
+            };
+            to_json_binary(&resp)
+        }
+        QueryMsg::GetDenoms {} => to_json_binary(&STATE.load(deps.storage)?.denoms),
+        QueryMsg::GetExposure { denom } => {
+            let state = STATE.load(deps.storage)?;
+            let denom = state.denom_or_primary(denom)?.denom.clone();
+            let resp = ExposureResponse {
+                locked_amount: Uint128::from(state.locked(&denom)),
+                reserved_amount: Uint128::from(state.reserved(&denom)),
+                free_amount: Uint128::from(state.free(&denom)),
+                denom,
             };
             to_json_binary(&resp)
         }
+        QueryMsg::GetBetLimits { game, denom } => {
+            to_json_binary(&query_bet_limits(deps, *game, denom)?)
+        }
+        QueryMsg::GetGameConfig { game } => to_json_binary(&GameConfigResponse {
+            game,
+            config: game_config(deps.storage, game)?,
+        }),
+        QueryMsg::ListGameConfigs {} => to_json_binary(&list_game_configs(deps)?),
         QueryMsg::GetBlackjackState { address } => {
-            let addr = deps.api.addr_validate(&address);
-            let state = BLACKJACK_STATE.load(deps.storage, &addr.unwrap())?;
-            let mut hide_dealer_cards = state.dealer_cards;
-            if !state.finished {
-                hide_dealer_cards[0] = 0;
-            }
+            let addr = deps.api.addr_validate(&address)?;
+            let state = BLACKJACK_STATE.load(deps.storage, &addr)?;
+            let dealer_cards = if state.finished {
+                state.dealer_cards
+            } else {
+                state.dealer_cards[1..].to_vec() // 未结束时隐藏庄家暗牌
+            };
             let resp = BlackjackStateResponse {
-                user_cards: state.user_cards,
-                dealer_cards: hide_dealer_cards,
+                hand_totals: state.hands.iter().map(BlackjackHand::total).collect(),
+                hands: state.hands,
+                active_hand: state.active_hand,
+                dealer_cards,
                 bet: state.bet,
+                insurance: state.insurance,
                 finished: state.finished,
+                rules: state.rules,
+                side_bets: state.side_bets,
+                side_payout: state.side_payout,
             };
             to_json_binary(&resp)
         }
@@ -220,2563 +452,3645 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             };
             to_json_binary(&resp)
         }
-    }
+        QueryMsg::GetSeedCommitment { id } => {
+            let id = match id {
+                Some(id) => id,
+                None => ACTIVE_COMMITMENT
+                    .may_load(deps.storage)?
+                    .ok_or(ContractError::NoActiveCommitment)?,
+            };
+            to_json_binary(&SEED_COMMITMENTS.load(deps.storage, id)?)
+        }
+        QueryMsg::GetBet { bet_id } => to_json_binary(&BETS.load(deps.storage, bet_id)?),
+        QueryMsg::GetDrandConfig {} => to_json_binary(&DRAND_CONFIG.may_load(deps.storage)?),
+        QueryMsg::GetBeacon { round } => {
+            to_json_binary(&DRAND_BEACONS.may_load(deps.storage, round)?)
+        }
+        QueryMsg::GetPendingBeaconBets { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            let pending = PENDING_BEACON_BETS
+                .prefix(&addr)
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(round, bet_ids)| PendingBeaconBets { round, bet_ids }))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&pending)
+        }
+        QueryMsg::GetCircuitBreaker {} => to_json_binary(&query_circuit_breaker(deps)?),
+        QueryMsg::GetOwnership {} => {
+            let state = STATE.load(deps.storage)?;
+            to_json_binary(&OwnershipResponse {
+                owner: state.owner,
+                pending_owner: state.pending_owner,
+            })
+        }
+        QueryMsg::GetRoleMembers { role } => {
+            let members = ROLE_MEMBERS
+                .prefix(role.as_str())
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&RoleMembersResponse { role, members })
+        }
+        QueryMsg::GetRoles { address } => to_json_binary(&query_roles(deps, address)?),
+        QueryMsg::GetTimelock {} => {
+            to_json_binary(&TIMELOCK.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::ListPendingAdminActions {} => {
+            let actions = ADMIN_ACTIONS
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(_, action)| action))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&PendingAdminActionsResponse { actions })
+        }
+        QueryMsg::GetGameTimeout {} => {
+            to_json_binary(&GAME_TIMEOUT.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetBlackjackRules {} => {
+            to_json_binary(&BLACKJACK_RULES.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetCallerGuard {} => {
+            let allowed_contracts = ALLOWED_CONTRACTS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&CallerGuardResponse {
+                eoa_only: EOA_ONLY.may_load(deps.storage)?.unwrap_or(true),
+                allowed_contracts,
+            })
+        }
+        QueryMsg::GetPlayerBalance { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let balances = PLAYER_BALANCES
+                .prefix(&address)
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&PlayerBalanceResponse { address, balances })
+        }
+        QueryMsg::GetPlayerNonce { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let nonce = PLAYER_NONCES.may_load(deps.storage, &address)?.unwrap_or(0);
+            to_json_binary(&PlayerNonceResponse { address, nonce })
+        }
+        QueryMsg::VerifyOutcome { bet_id } => to_json_binary(&verify_outcome(deps, bet_id)?),
+        QueryMsg::GetContractVersion {} => {
+            let contract = cw2::get_contract_version(deps.storage)?;
+            to_json_binary(&ContractVersionResponse {
+                contract: contract.contract,
+                version: contract.version,
+                state_version: STATE_VERSION
+                    .may_load(deps.storage)?
+                    .unwrap_or(LEGACY_STATE_VERSION),
+            })
+        }
+    };
+    Ok(binary?)
 }
 
-/// 比大小游戏
-///
-/// 用户和合约进行比大小游戏, 用户生成的数字大于合约生成的数字,则用户获胜,获得下注金额 ×2 的奖励.
-fn play_war(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-    // 检查用户是否发送了正确金额
-    let sent_amount = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+/// 合约迁移
+/// 校验 cw2 合约名并拒绝降级，再把存储结构从已存储的版本逐级升级到当前版本
+#[entry_point]
+pub fn migrate(mut deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // 最初部署的版本没有记录 cw2 版本信息
+    let previous_version = match cw2::CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::WrongContract {
+                    contract: stored.contract,
+                });
+            }
+            if parse_version(&stored.version)? > parse_version(CONTRACT_VERSION)? {
+                return Err(ContractError::CannotDowngrade {
+                    from: stored.version,
+                    to: CONTRACT_VERSION.to_string(),
+                });
+            }
+            stored.version
+        }
+        None => String::new(),
+    };
 
-    if sent_amount < 100_000 || sent_amount > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+    let from_state_version = STATE_VERSION
+        .may_load(deps.storage)?
+        .unwrap_or(LEGACY_STATE_VERSION);
+    if from_state_version > CURRENT_STATE_VERSION {
+        return Err(ContractError::CannotDowngradeState {
+            from: from_state_version,
+            to: CURRENT_STATE_VERSION,
+        });
     }
 
-    // 修改 State 的 locked_amount 值
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += sent_amount;
-
-    // 生成用户 1～100 的随机数
-    let user_rand = utils::generate_random_number(&info, &env, b"user");
-    // 生成的合约 1～100 的随机数
-    let contract_rand = utils::generate_random_number(&info, &env, b"contract");
-
-    // 比较结果
-    let mut response = Response::new();
-    let mut result = "lost";
-    if user_rand > contract_rand {
-        // 用户赢: 发送奖励（下注金额 ×2，扣除抽水）
-        let gross_payout = sent_amount * 2;
-        let net_payout = apply_house_edge(gross_payout, sent_amount, state.house_edge_bps);
-        let payout = Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(net_payout),
-        };
-        response = response.add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![payout],
-        });
-        state.locked_amount -= net_payout;
-        result = "win"
-    } else if user_rand == contract_rand {
-        // 平局: 退还下注
-        let refund = Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(sent_amount),
+    let mut response = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous_version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("from_state_version", from_state_version.to_string())
+        .add_attribute("to_state_version", CURRENT_STATE_VERSION.to_string());
+
+    // 逐级升级：每个例程只负责从上一版本升级到下一版本
+    for version in from_state_version..CURRENT_STATE_VERSION {
+        let step = match version {
+            1 => migrate_state_v1_to_v2(deps.branch(), &env)?,
+            _ => return Err(ContractError::NoMigrationPath { version }),
         };
-        response = response.add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![refund],
-        });
-        state.locked_amount -= sent_amount;
-        result = "tie"
+        response = response
+            .add_submessages(step.messages)
+            .add_attributes(step.attributes);
     }
 
-    // 更新锁仓金额
-    // 用户输: 无需操作（资金留在合约）
-    STATE.save(deps.storage, &state)?;
-
-    Ok(response
-        .add_attribute("action", "play_war")
-        .add_attribute("user_rand", user_rand.to_string())
-        .add_attribute("contract_rand", contract_rand.to_string())
-        .add_attribute("result", result))
+    STATE_VERSION.save(deps.storage, &CURRENT_STATE_VERSION)?;
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(response)
 }
 
-/// 老虎机游戏
-/// Slot 游戏
-///
-/// Basic 模式  (mode = basic)  : 3 轮 × 1 行
-///   - 3 个相同符号（Wild 可替代）→ 全额倍率
-///   - 2 个相同符号              → 半额倍率
-///   - 全不同                    → 无奖励
-///
-/// Advanced 模式 (mode = advanced) : 5 轮 × 3 行，5 条赢线
-///   - 每条赢线从左起连续 3/4/5 个相同（Wild 可替代）→ 倍率累加
-///   - Scatter 出现 3/4/5+ 个 → 额外奖励倍率 5/15/50
-///   - 总倍率 = 所有赢线倍率 + Scatter 奖励倍率
-///
-/// 下注范围：Basic 100,000 – 10,000,000 uatom
-///          Advanced 200,000 – 10,000,000 uatom（5 线消耗更高）
-fn play_slot(deps: DepsMut, env: Env, info: MessageInfo, mode: SlotMode) -> StdResult<Response> {
-    // ── 验证投注金额 ──────────────────────────────
-    let sent_amount = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+// ──────────────────────────────────────────────────────────────────────────────
+// 角色与所有权
+//
+// 所有者（可以是多签）拥有全部角色，并负责授予 / 撤销角色、解除暂停；
+// 日常操作交给单独授予的角色：财务（注入流动性、提款到所有者地址）、
+// 风控（抽水、单注上限、玩法配置、调用方限制）、暂停人（紧急暂停、关闭玩法）、
+// 运维（提交 / 揭示服务端种子）。
+// 所有权转移分两步：所有者提议，新所有者接受后才生效。
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// 检查调用者拥有某角色（所有者拥有全部角色）
+fn ensure_role(
+    storage: &dyn Storage,
+    state: &State,
+    sender: &Addr,
+    role: Role,
+) -> Result<(), ContractError> {
+    if *sender == state.owner || ROLE_MEMBERS.has(storage, (role.as_str(), sender)) {
+        return Ok(());
+    }
+    Err(ContractError::Unauthorized)
+}
 
-    let (min_bet, max_bet) = match mode {
-        SlotMode::Basic    => (100_000u128,  10_000_000u128),
-        SlotMode::Advanced => (200_000u128,  10_000_000u128),
-        SlotMode::Mega     => (500_000u128,  10_000_000u128),
+/// 授予或撤销角色（仅限所有者）
+fn update_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+    granted: bool,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized);
+    }
+    let member = deps.api.addr_validate(&address)?;
+    let action = if granted {
+        ROLE_MEMBERS.save(deps.storage, (role.as_str(), &member), &true)?;
+        "grant_role"
+    } else {
+        ROLE_MEMBERS.remove(deps.storage, (role.as_str(), &member));
+        "revoke_role"
     };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("role", role.as_str())
+        .add_attribute("address", member))
+}
 
-    if sent_amount < min_bet || sent_amount > max_bet {
-        return Err(StdError::generic_err(format!(
-            "Bet must be between {} and {} uatom for {:?} mode",
-            min_bet, max_bet, mode
-        )));
+/// 提议转移所有权（仅限所有者），新所有者接受后生效
+fn transfer_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized);
     }
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    state.pending_owner = Some(new_owner.clone());
+    STATE.save(deps.storage, &state)?;
+    Ok(Response::new()
+        .add_attribute("action", "transfer_ownership")
+        .add_attribute("owner", state.owner)
+        .add_attribute("pending_owner", new_owner))
+}
 
+/// 被提议的新所有者接受所有权
+fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += sent_amount;
+    if state.pending_owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::NotPendingOwner);
+    }
+    let previous_owner = std::mem::replace(&mut state.owner, info.sender.clone());
+    state.pending_owner = None;
+    STATE.save(deps.storage, &state)?;
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("previous_owner", previous_owner)
+        .add_attribute("owner", info.sender))
+}
 
-    // ── 生成随机数并构建符号 ──────────────────────
-    let mut response = Response::new()
-        .add_attribute("mode", format!("{:?}", mode))
-        .add_attribute("bet_amount", sent_amount.to_string());
+/// 取消尚未接受的所有权转移（仅限所有者）
+fn cancel_ownership_transfer(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized);
+    }
+    if state.pending_owner.take().is_none() {
+        return Err(ContractError::NoPendingOwnershipTransfer);
+    }
+    STATE.save(deps.storage, &state)?;
+    Ok(Response::new().add_attribute("action", "cancel_ownership_transfer"))
+}
 
-    let payout_multiplier: u64;
+/// 查询某地址拥有的角色
+fn query_roles(deps: Deps, address: String) -> Result<AddressRolesResponse, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    let roles = Role::ALL
+        .into_iter()
+        .filter(|role| ROLE_MEMBERS.has(deps.storage, (role.as_str(), &address)))
+        .collect();
+    Ok(AddressRolesResponse {
+        is_owner: address == state.owner,
+        address,
+        roles,
+    })
+}
 
-    match mode {
-        // ── Basic：3 轮 1 行 ─────────────────────
-        SlotMode::Basic => {
-            let r1 = utils::generate_random_number(&info, &env, b"slot_b1");
-            let r2 = utils::generate_random_number(&info, &env, b"slot_b2");
-            let r3 = utils::generate_random_number(&info, &env, b"slot_b3");
+// ──────────────────────────────────────────────────────────────────────────────
+// 管理操作时间锁
+//
+// 提款、修改抽水、修改玩法配置和修改时间锁先进入待执行队列（见 timelock.rs），
+// 到期后由具备相应角色的地址执行；到期前该角色或暂停人可以取消。
+// 延迟为 0 时提议即执行，与没有时间锁时的行为一致。
+// ──────────────────────────────────────────────────────────────────────────────
 
-            let s1 = Symbol::from_u8(r1);
-            let s2 = Symbol::from_u8(r2);
-            let s3 = Symbol::from_u8(r3);
+/// 校验调用者可以提议 / 执行该管理操作
+fn ensure_admin_action_allowed(
+    storage: &dyn Storage,
+    state: &State,
+    sender: &Addr,
+    action: &AdminAction,
+) -> Result<(), ContractError> {
+    match action.role() {
+        Some(role) => ensure_role(storage, state, sender, role),
+        None if *sender == state.owner => Ok(()),
+        None => Err(ContractError::Unauthorized),
+    }
+}
 
-            response = response
-                .add_attribute("reel1", format!("{:?}", s1))
-                .add_attribute("reel2", format!("{:?}", s2))
-                .add_attribute("reel3", format!("{:?}", s3));
+/// 提议管理操作：没有时间锁时立即执行，否则排队等待到期
+fn propose_admin_action(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    action: AdminAction,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    ensure_admin_action_allowed(deps.storage, &state, &info.sender, &action)?;
+    action.validate()?;
+
+    let timelock = TIMELOCK.may_load(deps.storage)?.unwrap_or_default();
+    if timelock.is_zero() {
+        return apply_admin_action(deps, action);
+    }
+
+    let id = ADMIN_ACTION_COUNT.may_load(deps.storage)?.unwrap_or(0) + 1;
+    ADMIN_ACTION_COUNT.save(deps.storage, &id)?;
+    let now = env.block.time.seconds();
+    let pending = PendingAdminAction {
+        id,
+        proposer: info.sender,
+        queued_height: env.block.height,
+        queued_time: now,
+        ready_height: env.block.height + timelock.delay_blocks,
+        ready_time: now + timelock.delay_seconds,
+        action,
+    };
+    ADMIN_ACTIONS.save(deps.storage, id, &pending)?;
 
-            let result = evaluate_basic(&s1, &s2, &s3);
-            payout_multiplier = result.multiplier;
-            response = response.add_attribute("win_desc", result.description);
-        }
+    Ok(Response::new()
+        .add_attribute("action", "propose_admin_action")
+        .add_attribute("admin_action", pending.action.name())
+        .add_attribute("admin_action_id", id.to_string())
+        .add_attribute("ready_height", pending.ready_height.to_string())
+        .add_attribute("ready_time", pending.ready_time.to_string()))
+}
 
-        // ── Advanced：5 轮 3 行 5 赢线 ───────────
-        SlotMode::Advanced => {
-            // 生成 5 列 × 3 行 = 15 个随机数
-            let salts: &[&[u8]; 15] = &[
-                b"adv00", b"adv01", b"adv02",
-                b"adv10", b"adv11", b"adv12",
-                b"adv20", b"adv21", b"adv22",
-                b"adv30", b"adv31", b"adv32",
-                b"adv40", b"adv41", b"adv42",
-            ];
-
-            let mut rands = [0u32; 15];
-            for (i, salt) in salts.iter().enumerate() {
-                rands[i] = utils::generate_random_number(&info, &env, salt);
-            }
+/// 执行已到期的管理操作
+fn execute_admin_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let pending = ADMIN_ACTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::AdminActionNotFound { id })?;
+    let state = STATE.load(deps.storage)?;
+    ensure_admin_action_allowed(deps.storage, &state, &info.sender, &pending.action)?;
+    if !pending.is_ready(env.block.height, env.block.time.seconds()) {
+        return Err(ContractError::AdminActionNotReady {
+            id,
+            height: pending.ready_height,
+            time: pending.ready_time,
+        });
+    }
 
-            // 构建 grid[col][row]
-            let grid: [[Symbol; 3]; 5] = [
-                [Symbol::from_u8(rands[0]),  Symbol::from_u8(rands[1]),  Symbol::from_u8(rands[2])],
-                [Symbol::from_u8(rands[3]),  Symbol::from_u8(rands[4]),  Symbol::from_u8(rands[5])],
-                [Symbol::from_u8(rands[6]),  Symbol::from_u8(rands[7]),  Symbol::from_u8(rands[8])],
-                [Symbol::from_u8(rands[9]),  Symbol::from_u8(rands[10]), Symbol::from_u8(rands[11])],
-                [Symbol::from_u8(rands[12]), Symbol::from_u8(rands[13]), Symbol::from_u8(rands[14])],
-            ];
+    ADMIN_ACTIONS.remove(deps.storage, id);
+    Ok(apply_admin_action(deps, pending.action)?.add_attribute("admin_action_id", id.to_string()))
+}
 
-            // 输出每列每行到 attributes
-            for col in 0..5usize {
-                for row in 0..3usize {
-                    response = response.add_attribute(
-                        format!("reel{}_{}", col + 1, row + 1),
-                        format!("{:?}", grid[col][row]),
-                    );
-                }
-            }
+/// 取消待执行的管理操作（该操作的角色或暂停人）
+fn cancel_admin_action(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let pending = ADMIN_ACTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::AdminActionNotFound { id })?;
+    let state = STATE.load(deps.storage)?;
+    ensure_admin_action_allowed(deps.storage, &state, &info.sender, &pending.action)
+        .or_else(|_| ensure_role(deps.storage, &state, &info.sender, Role::Pauser))?;
 
-            let (total_mult, descriptions) = evaluate_advanced(&grid);
-            payout_multiplier = total_mult;
-            response = response.add_attribute("win_desc", descriptions.join("|"));
+    ADMIN_ACTIONS.remove(deps.storage, id);
+    Ok(Response::new()
+        .add_attribute("action", "cancel_admin_action")
+        .add_attribute("admin_action", pending.action.name())
+        .add_attribute("admin_action_id", id.to_string()))
+}
+
+/// 执行管理操作（权限与参数已校验）
+fn apply_admin_action(deps: DepsMut, action: AdminAction) -> Result<Response, ContractError> {
+    match action {
+        AdminAction::Withdraw { amount, denom } => withdraw_funds(deps, amount, denom),
+        AdminAction::UpdateHouseEdge { new_house_edge_bps } => {
+            update_house_edge(deps, new_house_edge_bps)
+        }
+        AdminAction::UpdateGameConfig { game, config } => update_game_config(deps, game, config),
+        AdminAction::UpdateTimelock { config } => {
+            TIMELOCK.save(deps.storage, &config)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_timelock")
+                .add_attribute("delay_blocks", config.delay_blocks.to_string())
+                .add_attribute("delay_seconds", config.delay_seconds.to_string()))
+        }
+        AdminAction::UpdateGameTimeout { timeout } => {
+            GAME_TIMEOUT.save(deps.storage, &timeout)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_game_timeout")
+                .add_attribute("timeout_blocks", timeout.timeout_blocks.to_string())
+                .add_attribute("timeout_seconds", timeout.timeout_seconds.to_string()))
+        }
+        AdminAction::UpdateBlackjackRules { rules } => {
+            BLACKJACK_RULES.save(deps.storage, &rules)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_blackjack_rules")
+                .add_attribute("decks", rules.decks.to_string())
+                .add_attribute("dealer_hits_soft_17", rules.dealer_hits_soft_17.to_string()))
         }
+    }
+}
 
-        // ── Mega：6 轮 4 行 10 赢线 + 免费旋转 + Jackpot ──
-        SlotMode::Mega => {
-            // 生成 6 列 × 4 行 = 24 个随机数
-            let salts: &[&[u8]; 24] = &[
-                b"meg00", b"meg01", b"meg02", b"meg03",
-                b"meg10", b"meg11", b"meg12", b"meg13",
-                b"meg20", b"meg21", b"meg22", b"meg23",
-                b"meg30", b"meg31", b"meg32", b"meg33",
-                b"meg40", b"meg41", b"meg42", b"meg43",
-                b"meg50", b"meg51", b"meg52",
-                b"meg53",
-            ];
-
-            let mut rands = [0u32; 24];
-            for (i, salt) in salts.iter().enumerate() {
-                rands[i] = utils::generate_random_number(&info, &env, salt);
-            }
+// ──────────────────────────────────────────────────────────────────────────────
+// 多步游戏超时
+//
+// 开局超过配置的时间后任何人（通常是 keeper）都可以代玩家结算，规则见 expiry.rs。
+// 结算本身与停牌 / 摊牌 / 弃牌走同一套逻辑，随后删除牌局记录并发出 game_expired 事件。
+// ──────────────────────────────────────────────────────────────────────────────
 
-            // 构建 grid[col][row]，6 列 × 4 行
-            let grid: [[Symbol; 4]; 6] = [
-                [Symbol::from_u8(rands[0]),  Symbol::from_u8(rands[1]),  Symbol::from_u8(rands[2]),  Symbol::from_u8(rands[3])],
-                [Symbol::from_u8(rands[4]),  Symbol::from_u8(rands[5]),  Symbol::from_u8(rands[6]),  Symbol::from_u8(rands[7])],
-                [Symbol::from_u8(rands[8]),  Symbol::from_u8(rands[9]),  Symbol::from_u8(rands[10]), Symbol::from_u8(rands[11])],
-                [Symbol::from_u8(rands[12]), Symbol::from_u8(rands[13]), Symbol::from_u8(rands[14]), Symbol::from_u8(rands[15])],
-                [Symbol::from_u8(rands[16]), Symbol::from_u8(rands[17]), Symbol::from_u8(rands[18]), Symbol::from_u8(rands[19])],
-                [Symbol::from_u8(rands[20]), Symbol::from_u8(rands[21]), Symbol::from_u8(rands[22]), Symbol::from_u8(rands[23])],
-            ];
+/// 结算超时的多步游戏（任何人都可调用）
+fn expire_game(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    game: GameKind,
+    player: String,
+) -> Result<Response, ContractError> {
+    let player = deps.api.addr_validate(&player)?;
+    let timeout = GAME_TIMEOUT.may_load(deps.storage)?.unwrap_or_default();
+    let ensure_expired = |finished: bool, start_height: u64, start_time: u64| {
+        if finished {
+            return Err(ContractError::GameFinished);
+        }
+        let (height, time) = (env.block.height, env.block.time.seconds());
+        if !timeout.is_expired(start_height, start_time, height, time) {
+            let (height, time) = timeout.expires_at(start_height, start_time);
+            return Err(ContractError::GameNotExpired { game, height, time });
+        }
+        Ok(())
+    };
+    let not_found = ContractError::GameNotFound { game };
+
+    let (response, bet_id, resolution) = match game {
+        // 21 点：按当前手牌停牌，庄家补牌后比牌
+        GameKind::Blackjack => {
+            let state = BLACKJACK_STATE.may_load(deps.storage, &player)?.ok_or(not_found)?;
+            ensure_expired(state.finished, state.start_height, state.start_time)?;
+            let bet_id = state.bet_id;
+            let stand = Response::new().add_attribute("action", BlackjackAction::Stand.name());
+            let response = settle_blackjack(deps.branch(), &env, &player, state, stand)?;
+            BLACKJACK_STATE.remove(deps.storage, &player);
+            (response, bet_id, "dealer_plays_out")
+        }
+        // 奥马哈：已跟齐注额时自动摊牌，否则弃牌
+        GameKind::Omaha => {
+            let state = OMAHA_STATE.may_load(deps.storage, &player)?.ok_or(not_found)?;
+            ensure_expired(state.finished, state.start_height, state.start_time)?;
+            let bet_id = state.bet_id;
+            let (response, resolution) = if state.player_total_bet < state.current_call_amount {
+                (fold_omaha(deps.branch(), &player, state)?, "forfeit")
+            } else {
+                (settle_omaha(deps.branch(), &env, &player, state)?, "showdown")
+            };
+            OMAHA_STATE.remove(deps.storage, &player);
+            (response, bet_id, resolution)
+        }
+        // 德州：同奥马哈
+        GameKind::Texas => {
+            let mut state = TEXAS_STATE.may_load(deps.storage, &player)?.ok_or(not_found)?;
+            ensure_expired(state.finished, state.start_height, state.start_time)?;
+            let bet_id = state.bet_id;
+            let (response, resolution) = if state.player_total_bet < state.current_call_amount {
+                (fold_texas(deps.branch(), &player, state)?, "forfeit")
+            } else {
+                reveal_texas_board(deps.storage, &mut state)?;
+                (settle_texas(deps.branch(), &env, &player, state)?, "showdown")
+            };
+            TEXAS_STATE.remove(deps.storage, &player);
+            (response, bet_id, resolution)
+        }
+        game => return Err(ContractError::NotAMultiStepGame { game }),
+    };
 
-            // 输出每列每行到 attributes
-            for col in 0..6usize {
-                for row in 0..4usize {
-                    response = response.add_attribute(
-                        format!("reel{}_{}", col + 1, row + 1),
-                        format!("{:?}", grid[col][row]),
-                    );
-                }
-            }
+    Ok(response.add_event(
+        Event::new("game_expired")
+            .add_attribute("game", game.to_string())
+            .add_attribute("bet_id", bet_id.to_string())
+            .add_attribute("player", player)
+            .add_attribute("resolution", resolution)
+            .add_attribute("expired_by", info.sender),
+    ))
+}
 
-            let mega_result = evaluate_mega(&grid);
-            payout_multiplier = mega_result.total_multiplier;
-            let mut desc_parts = mega_result.descriptions;
-            if mega_result.free_spin_triggered {
-                desc_parts.push(format!("free_spin:triggered(x{})", mega_result.free_spin_multiplier));
-            }
-            if mega_result.jackpot {
-                desc_parts.push("jackpot:true".to_string());
-            }
-            response = response
-                .add_attribute("win_desc", desc_parts.join("|"))
-                .add_attribute("free_spin", mega_result.free_spin_triggered.to_string())
-                .add_attribute("jackpot", mega_result.jackpot.to_string());
+// ──────────────────────────────────────────────────────────────────────────────
+// 合约迁移
+//
+// 每个存储结构版本对应一个升级例程（版本说明见 migrate.rs），migrate 按顺序执行。
+// 例程先用旧结构读出数据，再按当前结构写回；进行中的牌局要么补齐新字段后继续，
+// 要么（无法在新逻辑下继续时）退还下注并结束。
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// 存储结构版本 1 → 2
+///
+/// - 单一 uatom 锁仓改为多币种锁仓，新增的全局字段取默认值
+/// - 进行中的奥马哈 / 德州牌局可以继续：按已下注总额补上最坏情况赔付预留，
+///   并以迁移所在区块作为开局时间，升级后玩家仍有完整的超时窗口；
+///   可用资金不足以预留时退还该局已下注总额并结束牌局
+/// - 进行中的 21 点没有牌靴，无法继续发牌：退还下注并结束牌局
+fn migrate_state_v1_to_v2(mut deps: DepsMut, env: &Env) -> Result<Response, ContractError> {
+    let legacy = STATE_V1.load(deps.storage)?;
+    let mut state = State {
+        owner: legacy.owner,
+        locked_amounts: vec![],
+        house_edge_bps: legacy.house_edge_bps,
+        denoms: vec![DenomConfig::uatom()],
+        reserved_amounts: vec![],
+        max_payout_bps: default_max_payout_bps(),
+        paused: false,
+        pending_owner: None,
+    };
+    state.lock(DEFAULT_DENOM, legacy.locked_amount);
+
+    // 奥马哈 / 德州：旧牌局没有下注记录，无法按种子重新洗出未发的牌。全部改写为当前结构
+    // （去掉存储的牌序），进行中的牌局退还已下注总额并结束
+    let mut poker_refunds: Vec<(Bettor, u128)> = vec![];
+    let omaha_games = OMAHA_STATE
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (player, mut game) in omaha_games {
+        if !game.finished {
+            let bettor = Bettor {
+                player: player.clone(),
+                denom: game.denom.clone(),
+                source: game.source,
+            };
+            poker_refunds.push((bettor, game.player_total_bet.u128()));
+            game.finished = true;
         }
+        OMAHA_STATE.save(deps.storage, &player, &game)?;
+    }
+    let texas_games = TEXAS_STATE
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (player, mut game) in texas_games {
+        if !game.finished {
+            let bettor = Bettor {
+                player: player.clone(),
+                denom: game.denom.clone(),
+                source: game.source,
+            };
+            poker_refunds.push((bettor, game.player_total_bet.u128()));
+            game.finished = true;
+        }
+        TEXAS_STATE.save(deps.storage, &player, &game)?;
     }
+    STATE.save(deps.storage, &state)?;
 
-    // ── 结算 ──────────────────────────────────────
-    if payout_multiplier > 0 {
-        let gross_payout = sent_amount * payout_multiplier as u128;
-        let payout_amount = apply_house_edge(gross_payout, sent_amount, state.house_edge_bps);
-
-        // 防止合约余额不足时超额赔付
-        if payout_amount > state.locked_amount {
-            return Err(StdError::generic_err("Contract has insufficient funds for payout"));
+    let mut refunds: Vec<CosmosMsg> = vec![];
+    let refunded_poker_games = poker_refunds.len();
+    for (bettor, amount) in poker_refunds {
+        refunds.extend(settle_payout(deps.branch(), env, &bettor, amount)?);
+    }
+
+    // 21 点：全部改写为当前结构（没有牌靴，牌面无法还原，留空），进行中的牌局退还下注
+    let mut refunded_games = 0u32;
+    let blackjack_games = BLACKJACK_STATE_V1
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (player, game) in blackjack_games {
+        if !game.finished {
+            let bettor = Bettor {
+                player: player.clone(),
+                denom: DEFAULT_DENOM.to_string(),
+                source: FundsSource::Attached,
+            };
+            refunds.extend(settle_payout(deps.branch(), env, &bettor, game.bet.u128())?);
+            refunded_games += 1;
         }
-
-        state.locked_amount -= payout_amount;
-        response = response
-            .add_attribute("result", "win")
-            .add_attribute("payout_multiplier", payout_multiplier.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout_amount),
-                }],
-            });
-    } else {
-        response = response.add_attribute("result", "lost");
+        let hand = BlackjackHand {
+            stood: true,
+            ..BlackjackHand::new(vec![], game.bet)
+        };
+        let migrated = BlackjackState {
+            hands: vec![hand],
+            active_hand: 0,
+            dealer_cards: vec![],
+            bet: game.bet,
+            insurance: Uint128::zero(),
+            peeked: true,
+            finished: true,
+            source: FundsSource::Attached,
+            denom: DEFAULT_DENOM.to_string(),
+            reserved: Uint128::zero(),
+            bet_id: 0,
+            start_height: 0,
+            start_time: 0,
+            rules: BlackjackRules::default(),
+            side_bets: BlackjackSideBets::default(),
+            side_payout: Uint128::zero(),
+        };
+        BLACKJACK_STATE.save(deps.storage, &player, &migrated)?;
     }
 
-    STATE.save(deps.storage, &state)?;
-
-    Ok(response)
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_attribute("refunded_poker_games", refunded_poker_games.to_string())
+        .add_attribute("refunded_blackjack_games", refunded_games.to_string()))
 }
 
-/// 猜数字游戏(范围 1 ～ 10)
-///
-/// 合约生成一个随机数,如果用户猜对,获得奖励。(完全猜中 x10、相邻 x1）
-fn play_guess_number(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    user_guess: u8,
-) -> StdResult<Response> {
-    if user_guess < 1 || user_guess > 10 {
-        return Err(StdError::generic_err("Guess must be between 1 and 10"));
-    }
-
-    let sent_amount = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+// ──────────────────────────────────────────────────────────────────────────────
+// 调用方限制
+//
+// 合约可以通过子消息调用 Play*，在同一交易内读取结果属性或余额变化，
+// 输了就让整笔交易回滚。开启 eoa_only 后只接受外部账户（以及白名单内的
+// 可信集成方合约）的游戏消息。
+// ──────────────────────────────────────────────────────────────────────────────
 
-    if sent_amount < 100_000 || sent_amount > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+/// 检查游戏消息的调用方：eoa_only 开启时，非白名单合约一律拒绝
+fn ensure_caller_allowed(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if !EOA_ONLY.may_load(deps.storage)?.unwrap_or(true) {
+        return Ok(());
+    }
+    if ALLOWED_CONTRACTS.has(deps.storage, sender) {
+        return Ok(());
+    }
+    // 能查到合约信息说明调用方是合约
+    if deps.querier.query_wasm_contract_info(sender).is_ok() {
+        return Err(ContractError::ContractNotAllowed {
+            address: sender.to_string(),
+        });
     }
+    Ok(())
+}
 
-    // 锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += sent_amount;
+/// 开启 / 关闭仅外部账户模式（风控）
+fn set_eoa_only(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    ensure_role(deps.storage, &state, &info.sender, Role::RiskManager)?;
+    EOA_ONLY.save(deps.storage, &enabled)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_eoa_only")
+        .add_attribute("eoa_only", enabled.to_string()))
+}
 
-    // 生成合约伪随机数 1~10
-    let rand = utils::generate_random_number(&info, &env, b"guess") % 10 + 1;
+/// 将合约加入或移出白名单（风控）
+fn update_allowed_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    allowed: bool,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    ensure_role(deps.storage, &state, &info.sender, Role::RiskManager)?;
+    let contract = deps.api.addr_validate(&address)?;
+    let action = if allowed {
+        ALLOWED_CONTRACTS.save(deps.storage, &contract, &true)?;
+        "allow_contract"
+    } else {
+        ALLOWED_CONTRACTS.remove(deps.storage, &contract);
+        "disallow_contract"
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("contract", contract))
+}
 
-    let mut payout = 0;
-    let mut result = "lost";
+// ──────────────────────────────────────────────────────────────────────────────
+// 熔断（紧急暂停 / 玩法开关）
+//
+// 发现赔付漏洞时，暂停人（或所有者）可以暂停整个合约或关闭单个玩法：
+// 新下注和多步游戏的加注 / 跟注 / 全押都会被拒绝；已下的注不受影响：
+// 待开奖下注照常结算，进行中的牌局仍可停牌、弃牌、摊牌，种子未揭示的待开奖下注可立即退款。
+// 恢复只能由所有者操作。
+// ──────────────────────────────────────────────────────────────────────────────
 
-    if user_guess as u32 == rand {
-        payout = apply_house_edge(sent_amount * 10, sent_amount, state.house_edge_bps);
-        result = "exact";
-    } else if (user_guess as i32 - rand as i32).abs() == 1 {
-        payout = apply_house_edge(sent_amount * 1, sent_amount, state.house_edge_bps);
-        result = "adjacent";
+/// 熔断检查：暂停或玩法关闭时拒绝新增下注；已下注的结算和退款不受影响
+fn check_circuit_breaker(storage: &dyn Storage, msg: &ExecuteMsg) -> Result<(), ContractError> {
+    let game = match msg {
+        ExecuteMsg::PlayFromBalance { game, .. } if game.adds_stake() => *game.clone(),
+        play if play.adds_stake() => play.clone(),
+        _ => return Ok(()),
+    };
+    if STATE.load(storage)?.paused {
+        return Err(ContractError::Paused);
     }
+    let kind = GameKind::from_msg(&game)?;
+    if !game_config(storage, kind)?.enabled {
+        return Err(ContractError::GameDisabled { game: kind });
+    }
+    Ok(())
+}
 
-    let mut response = Response::new();
-
-    if payout > 0 {
-        response = response.add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![Coin {
-                denom: "uatom".to_string(),
-                amount: Uint128::from(payout),
-            }],
-        });
+/// 合约已暂停或该玩法已关闭
+fn game_halted(storage: &dyn Storage, game: &ExecuteMsg) -> Result<bool, ContractError> {
+    Ok(STATE.load(storage)?.paused || !game_config(storage, GameKind::from_msg(game)?)?.enabled)
+}
 
-        state.locked_amount = state.locked_amount.saturating_sub(payout);
+/// 暂停（暂停人）/ 解除暂停（仅所有者）
+fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    if paused {
+        ensure_role(deps.storage, &state, &info.sender, Role::Pauser)?;
+    } else if info.sender != state.owner {
+        return Err(ContractError::Unauthorized);
     }
-
+    state.paused = paused;
     STATE.save(deps.storage, &state)?;
+    Ok(Response::new()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_attribute("sender", info.sender))
+}
 
-    Ok(response
-        .add_attribute("action", "play_guess_number")
-        .add_attribute("user_guess", user_guess.to_string())
-        .add_attribute("correct_number", rand.to_string())
-        .add_attribute("result", result))
+/// 关闭（暂停人）/ 重新开放（仅所有者）单个玩法
+fn set_game_enabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    game: GameKind,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if enabled {
+        if info.sender != state.owner {
+            return Err(ContractError::Unauthorized);
+        }
+    } else {
+        ensure_role(deps.storage, &state, &info.sender, Role::Pauser)?;
+    }
+    let mut config = game_config(deps.storage, game)?;
+    config.enabled = enabled;
+    GAME_CONFIG.save(deps.storage, game, &config)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_game_enabled")
+        .add_attribute("game", game.to_string())
+        .add_attribute("enabled", enabled.to_string()))
 }
 
-// 处理提款逻辑（仅限所有者）
-fn withdraw_funds(deps: DepsMut, info: MessageInfo, amount: u128) -> StdResult<Response> {
-    // 提取的钱不少于 0 uatom
-    if amount == 0 {
-        return Err(StdError::generic_err("Invalid amount"));
+/// 查询熔断状态
+fn query_circuit_breaker(deps: Deps) -> Result<CircuitBreakerResponse, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let mut disabled_games = vec![];
+    for game in GameKind::ALL {
+        if !game_config(deps.storage, game)?.enabled {
+            disabled_games.push(game);
+        }
     }
+    Ok(CircuitBreakerResponse {
+        paused: state.paused,
+        disabled_games,
+    })
+}
 
-    let mut state = STATE.load(deps.storage)?;
+// ──────────────────────────────────────────────────────────────────────────────
+// 玩家余额
+//
+// 玩家可先 deposit 存入可下注币种，之后用 play_from_balance 从余额扣款下注，
+// 赔付和退款记回余额而不是逐笔 BankMsg 转账，最后用 withdraw_balance 一次取出。
+// 余额属于玩家，不计入 locked_amount；下注时才从余额转入锁仓。
+// 多步游戏的赔付按开局下注的资金来源返还。
+// ──────────────────────────────────────────────────────────────────────────────
 
-    // 检查调用者是否为所有者
-    if info.sender != state.owner {
-        return Err(StdError::generic_err("Unauthorized"));
+/// 存入余额：附带的可下注币种逐一记入余额
+fn deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let funds: Vec<&Coin> = info.funds.iter().filter(|c| !c.amount.is_zero()).collect();
+    if funds.is_empty() {
+        return Err(ContractError::NoFunds { action: "deposit" });
     }
 
-    // 检查可提款余额
-    if amount > state.locked_amount {
-        return Err(StdError::generic_err("Insufficient locked funds"));
+    let mut response = Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("player", info.sender.clone());
+    for coin in funds {
+        state.denom_config(&coin.denom)?;
+        let balance = credit_balance(deps.storage, &info.sender, &coin.denom, coin.amount)?;
+        response = response
+            .add_attribute("denom", coin.denom.clone())
+            .add_attribute("amount", coin.amount.to_string())
+            .add_attribute("balance", balance.to_string());
     }
+    Ok(response)
+}
 
-    // 更新锁仓金额
-    state.locked_amount -= amount;
-    STATE.save(deps.storage, &state)?;
-
-    // 发送代币
-    let payout = Coin {
-        denom: "uatom".to_string(),
-        amount: Uint128::from(amount),
+/// 提取余额（amount 为空时全部提取）
+fn withdraw_balance(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let config = state.denom_or_primary(denom)?;
+    let denom = config.denom.clone();
+    let amount = match amount {
+        Some(amount) => amount,
+        None => PLAYER_BALANCES
+            .may_load(deps.storage, (&info.sender, &denom))?
+            .unwrap_or_default(),
     };
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw);
+    }
 
+    let balance = debit_balance(deps.storage, &info.sender, &denom, amount)?;
     Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: state.owner.to_string(),
-            amount: vec![payout],
-        })
-        .add_attribute("action", "withdraw")
-        .add_attribute("amount", amount.to_string()))
+        .add_message(config.transfer_msg(&info.sender, amount)?)
+        .add_attribute("action", "withdraw_balance")
+        .add_attribute("player", info.sender)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("balance", balance.to_string()))
 }
 
-/// 修改庄家抽水比例（仅限所有者）
-///
-/// 抽水比例以基点表示，100 基点 = 1%，最高 1000 基点 = 10%
-fn update_house_edge(
+/// 从余额扣除 amount，作为附带资金执行游戏消息
+fn play_from_balance(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_house_edge_bps: u16,
-) -> StdResult<Response> {
-    let mut state = STATE.load(deps.storage)?;
-
-    // 检查调用者是否为所有者
-    if info.sender != state.owner {
-        return Err(StdError::generic_err("Unauthorized"));
+    amount: Uint128,
+    denom: Option<String>,
+    game: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {
+            reason: "Do not attach funds when playing from balance",
+        });
     }
-
-    // 验证抽水比例不超过 10%
-    if new_house_edge_bps > 1000 {
-        return Err(StdError::generic_err(
-            "House edge must not exceed 1000 bps (10%)",
-        ));
+    if matches!(game, ExecuteMsg::PlayFromBalance { .. }) || !game.is_play() {
+        return Err(ContractError::NotAPlayMessage {
+            context: "played from balance",
+        });
     }
 
-    let old_bps = state.house_edge_bps;
-    state.house_edge_bps = new_house_edge_bps;
-    STATE.save(deps.storage, &state)?;
+    let state = STATE.load(deps.storage)?;
+    let denom = state.denom_or_primary(denom)?.denom.clone();
+    let mut funds = vec![];
+    if !amount.is_zero() {
+        debit_balance(deps.storage, &info.sender, &denom, amount)?;
+        funds.push(Coin { denom, amount });
+    }
+    let info = MessageInfo {
+        sender: info.sender,
+        funds,
+    };
+    execute_play(deps, env, info, game, FundsSource::Balance)
+}
 
-    Ok(Response::new()
-        .add_attribute("action", "update_house_edge")
-        .add_attribute("old_house_edge_bps", old_bps.to_string())
-        .add_attribute("new_house_edge_bps", new_house_edge_bps.to_string()))
+/// 增加玩家某币种余额，返回新余额
+fn credit_balance(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let key = (player, denom);
+    let balance = PLAYER_BALANCES.may_load(storage, key)?.unwrap_or_default() + amount;
+    PLAYER_BALANCES.save(storage, key, &balance)?;
+    let total = PLAYER_BALANCE_TOTALS.may_load(storage, denom)?.unwrap_or_default() + amount;
+    PLAYER_BALANCE_TOTALS.save(storage, denom, &total)?;
+    Ok(balance)
 }
 
-/// 对净赢利部分扣除庄家抽水
-///
-/// `gross_payout`  = 玩家总共应收到的金额（包含本金 + 赢利）
-/// `original_bet`  = 玩家原始下注额
-/// `house_edge_bps` = 抽水基点（0-1000）
-///
-/// 抽水仅作用于净赢利部分（gross_payout - original_bet），不影响退还本金。
-/// 返回扣除抽水后的实际支付金额。
-fn apply_house_edge(gross_payout: u128, original_bet: u128, house_edge_bps: u16) -> u128 {
-    if house_edge_bps == 0 || gross_payout <= original_bet {
-        return gross_payout; // 无抽水或无赢利（平局 / 亏损）
+/// 扣除玩家某币种余额，返回新余额
+fn debit_balance(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let key = (player, denom);
+    let balance = PLAYER_BALANCES.may_load(storage, key)?.unwrap_or_default();
+    if balance < amount {
+        return Err(ContractError::InsufficientBalance {
+            available: balance.u128(),
+            denom: denom.to_string(),
+        });
     }
-    let net_winnings = gross_payout - original_bet;
-    let rake = net_winnings * house_edge_bps as u128 / 10_000;
-    gross_payout - rake
+    let balance = balance - amount;
+    if balance.is_zero() {
+        PLAYER_BALANCES.remove(storage, key);
+    } else {
+        PLAYER_BALANCES.save(storage, key, &balance)?;
+    }
+    let total = PLAYER_BALANCE_TOTALS.may_load(storage, denom)?.unwrap_or_default();
+    PLAYER_BALANCE_TOTALS.save(storage, denom, &total.saturating_sub(amount))?;
+    Ok(balance)
 }
 
-/// 21 点游戏启动
-///
-/// 启动 21 点游戏, 用户下注金额必须介于 100,000 和 10,000,000 uatom 之间。
-fn play_blackjack_start(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-    // 验证下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
-
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+/// 向玩家支付：余额下注记回余额（返回 None），否则返回转回钱包的消息
+/// （原生币为 BankMsg，CW20 代币为 Transfer）
+fn pay_player(
+    storage: &mut dyn Storage,
+    bettor: &Bettor,
+    amount: Uint128,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    match bettor.source {
+        FundsSource::Attached => {
+            let state = STATE.load(storage)?;
+            let config = state.denom_config(&bettor.denom)?;
+            Ok(Some(config.transfer_msg(&bettor.player, amount)?))
+        }
+        FundsSource::Balance => {
+            credit_balance(storage, &bettor.player, &bettor.denom, amount)?;
+            Ok(None)
+        }
     }
+}
 
-    let bet_amount = Uint128::from(bet);
-
-    // 生成 4 张初始牌: 2 张牌是用户的、2 张牌是庄家的
-    let user_card1 = utils::generate_random_number(&info, &env, b"user1") % 10 + 1;
-    let user_card2 = utils::generate_random_number(&info, &env, b"user2") % 10 + 1;
-    let dealer_card1 = utils::generate_random_number(&info, &env, b"dealer1") % 10 + 1;
-    let dealer_card2 = utils::generate_random_number(&info, &env, b"dealer2") % 10 + 1;
+// ──────────────────────────────────────────────────────────────────────────────
+// 赔付敞口（Liability Reservation）
+//
+// 每笔下注在 place_bet 时按最坏情况（最高倍率，含本金、抽水前）预留赔付：
+// 单局游戏开奖时释放预留，再按实际结果赔付；多步游戏（21 点 / 奥马哈 / 德州）的
+// 预留开局后转入牌局状态，加注 / 跟注（21 点为加倍 / 分牌 / 保险）后按新的总下注额补足，
+// 牌局结束（结算、弃牌、退款）时释放。
+// 可用资金 = 锁仓 - 已预留；不足以覆盖新的预留时拒绝下注或加注，
+// 部署者也只能提取可用资金。下注时合约真实余额还必须覆盖全部预留。
+// ──────────────────────────────────────────────────────────────────────────────
 
-    // 保存游戏状态
-    let state = BlackjackState {
-        user_cards: vec![user_card1, user_card2],
-        dealer_cards: vec![dealer_card1, dealer_card2],
-        bet: bet_amount,
-        finished: false,
-    };
+/// 多步游戏最坏情况下的赔付（含本金、抽水前）
+fn max_multi_step_payout(total_bet: u128) -> u128 {
+    total_bet * MULTI_STEP_MAX_MULTIPLIER
+}
 
-    BLACKJACK_STATE.save(deps.storage, &info.sender, &state)?;
+/// 按新的总下注额补足牌局的预留敞口，可用资金不足时拒绝
+fn top_up_reservation(
+    state: &mut State,
+    denom: &str,
+    reserved: &mut Uint128,
+    total_bet: Uint128,
+) -> Result<(), ContractError> {
+    reserve_at_least(state, denom, reserved, max_multi_step_payout(total_bet.u128()))
+}
 
-    // 更新合约全局锁仓
-    let mut global_state = STATE.load(deps.storage)?;
-    global_state.locked_amount += bet;
-    STATE.save(deps.storage, &global_state)?;
+/// 把牌局的预留敞口补足到 required（已足够时不变），可用资金不足时拒绝
+fn reserve_at_least(
+    state: &mut State,
+    denom: &str,
+    reserved: &mut Uint128,
+    required: u128,
+) -> Result<(), ContractError> {
+    if required > reserved.u128() {
+        state.reserve(denom, required - reserved.u128())?;
+        *reserved = Uint128::from(required);
+    }
+    Ok(())
+}
 
-    // 构造返回
-    Ok(Response::new()
-        .add_attribute("action", "play_blackjack_start")
-        .add_attribute("user_card1", user_card1.to_string())
-        .add_attribute("user_card2", user_card2.to_string())
-        .add_attribute("dealer_card1", "hide") // 庄家的起手牌进行 hide
-        .add_attribute("dealer_card2", dealer_card2.to_string()))
+/// 奥马哈 / 德州追加下注（加注 / 跟注 / 全押）：按累计下注额校验下注上限后计入锁仓，
+/// 并按新的总下注额补足预留
+fn add_multi_step_stake(
+    storage: &mut dyn Storage,
+    game: GameKind,
+    denom: &str,
+    reserved: &mut Uint128,
+    stake: u128,
+    total_bet: Uint128,
+) -> Result<(), ContractError> {
+    let mut state = STATE.load(storage)?;
+    check_total_stake(storage, &state, game, denom, *reserved, total_bet.u128())?;
+    state.lock(denom, stake);
+    top_up_reservation(&mut state, denom, reserved, total_bet)?;
+    STATE.save(storage, &state)?;
+    Ok(())
 }
 
-/// 21 点玩家要牌
-///
-/// 当用户不超过 21 点数时, 用户可以继续要牌, 否则用户无法再要牌.
-fn play_blackjack_hit(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-    // 加载当前用户游戏状态
-    let mut state: BlackjackState = BLACKJACK_STATE.load(deps.storage, &info.sender)?;
+/// 牌局结束：释放为其预留的赔付敞口
+fn release_reservation(
+    storage: &mut dyn Storage,
+    denom: &str,
+    reserved: Uint128,
+) -> Result<(), ContractError> {
+    let mut state = STATE.load(storage)?;
+    state.release(denom, reserved.u128());
+    STATE.save(storage, &state)?;
+    Ok(())
+}
 
-    if state.finished {
-        return Err(StdError::generic_err("Game already finished"));
-    }
+/// 合约在某币种上可用于赔付的真实余额：原生币查询 bank 余额，CW20 代币查询代币合约，
+/// 再扣除玩家存入的余额（玩家的存款不能用来支付其他玩家的赢利）
+fn contract_balance(deps: Deps, env: &Env, config: &DenomConfig) -> Result<u128, ContractError> {
+    let address = &env.contract.address;
+    let held = if config.cw20 {
+        let res: Cw20BalanceResponse = deps.querier.query_wasm_smart(
+            &config.denom,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )?;
+        res.balance.u128()
+    } else {
+        deps.querier.query_balance(address, &config.denom)?.amount.u128()
+    };
+    let deposits = PLAYER_BALANCE_TOTALS.may_load(deps.storage, &config.denom)?.unwrap_or_default();
+    Ok(held.saturating_sub(deposits.u128()))
+}
 
-    // 检查用户当前点数是否已经达到或超过 21,防止继续要牌
-    let current_total: u32 = utils::calculate_blackjack_total(&state.user_cards);
-    if current_total >= 21 {
-        return Err(StdError::generic_err(
-            "You cannot hit after reaching 21 points",
-        ));
+/// 校验合约真实余额不少于 `required`（账面锁仓与链上资金不一致时拒绝）
+fn ensure_contract_balance(
+    deps: Deps,
+    env: &Env,
+    config: &DenomConfig,
+    required: u128,
+) -> Result<(), ContractError> {
+    let balance = contract_balance(deps, env, config)?;
+    if balance < required {
+        return Err(ContractError::InsufficientContractBalance {
+            denom: config.denom.clone(),
+            held: balance,
+            required,
+        });
     }
-
-    // 发一张新牌
-    let new_card = utils::generate_random_number(&info, &env, b"hit_card") % 10 + 1;
-    state.user_cards.push(new_card);
-
-    // 更新状态
-    BLACKJACK_STATE.save(deps.storage, &info.sender, &state)?;
-
-    // 返回结果
-    Ok(Response::new()
-        .add_attribute("action", "blackjack_hit")
-        .add_attribute("new_card", new_card.to_string())
-        .add_attribute(
-            "current_total",
-            utils::calculate_blackjack_total(&state.user_cards).to_string(),
-        ))
+    Ok(())
 }
 
-/// 21 点玩家停牌
+/// 统一赔付结算：所有游戏的赔付（含平局退还本金）都经由这里
 ///
-/// 当玩家开始停牌时, 庄家会根据实际情况进行要牌.
-fn play_blackjack_stand(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-    let mut state = BLACKJACK_STATE.load(deps.storage, &info.sender)?;
-    if state.finished {
-        return Err(StdError::generic_err("Game already finished"));
-    }
-
-    let user_total: u32 = utils::calculate_blackjack_total(&state.user_cards);
-    let mut dealer_total: u32 = utils::calculate_blackjack_total(&state.dealer_cards);
-
-    // 如果玩家爆牌, 直接结束游戏.
-    if user_total > 21 {
-        state.finished = true;
-        BLACKJACK_STATE.save(deps.storage, &info.sender, &state)?;
-        return Ok(Response::new()
-            .add_attribute("action", "blackjack_stand")
-            .add_attribute("result", "player_busted")
-            .add_attribute("user_total", user_total.to_string())
-            .add_attribute("dealer_total", dealer_total.to_string()));
-    }
-
-    // 庄家的补充牌逻辑: 小于 17 点或者小于用户的牌必须需要牌.
-    while dealer_total < 17 || dealer_total < user_total {
-        let new_card = utils::generate_random_number(&info, &env, b"dealer_hit") % 10 + 1;
-        state.dealer_cards.push(new_card);
-        dealer_total = utils::calculate_blackjack_total(&state.dealer_cards);
-    }
-
-    // 判断胜负
-    let result: &str;
-    let payout: Uint128;
-
-    // 加载抽水比例
-    let global_state_for_edge = STATE.load(deps.storage)?;
-    let house_edge = global_state_for_edge.house_edge_bps;
-
-    if dealer_total > 21 {
-        // 玩家赢了
-        result = "player_win";
-        let gross = (state.bet * Uint128::new(2)).u128();
-        payout = Uint128::from(apply_house_edge(gross, state.bet.u128(), house_edge));
-    } else if user_total < dealer_total {
-        // 玩家输了
-        result = "dealer_win";
-        payout = Uint128::zero();
-    } else {
-        // 平局,退还本金
-        result = "draw";
-        payout = state.bet;
+/// 先校验账面锁仓，再校验合约真实余额，任一不足都返回错误使整笔结算回滚，
+/// 下注保持未结算状态，补充流动性后可再次结算
+fn settle_payout(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    amount: u128,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    if amount == 0 {
+        return Ok(None);
+    }
+    let mut state = STATE.load(deps.storage)?;
+    if amount > state.locked(&bettor.denom) {
+        return Err(ContractError::InsufficientPayoutFunds);
     }
+    ensure_contract_balance(deps.as_ref(), env, state.denom_config(&bettor.denom)?, amount)?;
 
-    // 更新 locked_amount 锁仓状态
-    let mut global_state = STATE.load(deps.storage)?;
-    global_state.locked_amount = global_state.locked_amount.saturating_sub(payout.u128());
-    STATE.save(deps.storage, &global_state)?;
+    state.unlock(&bettor.denom, amount);
+    STATE.save(deps.storage, &state)?;
+    pay_player(deps.storage, bettor, Uint128::from(amount))
+}
 
-    // 结束游戏.
-    state.finished = true;
-    BLACKJACK_STATE.save(deps.storage, &info.sender, &state)?;
+// ──────────────────────────────────────────────────────────────────────────────
+// 单注风险上限（Kelly 式）
+//
+// 单笔下注最多可赢取可用资金的 max_payout_bps / 10_000，按玩法最高赔率倍数换算出
+// 动态最高下注额（见 risk.rs），与静态下注范围一起在下注时校验；多步游戏追加下注时
+// 按本局累计下注额再次校验。
+// ──────────────────────────────────────────────────────────────────────────────
 
-    // 创建响应对象
-    let mut response = Response::new();
+/// 校验下注额不超过按可用资金换算的动态上限
+fn check_max_payout(
+    state: &State,
+    denom: &str,
+    msg: &ExecuteMsg,
+    amount: u128,
+) -> Result<(), ContractError> {
+    let multiplier = max_payout_multiplier(msg)?;
+    // 21 点边注的最高赔付与主注共用单注可赢取额，剩余部分按主注倍数换算
+    let side_payout = msg.blackjack_side_bets().map_or(0, BlackjackSideBets::max_payout);
+    let max_bet = max_payout(state.free(denom), state.max_payout_bps)
+        .saturating_sub(side_payout)
+        .checked_div(multiplier)
+        .unwrap_or(0);
+    if amount > max_bet {
+        return Err(ContractError::BetExceedsMaxPayout {
+            max_bet,
+            denom: denom.to_string(),
+            multiplier,
+            max_payout_bps: state.max_payout_bps,
+        });
+    }
+    Ok(())
+}
 
-    // 如果是平局或者玩家赢了, 发送支付金额给玩家
-    if payout != Uint128::zero() {
-        response = response.add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![Coin {
-                denom: "uatom".to_string(),
-                amount: payout,
-            }],
+/// 多步游戏追加下注后按本局累计下注额重新校验下注上限：静态上限取玩法配置的 max_bet，
+/// 动态上限与下注时相同，可用资金计入本局已有的预留（追加前这部分已为本局占用）
+fn check_total_stake(
+    storage: &dyn Storage,
+    state: &State,
+    game: GameKind,
+    denom: &str,
+    reserved: Uint128,
+    total_stake: u128,
+) -> Result<(), ContractError> {
+    let config = game_config(storage, game)?;
+    let (_, static_max_bet) = scaled_bet_range(&config, state.denom_config(denom)?);
+    if total_stake > static_max_bet {
+        return Err(ContractError::TotalStakeExceedsMaxBet {
+            game,
+            max: static_max_bet,
+            denom: denom.to_string(),
         });
     }
+    let multiplier = match game {
+        GameKind::Blackjack => BLACKJACK_MAX_MULTIPLIER,
+        _ => MULTI_STEP_MAX_MULTIPLIER,
+    };
+    let free = state.free(denom) + reserved.u128();
+    let max_bet = dynamic_max_bet(free, state.max_payout_bps, multiplier);
+    if total_stake > max_bet {
+        return Err(ContractError::BetExceedsMaxPayout {
+            max_bet,
+            denom: denom.to_string(),
+            multiplier,
+            max_payout_bps: state.max_payout_bps,
+        });
+    }
+    Ok(())
+}
 
-    // 返回结果
-    Ok(response
-        .add_attribute("action", "blackjack_stand")
-        .add_attribute("result", result)
-        .add_attribute("user_total", user_total.to_string())
-        .add_attribute("dealer_total", dealer_total.to_string())
-        .add_attribute("user_cards", format!("{:?}", state.user_cards))
-        .add_attribute("dealer_cards", format!("{:?}", state.dealer_cards))
-        .add_attribute("payout", payout.to_string()))
+/// 校验单注赔付比例：1 – 10000 基点
+fn validate_max_payout_bps(max_payout_bps: u16) -> Result<(), ContractError> {
+    if max_payout_bps == 0 || max_payout_bps > MAX_PAYOUT_BPS_CAP {
+        return Err(ContractError::InvalidMaxPayout {
+            max: MAX_PAYOUT_BPS_CAP,
+        });
+    }
+    Ok(())
 }
 
-/// 玩硬币翻牌
-///
-/// 用户猜硬币的结果,如果猜对了,则获得 bet * 2 的金额,否则损失 bet 的金额。
-fn play_coin_flip(
+/// 修改单注最多可赢取可用资金的比例（风控）
+fn update_max_payout(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    choice: CoinSide,
-) -> StdResult<Response> {
-    // 验证下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
-
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
-    }
-
-    // 修改锁仓 lock_amount
+    max_payout_bps: u16,
+) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
+    ensure_role(deps.storage, &state, &info.sender, Role::RiskManager)?;
+    validate_max_payout_bps(max_payout_bps)?;
+
+    let old_bps = state.max_payout_bps;
+    state.max_payout_bps = max_payout_bps;
     STATE.save(deps.storage, &state)?;
 
-    // 抛硬币: 0 -> Heads, 1 -> Tails
-    let rand = utils::generate_random_number(&info, &env, b"coin_flip") % 2;
-    let result = if rand == 0 {
-        CoinSide::Heads
-    } else {
-        CoinSide::Tails
-    };
+    Ok(Response::new()
+        .add_attribute("action", "update_max_payout")
+        .add_attribute("old_max_payout_bps", old_bps.to_string())
+        .add_attribute("new_max_payout_bps", max_payout_bps.to_string()))
+}
 
-    let mut response = Response::new()
-        .add_attribute("action", "coin_flip")
-        .add_attribute("player_choice", format!("{:?}", choice))
-        .add_attribute("actual_result", format!("{:?}", result));
+/// 查询某玩法当前的下注限额：静态范围与按可用资金换算的动态上限
+fn query_bet_limits(
+    deps: Deps,
+    game: ExecuteMsg,
+    denom: Option<String>,
+) -> Result<BetLimitsResponse, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let config = state.denom_or_primary(denom)?;
+    let play_config = game_config(deps.storage, GameKind::from_msg(&game)?)?;
+    let (min_bet, static_max_bet) = scaled_bet_range(&play_config, config);
+    let multiplier = max_payout_multiplier(&game)?;
+    let free = state.free(&config.denom);
+    let max_bet = dynamic_max_bet(free, state.max_payout_bps, multiplier).min(static_max_bet);
+
+    Ok(BetLimitsResponse {
+        denom: config.denom.clone(),
+        min_bet: Uint128::from(min_bet),
+        max_bet: Uint128::from(max_bet),
+        static_max_bet: Uint128::from(static_max_bet),
+        max_multiplier: Uint128::from(multiplier),
+        max_payout: Uint128::from(max_payout(free, state.max_payout_bps)),
+        max_payout_bps: state.max_payout_bps,
+    })
+}
 
-    if choice == result {
-        // 赢了,奖励翻倍（扣除抽水）
-        let gross = bet * 2;
-        let net = apply_house_edge(gross, bet, state.house_edge_bps);
-        let payout = Uint128::from(net);
-        state.locked_amount = state.locked_amount.saturating_sub(payout.u128());
-        STATE.save(deps.storage, &state)?;
+// ──────────────────────────────────────────────────────────────────────────────
+// 玩法配置
+//
+// 各玩法的开放状态、下注上下限、抽水覆盖、赔率表版本和最低加注额保存在 GAME_CONFIG，
+// 未保存的玩法使用默认配置（见 game_config.rs）。
+// ──────────────────────────────────────────────────────────────────────────────
 
-        response = response
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: payout,
-                }],
-            })
-            .add_attribute("result", "win")
-            .add_attribute("payout", payout.to_string());
-    } else {
-        response = response
-            .add_attribute("result", "lose")
-            .add_attribute("payout", "0");
-    }
+/// 读取玩法配置，未保存时返回默认配置
+fn game_config(storage: &dyn Storage, kind: GameKind) -> Result<GameConfig, ContractError> {
+    Ok(GAME_CONFIG
+        .may_load(storage, kind)?
+        .unwrap_or_else(|| kind.default_config()))
+}
 
-    Ok(response)
+/// 玩法实际生效的抽水：配置了覆盖值时使用覆盖值，否则使用全局抽水
+fn game_house_edge(storage: &dyn Storage, kind: GameKind) -> Result<u16, ContractError> {
+    let global_house_edge_bps = STATE.load(storage)?.house_edge_bps;
+    Ok(game_config(storage, kind)?.house_edge(global_house_edge_bps))
 }
 
-/// 玩骰子猜大小
-///
-/// 用户猜中大小的概率为 1/2, 用户猜中获得 bet * 2 的金额, 否则损失 bet 的金额.
-fn play_dice_guess_size(
+/// 修改某玩法的配置（经由时间锁执行，调用前已校验风控角色与配置）
+fn update_game_config(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    guess_big: DiceGuessSize,
-) -> StdResult<Response> {
-    // 验证下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
-
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+    game: GameKind,
+    config: GameConfig,
+) -> Result<Response, ContractError> {
+    // 与暂停人关闭玩法一样，配置可以关闭玩法；重新开放仅限所有者通过 set_game_enabled
+    if config.enabled && !game_config(deps.storage, game)?.enabled {
+        return Err(ContractError::GameReenableRequiresOwner { game });
     }
+    GAME_CONFIG.save(deps.storage, game, &config)?;
 
-    // 修改锁仓 lock_amount
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
-    STATE.save(deps.storage, &state)?;
-
-    // 抛骰子: [1,3] 为小, [4,6] 为大
-    let rand_number = utils::generate_random_number(&info, &env, b"dice_guess_size") % 6 + 1;
-    let result = if rand_number <= 3 {
-        DiceGuessSize::Small
-    } else {
-        DiceGuessSize::Big
-    };
-
-    let mut response = Response::new()
-        .add_attribute("action", "dice_guess_size")
-        .add_attribute("player_guess", format!("{:?}", guess_big))
-        .add_attribute("actual_result", format!("{:?}", result));
-
-    if guess_big == result {
-        // 赢了发送奖励（扣除抽水）
-        let gross = bet * 2;
-        let net = apply_house_edge(gross, bet, state.house_edge_bps);
-        let payout = Uint128::from(net);
-        state.locked_amount = state.locked_amount.saturating_sub(payout.u128());
-        STATE.save(deps.storage, &state)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_game_config")
+        .add_attribute("game", game.to_string())
+        .add_attribute("enabled", config.enabled.to_string())
+        .add_attribute("min_bet", config.min_bet)
+        .add_attribute("max_bet", config.max_bet)
+        .add_attribute("paytable_version", config.paytable_version.to_string()))
+}
 
-        response = response
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: payout,
-                }],
+/// 查询全部玩法的配置
+fn list_game_configs(deps: Deps) -> Result<GameConfigsResponse, ContractError> {
+    let configs = GameKind::ALL
+        .into_iter()
+        .map(|game| {
+            Ok(GameConfigResponse {
+                game,
+                config: game_config(deps.storage, game)?,
             })
-            .add_attribute("result", "win");
-    } else {
-        response = response.add_attribute("result", "lose");
-    }
-    Ok(response)
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    Ok(GameConfigsResponse { configs })
 }
 
-/// 玩骰子猜点数
-///
-/// 用户猜中数字的概率为 1/6, 猜中数字的奖励为 bet * 6 的金额, 否则损失 bet 的金额.
-fn play_dice_exact_number(
+// ──────────────────────────────────────────────────────────────────────────────
+// 承诺-揭示下注流程
+//
+//   1. place_bet          → 校验参数、锁仓下注，记录在当前种子承诺下
+//   2. reveal_server_seed → 庄家揭示种子并提交下一轮承诺
+//   3. settle_bet         → 任何人可触发，用揭示的种子推导本局随机数并结算
+//   4. refund_bet         → 庄家超时未揭示时，玩家取回下注
+//
+// 配置了 drand 时，单局游戏在 place_bet 时锁定未来的 drand 轮次，
+// 该轮发布后由任何人 submit_beacon 提交签名，再 settle_bet 结算。
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// 下注：校验游戏参数与金额并锁仓，等待开奖
+fn place_bet(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    number: u8,
-) -> StdResult<Response> {
-    // 验证下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+    msg: ExecuteMsg,
+    source: FundsSource,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (denom, funds) = bet_funds(&state, &info.funds)?;
+    let amount = main_bet(&msg, &denom, funds)?;
 
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
-    }
+    validate_play(deps.as_ref(), &info.sender, &msg, amount, state.denom_config(&denom)?)?;
+    check_max_payout(&state, &denom, &msg, amount)?;
 
-    if number < 1 || number > 6 {
-        return Err(StdError::generic_err("Number must be between 1 and 6"));
+    let client_seed = msg.client_seed().unwrap_or_default().to_string();
+    if client_seed.len() > 64 {
+        return Err(ContractError::ClientSeedTooLong { max: 64 });
     }
 
-    // 修改锁仓 lock_amount
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
+    // 单局游戏优先使用 drand 信标；多步游戏（开局后还要继续发牌）仍使用承诺-揭示
+    let drand = DRAND_CONFIG.may_load(deps.storage)?;
+    let entropy = match drand {
+        Some(drand) if !msg.is_multi_step() => {
+            // 锁定区块时间 + 安全余量之后才发布的轮次，下注时该轮随机数尚未产生
+            let round = drand
+                .round_after(env.block.time.seconds() + BEACON_SAFETY_MARGIN_SECS);
+            BetEntropy::Drand { round }
+        }
+        _ => {
+            let id = ACTIVE_COMMITMENT
+                .may_load(deps.storage)?
+                .ok_or(ContractError::NoActiveCommitment)?;
+            BetEntropy::Commitment { id }
+        }
+    };
+
+    // 下注金额计入锁仓并预留最坏情况赔付（多步游戏开局后转入牌局状态）：
+    // 可用资金或合约真实余额不足时在开奖前拒绝
+    let side_bets = msg.blackjack_side_bets().cloned().unwrap_or_default();
+    state.lock(&denom, funds);
+    let reserved = amount * max_payout_multiplier(&msg)? + side_bets.max_payout();
+    state.reserve(&denom, reserved)?;
+    let config = state.denom_config(&denom)?;
+    ensure_contract_balance(deps.as_ref(), &env, config, state.reserved(&denom))?;
     STATE.save(deps.storage, &state)?;
 
-    // 抛骰子
-    let rand_number = utils::generate_random_number(&info, &env, b"dice_exact_number") % 6 + 1;
+    let bet_id = BET_COUNT.may_load(deps.storage)?.unwrap_or(0) + 1;
+    BET_COUNT.save(deps.storage, &bet_id)?;
 
-    let mut response = Response::new()
-        .add_attribute("action", "play_dice_exact_number")
-        .add_attribute("player_guess", number.to_string())
-        .add_attribute("actual_result", rand_number.to_string());
+    // 玩家下注序号：同一区块 / 同一交易内的多笔下注也各不相同
+    let nonce = PLAYER_NONCES.may_load(deps.storage, &info.sender)?.unwrap_or(0) + 1;
+    PLAYER_NONCES.save(deps.storage, &info.sender, &nonce)?;
 
-    // 如果猜对,玩家则获得 bet * 6 的金额,否则损失 bet 的金额。
-    if number as u32 == rand_number {
-        let gross = bet * 6;
-        let net = apply_house_edge(gross, bet, state.house_edge_bps);
-        let payout = Uint128::from(net);
-        state.locked_amount = state.locked_amount.saturating_sub(payout.u128());
-        STATE.save(deps.storage, &state)?;
+    // 21 点庄规在下注时确定，之后修改庄规不影响本局开局与复算
+    let blackjack_rules = match msg {
+        ExecuteMsg::PlayBlackjack { .. } => {
+            Some(BLACKJACK_RULES.may_load(deps.storage)?.unwrap_or_default())
+        }
+        _ => None,
+    };
 
-        // 赢了发送奖励
-        response = response
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: payout,
-                }],
-            })
-            .add_attribute("payout", payout.to_string())
-            .add_attribute("result", "win");
-    } else {
-        response = response.add_attribute("result", "lose");
+    let bet = BetRecord {
+        id: bet_id,
+        player: info.sender.clone(),
+        game: msg,
+        amount: Uint128::from(funds),
+        denom: denom.clone(),
+        client_seed: client_seed.clone(),
+        nonce,
+        entropy: entropy.clone(),
+        placed_height: env.block.height,
+        source,
+        reserved: Uint128::from(reserved),
+        blackjack_rules,
+        settled: false,
+    };
+    BETS.save(deps.storage, bet_id, &bet)?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "place_bet")
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attribute("player", info.sender.clone())
+        .add_attribute("amount", funds.to_string());
+    if side_bets.total() > 0 {
+        response = response.add_attribute("side_bets", side_bets.total().to_string());
+    }
+    match entropy {
+        BetEntropy::Commitment { id } => {
+            let commitment = SEED_COMMITMENTS.load(deps.storage, id)?;
+            response = response
+                .add_attribute("commitment_id", id.to_string())
+                .add_attribute("seed_hash", commitment.seed_hash);
+        }
+        BetEntropy::Drand { round } => {
+            // 按 (玩家, 轮次) 索引待开奖下注
+            let key = (&info.sender, round);
+            let mut pending = PENDING_BEACON_BETS.may_load(deps.storage, key)?.unwrap_or_default();
+            pending.push(bet_id);
+            PENDING_BEACON_BETS.save(deps.storage, key, &pending)?;
+            response = response.add_attribute("drand_round", round.to_string());
+        }
     }
-    Ok(response)
+
+    Ok(response
+        .add_attribute("denom", denom)
+        .add_attribute("client_seed", client_seed)
+        .add_attribute("nonce", nonce.to_string()))
 }
 
-/// 玩骰子猜范围
+/// 校验下注消息的游戏参数与下注金额（下注时即拒绝非法参数，避免开奖后才失败）
 ///
-/// 用户在指定范围内猜骰子点, 猜中范围的概率为 1 / (6 / ( end - start + 1 )), 猜中范围的奖励为 bet * times 的金额, 否则损失 bet 的金额.
-fn play_dice_range_bet(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    start: u8,
-    end: u8,
-) -> StdResult<Response> {
-    if start > end || start < 1 || start > 6 || end < 1 || end > 6 {
-        return Err(StdError::generic_err("Invalid range"));
+/// 各游戏的下注上下限取自玩法配置（以 uatom 计），按下注币种的 bet_scale 换算
+fn validate_play(
+    deps: Deps,
+    player: &Addr,
+    msg: &ExecuteMsg,
+    amount: u128,
+    denom: &DenomConfig,
+) -> Result<(), ContractError> {
+    let kind = GameKind::from_msg(msg)?;
+    let config = game_config(deps.storage, kind)?;
+
+    match msg {
+        ExecuteMsg::GuessNumber { guess, .. } if !(1..=10).contains(guess) => {
+            return Err(ContractError::InvalidGuess { min: 1, max: 10 });
+        }
+        ExecuteMsg::PlayDice { mode, .. } => match mode {
+            DiceGameMode::GuessSize { .. } => {}
+            DiceGameMode::ExactNumber { guess_number } if !(1..=6).contains(guess_number) => {
+                return Err(ContractError::InvalidGuess { min: 1, max: 6 });
+            }
+            DiceGameMode::ExactNumber { .. } => {}
+            DiceGameMode::RangeBet { start, end } => {
+                dice_range_multiplier(*start, *end)?;
+            }
+        },
+        ExecuteMsg::PlaySicBo { bet_type, .. } => {
+            validate_bet(bet_type)?;
+        }
+        ExecuteMsg::PlayKeno { picks, .. } => {
+            validate_picks(picks)?;
+        }
+        ExecuteMsg::PlayBlackjack { .. } => {
+            // 检查是否已有进行中游戏（后续操作不经过下注，这里只会是开局）
+            if let Some(existing) = BLACKJACK_STATE.may_load(deps.storage, player)? {
+                if !existing.finished {
+                    return Err(ContractError::GameInProgress { game: GameKind::Blackjack });
+                }
+            }
+        }
+        ExecuteMsg::PlayOmaha { .. } => {
+            // 检查是否已有进行中游戏
+            if let Some(existing) = OMAHA_STATE.may_load(deps.storage, player)? {
+                if !existing.finished {
+                    return Err(ContractError::GameInProgress { game: GameKind::Omaha });
+                }
+            }
+        }
+        ExecuteMsg::PlayTexas { .. } => {
+            // 检查是否已有进行中游戏
+            if let Some(existing) = TEXAS_STATE.may_load(deps.storage, player)? {
+                if !existing.finished {
+                    return Err(ContractError::GameInProgress { game: GameKind::Texas });
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let (min_bet, max_bet) = scaled_bet_range(&config, denom);
+    if !(min_bet..=max_bet).contains(&amount) {
+        return Err(ContractError::InvalidBet {
+            game: kind,
+            min: min_bet,
+            max: max_bet,
+            denom: denom.denom.clone(),
+        });
     }
+    Ok(())
+}
+
+/// 玩法配置的下注上下限，按下注币种的 bet_scale 换算
+fn scaled_bet_range(config: &GameConfig, denom: &DenomConfig) -> (u128, u128) {
+    (denom.scale(config.min_bet.u128()), denom.scale(config.max_bet.u128()))
+}
+
+/// 下注附带的资金：只能附带一种可下注币种，未附带时按主币种 0 处理（由限额校验拒绝）
+fn bet_funds(state: &State, funds: &[Coin]) -> Result<(String, u128), ContractError> {
+    let funds: Vec<&Coin> = funds.iter().filter(|c| !c.amount.is_zero()).collect();
+    match funds.as_slice() {
+        [] => Ok((state.primary_denom().denom.clone(), 0)),
+        [coin] => {
+            state.denom_config(&coin.denom)?;
+            Ok((coin.denom.clone(), coin.amount.u128()))
+        }
+        _ => Err(ContractError::MultipleDenoms),
+    }
+}
 
-    let width = end - start + 1;
-    let times = match width {
-        2 => 3,
-        3 => 2,
-        _ => return Err(StdError::generic_err("Range width must be 2 or 3")),
+/// 主注金额：21 点开局附带的资金扣除边注，每个边注不能超过主注
+fn main_bet(msg: &ExecuteMsg, denom: &str, funds: u128) -> Result<u128, ContractError> {
+    let side_bets = match msg.blackjack_side_bets() {
+        Some(side_bets) => side_bets,
+        None => return Ok(funds),
     };
+    let main_bet = funds.saturating_sub(side_bets.total());
+    if side_bets.perfect_pairs.u128().max(side_bets.twenty_one_plus_three.u128()) > main_bet {
+        return Err(ContractError::SideBetExceedsBet {
+            main_bet,
+            denom: denom.to_string(),
+        });
+    }
+    Ok(main_bet)
+}
 
-    // 验证下注金额
-    let bet = info
-        .funds
+/// 继续下注（加注 / 跟注 / 全押）附带的资金：只计入与开局相同币种的金额
+fn sent_in_denom(funds: &[Coin], denom: &str) -> u128 {
+    funds
         .iter()
-        .find(|c| c.denom == "uatom")
+        .find(|c| c.denom == denom)
         .map(|c| c.amount.u128())
-        .unwrap_or(0);
+        .unwrap_or(0)
+}
 
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+/// 不追加下注的操作（停牌、过牌、弃牌、摊牌等）不接受附带资金，否则资金会被合约留下
+fn ensure_no_funds(funds: &[Coin], action: &'static str, denom: &str) -> Result<(), ContractError> {
+    if funds.iter().any(|c| !c.amount.is_zero()) {
+        return Err(ContractError::FundsMismatch {
+            action,
+            denom: denom.to_string(),
+        });
     }
+    Ok(())
+}
 
-    // 修改锁仓 lock_amount
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
-    STATE.save(deps.storage, &state)?;
+/// 校验可下注币种：至少一个、不重复、bet_scale 大于 0；
+/// 主币种必须是原生币（初始化锁仓随消息附带），CW20 代币必须是合法的合约地址
+fn validate_denoms(api: &dyn Api, denoms: &[DenomConfig]) -> Result<(), ContractError> {
+    if denoms.is_empty() {
+        return Err(ContractError::InvalidDenomConfig {
+            reason: "At least one denom must be accepted".to_string(),
+        });
+    }
+    if denoms[0].cw20 {
+        return Err(ContractError::InvalidDenomConfig {
+            reason: "Primary denom must be a native coin".to_string(),
+        });
+    }
+    for (i, config) in denoms.iter().enumerate() {
+        if config.denom.is_empty() {
+            return Err(ContractError::InvalidDenomConfig {
+                reason: "Denom must not be empty".to_string(),
+            });
+        }
+        if config.cw20 {
+            api.addr_validate(&config.denom)?;
+        }
+        if config.bet_scale.is_zero() {
+            return Err(ContractError::InvalidDenomConfig {
+                reason: format!("Bet scale for {} must be greater than 0", config.denom),
+            });
+        }
+        if denoms[..i].iter().any(|d| d.denom == config.denom) {
+            return Err(ContractError::InvalidDenomConfig {
+                reason: format!("Duplicate denom: {}", config.denom),
+            });
+        }
+    }
+    Ok(())
+}
 
-    let rand_number = utils::generate_random_number(&info, &env, b"dice_range_bet") % 6 + 1;
+/// 保存新的服务端种子承诺并设为当前生效承诺
+fn save_commitment(
+    storage: &mut dyn Storage,
+    seed_hash: String,
+    height: u64,
+) -> Result<u64, ContractError> {
+    if !is_valid_seed_hash(&seed_hash) {
+        return Err(ContractError::InvalidSeedHash);
+    }
+    let id = COMMITMENT_COUNT.may_load(storage)?.unwrap_or(0) + 1;
+    COMMITMENT_COUNT.save(storage, &id)?;
+    SEED_COMMITMENTS.save(
+        storage,
+        id,
+        &SeedCommitment {
+            id,
+            seed_hash: seed_hash.to_lowercase(),
+            server_seed: None,
+            committed_height: height,
+            revealed_height: None,
+        },
+    )?;
+    ACTIVE_COMMITMENT.save(storage, &id)?;
+    Ok(id)
+}
 
-    if rand_number >= start as u32 && rand_number <= end as u32 {
-        let gross = bet * times as u128;
-        let net = apply_house_edge(gross, bet, state.house_edge_bps);
-        let payout = Uint128::from(net);
-        state.locked_amount = state.locked_amount.saturating_sub(payout.u128());
-        STATE.save(deps.storage, &state)?;
-
-        return Ok(Response::new()
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: payout,
-                }],
-            })
-            .add_attribute("result", "win")
-            .add_attribute("payout", payout.to_string())
-            .add_attribute("actual_result", rand_number.to_string())
-            .add_attribute("player_start", start.to_string())
-            .add_attribute("player_end", end.to_string()));
+/// 提交服务端种子承诺（运维，且当前没有生效中的承诺）
+fn commit_server_seed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seed_hash: String,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    ensure_role(deps.storage, &state, &info.sender, Role::Operator)?;
+    if ACTIVE_COMMITMENT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::ActiveCommitmentExists);
     }
+
+    let id = save_commitment(deps.storage, seed_hash.clone(), env.block.height)?;
+
     Ok(Response::new()
-        .add_attribute("result", "lose")
-        .add_attribute("actual_result", rand_number.to_string())
-        .add_attribute("player_start", start.to_string())
-        .add_attribute("player_end", end.to_string()))
+        .add_attribute("action", "commit_server_seed")
+        .add_attribute("commitment_id", id.to_string())
+        .add_attribute("seed_hash", seed_hash))
 }
 
-/// 百家乐游戏
+/// 揭示当前服务端种子并提交下一轮承诺（运维）
 ///
-/// 玩家可以在庄家、闲家或平局中选择下注
-fn play_baccarat(
+/// 揭示后该承诺下的所有下注都可以结算
+fn reveal_server_seed(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    bet_choice: BaccaratBet,
-) -> StdResult<Response> {
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+    server_seed: String,
+    next_seed_hash: String,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    ensure_role(deps.storage, &state, &info.sender, Role::Operator)?;
 
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+    let id = ACTIVE_COMMITMENT
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoActiveCommitment)?;
+    let mut commitment = SEED_COMMITMENTS.load(deps.storage, id)?;
+    if hash_server_seed(&server_seed) != commitment.seed_hash {
+        return Err(ContractError::SeedMismatch);
     }
 
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
-    STATE.save(deps.storage, &state)?;
+    commitment.server_seed = Some(server_seed.clone());
+    commitment.revealed_height = Some(env.block.height);
+    SEED_COMMITMENTS.save(deps.storage, id, &commitment)?;
 
-    // 发牌 - 百家乐规则：每人先发两张牌
-    let mut player_cards = vec![
-        (utils::generate_random_number(&info, &env, b"player1") % 10) as u8,
-        (utils::generate_random_number(&info, &env, b"player2") % 10) as u8,
-    ];
+    let next_id = save_commitment(deps.storage, next_seed_hash, env.block.height)?;
 
-    let mut banker_cards = vec![
-        (utils::generate_random_number(&info, &env, b"banker1") % 10) as u8,
-        (utils::generate_random_number(&info, &env, b"banker2") % 10) as u8,
-    ];
+    Ok(Response::new()
+        .add_attribute("action", "reveal_server_seed")
+        .add_attribute("commitment_id", id.to_string())
+        .add_attribute("server_seed", server_seed)
+        .add_attribute("next_commitment_id", next_id.to_string()))
+}
 
-    // 计算点数（百家乐中只有个位数有效）
-    let mut player_total = (player_cards[0] + player_cards[1]) % 10;
-    let mut banker_total = (banker_cards[0] + banker_cards[1]) % 10;
+/// 提交 drand 轮次签名（任何人都可以调用）
+///
+/// 合约用配置的公钥验签，保存该轮随机数 sha256(signature)
+fn submit_beacon(
+    deps: DepsMut,
+    round: u64,
+    signature: HexBinary,
+) -> Result<Response, ContractError> {
+    let drand = DRAND_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::DrandNotConfigured)?;
+    if DRAND_BEACONS.has(deps.storage, round) {
+        return Err(ContractError::BeaconAlreadySubmitted { round });
+    }
 
-    // 根据规则决定是否补牌
-    let player_third_card = if player_total <= 5 {
-        let third_card = (utils::generate_random_number(&info, &env, b"player3") % 10) as u8;
-        player_cards.push(third_card);
-        player_total = (player_total + third_card) % 10;
-        Some(third_card)
-    } else {
-        None
-    };
+    let randomness = HexBinary::from(drand.verify(round, signature.as_slice())?);
+    DRAND_BEACONS.save(deps.storage, round, &randomness)?;
 
-    // 庄家是否补牌取决于闲家是否补牌以及当前点数
-    if banker_total <= 5 {
-        let should_draw = match player_third_card {
-            Some(third_card) => {
-                // 根据百家乐规则确定庄家是否补牌
-                match banker_total {
-                    0..=2 => true,                                    // 庄家0-2点必补牌
-                    3 => third_card != 8, // 庄家3点，闲家第三张为8时不补牌
-                    4 => matches!(third_card, 2 | 3 | 4 | 5 | 6 | 7), // 庄家4点规则
-                    5 => matches!(third_card, 4 | 5 | 6 | 7), // 庄家5点规则
-                    6 => matches!(third_card, 6 | 7), // 庄家6点规则
-                    _ => false,           // 庄家7点以上不补牌
-                }
-            }
-            None => banker_total <= 5, // 如果闲家没补牌，庄家按基本规则补牌
-        };
+    Ok(Response::new()
+        .add_attribute("action", "submit_beacon")
+        .add_attribute("round", round.to_string())
+        .add_attribute("randomness", randomness.to_hex()))
+}
 
-        if should_draw {
-            let third_card = (utils::generate_random_number(&info, &env, b"banker3") % 10) as u8;
-            banker_cards.push(third_card);
-            banker_total = (banker_total + third_card) % 10;
+/// 结算下注（任何人都可以调用）
+///
+/// 本局随机种子 = sha256("{server_seed 或 drand 随机数}:{client_seed}:{player}:{nonce}:{bet_id}")
+fn settle_bet(deps: DepsMut, env: Env, bet_id: u64) -> Result<Response, ContractError> {
+    let mut bet = BETS.load(deps.storage, bet_id)?;
+    if bet.settled {
+        return Err(ContractError::BetAlreadySettled);
+    }
+
+    let entropy = load_bet_entropy(deps.storage, &bet)?;
+
+    // 与揭示同一区块的下注可能排在揭示交易之前，下注者可以从内存池中看到服务端种子
+    // 并挑选客户端种子：不开奖，原路退还
+    if let BetEntropy::Commitment { id } = bet.entropy {
+        let revealed_height = SEED_COMMITMENTS.load(deps.storage, id)?.revealed_height;
+        if revealed_height.is_some_and(|height| bet.placed_height >= height) {
+            bet.settled = true;
+            BETS.save(deps.storage, bet_id, &bet)?;
+            let (bettor, amount) = (bet.bettor(), bet.amount.u128());
+            let event = refund_event(GameKind::from_msg(&bet.game)?, bet_id, &bettor, amount)?;
+            let response = refund_stake(deps, &env, &bettor, amount, bet.reserved)?;
+            return Ok(response
+                .add_event(event)
+                .add_attribute("action", "refund_bet")
+                .add_attribute("bet_id", bet_id.to_string())
+                .add_attribute("reason", "placed_in_reveal_block")
+                .add_attribute("amount", bet.amount));
         }
     }
 
-    // 确定赢家
-    let winner = if player_total > banker_total {
-        BaccaratBet::Player
-    } else if banker_total > player_total {
-        BaccaratBet::Banker
-    } else {
-        BaccaratBet::Tie
+    let entropy_key = match bet.entropy {
+        BetEntropy::Commitment { .. } => "server_seed",
+        BetEntropy::Drand { round } => {
+            remove_pending_beacon_bet(deps.storage, &bet.player, round, bet_id)?;
+            "drand_randomness"
+        }
     };
+    let seed = derive_bet_seed(&entropy, &bet.client_seed, &bet.player, bet.nonce, bet.id);
 
-    // 计算赔付
-    let (payout_multiplier, commission) = match winner {
-        BaccaratBet::Player => (2, 0), // 1:1 赔付，无佣金
-        BaccaratBet::Banker => (2, 5), // 1:1 赔付，但收取5%佣金
-        BaccaratBet::Tie => (9, 0),    // 8:1 赔付 (我们设置为9倍因为包含本金)
-    };
-
-    let mut response = Response::new()
-        .add_attribute("action", "play_baccarat")
-        .add_attribute("player_cards", format!("{:?}", player_cards))
-        .add_attribute("banker_cards", format!("{:?}", banker_cards))
-        .add_attribute("player_total", player_total.to_string())
-        .add_attribute("banker_total", banker_total.to_string())
-        .add_attribute("player_bet", format!("{:?}", bet_choice))
-        .add_attribute("winner", format!("{:?}", winner));
-
-    // 如果玩家猜中了结果
-    if winner == bet_choice {
-        let mut winnings = bet * (payout_multiplier as u128 - 1); // 奖金不包括本金
-
-        // 如果投注庄家且获胜，扣除佣金
-        if winner == BaccaratBet::Banker {
-            let commission_amount = winnings * commission / 100;
-            winnings = winnings.saturating_sub(commission_amount);
-        }
-
-        // 特殊处理平局情况
-        if winner == BaccaratBet::Tie {
-            winnings = bet * 8; // 8倍奖金
-        }
-
-        let gross_payout = bet + winnings; // 本金+奖金
-        let payout_amount = apply_house_edge(gross_payout, bet, state.house_edge_bps);
-        let payout = Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(payout_amount),
-        };
+    bet.settled = true;
+    BETS.save(deps.storage, bet_id, &bet)?;
 
-        response = response
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![payout],
-            })
-            .add_attribute("result", "win")
-            .add_attribute("winnings", winnings.to_string())
-            .add_attribute("payout", payout_amount.to_string());
-
-        state.locked_amount = state.locked_amount.saturating_sub(payout_amount);
-        STATE.save(deps.storage, &state)?;
-    } else {
-        response = response
-            .add_attribute("result", "lose")
-            .add_attribute("payout", "0");
+    // 单局游戏开奖即结束，先释放下注时的预留再按实际结果赔付；
+    // 多步游戏的预留转入牌局状态
+    if !bet.game.is_multi_step() {
+        release_reservation(deps.storage, &bet.denom, bet.reserved)?;
     }
 
-    Ok(response)
+    let mut response = resolve_bet(deps, &env, &bet, &seed)?
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attribute("player", bet.player);
+    if let BetEntropy::Drand { round } = bet.entropy {
+        response = response.add_attribute("drand_round", round.to_string());
+    }
+    Ok(response
+        .add_attribute(entropy_key, entropy)
+        .add_attribute("client_seed", bet.client_seed)
+        .add_attribute("nonce", bet.nonce.to_string()))
 }
 
-/// 轮盘游戏
-///
-/// 轮盘包含数字 0-36，其中：
-/// - 0 用于显示颜色但不参与颜色押注
-/// - 仅保留四种玩法：单个数字、颜色、奇偶、大小
-fn play_roulette(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    bet_type: RouletteBetType,
-) -> StdResult<Response> {
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
-
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+/// 读取下注的熵：揭示的服务端种子，或 drand 轮次随机数（十六进制）
+fn load_bet_entropy(storage: &dyn Storage, bet: &BetRecord) -> Result<String, ContractError> {
+    match bet.entropy {
+        BetEntropy::Commitment { id } => SEED_COMMITMENTS
+            .load(storage, id)?
+            .server_seed
+            .ok_or(ContractError::SeedNotRevealed),
+        BetEntropy::Drand { round } => DRAND_BEACONS
+            .may_load(storage, round)?
+            .map(|randomness| randomness.to_hex())
+            .ok_or(ContractError::BeaconNotSubmitted { round }),
     }
+}
 
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
-    STATE.save(deps.storage, &state)?;
-
-    // 生成 0-36 的随机数作为轮盘结果
-    let winning_number = (utils::generate_random_number(&info, &env, b"roulette") % 37) as u8;
-
-    // 根据轮盘规则确定颜色
-    let winning_color = get_roulette_color(winning_number);
-
-    // 判断是否为偶数
-    let is_even = winning_number != 0 && winning_number % 2 == 0;
+/// 重新推导已开奖下注的种子：多步游戏的牌序不在链上保存，每次操作按开局下注的种子重新洗出
+fn bet_seed(storage: &dyn Storage, bet_id: u64) -> Result<[u8; 32], ContractError> {
+    let bet = BETS.load(storage, bet_id)?;
+    let entropy = load_bet_entropy(storage, &bet)?;
+    Ok(derive_bet_seed(&entropy, &bet.client_seed, &bet.player, bet.nonce, bet.id))
+}
 
-    // 判断大小（0 不属于任何一类）
-    let is_low = if winning_number == 0 {
-        None
-    } else if winning_number <= 18 {
-        Some(true) // Low (1-18)
+/// 从 (玩家, 轮次) 待开奖索引中移除一笔下注
+fn remove_pending_beacon_bet(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    round: u64,
+    bet_id: u64,
+) -> Result<(), ContractError> {
+    let key = (player, round);
+    let mut pending = PENDING_BEACON_BETS.may_load(storage, key)?.unwrap_or_default();
+    pending.retain(|id| *id != bet_id);
+    if pending.is_empty() {
+        PENDING_BEACON_BETS.remove(storage, key);
     } else {
-        Some(false) // High (19-36)
-    };
+        PENDING_BEACON_BETS.save(storage, key, &pending)?;
+    }
+    Ok(())
+}
 
-    // 创建结果对象
-    let result = RouletteResult {
-        winning_number,
-        winning_color: winning_color.clone(),
-        is_even,
-        is_low,
+/// 按下注消息分派到对应游戏，用推导出的种子开奖
+fn resolve_bet(
+    deps: DepsMut,
+    env: &Env,
+    bet: &BetRecord,
+    seed: &[u8],
+) -> Result<Response, ContractError> {
+    let bettor = &bet.bettor();
+    let amount = bet.amount.u128();
+    let (response, settlement) = match bet.game.clone() {
+        // 多步游戏开局只发牌，结束时再发出结算事件（21 点开局即有天然 21 点时当场结算）
+        ExecuteMsg::PlayBlackjack { .. } => return play_blackjack_start(deps, env, bet, seed),
+        ExecuteMsg::PlayOmaha { .. } => {
+            return play_omaha_start(deps, env, bettor, amount, bet.reserved, bet.id, seed);
+        }
+        ExecuteMsg::PlayTexas { .. } => {
+            return play_texas_start(deps, env, bettor, amount, bet.reserved, bet.id, seed);
+        }
+        ExecuteMsg::PlayWar { .. } => play_war(deps, env, bettor, amount, seed)?,
+        ExecuteMsg::PlaySlot { mode, .. } => play_slot(deps, env, bettor, amount, mode, seed)?,
+        ExecuteMsg::GuessNumber { guess, .. } => {
+            play_guess_number(deps, env, bettor, amount, guess, seed)?
+        }
+        ExecuteMsg::PlayCoinFlip { choice, .. } => {
+            play_coin_flip(deps, env, bettor, amount, choice, seed)?
+        }
+        ExecuteMsg::PlayDice { mode, .. } => match mode {
+            DiceGameMode::GuessSize { guess_big } => {
+                play_dice_guess_size(deps, env, bettor, amount, guess_big, seed)?
+            }
+            DiceGameMode::ExactNumber { guess_number } => {
+                play_dice_exact_number(deps, env, bettor, amount, guess_number, seed)?
+            }
+            DiceGameMode::RangeBet { start, end } => {
+                play_dice_range_bet(deps, env, bettor, amount, start, end, seed)?
+            }
+        },
+        ExecuteMsg::PlayBaccarat { bet_choice, .. } => {
+            play_baccarat(deps, env, bettor, amount, bet_choice, seed)?
+        }
+        ExecuteMsg::PlayRoulette { bet_type, .. } => {
+            play_roulette(deps, env, bettor, amount, bet_type, seed)?
+        }
+        ExecuteMsg::PlaySanGong { .. } => play_sangong(deps, env, bettor, amount, seed)?,
+        ExecuteMsg::PlaySicBo { bet_type, .. } => {
+            play_sicbo(deps, env, bettor, amount, bet_type, seed)?
+        }
+        ExecuteMsg::PlayKeno { picks, .. } => play_keno(deps, env, bettor, amount, picks, seed)?,
+        ExecuteMsg::PlayScratchCard { card_type, .. } => {
+            play_scratch_card(deps, env, bettor, amount, card_type, seed)?
+        }
+        ExecuteMsg::PlayBullFight { .. } => play_bullfight(deps, env, bettor, amount, seed)?,
+        _ => return Err(ContractError::NotABet),
     };
 
-    // 计算赔付
-    let (won, payout_multiplier) = calculate_roulette_payout(&bet_type, &result);
-
-    let mut response = Response::new()
-        .add_attribute("action", "play_roulette")
-        .add_attribute("winning_number", winning_number.to_string())
-        .add_attribute("winning_color", format!("{:?}", winning_color))
-        .add_attribute("is_even", is_even.to_string())
-        .add_attribute("bet_type", format!("{:?}", bet_type));
-
-    if won {
-        let gross = bet * payout_multiplier;
-        let winnings = apply_house_edge(gross, bet, state.house_edge_bps);
-        let payout = Coin {
-            denom: "uatom".to_string(),
-            amount: Uint128::from(winnings),
-        };
-
-        response = response
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![payout],
-            })
-            .add_attribute("result", "win")
-            .add_attribute("winnings", winnings.to_string());
+    // 单局游戏的开奖结果与 VerifyOutcome 复算的结构相同
+    let outcome = game_outcome(bet, seed)?;
+    let event = settlement.event(bet.id, &bet.player, &bet.denom, &outcome)?;
+    Ok(response.add_event(event))
+}
 
-        state.locked_amount = state.locked_amount.saturating_sub(winnings);
-        STATE.save(deps.storage, &state)?;
-    } else {
-        response = response
-            .add_attribute("result", "lose")
-            .add_attribute("payout", "0");
-    }
+/// 复算下注的开奖结果（只读，与 resolve_bet 使用相同的推导函数）
+///
+/// 多步游戏的结果含庄家暗牌、未发的公共牌与整副牌序，牌局结束前不返回.
+/// 注意暗牌已由揭示的服务端种子决定，其余推导输入都是公开的，仍可离线复算（见 README）
+fn verify_outcome(deps: Deps, bet_id: u64) -> Result<VerifyOutcomeResponse, ContractError> {
+    let bet = BETS.load(deps.storage, bet_id)?;
+    if multi_step_game_open(deps.storage, &bet)? {
+        return Err(ContractError::GameNotFinished { game: GameKind::from_msg(&bet.game)? });
+    }
+    let entropy_value = load_bet_entropy(deps.storage, &bet)?;
+    let seed = derive_bet_seed(&entropy_value, &bet.client_seed, &bet.player, bet.nonce, bet.id);
+    let outcome = game_outcome(&bet, &seed)?;
+
+    Ok(VerifyOutcomeResponse {
+        bet_id,
+        player: bet.player,
+        game: bet.game,
+        entropy: bet.entropy,
+        entropy_value,
+        client_seed: bet.client_seed,
+        nonce: bet.nonce,
+        seed: HexBinary::from(seed.to_vec()),
+        settled: bet.settled,
+        outcome,
+    })
+}
 
-    Ok(response)
+/// 多步游戏的下注是否尚未开局或牌局仍在进行（玩家的牌局已换成之后的下注时视为结束）
+fn multi_step_game_open(storage: &dyn Storage, bet: &BetRecord) -> Result<bool, ContractError> {
+    if !bet.game.is_multi_step() {
+        return Ok(false);
+    }
+    if !bet.settled {
+        return Ok(true);
+    }
+    let open = match bet.game {
+        ExecuteMsg::PlayBlackjack { .. } => BLACKJACK_STATE
+            .may_load(storage, &bet.player)?
+            .map(|game| (game.bet_id, game.finished)),
+        ExecuteMsg::PlayOmaha { .. } => OMAHA_STATE
+            .may_load(storage, &bet.player)?
+            .map(|game| (game.bet_id, game.finished)),
+        _ => TEXAS_STATE
+            .may_load(storage, &bet.player)?
+            .map(|game| (game.bet_id, game.finished)),
+    };
+    Ok(open.is_some_and(|(bet_id, finished)| bet_id == bet.id && !finished))
 }
 
-/// 获取轮盘数字的颜色
-fn get_roulette_color(number: u8) -> Color {
-    if number == 0 {
-        // 0 仅用于显示为绿色/特殊色，颜色押注不生效
-        Color::Black // 若 Color 枚举无绿色，保持显示但不允许颜色押注中奖
-    } else {
-        // 轮盘红色数字: 1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36
-        match number {
-            1 | 3 | 5 | 7 | 9 | 12 | 14 | 16 | 18 | 19 | 21 | 23 | 25 | 27 | 30 | 32 | 34 | 36 => {
-                Color::Red
+/// 按下注消息从种子推导开奖结果（不涉及赔付）
+fn game_outcome(bet: &BetRecord, seed: &[u8]) -> Result<GameOutcome, ContractError> {
+    let outcome = match &bet.game {
+        ExecuteMsg::PlayWar { .. } => {
+            let (user_rand, contract_rand) = roll_war(seed);
+            GameOutcome::War { user_rand, contract_rand }
+        }
+        ExecuteMsg::PlaySlot { mode, .. } => {
+            let reels = match mode {
+                SlotMode::Basic => spin_slot_basic(seed).map(|s| vec![s]).to_vec(),
+                SlotMode::Advanced => spin_slot_advanced(seed).map(Vec::from).to_vec(),
+                SlotMode::Mega => spin_slot_mega(seed).map(Vec::from).to_vec(),
+            };
+            GameOutcome::Slot { reels }
+        }
+        ExecuteMsg::GuessNumber { .. } => GameOutcome::GuessNumber {
+            correct_number: roll_guess_number(seed),
+        },
+        ExecuteMsg::PlayBlackjack { .. } => {
+            let decks = bet.blackjack_rules.clone().unwrap_or_default().decks;
+            let shoe = blackjack_shoe(seed, decks);
+            let (user_cards, dealer_cards) = deal_blackjack(&shoe);
+            GameOutcome::Blackjack {
+                shoe,
+                user_hands: vec![user_cards],
+                dealer_cards,
             }
-            _ => Color::Black, // 其余为黑色
         }
-    }
-}
-
-/// 计算轮盘游戏的赔付
-fn calculate_roulette_payout(bet_type: &RouletteBetType, result: &RouletteResult) -> (bool, u128) {
-    match bet_type {
-        // 单个数字下注，赔率 35:1
-        RouletteBetType::SingleNumber { number } => {
-            if *number == result.winning_number {
-                (true, 36) // 包含本金的总倍数
-            } else {
-                (false, 0)
+        ExecuteMsg::PlayCoinFlip { .. } => GameOutcome::CoinFlip { result: flip_coin(seed) },
+        ExecuteMsg::PlayDice { mode, .. } => {
+            let salt = match mode {
+                DiceGameMode::GuessSize { .. } => DICE_GUESS_SIZE_SALT,
+                DiceGameMode::ExactNumber { .. } => DICE_EXACT_NUMBER_SALT,
+                DiceGameMode::RangeBet { .. } => DICE_RANGE_BET_SALT,
+            };
+            GameOutcome::Dice { roll: roll_die(seed, salt) }
+        }
+        ExecuteMsg::PlayBaccarat { .. } => GameOutcome::Baccarat(deal_baccarat(seed)),
+        ExecuteMsg::PlayRoulette { .. } => GameOutcome::Roulette(spin_roulette(seed)),
+        ExecuteMsg::PlayOmaha { .. } => {
+            let deck = shuffled_deck(seed, b"omaha_deck");
+            let cards = |range: std::ops::Range<usize>| {
+                deck[range].iter().map(|&id| Card::from_id(id)).collect()
+            };
+            GameOutcome::Omaha {
+                player_hand: cards(0..4),
+                dealer_hand: cards(4..8),
+                community_cards: cards(8..13),
+                deck: deck.clone(),
             }
         }
-        // 颜色下注，赔率 1:1；开出 0 时颜色押注必输
-        RouletteBetType::Color { color } => {
-            if result.winning_number != 0 && *color == result.winning_color {
-                (true, 2)
-            } else {
-                (false, 0)
+        ExecuteMsg::PlayTexas { .. } => {
+            let deck = shuffled_deck(seed, b"texas_deck");
+            let cards = |range: std::ops::Range<usize>| {
+                deck[range].iter().map(|&id| texas::Card::from_id(id)).collect()
+            };
+            GameOutcome::Texas {
+                player_hand: cards(0..2),
+                dealer_hand: cards(2..4),
+                community_cards: cards(4..9),
+                deck: deck.clone(),
             }
         }
-        // 奇偶下注，赔率 1:1
-        RouletteBetType::EvenOdd { bet } => {
-            // 0 既不是奇数也不是偶数，投注奇偶都不会中奖
-            if result.winning_number == 0 {
-                (false, 0)
-            } else {
-                match bet {
-                    EvenOdd::Even => (result.is_even, 2), // 包含本金的总倍数
-                    EvenOdd::Odd => (!result.is_even, 2), // 包含本金的总倍数
-                }
+        ExecuteMsg::PlaySanGong { .. } => {
+            let (player_cards, dealer_cards) = deal_sangong(seed);
+            GameOutcome::SanGong {
+                player_cards: player_cards.to_vec(),
+                dealer_cards: dealer_cards.to_vec(),
             }
         }
-
-        // 大小下注（1-18 / 19-36），赔率 1:1
-        RouletteBetType::HighLow { bet } => {
-            if let Some(is_low) = result.is_low {
-                match bet {
-                    HighLow::Low => (is_low, 2),   // 包含本金的总倍数
-                    HighLow::High => (!is_low, 2), // 包含本金的总倍数
-                }
-            } else {
-                // 如果是0，大小投注都不中奖
-                (false, 0)
+        ExecuteMsg::PlaySicBo { .. } => GameOutcome::SicBo(roll_sicbo(seed)),
+        ExecuteMsg::PlayKeno { picks, .. } => GameOutcome::Keno(draw_keno(picks.clone(), seed)),
+        ExecuteMsg::PlayScratchCard { .. } => GameOutcome::ScratchCard {
+            grid: scratch_grid(seed).to_vec(),
+        },
+        ExecuteMsg::PlayBullFight { .. } => {
+            let (player_cards, dealer_cards) = deal_bullfight(seed);
+            GameOutcome::BullFight {
+                player_cards: player_cards.to_vec(),
+                dealer_cards: dealer_cards.to_vec(),
             }
         }
-    }
+        _ => return Err(ContractError::NotABet),
+    };
+    Ok(outcome)
 }
 
-// ──────────────────────────────────────────────────────────────────────────────
-// 奥马哈扑克（Omaha Hold'em）游戏
-//
-// 游戏流程：
-//   1. Start        → 玩家下注底注，发 4 张手牌（庄家也发 4 张），进入 PreFlop
-//   2. Raise/Call   → 在 PreFlop / Flop / Turn 阶段追加/跟注
-//   3. Showdown     → 任意阶段直接摊牌结算
-//   4. Fold         → 放弃本局，损失已押注金额
-//
-// 公共牌揭示节奏：
-//   PreFlop  → Flop（3 张）→ Turn（+1 张）→ River（+1 张）→ Showdown
-//
-// 奥马哈规则：必须用恰好 2 张手牌 + 3 张公共牌组成最佳 5 张
-// 加注规则：
-//   - 加注 (Raise): 附带 funds 金额作为追加注额，当前跟注额 += raise 金额
-//   - 跟注 (Call): 附带 funds 补齐差额（不少于 current_call_amount - player_total_bet）
-//   - Showdown/Fold 时无需再附带 funds
-// ──────────────────────────────────────────────────────────────────────────────
-fn play_omaha(
+/// 退还下注（仅限下注玩家，且庄家超过 REVEAL_TIMEOUT_BLOCKS 未揭示种子）
+fn refund_bet(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    action: OmahaAction,
-) -> StdResult<Response> {
-    match action {
-        // ── 开始游戏 ──────────────────────────────────────────────────
-        OmahaAction::Start => {
-            // 检查是否已有进行中游戏
-            if let Ok(existing) = OMAHA_STATE.load(deps.storage, &info.sender) {
-                if !existing.finished {
-                    return Err(StdError::generic_err(
-                        "You already have an active Omaha game. Fold or Showdown first.",
-                    ));
-                }
-            }
-
-            let bet = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if bet < 100_000 || bet > 5_000_000 {
-                return Err(StdError::generic_err(
-                    "Initial bet must be between 100,000 and 5,000,000 uatom",
-                ));
-            }
+    bet_id: u64,
+) -> Result<Response, ContractError> {
+    let mut bet = BETS.load(deps.storage, bet_id)?;
+    if info.sender != bet.player {
+        return Err(ContractError::Unauthorized);
+    }
+    if bet.settled {
+        return Err(ContractError::BetAlreadySettled);
+    }
+
+    // drand 轮次公开后任何人都能提交，玩家可自行开奖；
+    // 若允许退款，玩家可以先看到该轮随机数再决定是否退款
+    let commitment_id = match bet.entropy {
+        BetEntropy::Commitment { id } => id,
+        BetEntropy::Drand { .. } => {
+            return Err(ContractError::DrandBetNotRefundable)
+        }
+    };
+    let commitment = SEED_COMMITMENTS.load(deps.storage, commitment_id)?;
+    if commitment.server_seed.is_some() {
+        return Err(ContractError::SeedAlreadyRevealed);
+    }
+    // 熔断期间（暂停或该玩法已关闭）无需等待超时
+    let refundable_height = bet.placed_height + REVEAL_TIMEOUT_BLOCKS;
+    if !game_halted(deps.storage, &bet.game)? && env.block.height < refundable_height {
+        return Err(ContractError::RefundNotAvailable {
+            height: refundable_height,
+        });
+    }
 
-            // 生成洗牌后的牌组（0..=51 随机排列）
-            let deck = shuffle_deck(&info, &env);
+    bet.settled = true;
+    BETS.save(deps.storage, bet_id, &bet)?;
 
-            // 发牌：玩家 4 张 (pos 0-3)，庄家 4 张 (pos 4-7)
-            // 公共牌 5 张 (pos 8-12)，先全部生成但按阶段揭示
-            let player_hand: Vec<Card> = (0..4).map(|i| Card::from_id(deck[i])).collect();
-            let dealer_hand: Vec<Card> = (4..8).map(|i| Card::from_id(deck[i])).collect();
-            // 公共牌全部预生成在 deck[8..13]，按阶段通过 advance_stage 揭示
-            let state = OmahaState {
-                player_hand: player_hand.clone(),
-                dealer_hand,
-                community_cards: vec![],
-                player_total_bet: Uint128::from(bet),
-                current_call_amount: Uint128::from(bet),
-                stage: OmahaStage::PreFlop,
-                finished: false,
-                deck: deck.clone(),
-                deck_pos: 13, // 前 13 张已用
-            };
+    let (bettor, amount) = (bet.bettor(), bet.amount.u128());
+    let event = refund_event(GameKind::from_msg(&bet.game)?, bet_id, &bettor, amount)?;
+    let response = refund_stake(deps, &env, &bettor, amount, bet.reserved)?;
+    Ok(response
+        .add_event(event)
+        .add_attribute("action", "refund_bet")
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attribute("amount", bet.amount))
+}
 
-            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
+/// 从锁仓中原路退还玩家本金，并释放为其预留的赔付敞口
+fn refund_stake(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    amount: u128,
+    reserved: Uint128,
+) -> Result<Response, ContractError> {
+    release_reservation(deps.storage, &bettor.denom, reserved)?;
+    let refund = settle_payout(deps, env, bettor, amount)?;
+    Ok(Response::new().add_messages(refund))
+}
 
-            // 同时存储全部公共牌到 deck 的位置已固定，community 按阶段从 deck 取
-            // 更新合约锁仓
-            let mut global_state = STATE.load(deps.storage)?;
-            global_state.locked_amount += bet;
-            STATE.save(deps.storage, &global_state)?;
+/// 退款同样发出 game_settled 事件（outcome 为 "refund"），索引器据此关闭这笔下注
+fn refund_event(
+    game: GameKind,
+    bet_id: u64,
+    bettor: &Bettor,
+    amount: u128,
+) -> Result<Event, ContractError> {
+    let settlement = Settlement::refunded(game, amount);
+    Ok(settlement.event(bet_id, &bettor.player, &bettor.denom, &REFUND_OUTCOME)?)
+}
 
-            Ok(Response::new()
-                .add_attribute("action", "omaha_start")
-                .add_attribute("stage", "PreFlop")
-                .add_attribute("player_hand", format_cards(&player_hand))
-                .add_attribute("community_cards", "[]")
-                .add_attribute("initial_bet", bet.to_string()))
-        }
+/// 比大小游戏
+///
+/// 用户和合约进行比大小游戏, 用户生成的数字大于合约生成的数字,则用户获胜,获得下注金额 ×2 的奖励.
+fn play_war(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    sent_amount: u128,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::War)?;
 
-        // ── 加注 ──────────────────────────────────────────────────────
-        OmahaAction::Raise { amount } => {
-            let mut state = OMAHA_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
-            if matches!(state.stage, OmahaStage::Showdown) {
-                return Err(StdError::generic_err("Game is at Showdown, cannot raise"));
-            }
+    let (user_rand, contract_rand) = roll_war(seed);
 
-            // 检查附带的 funds
-            let sent = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if sent < amount || amount == 0 {
-                return Err(StdError::generic_err(
-                    "Must attach exactly the raise amount in uatom funds",
-                ));
-            }
+    // 比较结果
+    let mut response = Response::new();
+    let mut result = "lost";
+    let mut settlement = Settlement::lost(GameKind::War, sent_amount);
+    if user_rand > contract_rand {
+        // 用户赢: 发送奖励（下注金额 ×2，扣除抽水）
+        let gross_payout = sent_amount * 2;
+        let net_payout = apply_house_edge(gross_payout, sent_amount, house_edge_bps);
+        response = response.add_messages(settle_payout(deps, env, bettor, net_payout)?);
+        settlement = Settlement::new(GameKind::War, sent_amount, gross_payout, net_payout);
+        result = "win"
+    } else if user_rand == contract_rand {
+        // 平局: 退还下注
+        response = response.add_messages(settle_payout(deps, env, bettor, sent_amount)?);
+        settlement = Settlement::new(GameKind::War, sent_amount, sent_amount, sent_amount);
+        result = "tie"
+    }
 
-            if amount < 50_000 {
-                return Err(StdError::generic_err("Minimum raise is 50,000 uatom"));
-            }
+    // 用户输: 无需操作（资金留在合约）
 
-            // 推进阶段并揭示公共牌
-            let (new_stage, community) = advance_stage(&state);
+    let response = response
+        .add_attribute("action", "play_war")
+        .add_attribute("user_rand", user_rand.to_string())
+        .add_attribute("contract_rand", contract_rand.to_string())
+        .add_attribute("result", result);
+    Ok((response, settlement))
+}
 
-            state.player_total_bet += Uint128::from(amount);
-            state.current_call_amount += Uint128::from(amount);
-            state.stage = new_stage.clone();
-            state.community_cards = community.clone();
+/// 比大小开奖：返回 (用户 1～100 的随机数, 合约 1～100 的随机数)
+fn roll_war(seed: &[u8]) -> (u32, u32) {
+    let mut rng = RngStream::new(seed, b"war");
+    let user_rand = rng.range_inclusive(1, 100);
+    let contract_rand = rng.range_inclusive(1, 100);
+    (user_rand, contract_rand)
+}
 
-            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
+/// 老虎机游戏
+/// Slot 游戏
+///
+/// Basic 模式  (mode = basic)  : 3 轮 × 1 行
+///   - 3 个相同符号（Wild 可替代）→ 全额倍率
+///   - 2 个相同符号              → 半额倍率
+///   - 全不同                    → 无奖励
+///
+/// Advanced 模式 (mode = advanced) : 5 轮 × 3 行，5 条赢线
+///   - 每条赢线从左起连续 3/4/5 个相同（Wild 可替代）→ 倍率累加
+///   - Scatter 出现 3/4/5+ 个 → 额外奖励倍率 5/15/50
+///   - 总倍率 = 所有赢线倍率 + Scatter 奖励倍率
+///
+/// 下注范围：Basic 100,000 – 10,000,000 uatom
+///          Advanced 200,000 – 10,000,000 uatom（5 线消耗更高）
+fn play_slot(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    sent_amount: u128,
+    mode: SlotMode,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let kind = GameKind::slot(&mode);
+    let house_edge_bps = game_house_edge(deps.storage, kind)?;
 
-            let mut global_state = STATE.load(deps.storage)?;
-            global_state.locked_amount += sent;
-            STATE.save(deps.storage, &global_state)?;
+    // ── 生成随机数并构建符号 ──────────────────────
+    let mut response = Response::new()
+        .add_attribute("mode", format!("{:?}", mode))
+        .add_attribute("bet_amount", sent_amount.to_string());
 
-            Ok(Response::new()
-                .add_attribute("action", "omaha_raise")
-                .add_attribute("raise_amount", amount.to_string())
-                .add_attribute("total_bet", state.player_total_bet.to_string())
-                .add_attribute("stage", format!("{:?}", new_stage))
-                .add_attribute("community_cards", format_cards(&community)))
-        }
+    let payout_multiplier: u64;
 
-        // ── 跟注 ──────────────────────────────────────────────────────
-        OmahaAction::Call => {
-            let mut state = OMAHA_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
-            if matches!(state.stage, OmahaStage::Showdown) {
-                return Err(StdError::generic_err("Game is at Showdown, use Showdown action"));
-            }
+    match mode {
+        // ── Basic：3 轮 1 行 ─────────────────────
+        SlotMode::Basic => {
+            let [s1, s2, s3] = spin_slot_basic(seed);
 
-            // 需要补齐的差额
-            let call_diff = state
-                .current_call_amount
-                .saturating_sub(state.player_total_bet)
-                .u128();
+            response = response
+                .add_attribute("reel1", format!("{:?}", s1))
+                .add_attribute("reel2", format!("{:?}", s2))
+                .add_attribute("reel3", format!("{:?}", s3));
 
-            let sent = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if call_diff > 0 && sent < call_diff {
-                return Err(StdError::generic_err(format!(
-                    "Need to call at least {} uatom to match current bet",
-                    call_diff
-                )));
-            }
+            let result = evaluate_basic(&s1, &s2, &s3);
+            payout_multiplier = result.multiplier;
+            response = response.add_attribute("win_desc", result.description);
+        }
 
-            // 推进阶段
-            let (new_stage, community) = advance_stage(&state);
+        // ── Advanced：5 轮 3 行 5 赢线 ───────────
+        SlotMode::Advanced => {
+            let grid = spin_slot_advanced(seed);
 
-            state.player_total_bet += Uint128::from(call_diff.max(sent));
-            state.stage = new_stage.clone();
-            state.community_cards = community.clone();
+            // 输出每列每行到 attributes
+            for (col, reel) in grid.iter().enumerate() {
+                for (row, symbol) in reel.iter().enumerate() {
+                    response = response.add_attribute(
+                        format!("reel{}_{}", col + 1, row + 1),
+                        format!("{:?}", symbol),
+                    );
+                }
+            }
 
-            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
+            let (total_mult, descriptions) = evaluate_advanced(&grid);
+            payout_multiplier = total_mult;
+            response = response.add_attribute("win_desc", descriptions.join("|"));
+        }
+
+        // ── Mega：6 轮 4 行 10 赢线 + 免费旋转 + Jackpot ──
+        SlotMode::Mega => {
+            let grid = spin_slot_mega(seed);
 
-            if sent > 0 {
-                let mut global_state = STATE.load(deps.storage)?;
-                global_state.locked_amount += sent;
-                STATE.save(deps.storage, &global_state)?;
+            // 输出每列每行到 attributes
+            for (col, reel) in grid.iter().enumerate() {
+                for (row, symbol) in reel.iter().enumerate() {
+                    response = response.add_attribute(
+                        format!("reel{}_{}", col + 1, row + 1),
+                        format!("{:?}", symbol),
+                    );
+                }
             }
 
-            Ok(Response::new()
-                .add_attribute("action", "omaha_call")
-                .add_attribute("call_amount", call_diff.to_string())
-                .add_attribute("total_bet", state.player_total_bet.to_string())
-                .add_attribute("stage", format!("{:?}", new_stage))
-                .add_attribute("community_cards", format_cards(&community)))
+            let mega_result = evaluate_mega(&grid);
+            payout_multiplier = mega_result.total_multiplier;
+            let mut desc_parts = mega_result.descriptions;
+            if mega_result.free_spin_triggered {
+                desc_parts.push(format!("free_spin:triggered(x{})", mega_result.free_spin_multiplier));
+            }
+            if mega_result.jackpot {
+                desc_parts.push("jackpot:true".to_string());
+            }
+            response = response
+                .add_attribute("win_desc", desc_parts.join("|"))
+                .add_attribute("free_spin", mega_result.free_spin_triggered.to_string())
+                .add_attribute("jackpot", mega_result.jackpot.to_string());
         }
+    }
 
-        // ── 弃牌 ──────────────────────────────────────────────────────
-        OmahaAction::Fold => {
-            let mut state = OMAHA_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
+    // ── 结算 ──────────────────────────────────────
+    let mut settlement = Settlement::lost(kind, sent_amount);
+    if payout_multiplier > 0 {
+        let gross_payout = sent_amount * payout_multiplier as u128;
+        let payout_amount = apply_house_edge(gross_payout, sent_amount, house_edge_bps);
+        settlement = Settlement::new(kind, sent_amount, gross_payout, payout_amount);
 
-            state.finished = true;
-            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
+        response = response
+            .add_attribute("result", "win")
+            .add_attribute("payout_multiplier", payout_multiplier.to_string())
+            .add_messages(settle_payout(deps, env, bettor, payout_amount)?);
+    } else {
+        response = response.add_attribute("result", "lost");
+    }
 
-            // 玩家已下注金额归合约
-            Ok(Response::new()
-                .add_attribute("action", "omaha_fold")
-                .add_attribute("result", "folded")
-                .add_attribute("lost_amount", state.player_total_bet.to_string()))
-        }
+    Ok((response, settlement))
+}
 
-        // ── 摊牌结算 ───────────────────────────────────────────────────
-        OmahaAction::Showdown => {
-            let mut state = OMAHA_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
+/// Basic 老虎机：3 轮 1 行
+fn spin_slot_basic(seed: &[u8]) -> [Symbol; 3] {
+    let mut rng = RngStream::new(seed, b"slot_basic");
+    std::array::from_fn(|_| Symbol::draw(&mut rng))
+}
 
-            // 揭示全部 5 张公共牌
-            let full_community: Vec<Card> = (8..13).map(|i| Card::from_id(state.deck[i])).collect();
-
-            // 评估双方最佳手牌
-            let player_rank = best_omaha_hand_rank(&state.player_hand, &full_community);
-            let dealer_rank = best_omaha_hand_rank(&state.dealer_hand, &full_community);
-
-            let player_hand_name = hand_rank_name(player_rank);
-            let dealer_hand_name = hand_rank_name(dealer_rank);
-
-            let total_bet = state.player_total_bet.u128();
-
-            let mut global_state = STATE.load(deps.storage)?;
-            let mut response = Response::new()
-                .add_attribute("action", "omaha_showdown")
-                .add_attribute("player_hand", format_cards(&state.player_hand))
-                .add_attribute("dealer_hand", format_cards(&state.dealer_hand))
-                .add_attribute("community_cards", format_cards(&full_community))
-                .add_attribute("player_rank_name", player_hand_name)
-                .add_attribute("dealer_rank_name", dealer_hand_name)
-                .add_attribute("player_rank", player_rank.to_string())
-                .add_attribute("dealer_rank", dealer_rank.to_string());
-
-            if player_rank > dealer_rank {
-                // 玩家赢：获得 2× 下注额（扣除抽水）
-                let gross = total_bet * 2;
-                let payout = apply_house_edge(gross, total_bet, global_state.house_edge_bps);
-                global_state.locked_amount =
-                    global_state.locked_amount.saturating_sub(payout);
-                response = response
-                    .add_attribute("result", "player_win")
-                    .add_attribute("payout", payout.to_string())
-                    .add_message(BankMsg::Send {
-                        to_address: info.sender.to_string(),
-                        amount: vec![Coin {
-                            denom: "uatom".to_string(),
-                            amount: Uint128::from(payout),
-                        }],
-                    });
-            } else if dealer_rank > player_rank {
-                // 庄家赢：玩家损失下注额（已留在合约中）
-                response = response
-                    .add_attribute("result", "dealer_win")
-                    .add_attribute("payout", "0");
-            } else {
-                // 平局：退还下注额
-                global_state.locked_amount =
-                    global_state.locked_amount.saturating_sub(total_bet);
-                response = response
-                    .add_attribute("result", "tie")
-                    .add_attribute("payout", total_bet.to_string())
-                    .add_message(BankMsg::Send {
-                        to_address: info.sender.to_string(),
-                        amount: vec![Coin {
-                            denom: "uatom".to_string(),
-                            amount: Uint128::from(total_bet),
-                        }],
-                    });
-            }
+/// Advanced 老虎机：grid[col][row]，5 列 × 3 行
+fn spin_slot_advanced(seed: &[u8]) -> [[Symbol; 3]; 5] {
+    let mut rng = RngStream::new(seed, b"slot_advanced");
+    std::array::from_fn(|_| std::array::from_fn(|_| Symbol::draw(&mut rng)))
+}
 
-            state.finished = true;
-            state.community_cards = full_community;
-            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
-            STATE.save(deps.storage, &global_state)?;
+/// Mega 老虎机：grid[col][row]，6 列 × 4 行
+fn spin_slot_mega(seed: &[u8]) -> [[Symbol; 4]; 6] {
+    let mut rng = RngStream::new(seed, b"slot_mega");
+    std::array::from_fn(|_| std::array::from_fn(|_| Symbol::draw(&mut rng)))
+}
 
-            Ok(response)
-        }
+/// 猜数字游戏(范围 1 ～ 10)
+///
+/// 合约生成一个随机数,如果用户猜对,获得奖励。(完全猜中 x10、相邻 x1）
+fn play_guess_number(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    sent_amount: u128,
+    user_guess: u8,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::GuessNumber)?;
+
+    let rand = roll_guess_number(seed);
+
+    let mut gross_payout = 0;
+    let mut result = "lost";
+
+    if user_guess as u32 == rand {
+        gross_payout = sent_amount * 10;
+        result = "exact";
+    } else if (user_guess as i32 - rand as i32).abs() == 1 {
+        gross_payout = sent_amount;
+        result = "adjacent";
     }
-}
+    let payout = apply_house_edge(gross_payout, sent_amount, house_edge_bps);
 
-/// 根据当前阶段推进到下一阶段，并返回应揭示的公共牌列表
-fn advance_stage(state: &OmahaState) -> (OmahaStage, Vec<Card>) {
-    let deck = &state.deck;
-    match state.stage {
-        OmahaStage::PreFlop => {
-            // 翻牌：揭示 3 张公共牌（deck[8..11]）
-            let community = (8..11).map(|i| Card::from_id(deck[i])).collect();
-            (OmahaStage::Flop, community)
-        }
-        OmahaStage::Flop => {
-            // 转牌：揭示 4 张（deck[8..12]）
-            let community = (8..12).map(|i| Card::from_id(deck[i])).collect();
-            (OmahaStage::Turn, community)
-        }
-        OmahaStage::Turn => {
-            // 河牌：揭示全部 5 张（deck[8..13]）
-            let community = (8..13).map(|i| Card::from_id(deck[i])).collect();
-            (OmahaStage::River, community)
-        }
-        OmahaStage::River | OmahaStage::Showdown => {
-            // 已经揭示完毕，保持不变
-            let community = state.community_cards.clone();
-            (OmahaStage::Showdown, community)
-        }
+    let mut response = Response::new();
+
+    if payout > 0 {
+        response = response.add_messages(settle_payout(deps, env, bettor, payout)?);
     }
+
+    let response = response
+        .add_attribute("action", "play_guess_number")
+        .add_attribute("user_guess", user_guess.to_string())
+        .add_attribute("correct_number", rand.to_string())
+        .add_attribute("result", result);
+    let settlement = Settlement::new(GameKind::GuessNumber, sent_amount, gross_payout, payout);
+    Ok((response, settlement))
 }
 
-/// 生成洗牌后的 52 张牌（card_id 0..=51）
-fn shuffle_deck(info: &MessageInfo, env: &Env) -> Vec<u8> {
-    // 用多个盐生成足够熵来 Fisher-Yates 洗牌
-    let mut deck: Vec<u8> = (0u8..52).collect();
-    // 为每张牌生成一个随机权重
-    let mut weights: Vec<u32> = (0u8..52)
-        .map(|i| {
-            let salt = format!("omaha_deck_{}", i);
-            utils::generate_random_number(info, env, salt.as_bytes())
-        })
-        .collect();
+/// 猜数字开奖：合约伪随机数 1~10
+fn roll_guess_number(seed: &[u8]) -> u32 {
+    RngStream::new(seed, b"guess").range_inclusive(1, 10)
+}
 
-    // 按权重排序模拟洗牌（简单方案：稳定的伪随机排列）
-    // Fisher-Yates 风格：用权重 XOR index 保证唯一性
-    for i in (1..52usize).rev() {
-        let j = (weights[i] as usize + i * 97) % (i + 1);
-        deck.swap(i, j);
-        weights.swap(i, j);
+/// 注入流动性（财务）：附带的可下注币种计入锁仓
+fn add_liquidity(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    ensure_role(deps.storage, &state, &info.sender, Role::Treasurer)?;
+    let funds: Vec<&Coin> = info.funds.iter().filter(|c| !c.amount.is_zero()).collect();
+    if funds.is_empty() {
+        return Err(ContractError::NoFunds { action: "add liquidity" });
     }
-    deck
-}
 
-/// 格式化 Card 列表为可读字符串
-fn format_cards(cards: &[Card]) -> String {
-    let parts: Vec<String> = cards
-        .iter()
-        .map(|c| {
-            let rank_str = match c.rank {
-                11 => "J".to_string(),
-                12 => "Q".to_string(),
-                13 => "K".to_string(),
-                14 => "A".to_string(),
-                n => n.to_string(),
-            };
-            let suit_str = match c.suit {
-                omaha::Suit::Spades   => "♠",
-                omaha::Suit::Hearts   => "♥",
-                omaha::Suit::Diamonds => "♦",
-                omaha::Suit::Clubs    => "♣",
-            };
-            format!("{}{}", rank_str, suit_str)
-        })
-        .collect();
-    format!("[{}]", parts.join(","))
+    let mut response = Response::new().add_attribute("action", "add_liquidity");
+    for coin in funds {
+        state.denom_config(&coin.denom)?;
+        state.lock(&coin.denom, coin.amount.u128());
+        response = response
+            .add_attribute("denom", coin.denom.clone())
+            .add_attribute("amount", coin.amount.to_string())
+            .add_attribute("locked_amount", state.locked(&coin.denom).to_string());
+    }
+    STATE.save(deps.storage, &state)?;
+    Ok(response)
 }
 
 // ──────────────────────────────────────────────────────────────────────────────
-// 德州扑克（Texas Hold'em）
-//
-// 流程：
-//   1. Start        → 玩家下注底注，发 2 张手牌（庄家也发 2 张），进入 PreFlop
-//   2. Raise/Call/Check → 在 PreFlop / Flop / Turn / River 阶段操作
-//   3. AllIn        → 全押，直接进入 Showdown 自动揭示所有公共牌
-//   4. Showdown     → 任意阶段直接摊牌结算
-//   5. Fold         → 放弃本局，损失已押注金额
-//
-// 公共牌揭示节奏：
-//   PreFlop  → Flop（3 张）→ Turn（+1 张）→ River（+1 张）→ Showdown
+// CW20 代币
 //
-// 德州规则：从 2 张手牌 + 5 张公共牌中选最佳 5 张组合
+// 玩家调用代币合约的 Send，把代币连同内嵌消息发给本合约，代币合约再以
+// Receive 回调本合约：info.sender 为代币合约，Cw20ReceiveMsg.sender 为玩家。
+// 内嵌消息按附带等额代币的原消息执行，赔付与提款通过 Cw20ExecuteMsg::Transfer，
+// 锁仓以代币合约地址为币种单独记账。
 // ──────────────────────────────────────────────────────────────────────────────
-fn play_texas(
+
+/// 处理 CW20 Receive 回调：内嵌 Play* 消息为下注，AddLiquidity 为部署者注入流动性
+fn receive_cw20(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    action: TexasAction,
-) -> StdResult<Response> {
-    match action {
-        // ── 开始游戏 ──────────────────────────────────────────────────
-        TexasAction::Start => {
-            // 检查是否已有进行中游戏
-            if let Ok(existing) = TEXAS_STATE.load(deps.storage, &info.sender) {
-                if !existing.finished {
-                    return Err(StdError::generic_err(
-                        "You already have an active Texas Hold'em game. Fold or Showdown first.",
-                    ));
-                }
-            }
-
-            let bet = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if bet < 100_000 || bet > 5_000_000 {
-                return Err(StdError::generic_err(
-                    "Initial bet must be between 100,000 and 5,000,000 uatom",
-                ));
-            }
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let token = info.sender.to_string();
+    if !state.denom_config(&token).is_ok_and(|d| d.cw20) {
+        return Err(ContractError::UnsupportedCw20 { token });
+    }
+    if !info.funds.is_empty() {
+        return Err(ContractError::InvalidFunds {
+            reason: "Do not attach native funds with CW20 tokens",
+        });
+    }
 
-            // 洗牌
-            let deck = shuffle_texas_deck(&info, &env);
+    let msg: ExecuteMsg = from_json(&wrapper.msg)?;
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&wrapper.sender)?,
+        funds: vec![Coin {
+            denom: token,
+            amount: wrapper.amount,
+        }],
+    };
+    match msg {
+        ExecuteMsg::AddLiquidity {} => add_liquidity(deps, info),
+        game if game.is_play() && !matches!(game, ExecuteMsg::PlayFromBalance { .. }) => {
+            // 代币合约只是转发，检查的是发起 Send 的玩家
+            ensure_caller_allowed(deps.as_ref(), &info.sender)?;
+            check_circuit_breaker(deps.storage, &game)?;
+            execute_play(deps, env, info, game, FundsSource::Attached)
+        }
+        _ => Err(ContractError::NotAPlayMessage {
+            context: "sent with CW20 tokens",
+        }),
+    }
+}
 
-            // 发牌：玩家 2 张 (pos 0-1)，庄家 2 张 (pos 2-3)
-            // 公共牌 5 张 (pos 4-8)，按阶段揭示
-            let player_hand: Vec<texas::Card> =
-                (0..2).map(|i| texas::Card::from_id(deck[i])).collect();
-            let dealer_hand: Vec<texas::Card> =
-                (2..4).map(|i| texas::Card::from_id(deck[i])).collect();
+// 处理提款逻辑：提款只能转入所有者地址
+// 经由时间锁执行（见 apply_admin_action），调用前已校验财务角色与金额
+fn withdraw_funds(
+    deps: DepsMut,
+    amount: u128,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
 
-            let state = TexasState {
-                player_hand: player_hand.clone(),
-                dealer_hand,
-                community_cards: vec![],
-                player_total_bet: Uint128::from(bet),
-                current_call_amount: Uint128::from(bet),
-                stage: TexasStage::PreFlop,
-                finished: false,
-                all_in: false,
-                deck: deck.clone(),
-            };
+    // 检查可提款余额：为进行中牌局预留的赔付不能提取
+    let config = state.denom_or_primary(denom)?.clone();
+    if amount > state.free(&config.denom) {
+        return Err(ContractError::InsufficientLockedFunds);
+    }
 
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+    // 更新锁仓金额
+    state.unlock(&config.denom, amount);
+    STATE.save(deps.storage, &state)?;
 
-            let mut global_state = STATE.load(deps.storage)?;
-            global_state.locked_amount += bet;
-            STATE.save(deps.storage, &global_state)?;
+    // 发送代币
+    Ok(Response::new()
+        .add_message(config.transfer_msg(&state.owner, Uint128::from(amount))?)
+        .add_attribute("action", "withdraw")
+        .add_attribute("amount", amount.to_string()))
+}
 
-            Ok(Response::new()
-                .add_attribute("action", "texas_start")
-                .add_attribute("stage", "PreFlop")
-                .add_attribute("player_hand", format_texas_cards(&player_hand))
-                .add_attribute("community_cards", "[]")
-                .add_attribute("initial_bet", bet.to_string()))
-        }
-
-        // ── 加注 ──────────────────────────────────────────────────────
-        TexasAction::Raise { amount } => {
-            let mut state = TEXAS_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
-            if state.all_in {
-                return Err(StdError::generic_err("Already all-in, cannot raise"));
-            }
-            if matches!(state.stage, TexasStage::Showdown) {
-                return Err(StdError::generic_err("Game is at Showdown, cannot raise"));
-            }
-
-            let sent = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if sent < amount || amount == 0 {
-                return Err(StdError::generic_err(
-                    "Must attach exactly the raise amount in uatom funds",
-                ));
-            }
-
-            if amount < 50_000 {
-                return Err(StdError::generic_err("Minimum raise is 50,000 uatom"));
-            }
-
-            let (new_stage, community) = advance_texas_stage(&state);
+/// 修改庄家抽水比例
+///
+/// 抽水比例以基点表示，100 基点 = 1%，最高 1000 基点 = 10%；
+/// 经由时间锁执行，调用前已校验风控角色与取值范围
+fn update_house_edge(deps: DepsMut, new_house_edge_bps: u16) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
 
-            state.player_total_bet += Uint128::from(amount);
-            state.current_call_amount += Uint128::from(amount);
-            state.stage = new_stage.clone();
-            state.community_cards = community.clone();
+    let old_bps = state.house_edge_bps;
+    state.house_edge_bps = new_house_edge_bps;
+    STATE.save(deps.storage, &state)?;
 
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_house_edge")
+        .add_attribute("old_house_edge_bps", old_bps.to_string())
+        .add_attribute("new_house_edge_bps", new_house_edge_bps.to_string()))
+}
 
-            let mut global_state = STATE.load(deps.storage)?;
-            global_state.locked_amount += sent;
-            STATE.save(deps.storage, &global_state)?;
+/// 对净赢利部分扣除庄家抽水
+///
+/// `gross_payout`  = 玩家总共应收到的金额（包含本金 + 赢利）
+/// `original_bet`  = 玩家原始下注额
+/// `house_edge_bps` = 抽水基点（0-1000）
+///
+/// 抽水仅作用于净赢利部分（gross_payout - original_bet），不影响退还本金。
+/// 返回扣除抽水后的实际支付金额。
+fn apply_house_edge(gross_payout: u128, original_bet: u128, house_edge_bps: u16) -> u128 {
+    if house_edge_bps == 0 || gross_payout <= original_bet {
+        return gross_payout; // 无抽水或无赢利（平局 / 亏损）
+    }
+    let net_winnings = gross_payout - original_bet;
+    let rake = net_winnings * house_edge_bps as u128 / 10_000;
+    gross_payout - rake
+}
 
-            Ok(Response::new()
-                .add_attribute("action", "texas_raise")
-                .add_attribute("raise_amount", amount.to_string())
-                .add_attribute("total_bet", state.player_total_bet.to_string())
-                .add_attribute("stage", format!("{:?}", new_stage))
-                .add_attribute("community_cards", format_texas_cards(&community)))
+/// 21 点游戏启动
+///
+/// 启动 21 点游戏, 用户下注金额必须介于 100,000 和 10,000,000 uatom 之间。
+/// 开局时按下注时庄规的副数用本局种子洗好牌靴发牌; 牌靴不随状态保存,
+/// 后续要牌和庄家补牌时按同一种子重新洗出, 按已发牌数顺序发出.
+/// 牌局使用下注时保存的整套庄规, 下注之后修改庄规不影响该局.
+/// 庄家明牌不是 A 时当场看暗牌, 玩家或庄家有天然 21 点时当场结算;
+/// 明牌为 A 时先给玩家买保险的机会, 到第一个操作时再看暗牌.
+/// 开局附带的边注（Perfect Pairs / 21+3）发牌后当场结算赔付, 其余下注额为主注.
+///
+/// 若结算时玩家已有进行中的牌局（同时挂了多笔 Start 下注），退还本次下注
+fn play_blackjack_start(
+    mut deps: DepsMut,
+    env: &Env,
+    bet: &BetRecord,
+    seed: &[u8],
+) -> Result<Response, ContractError> {
+    let player = &bet.player;
+
+    if let Some(existing) = BLACKJACK_STATE.may_load(deps.storage, player)? {
+        if !existing.finished {
+            let (bettor, amount) = (bet.bettor(), bet.amount.u128());
+            let event = refund_event(GameKind::Blackjack, bet.id, &bettor, amount)?;
+            let response = refund_stake(deps, env, &bettor, amount, bet.reserved)?;
+            return Ok(response
+                .add_event(event)
+                .add_attribute("action", BlackjackAction::Start.name())
+                .add_attribute("result", "refunded"));
         }
+    }
 
-        // ── 跟注 ──────────────────────────────────────────────────────
-        TexasAction::Call => {
-            let mut state = TEXAS_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
-            if state.all_in {
-                return Err(StdError::generic_err("Already all-in, cannot call"));
-            }
-            if matches!(state.stage, TexasStage::Showdown) {
-                return Err(StdError::generic_err(
-                    "Game is at Showdown, use Showdown action",
-                ));
-            }
+    // 洗牌并发 4 张初始牌: 2 张牌是用户的、2 张牌是庄家的
+    let rules = bet.blackjack_rules.clone().unwrap_or_default();
+    let shoe = blackjack_shoe(seed, rules.decks);
+    let (user_cards, dealer_cards) = deal_blackjack(&shoe);
+    let upcard = dealer_cards[1];
 
-            let call_diff = state
-                .current_call_amount
-                .saturating_sub(state.player_total_bet)
-                .u128();
+    // 边注按玩家前两张牌与庄家明牌当场结算，释放其预留敞口；下注额中其余部分为主注
+    let side_bets = bet.game.blackjack_side_bets().cloned().unwrap_or_default();
+    let side_results = side_bets.settle(&user_cards, &upcard);
+    let house_edge = game_house_edge(deps.storage, GameKind::Blackjack)?;
+    let side_payout: u128 = side_results
+        .iter()
+        .map(|side| apply_house_edge(side.gross, side.bet, house_edge))
+        .sum();
+    release_reservation(deps.storage, &bet.denom, Uint128::from(side_bets.max_payout()))?;
+    let side_messages = settle_payout(deps.branch(), env, &bet.bettor(), side_payout)?;
+    let main_bet = Uint128::from(bet.amount.u128() - side_bets.total());
+
+    let mut state = BlackjackState {
+        hands: vec![BlackjackHand::new(user_cards, main_bet)],
+        active_hand: 0,
+        dealer_cards,
+        bet: main_bet,
+        insurance: Uint128::zero(),
+        peeked: !upcard.is_ace(),
+        finished: false,
+        source: bet.source,
+        denom: bet.denom.clone(),
+        reserved: bet.reserved - Uint128::from(side_bets.max_payout()),
+        bet_id: bet.id,
+        start_height: env.block.height,
+        start_time: env.block.time.seconds(),
+        rules,
+        side_bets,
+        side_payout: Uint128::from(side_payout),
+    };
 
-            let sent = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if call_diff > 0 && sent < call_diff {
-                return Err(StdError::generic_err(format!(
-                    "Need to call at least {} uatom to match current bet",
-                    call_diff
-                )));
-            }
+    let mut response = Response::new()
+        .add_messages(side_messages)
+        .add_attribute("action", BlackjackAction::Start.name())
+        .add_attribute("user_card1", format_blackjack_card(&state.hands[0].cards[0]))
+        .add_attribute("user_card2", format_blackjack_card(&state.hands[0].cards[1]))
+        .add_attribute("dealer_card1", "hide") // 庄家的起手牌进行 hide
+        .add_attribute("dealer_card2", format_blackjack_card(&upcard));
+    for side in &side_results {
+        response = response.add_attribute(format!("{}_result", side.side_bet), side.result);
+    }
+    if !side_results.is_empty() {
+        response = response.add_attribute("side_payout", side_payout.to_string());
+    }
 
-            let (new_stage, community) = advance_texas_stage(&state);
+    // 庄家看暗牌：庄家或玩家有天然 21 点时不再继续要牌，直接比牌
+    if state.peeked && (is_natural(&state.dealer_cards) || state.hands[0].is_natural()) {
+        return finish_blackjack(deps.branch(), env, player, &mut state, 0, response);
+    }
 
-            state.player_total_bet += Uint128::from(call_diff.max(sent));
-            state.stage = new_stage.clone();
-            state.community_cards = community.clone();
+    BLACKJACK_STATE.save(deps.storage, player, &state)?;
+    Ok(response)
+}
 
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+/// 21 点开局后的操作
+///
+/// 要牌、停牌、加倍、分牌作用于当前这手牌; 加倍 / 分牌 / 保险需附带对应的下注.
+/// 庄家明牌为 A 且尚未看暗牌时, 先看暗牌再执行操作: 庄家天然 21 点（或玩家天然 21 点）
+/// 时当场结算, 本次附带的加倍 / 分牌下注原样退还.
+fn play_blackjack(
+    mut deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    action: BlackjackAction,
+) -> Result<Response, ContractError> {
+    // 开局需先下注并等待开奖，由 settle_bet 调用 play_blackjack_start
+    if matches!(action, BlackjackAction::Start) {
+        return Err(ContractError::StartRequiresBet { game: GameKind::Blackjack });
+    }
+    let player = &info.sender;
+    let mut state = BLACKJACK_STATE
+        .may_load(deps.storage, player)?
+        .ok_or(ContractError::GameNotFound { game: GameKind::Blackjack })?;
+    if state.finished {
+        return Err(ContractError::GameFinished);
+    }
 
-            if sent > 0 {
-                let mut global_state = STATE.load(deps.storage)?;
-                global_state.locked_amount += sent;
-                STATE.save(deps.storage, &global_state)?;
-            }
+    // 检查操作是否允许，并核对附带的追加下注；不需要追加下注的操作不接受附带资金
+    let stake = blackjack_stake(&state, &action)?;
+    if stake == 0 {
+        ensure_no_funds(&info.funds, action.name(), &state.denom)?;
+    } else if sent_in_denom(&info.funds, &state.denom) != stake {
+        return Err(ContractError::FundsMismatch {
+            action: action.name(),
+            denom: state.denom,
+        });
+    }
+    let response = Response::new().add_attribute("action", action.name());
 
-            Ok(Response::new()
-                .add_attribute("action", "texas_call")
-                .add_attribute("call_amount", call_diff.to_string())
-                .add_attribute("total_bet", state.player_total_bet.to_string())
-                .add_attribute("stage", format!("{:?}", new_stage))
-                .add_attribute("community_cards", format_texas_cards(&community)))
+    // 庄家明牌为 A：第一个操作时看暗牌（保险在看暗牌前买入）
+    if !state.peeked {
+        state.peeked = true;
+        let returned = if matches!(action, BlackjackAction::Insurance) {
+            state.insurance = Uint128::from(stake);
+            0
+        } else {
+            stake
+        };
+        if is_natural(&state.dealer_cards) || state.hands[0].is_natural() {
+            add_blackjack_stake(deps.storage, &mut state, stake)?;
+            return finish_blackjack(deps.branch(), env, player, &mut state, returned, response);
         }
+    }
 
-        // ── 过牌 ──────────────────────────────────────────────────────
-        TexasAction::Check => {
-            let mut state = TEXAS_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
-            if state.all_in {
-                return Err(StdError::generic_err("Already all-in, cannot check"));
-            }
-            if matches!(state.stage, TexasStage::Showdown) {
-                return Err(StdError::generic_err(
-                    "Game is at Showdown, use Showdown action",
-                ));
+    let index = usize::from(state.active_hand);
+    let response = match action {
+        BlackjackAction::Start => unreachable!("Start is rejected above"),
+        BlackjackAction::Insurance => {
+            add_blackjack_stake(deps.storage, &mut state, stake)?;
+            response.add_attribute("insurance", state.insurance.to_string())
+        }
+        BlackjackAction::Hit => {
+            let shoe = blackjack_game_shoe(deps.storage, &state)?;
+            let new_card = draw_blackjack_card(&shoe, &state);
+            state.hands[index].cards.push(new_card);
+            response
+                .add_attribute("hand", index.to_string())
+                .add_attribute("new_card", format_blackjack_card(&new_card))
+                .add_attribute("current_total", state.hands[index].total().to_string())
+        }
+        BlackjackAction::Stand => {
+            state.hands[index].stood = true;
+            response.add_attribute("hand", index.to_string())
+        }
+        BlackjackAction::DoubleDown => {
+            let hand = &mut state.hands[index];
+            hand.bet += Uint128::from(stake);
+            hand.doubled = true;
+            add_blackjack_stake(deps.storage, &mut state, stake)?;
+            // 加倍后只再发一张牌
+            let shoe = blackjack_game_shoe(deps.storage, &state)?;
+            let new_card = draw_blackjack_card(&shoe, &state);
+            let hand = &mut state.hands[index];
+            hand.cards.push(new_card);
+            hand.stood = true;
+            response
+                .add_attribute("hand", index.to_string())
+                .add_attribute("new_card", format_blackjack_card(&new_card))
+                .add_attribute("current_total", hand.total().to_string())
+                .add_attribute("hand_bet", hand.bet.to_string())
+        }
+        BlackjackAction::Split => {
+            // 第二张牌移到紧随其后的新一手，两手各补一张牌
+            let hand = &mut state.hands[index];
+            hand.split = true;
+            let second = hand.cards.pop().expect("pair has two cards");
+            let new_hand = BlackjackHand {
+                split: true,
+                ..BlackjackHand::new(vec![second], Uint128::from(stake))
+            };
+            state.hands.insert(index + 1, new_hand);
+            add_blackjack_stake(deps.storage, &mut state, stake)?;
+            let shoe = blackjack_game_shoe(deps.storage, &state)?;
+            for i in [index, index + 1] {
+                let new_card = draw_blackjack_card(&shoe, &state);
+                let hand = &mut state.hands[i];
+                hand.cards.push(new_card);
+                // A 分牌后各只补一张牌
+                hand.stood = second.is_ace();
             }
+            response
+                .add_attribute("hand", index.to_string())
+                .add_attribute("hands", state.hands.len().to_string())
+                .add_attribute("user_cards", format_blackjack_hands(&state.hands))
+        }
+        BlackjackAction::Surrender => {
+            let hand = &mut state.hands[index];
+            hand.surrendered = true;
+            hand.stood = true;
+            response
+        }
+    };
 
-            // Check 仅在差额为 0 时允许（即无需补齐）
-            let diff = state
-                .current_call_amount
-                .saturating_sub(state.player_total_bet)
-                .u128();
-            if diff > 0 {
-                return Err(StdError::generic_err(
-                    "Cannot check when you owe a call amount. Use Call or Raise instead.",
-                ));
-            }
+    // 当前这手结束后轮到下一手，全部结束时庄家补牌比牌
+    match state.hands.iter().position(|hand| !hand.stood) {
+        Some(next) => {
+            state.active_hand = next as u8;
+            BLACKJACK_STATE.save(deps.storage, player, &state)?;
+            Ok(response.add_attribute("active_hand", next.to_string()))
+        }
+        None => settle_blackjack(deps, env, player, state, response),
+    }
+}
 
-            let (new_stage, community) = advance_texas_stage(&state);
+/// 检查 21 点操作是否允许，返回需要附带的追加下注
+fn blackjack_stake(
+    state: &BlackjackState,
+    action: &BlackjackAction,
+) -> Result<u128, ContractError> {
+    let hand = &state.hands[usize::from(state.active_hand)];
+    let invalid = |reason| Err(ContractError::InvalidAction { reason });
+    match action {
+        BlackjackAction::Start => Err(ContractError::StartRequiresBet {
+            game: GameKind::Blackjack,
+        }),
+        BlackjackAction::Hit if hand.total() >= 21 => {
+            invalid("You cannot hit after reaching 21 points")
+        }
+        BlackjackAction::Hit | BlackjackAction::Stand => Ok(0),
+        BlackjackAction::DoubleDown if hand.cards.len() != 2 => {
+            invalid("You can only double down on your first two cards")
+        }
+        BlackjackAction::DoubleDown if hand.split && !state.rules.double_after_split => {
+            invalid("Double after split is not allowed")
+        }
+        BlackjackAction::DoubleDown => Ok(hand.bet.u128()),
+        BlackjackAction::Split if !hand.is_pair() => {
+            invalid("You can only split two cards of the same rank")
+        }
+        BlackjackAction::Split if state.hands.len() >= blackjack::MAX_HANDS => {
+            invalid("You cannot split into more hands")
+        }
+        BlackjackAction::Split => Ok(hand.bet.u128()),
+        BlackjackAction::Insurance if state.peeked || !state.dealer_cards[1].is_ace() => {
+            invalid("Insurance is only offered on a dealer ace before any other action")
+        }
+        BlackjackAction::Insurance => Ok(state.bet.u128() / 2),
+        BlackjackAction::Surrender if !state.rules.surrender_allowed => {
+            invalid("Surrender is not allowed")
+        }
+        BlackjackAction::Surrender if state.hands.len() != 1 || hand.cards.len() != 2 => {
+            invalid("You can only surrender as your first decision")
+        }
+        BlackjackAction::Surrender => Ok(0),
+    }
+}
 
-            state.stage = new_stage.clone();
-            state.community_cards = community.clone();
+/// 21 点追加下注：按各手与保险的累计下注额校验下注上限后计入锁仓，
+/// 并把预留敞口补足到各手与保险的最坏情况赔付
+fn add_blackjack_stake(
+    storage: &mut dyn Storage,
+    state: &mut BlackjackState,
+    stake: u128,
+) -> Result<(), ContractError> {
+    let required = state.hands.iter().map(|hand| hand.max_payout(&state.rules)).sum::<u128>()
+        + insurance_payout(state.insurance.u128());
+    let total_stake = state.hands.iter().map(|hand| hand.bet.u128()).sum::<u128>()
+        + state.insurance.u128();
+    let mut global_state = STATE.load(storage)?;
+    let (denom, reserved) = (state.denom.as_str(), state.reserved);
+    check_total_stake(storage, &global_state, GameKind::Blackjack, denom, reserved, total_stake)?;
+    global_state.lock(&state.denom, stake);
+    reserve_at_least(&mut global_state, &state.denom, &mut state.reserved, required)?;
+    STATE.save(storage, &global_state)?;
+    Ok(())
+}
 
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+/// 21 点结算：所有手停牌，庄家补牌后按开局下注的币种与资金来源赔付（操作与超时共用）
+fn settle_blackjack(
+    deps: DepsMut,
+    env: &Env,
+    player: &Addr,
+    mut state: BlackjackState,
+    response: Response,
+) -> Result<Response, ContractError> {
+    state.peeked = true;
+    for hand in &mut state.hands {
+        hand.stood = true;
+    }
+
+    // 庄家的补充牌逻辑: 庄家没有天然 21 点且玩家还有未爆牌 / 未投降 / 非天然 21 点的手时,
+    // 按庄规补牌（小于 17 点要牌, 软 17 点按庄规）, 不看玩家的牌.
+    let live_hand = state
+        .hands
+        .iter()
+        .any(|hand| !hand.surrendered && !hand.is_natural() && hand.total() <= 21);
+    if live_hand && !is_natural(&state.dealer_cards) {
+        let shoe = blackjack_game_shoe(deps.storage, &state)?;
+        while state.rules.dealer_hits(&state.dealer_cards) {
+            let new_card = draw_blackjack_card(&shoe, &state);
+            state.dealer_cards.push(new_card);
+        }
+    }
 
-            Ok(Response::new()
-                .add_attribute("action", "texas_check")
-                .add_attribute("total_bet", state.player_total_bet.to_string())
-                .add_attribute("stage", format!("{:?}", new_stage))
-                .add_attribute("community_cards", format_texas_cards(&community)))
+    finish_blackjack(deps, env, player, &mut state, 0, response)
+}
+
+/// 21 点比牌：结束牌局、释放预留敞口、赔付并发出结算事件
+///
+/// 每手牌与保险分别比牌和抽水; returned 为看暗牌即结束时原样退还的追加下注.
+fn finish_blackjack(
+    deps: DepsMut,
+    env: &Env,
+    player: &Addr,
+    state: &mut BlackjackState,
+    returned: u128,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let house_edge = game_house_edge(deps.storage, GameKind::Blackjack)?;
+    let (mut wager, mut gross, mut payout) = (returned, returned, returned);
+    let mut results = vec![];
+    for hand in &state.hands {
+        let (result, hand_gross) = hand.settle(&state.dealer_cards, &state.rules);
+        wager += hand.bet.u128();
+        gross += hand_gross;
+        payout += apply_house_edge(hand_gross, hand.bet.u128(), house_edge);
+        results.push(result);
+    }
+
+    // 保险：庄家天然 21 点时赔 2:1
+    let insurance = state.insurance.u128();
+    let insurance_result = match insurance {
+        0 => "none",
+        _ if is_natural(&state.dealer_cards) => {
+            gross += insurance_payout(insurance);
+            payout += apply_house_edge(insurance_payout(insurance), insurance, house_edge);
+            "won"
         }
+        _ => "lost",
+    };
+    wager += insurance;
+
+    // 边注开局时已结算赔付，一并计入本局的结算事件
+    let shoe = blackjack_game_shoe(deps.storage, state)?;
+    let (user_cards, dealer_cards) = deal_blackjack(&shoe);
+    let side_gross: u128 =
+        state.side_bets.settle(&user_cards, &dealer_cards[1]).iter().map(|side| side.gross).sum();
+    let settlement = Settlement::new(
+        GameKind::Blackjack,
+        wager + state.side_bets.total(),
+        gross + side_gross,
+        payout + state.side_payout.u128(),
+    );
+
+    // 赔付按开局下注的币种与资金来源返还
+    let bettor = Bettor {
+        player: player.clone(),
+        denom: state.denom.clone(),
+        source: state.source,
+    };
 
-        // ── 全押 ──────────────────────────────────────────────────────
-        TexasAction::AllIn { amount } => {
-            let mut state = TEXAS_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
-            if state.all_in {
-                return Err(StdError::generic_err("Already all-in"));
-            }
-            if matches!(state.stage, TexasStage::Showdown) {
-                return Err(StdError::generic_err("Game is at Showdown, cannot all-in"));
-            }
+    // 结束游戏并释放预留敞口.
+    state.finished = true;
+    BLACKJACK_STATE.save(deps.storage, player, state)?;
+    release_reservation(deps.storage, &bettor.denom, state.reserved)?;
 
-            let sent = info
-                .funds
-                .iter()
-                .find(|c| c.denom == "uatom")
-                .map(|c| c.amount.u128())
-                .unwrap_or(0);
-
-            if sent < amount || amount == 0 {
-                return Err(StdError::generic_err(
-                    "Must attach exactly the all-in amount in uatom funds",
-                ));
-            }
+    let totals: Vec<String> = state.hands.iter().map(|hand| hand.total().to_string()).collect();
+    let event = blackjack_settled_event(&settlement, player, state, shoe)?;
+    // 如果是平局或者玩家赢了, 发送支付金额给玩家
+    Ok(response
+        .add_messages(settle_payout(deps, env, &bettor, payout)?)
+        .add_event(event)
+        .add_attribute("result", results.join(","))
+        .add_attribute("user_total", totals.join(","))
+        .add_attribute("dealer_total", hand_total(&state.dealer_cards).to_string())
+        .add_attribute("user_cards", format_blackjack_hands(&state.hands))
+        .add_attribute("dealer_cards", format_blackjack_cards(&state.dealer_cards))
+        .add_attribute("insurance_result", insurance_result)
+        .add_attribute("payout", payout.to_string()))
+}
 
-            if amount < 100_000 {
-                return Err(StdError::generic_err(
-                    "All-in amount must be at least 100,000 uatom",
-                ));
-            }
+/// 21 点结算事件：outcome 为结束时的牌面与牌靴
+fn blackjack_settled_event(
+    settlement: &Settlement,
+    player: &Addr,
+    state: &BlackjackState,
+    shoe: Vec<u8>,
+) -> Result<Event, ContractError> {
+    let outcome = GameOutcome::Blackjack {
+        shoe,
+        user_hands: state.hands.iter().map(|hand| hand.cards.clone()).collect(),
+        dealer_cards: state.dealer_cards.clone(),
+    };
+    Ok(settlement.event(state.bet_id, player, &state.denom, &outcome)?)
+}
 
-            // 全押：揭示全部公共牌，进入 Showdown
-            let full_community: Vec<texas::Card> =
-                (4..9).map(|i| texas::Card::from_id(state.deck[i])).collect();
+/// 21 点牌靴：decks 副牌用本局种子洗好（0 为旧版下注记录，按单副牌处理）
+fn blackjack_shoe(seed: &[u8], decks: u8) -> Vec<u8> {
+    ShoeSpec::decks(decks.max(1)).shuffle(&mut RngStream::new(seed, b"blackjack_shoe"))
+}
 
-            state.player_total_bet += Uint128::from(amount);
-            state.current_call_amount += Uint128::from(amount);
-            state.all_in = true;
-            state.community_cards = full_community.clone();
-            state.stage = TexasStage::Showdown;
+/// 21 点初始发牌：牌靴前 2 张给用户、第 3-4 张给庄家（第 3 张为暗牌）
+fn deal_blackjack(shoe: &[u8]) -> (Vec<BlackjackCard>, Vec<BlackjackCard>) {
+    let user_cards = vec![BlackjackCard::from_id(shoe[0]), BlackjackCard::from_id(shoe[1])];
+    let dealer_cards = vec![BlackjackCard::from_id(shoe[2]), BlackjackCard::from_id(shoe[3])];
+    (user_cards, dealer_cards)
+}
 
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+/// 21 点本局的牌靴：按开局下注的种子与庄规副数重新洗出（牌靴不在链上保存）
+fn blackjack_game_shoe(
+    storage: &dyn Storage,
+    state: &BlackjackState,
+) -> Result<Vec<u8>, ContractError> {
+    #[cfg(test)]
+    if let Some(shoe) = tests::RIGGED_SHOES.may_load(storage, state.bet_id)? {
+        return Ok(shoe);
+    }
+    Ok(blackjack_shoe(&bet_seed(storage, state.bet_id)?, state.rules.decks))
+}
 
-            let mut global_state = STATE.load(deps.storage)?;
-            global_state.locked_amount += sent;
-            STATE.save(deps.storage, &global_state)?;
+/// 21 点从牌靴发下一张牌（已发牌数即牌靴中的位置）
+fn draw_blackjack_card(shoe: &[u8], state: &BlackjackState) -> BlackjackCard {
+    let user_cards: usize = state.hands.iter().map(|hand| hand.cards.len()).sum();
+    let dealt = user_cards + state.dealer_cards.len();
+    BlackjackCard::from_id(shoe[dealt])
+}
 
-            // 自动进入 Showdown 结算
-            settle_texas(deps, info, state)
-        }
+/// 21 点单张牌的显示，如 A♠、10♥、K♣
+fn format_blackjack_card(card: &BlackjackCard) -> String {
+    let rank_str = match card.rank {
+        1 => "A".to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        n => n.to_string(),
+    };
+    let suit_str = match card.suit {
+        blackjack::Suit::Spades   => "♠",
+        blackjack::Suit::Hearts   => "♥",
+        blackjack::Suit::Diamonds => "♦",
+        blackjack::Suit::Clubs    => "♣",
+    };
+    format!("{}{}", rank_str, suit_str)
+}
 
-        // ── 弃牌 ──────────────────────────────────────────────────────
-        TexasAction::Fold => {
-            let mut state = TEXAS_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
+fn format_blackjack_cards(cards: &[BlackjackCard]) -> String {
+    let parts: Vec<String> = cards.iter().map(format_blackjack_card).collect();
+    format!("[{}]", parts.join(","))
+}
 
-            state.finished = true;
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+/// 玩家各手牌的显示，如 [8♠,3♥],[8♦,K♣]
+fn format_blackjack_hands(hands: &[BlackjackHand]) -> String {
+    let parts: Vec<String> = hands.iter().map(|hand| format_blackjack_cards(&hand.cards)).collect();
+    parts.join(",")
+}
 
-            Ok(Response::new()
-                .add_attribute("action", "texas_fold")
-                .add_attribute("result", "folded")
-                .add_attribute("lost_amount", state.player_total_bet.to_string()))
-        }
+/// 玩硬币翻牌
+///
+/// 用户猜硬币的结果,如果猜对了,则获得 bet * 2 的金额,否则损失 bet 的金额。
+fn play_coin_flip(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    choice: CoinSide,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::CoinFlip)?;
 
-        // ── 摊牌结算 ─────────────────────────────────────────────────
-        TexasAction::Showdown => {
-            let mut state = TEXAS_STATE.load(deps.storage, &info.sender)?;
-            if state.finished {
-                return Err(StdError::generic_err("Game already finished"));
-            }
+    let result = flip_coin(seed);
 
-            // 揭示全部 5 张公共牌
-            let full_community: Vec<texas::Card> =
-                (4..9).map(|i| texas::Card::from_id(state.deck[i])).collect();
-            state.community_cards = full_community;
-            state.stage = TexasStage::Showdown;
+    let mut response = Response::new()
+        .add_attribute("action", "coin_flip")
+        .add_attribute("player_choice", format!("{:?}", choice))
+        .add_attribute("actual_result", format!("{:?}", result));
 
-            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+    let settlement = if choice == result {
+        // 赢了,奖励翻倍（扣除抽水）
+        let gross = bet * 2;
+        let net = apply_house_edge(gross, bet, house_edge_bps);
+        let payout = Uint128::from(net);
 
-            settle_texas(deps, info, state)
-        }
-    }
-}
+        response = response
+            .add_messages(settle_payout(deps, env, bettor, net)?)
+            .add_attribute("result", "win")
+            .add_attribute("payout", payout.to_string());
+        Settlement::new(GameKind::CoinFlip, bet, gross, net)
+    } else {
+        response = response
+            .add_attribute("result", "lose")
+            .add_attribute("payout", "0");
+        Settlement::lost(GameKind::CoinFlip, bet)
+    };
 
-/// 德州扑克结算逻辑
-fn settle_texas(
-    deps: DepsMut,
-    info: MessageInfo,
-    mut state: TexasState,
-) -> StdResult<Response> {
-    let full_community = &state.community_cards;
+    Ok((response, settlement))
+}
 
-    // 评估双方最佳手牌
-    let player_rank = best_texas_hand_rank(&state.player_hand, full_community);
-    let dealer_rank = best_texas_hand_rank(&state.dealer_hand, full_community);
+/// 抛硬币: 0 -> Heads, 1 -> Tails
+fn flip_coin(seed: &[u8]) -> CoinSide {
+    if RngStream::new(seed, b"coin_flip").below(2) == 0 {
+        CoinSide::Heads
+    } else {
+        CoinSide::Tails
+    }
+}
 
-    let player_hand_name = texas::hand_rank_name(player_rank);
-    let dealer_hand_name = texas::hand_rank_name(dealer_rank);
+// 骰子各玩法的随机数盐
+const DICE_GUESS_SIZE_SALT: &[u8] = b"dice_guess_size";
+const DICE_EXACT_NUMBER_SALT: &[u8] = b"dice_exact_number";
+const DICE_RANGE_BET_SALT: &[u8] = b"dice_range_bet";
 
-    let total_bet = state.player_total_bet.u128();
+/// 掷一颗骰子（1-6）
+fn roll_die(seed: &[u8], salt: &[u8]) -> u32 {
+    RngStream::new(seed, salt).range_inclusive(1, 6)
+}
 
-    let mut global_state = STATE.load(deps.storage)?;
-    let mut response = Response::new()
-        .add_attribute("action", "texas_showdown")
-        .add_attribute("player_hand", format_texas_cards(&state.player_hand))
-        .add_attribute("dealer_hand", format_texas_cards(&state.dealer_hand))
-        .add_attribute(
-            "community_cards",
-            format_texas_cards(full_community),
-        )
-        .add_attribute("player_rank_name", player_hand_name)
-        .add_attribute("dealer_rank_name", dealer_hand_name)
-        .add_attribute("player_rank", player_rank.to_string())
-        .add_attribute("dealer_rank", dealer_rank.to_string());
+/// 玩骰子猜大小
+///
+/// 用户猜中大小的概率为 1/2, 用户猜中获得 bet * 2 的金额, 否则损失 bet 的金额.
+fn play_dice_guess_size(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    guess_big: DiceGuessSize,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Dice)?;
 
-    if player_rank > dealer_rank {
-        // 玩家赢：获得 2× 下注额（扣除抽水）
-        let gross = total_bet * 2;
-        let payout = apply_house_edge(gross, total_bet, global_state.house_edge_bps);
-        global_state.locked_amount = global_state.locked_amount.saturating_sub(payout);
-        response = response
-            .add_attribute("result", "player_win")
-            .add_attribute("payout", payout.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout),
-                }],
-            });
-    } else if dealer_rank > player_rank {
-        // 庄家赢
-        response = response
-            .add_attribute("result", "dealer_win")
-            .add_attribute("payout", "0");
+    // 抛骰子: [1,3] 为小, [4,6] 为大
+    let rand_number = roll_die(seed, DICE_GUESS_SIZE_SALT);
+    let result = if rand_number <= 3 {
+        DiceGuessSize::Small
     } else {
-        // 平局：退还下注额
-        global_state.locked_amount = global_state.locked_amount.saturating_sub(total_bet);
-        response = response
-            .add_attribute("result", "tie")
-            .add_attribute("payout", total_bet.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(total_bet),
-                }],
-            });
-    }
+        DiceGuessSize::Big
+    };
 
-    state.finished = true;
-    TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
-    STATE.save(deps.storage, &global_state)?;
+    let mut response = Response::new()
+        .add_attribute("action", "dice_guess_size")
+        .add_attribute("player_guess", format!("{:?}", guess_big))
+        .add_attribute("actual_result", format!("{:?}", result));
 
-    Ok(response)
-}
+    let settlement = if guess_big == result {
+        // 赢了发送奖励（扣除抽水）
+        let gross = bet * 2;
+        let net = apply_house_edge(gross, bet, house_edge_bps);
 
-/// 德州扑克阶段推进
-fn advance_texas_stage(state: &TexasState) -> (TexasStage, Vec<texas::Card>) {
-    let deck = &state.deck;
-    match state.stage {
-        TexasStage::PreFlop => {
-            // Flop: 揭示 3 张公共牌 (deck[4..7])
-            let community = (4..7).map(|i| texas::Card::from_id(deck[i])).collect();
-            (TexasStage::Flop, community)
-        }
-        TexasStage::Flop => {
-            // Turn: 揭示 4 张 (deck[4..8])
-            let community = (4..8).map(|i| texas::Card::from_id(deck[i])).collect();
-            (TexasStage::Turn, community)
-        }
-        TexasStage::Turn => {
-            // River: 揭示全部 5 张 (deck[4..9])
-            let community = (4..9).map(|i| texas::Card::from_id(deck[i])).collect();
-            (TexasStage::River, community)
-        }
-        TexasStage::River | TexasStage::Showdown => {
-            let community = state.community_cards.clone();
-            (TexasStage::Showdown, community)
-        }
-    }
+        response = response
+            .add_messages(settle_payout(deps, env, bettor, net)?)
+            .add_attribute("result", "win");
+        Settlement::new(GameKind::Dice, bet, gross, net)
+    } else {
+        response = response.add_attribute("result", "lose");
+        Settlement::lost(GameKind::Dice, bet)
+    };
+    Ok((response, settlement))
 }
 
-/// 德州扑克洗牌（使用不同盐与奥马哈区分）
-fn shuffle_texas_deck(info: &MessageInfo, env: &Env) -> Vec<u8> {
-    let mut deck: Vec<u8> = (0u8..52).collect();
-    let mut weights: Vec<u32> = (0u8..52)
-        .map(|i| {
-            let salt = format!("texas_deck_{}", i);
-            utils::generate_random_number(info, env, salt.as_bytes())
-        })
-        .collect();
+/// 玩骰子猜点数
+///
+/// 用户猜中数字的概率为 1/6, 猜中数字的奖励为 bet * 6 的金额, 否则损失 bet 的金额.
+fn play_dice_exact_number(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    number: u8,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Dice)?;
 
-    for i in (1..52usize).rev() {
-        let j = (weights[i] as usize + i * 97) % (i + 1);
-        deck.swap(i, j);
-        weights.swap(i, j);
-    }
-    deck
-}
+    // 抛骰子
+    let rand_number = roll_die(seed, DICE_EXACT_NUMBER_SALT);
 
-/// 格式化德州扑克 Card 列表为可读字符串
-fn format_texas_cards(cards: &[texas::Card]) -> String {
-    let parts: Vec<String> = cards
-        .iter()
-        .map(|c| {
-            let rank_str = match c.rank {
-                11 => "J".to_string(),
-                12 => "Q".to_string(),
-                13 => "K".to_string(),
-                14 => "A".to_string(),
-                n => n.to_string(),
-            };
-            let suit_str = match c.suit {
-                texas::Suit::Spades   => "♠",
-                texas::Suit::Hearts   => "♥",
-                texas::Suit::Diamonds => "♦",
-                texas::Suit::Clubs    => "♣",
-            };
-            format!("{}{}", rank_str, suit_str)
-        })
-        .collect();
-    format!("[{}]", parts.join(","))
+    let mut response = Response::new()
+        .add_attribute("action", "play_dice_exact_number")
+        .add_attribute("player_guess", number.to_string())
+        .add_attribute("actual_result", rand_number.to_string());
+
+    // 如果猜对,玩家则获得 bet * 6 的金额,否则损失 bet 的金额。
+    let settlement = if number as u32 == rand_number {
+        let gross = bet * 6;
+        let net = apply_house_edge(gross, bet, house_edge_bps);
+        let payout = Uint128::from(net);
+
+        // 赢了发送奖励
+        response = response
+            .add_messages(settle_payout(deps, env, bettor, net)?)
+            .add_attribute("payout", payout.to_string())
+            .add_attribute("result", "win");
+        Settlement::new(GameKind::Dice, bet, gross, net)
+    } else {
+        response = response.add_attribute("result", "lose");
+        Settlement::lost(GameKind::Dice, bet)
+    };
+    Ok((response, settlement))
 }
 
-// ──────────────────────────────────────────────────────────────────────────────
-// 三公（San Gong / Three Face Cards）
-//
-// 单局游戏：下注后双方各发 3 张牌，直接比较牌力
-// 牌型：三公 > 混合九 > 普通点数 9 > ... > 0
-// 赔率：三公赢 3×，混合九/普通赢 2×，平局退还本金
-// ──────────────────────────────────────────────────────────────────────────────
-fn play_sangong(
+/// 玩骰子猜范围
+///
+/// 用户在指定范围内猜骰子点, 猜中范围的概率为 1 / (6 / ( end - start + 1 )), 猜中范围的奖励为 bet * times 的金额, 否则损失 bet 的金额.
+fn play_dice_range_bet(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-) -> StdResult<Response> {
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    start: u8,
+    end: u8,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let times = dice_range_multiplier(start, end)?;
 
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
-    }
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Dice)?;
 
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
+    let rand_number = roll_die(seed, DICE_RANGE_BET_SALT);
 
-    // 洗牌并发牌：玩家 3 张 + 庄家 3 张
-    let deck = shuffle_sangong_deck(&info, &env);
-    let player_cards: [SanGongCard; 3] = [
-        SanGongCard::from_id(deck[0]),
-        SanGongCard::from_id(deck[1]),
-        SanGongCard::from_id(deck[2]),
-    ];
-    let dealer_cards: [SanGongCard; 3] = [
-        SanGongCard::from_id(deck[3]),
-        SanGongCard::from_id(deck[4]),
-        SanGongCard::from_id(deck[5]),
-    ];
+    if (start as u32..=end as u32).contains(&rand_number) {
+        let gross = bet * times;
+        let net = apply_house_edge(gross, bet, house_edge_bps);
+        let payout = Uint128::from(net);
 
-    // 评估双方牌力
-    let player_rank = evaluate_sangong_hand(&player_cards);
-    let dealer_rank = evaluate_sangong_hand(&dealer_cards);
+        let response = Response::new()
+            .add_messages(settle_payout(deps, env, bettor, net)?)
+            .add_attribute("result", "win")
+            .add_attribute("payout", payout.to_string())
+            .add_attribute("actual_result", rand_number.to_string())
+            .add_attribute("player_start", start.to_string())
+            .add_attribute("player_end", end.to_string());
+        return Ok((response, Settlement::new(GameKind::Dice, bet, gross, net)));
+    }
+    let response = Response::new()
+        .add_attribute("result", "lose")
+        .add_attribute("actual_result", rand_number.to_string())
+        .add_attribute("player_start", start.to_string())
+        .add_attribute("player_end", end.to_string());
+    Ok((response, Settlement::lost(GameKind::Dice, bet)))
+}
 
-    let player_type_name = hand_type_name(&player_rank.hand_type);
-    let dealer_type_name = hand_type_name(&dealer_rank.hand_type);
+/// 百家乐游戏
+///
+/// 玩家可以在庄家、闲家或平局中选择下注
+fn play_baccarat(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    bet_choice: BaccaratBet,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Baccarat)?;
+    let BaccaratResult {
+        player_cards,
+        banker_cards,
+        player_total,
+        banker_total,
+        winner,
+    } = deal_baccarat(seed);
 
-    let player_points = match &player_rank.hand_type {
-        SanGongHandType::SanGong => 10,     // 显示用
-        SanGongHandType::MixedNine => 9,
-        SanGongHandType::Normal { points } => *points,
-    };
-    let dealer_points = match &dealer_rank.hand_type {
-        SanGongHandType::SanGong => 10,
-        SanGongHandType::MixedNine => 9,
-        SanGongHandType::Normal { points } => *points,
+    // 计算赔付
+    let (payout_multiplier, commission) = match winner {
+        BaccaratBet::Player => (2, 0), // 1:1 赔付，无佣金
+        BaccaratBet::Banker => (2, 5), // 1:1 赔付，但收取5%佣金
+        BaccaratBet::Tie => (9, 0),    // 8:1 赔付 (我们设置为9倍因为包含本金)
     };
 
     let mut response = Response::new()
-        .add_attribute("action", "play_sangong")
-        .add_attribute("player_cards", format_sangong_cards(&player_cards))
-        .add_attribute("dealer_cards", format_sangong_cards(&dealer_cards))
-        .add_attribute("player_hand_type", player_type_name)
-        .add_attribute("dealer_hand_type", dealer_type_name)
-        .add_attribute("player_points", player_points.to_string())
-        .add_attribute("dealer_points", dealer_points.to_string())
-        .add_attribute("player_score", player_rank.score.to_string())
-        .add_attribute("dealer_score", dealer_rank.score.to_string());
+        .add_attribute("action", "play_baccarat")
+        .add_attribute("player_cards", format!("{:?}", player_cards))
+        .add_attribute("banker_cards", format!("{:?}", banker_cards))
+        .add_attribute("player_total", player_total.to_string())
+        .add_attribute("banker_total", banker_total.to_string())
+        .add_attribute("player_bet", format!("{:?}", bet_choice))
+        .add_attribute("winner", format!("{:?}", winner));
 
-    if player_rank.score > dealer_rank.score {
-        // 玩家赢（扣除抽水）
-        let multiplier = sangong_payout_multiplier(&player_rank.hand_type);
-        let gross = bet * multiplier;
-        let payout = apply_house_edge(gross, bet, state.house_edge_bps);
+    // 如果玩家猜中了结果
+    let settlement = if winner == bet_choice {
+        let mut winnings = bet * (payout_multiplier as u128 - 1); // 奖金不包括本金
+
+        // 如果投注庄家且获胜，扣除佣金
+        if winner == BaccaratBet::Banker {
+            let commission_amount = winnings * commission / 100;
+            winnings = winnings.saturating_sub(commission_amount);
+        }
+
+        // 特殊处理平局情况
+        if winner == BaccaratBet::Tie {
+            winnings = bet * 8; // 8倍奖金
+        }
+
+        let gross_payout = bet + winnings; // 本金+奖金
+        let payout_amount = apply_house_edge(gross_payout, bet, house_edge_bps);
 
-        state.locked_amount = state.locked_amount.saturating_sub(payout);
         response = response
+            .add_messages(settle_payout(deps, env, bettor, payout_amount)?)
             .add_attribute("result", "win")
-            .add_attribute("payout_multiplier", multiplier.to_string())
-            .add_attribute("payout", payout.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout),
-                }],
-            });
-    } else if dealer_rank.score > player_rank.score {
-        // 庄家赢
+            .add_attribute("winnings", winnings.to_string())
+            .add_attribute("payout", payout_amount.to_string());
+        Settlement::new(GameKind::Baccarat, bet, gross_payout, payout_amount)
+    } else {
         response = response
             .add_attribute("result", "lose")
             .add_attribute("payout", "0");
-    } else {
-        // 平局，退还本金
-        state.locked_amount = state.locked_amount.saturating_sub(bet);
-        response = response
-            .add_attribute("result", "tie")
-            .add_attribute("payout", bet.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(bet),
-                }],
-            });
-    }
+        Settlement::lost(GameKind::Baccarat, bet)
+    };
 
-    STATE.save(deps.storage, &state)?;
-    Ok(response)
+    Ok((response, settlement))
 }
 
-/// 三公洗牌
-fn shuffle_sangong_deck(info: &MessageInfo, env: &Env) -> Vec<u8> {
-    let mut deck: Vec<u8> = (0u8..52).collect();
-    let mut weights: Vec<u32> = (0u8..52)
-        .map(|i| {
-            let salt = format!("sangong_deck_{}", i);
-            utils::generate_random_number(info, env, salt.as_bytes())
-        })
-        .collect();
+/// 百家乐牌靴：8 副牌
+const BACCARAT_SHOE: ShoeSpec = ShoeSpec::decks(8);
 
-    for i in (1..52usize).rev() {
-        let j = (weights[i] as usize + i * 97) % (i + 1);
-        deck.swap(i, j);
-        weights.swap(i, j);
+/// 百家乐牌点：A=1，2-9=面值，10/J/Q/K=0
+fn baccarat_card_point(card_id: u8) -> u8 {
+    let rank = card_id / 4 + 1;
+    if rank >= 10 {
+        0
+    } else {
+        rank
     }
-    deck
 }
 
-/// 格式化三公牌面
-fn format_sangong_cards(cards: &[SanGongCard]) -> String {
-    let parts: Vec<String> = cards
-        .iter()
-        .map(|c| {
-            let rank_str = match c.rank {
-                1 => "A".to_string(),
-                11 => "J".to_string(),
-                12 => "Q".to_string(),
-                13 => "K".to_string(),
-                n => n.to_string(),
-            };
-            let suit_str = match c.suit {
-                sangong::Suit::Spades   => "♠",
-                sangong::Suit::Hearts   => "♥",
-                sangong::Suit::Diamonds => "♦",
-                sangong::Suit::Clubs    => "♣",
-            };
-            format!("{}{}", rank_str, suit_str)
-        })
-        .collect();
-    format!("[{}]", parts.join(","))
-}
+/// 百家乐发牌与补牌（按标准补牌规则）
+fn deal_baccarat(seed: &[u8]) -> BaccaratResult {
+    // 8 副牌的牌靴，一局最多用 6 张：按 闲、闲、庄、庄、(闲补)、(庄补) 的顺序发出
+    let shoe = BACCARAT_SHOE.deal(&mut RngStream::new(seed, b"baccarat_shoe"), 6);
+    let mut next_card = shoe.into_iter().map(baccarat_card_point);
 
-// ──────────────────────────────────────────────────────────────────────────────
-// 骰宝（Sic Bo）
-//
-// 单局游戏：下注后摇三颗骰子，根据投注类型判定输赢
-// 投注类型：大/小、单/双、总和、三同号通选、指定三同号、双骰、单骰、两骰组合
-// ──────────────────────────────────────────────────────────────────────────────
-fn play_sicbo(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    bet_type: SicBoBetType,
-) -> StdResult<Response> {
-    // 验证投注参数
-    if let Err(e) = validate_bet(&bet_type) {
-        return Err(StdError::generic_err(e));
-    }
+    // 发牌 - 百家乐规则：每人先发两张牌
+    let mut player_cards = vec![next_card.next().unwrap(), next_card.next().unwrap()];
 
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+    let mut banker_cards = vec![next_card.next().unwrap(), next_card.next().unwrap()];
+
+    // 计算点数（百家乐中只有个位数有效）
+    let mut player_total = (player_cards[0] + player_cards[1]) % 10;
+    let mut banker_total = (banker_cards[0] + banker_cards[1]) % 10;
+
+    // 根据规则决定是否补牌
+    let player_third_card = if player_total <= 5 {
+        let third_card = next_card.next().unwrap();
+        player_cards.push(third_card);
+        player_total = (player_total + third_card) % 10;
+        Some(third_card)
+    } else {
+        None
+    };
+
+    // 庄家是否补牌取决于闲家是否补牌以及当前点数
+    if banker_total <= 5 {
+        let should_draw = match player_third_card {
+            Some(third_card) => {
+                // 根据百家乐规则确定庄家是否补牌
+                match banker_total {
+                    0..=2 => true,                                    // 庄家0-2点必补牌
+                    3 => third_card != 8, // 庄家3点，闲家第三张为8时不补牌
+                    4 => matches!(third_card, 2..=7),                 // 庄家4点规则
+                    5 => matches!(third_card, 4..=7), // 庄家5点规则
+                    6 => matches!(third_card, 6 | 7), // 庄家6点规则
+                    _ => false,           // 庄家7点以上不补牌
+                }
+            }
+            None => banker_total <= 5, // 如果闲家没补牌，庄家按基本规则补牌
+        };
 
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
+        if should_draw {
+            let third_card = next_card.next().unwrap();
+            banker_cards.push(third_card);
+            banker_total = (banker_total + third_card) % 10;
+        }
     }
 
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
+    // 确定赢家
+    let winner = if player_total > banker_total {
+        BaccaratBet::Player
+    } else if banker_total > player_total {
+        BaccaratBet::Banker
+    } else {
+        BaccaratBet::Tie
+    };
+
+    BaccaratResult {
+        player_cards,
+        banker_cards,
+        player_total,
+        banker_total,
+        winner,
+    }
+}
 
-    // 摇三颗骰子（每颗 1-6）
-    let die1 = (utils::generate_random_number(&info, &env, b"sicbo_die1") % 6 + 1) as u8;
-    let die2 = (utils::generate_random_number(&info, &env, b"sicbo_die2") % 6 + 1) as u8;
-    let die3 = (utils::generate_random_number(&info, &env, b"sicbo_die3") % 6 + 1) as u8;
+/// 轮盘游戏
+///
+/// 轮盘包含数字 0-36，其中：
+/// - 0 用于显示颜色但不参与颜色押注
+/// - 仅保留四种玩法：单个数字、颜色、奇偶、大小
+fn play_roulette(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    bet_type: RouletteBetType,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Roulette)?;
 
-    let result = SicBoResult::new(die1, die2, die3);
+    let result = spin_roulette(seed);
 
     // 计算赔付
-    let (won, multiplier) = calculate_sicbo_payout(&bet_type, &result);
+    let (won, payout_multiplier) = calculate_roulette_payout(&bet_type, &result);
 
     let mut response = Response::new()
-        .add_attribute("action", "play_sicbo")
-        .add_attribute("die1", die1.to_string())
-        .add_attribute("die2", die2.to_string())
-        .add_attribute("die3", die3.to_string())
-        .add_attribute("total", result.total.to_string())
-        .add_attribute("is_triple", result.is_triple.to_string())
+        .add_attribute("action", "play_roulette")
+        .add_attribute("winning_number", result.winning_number.to_string())
+        .add_attribute("winning_color", format!("{:?}", result.winning_color))
+        .add_attribute("is_even", result.is_even.to_string())
         .add_attribute("bet_type", format!("{:?}", bet_type));
 
-    if won {
-        let gross = bet * multiplier;
-        let payout = apply_house_edge(gross, bet, state.house_edge_bps);
+    let settlement = if won {
+        let gross = bet * payout_multiplier;
+        let winnings = apply_house_edge(gross, bet, house_edge_bps);
 
-        state.locked_amount = state.locked_amount.saturating_sub(payout);
         response = response
+            .add_messages(settle_payout(deps, env, bettor, winnings)?)
             .add_attribute("result", "win")
-            .add_attribute("payout_multiplier", multiplier.to_string())
-            .add_attribute("payout", payout.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout),
-                }],
-            });
+            .add_attribute("winnings", winnings.to_string());
+        Settlement::new(GameKind::Roulette, bet, gross, winnings)
     } else {
         response = response
             .add_attribute("result", "lose")
             .add_attribute("payout", "0");
-    }
+        Settlement::lost(GameKind::Roulette, bet)
+    };
 
-    STATE.save(deps.storage, &state)?;
-    Ok(response)
+    Ok((response, settlement))
 }
 
-// ──────────────────────────────────────────────────────────────────────────────
-// 基诺（Keno）
-//
-// 单局游戏：玩家选 1-10 个号码（1-80），系统随机开出 20 个号码
-// 命中越多赔率越高，选 10 中 10 最高赔 2000×
-// ──────────────────────────────────────────────────────────────────────────────
-fn play_keno(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    picks: Vec<u8>,
-) -> StdResult<Response> {
-    // 验证选号
-    if let Err(e) = validate_picks(&picks) {
-        return Err(StdError::generic_err(e));
-    }
-
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
-
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
-    }
-
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
-
-    // 从 1-80 中随机抽取 20 个不重复号码
-    let drawn = draw_keno_numbers(&info, &env);
-
-    // 计算命中
-    let hits = calculate_hits(&picks, &drawn);
-    let hit_count = hits.len() as u8;
-    let pick_count = picks.len() as u8;
+/// 转动轮盘：生成 0-36 的结果
+fn spin_roulette(seed: &[u8]) -> RouletteResult {
+    // 生成 0-36 的随机数作为轮盘结果
+    let winning_number = RngStream::new(seed, b"roulette").below(37) as u8;
 
-    // 计算赔率
-    let multiplier = keno_payout_multiplier(pick_count, hit_count);
+    // 根据轮盘规则确定颜色
+    let winning_color = get_roulette_color(winning_number);
 
-    let drawn_str: Vec<String> = drawn.iter().map(|n| n.to_string()).collect();
-    let picks_str: Vec<String> = picks.iter().map(|n| n.to_string()).collect();
-    let hits_str: Vec<String> = hits.iter().map(|n| n.to_string()).collect();
+    // 判断是否为偶数
+    let is_even = winning_number != 0 && winning_number.is_multiple_of(2);
 
-    let mut response = Response::new()
-        .add_attribute("action", "play_keno")
-        .add_attribute("picks", format!("[{}]", picks_str.join(",")))
-        .add_attribute("drawn", format!("[{}]", drawn_str.join(",")))
-        .add_attribute("hits", format!("[{}]", hits_str.join(",")))
-        .add_attribute("pick_count", pick_count.to_string())
-        .add_attribute("hit_count", hit_count.to_string());
+    // 判断大小（0 不属于任何一类）
+    let is_low = if winning_number == 0 {
+        None
+    } else if winning_number <= 18 {
+        Some(true) // Low (1-18)
+    } else {
+        Some(false) // High (19-36)
+    };
 
-    if multiplier > 0 {
-        let gross = bet * multiplier;
-        let payout = apply_house_edge(gross, bet, state.house_edge_bps);
+    RouletteResult {
+        winning_number,
+        winning_color,
+        is_even,
+        is_low,
+    }
+}
 
-        state.locked_amount = state.locked_amount.saturating_sub(payout);
-        response = response
-            .add_attribute("result", "win")
-            .add_attribute("payout_multiplier", multiplier.to_string())
-            .add_attribute("payout", payout.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout),
-                }],
-            });
+/// 获取轮盘数字的颜色
+fn get_roulette_color(number: u8) -> Color {
+    if number == 0 {
+        // 0 仅用于显示为绿色/特殊色，颜色押注不生效
+        Color::Black // 若 Color 枚举无绿色，保持显示但不允许颜色押注中奖
     } else {
-        response = response
-            .add_attribute("result", "lose")
-            .add_attribute("payout", "0");
+        // 轮盘红色数字: 1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36
+        match number {
+            1 | 3 | 5 | 7 | 9 | 12 | 14 | 16 | 18 | 19 | 21 | 23 | 25 | 27 | 30 | 32 | 34 | 36 => {
+                Color::Red
+            }
+            _ => Color::Black, // 其余为黑色
+        }
     }
-
-    STATE.save(deps.storage, &state)?;
-    Ok(response)
 }
 
-/// 从 1-80 中随机抽取 20 个不重复号码
-fn draw_keno_numbers(info: &MessageInfo, env: &Env) -> Vec<u8> {
-    // 生成 1-80 的号码池
-    let mut pool: Vec<u8> = (1u8..=80).collect();
+/// 计算轮盘游戏的赔付
+fn calculate_roulette_payout(bet_type: &RouletteBetType, result: &RouletteResult) -> (bool, u128) {
+    match bet_type {
+        // 单个数字下注，赔率 35:1
+        RouletteBetType::SingleNumber { number } => {
+            if *number == result.winning_number {
+                (true, 36) // 包含本金的总倍数
+            } else {
+                (false, 0)
+            }
+        }
+        // 颜色下注，赔率 1:1；开出 0 时颜色押注必输
+        RouletteBetType::Color { color } => {
+            if result.winning_number != 0 && *color == result.winning_color {
+                (true, 2)
+            } else {
+                (false, 0)
+            }
+        }
+        // 奇偶下注，赔率 1:1
+        RouletteBetType::EvenOdd { bet } => {
+            // 0 既不是奇数也不是偶数，投注奇偶都不会中奖
+            if result.winning_number == 0 {
+                (false, 0)
+            } else {
+                match bet {
+                    EvenOdd::Even => (result.is_even, 2), // 包含本金的总倍数
+                    EvenOdd::Odd => (!result.is_even, 2), // 包含本金的总倍数
+                }
+            }
+        }
 
-    // Fisher-Yates 洗牌（只需前 20 个位置）
-    for i in 0..20usize {
-        let salt = format!("keno_draw_{}", i);
-        let rand = utils::generate_random_number(info, env, salt.as_bytes()) as usize;
-        let j = i + (rand % (80 - i));
-        pool.swap(i, j);
+        // 大小下注（1-18 / 19-36），赔率 1:1
+        RouletteBetType::HighLow { bet } => {
+            if let Some(is_low) = result.is_low {
+                match bet {
+                    HighLow::Low => (is_low, 2),   // 包含本金的总倍数
+                    HighLow::High => (!is_low, 2), // 包含本金的总倍数
+                }
+            } else {
+                // 如果是0，大小投注都不中奖
+                (false, 0)
+            }
+        }
     }
-
-    // 取前 20 个并排序（便于展示）
-    let mut drawn: Vec<u8> = pool[..20].to_vec();
-    drawn.sort_unstable();
-    drawn
 }
 
 // ──────────────────────────────────────────────────────────────────────────────
-// 刮刮乐（Scratch Card）
+// 奥马哈扑克（Omaha Hold'em）游戏
 //
-// 单局游戏：购买一张 3×3 的刮刮卡，刮开后检查 8 条中奖线
-// 同行/同列/对角线三个相同符号即中奖，多线可叠加
-// 符号：💎(50×) ⭐(20×) 🍀(10×) 🔔(5×) 🍒(3×) 🍋(2×)
+// 游戏流程：
+//   1. Start        → 玩家下注底注，发 4 张手牌（庄家也发 4 张），进入 PreFlop
+//   2. Raise/Call   → 在 PreFlop / Flop / Turn 阶段追加/跟注
+//   3. Showdown     → 任意阶段直接摊牌结算
+//   4. Fold         → 放弃本局，损失已押注金额
+//
+// 公共牌揭示节奏：
+//   PreFlop  → Flop（3 张）→ Turn（+1 张）→ River（+1 张）→ Showdown
+//
+// 奥马哈规则：必须用恰好 2 张手牌 + 3 张公共牌组成最佳 5 张
+// 加注规则：
+//   - 加注 (Raise): 附带 funds 金额作为追加注额，当前跟注额 += raise 金额
+//   - 跟注 (Call): 附带 funds 补齐差额（不少于 current_call_amount - player_total_bet）
+//   - Showdown/Fold 时无需再附带 funds
 // ──────────────────────────────────────────────────────────────────────────────
-fn play_scratch_card(
+fn play_omaha(
     deps: DepsMut,
-    env: Env,
+    env: &Env,
     info: MessageInfo,
-    card_type: ScratchCardType,
-) -> StdResult<Response> {
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
-
-    let (min_bet, max_bet) = scratch_bet_range(&card_type);
-    if bet < min_bet || bet > max_bet {
-        return Err(StdError::generic_err(format!(
-            "Bet must be between {} and {} uatom for {:?} card",
-            min_bet, max_bet, card_type
-        )));
-    }
+    action: OmahaAction,
+) -> Result<Response, ContractError> {
+    match action {
+        // ── 开始游戏 ──────────────────────────────────────────────────
+        // 开局需先下注并等待开奖，由 settle_bet 调用 play_omaha_start
+        OmahaAction::Start => Err(ContractError::StartRequiresBet { game: GameKind::Omaha }),
 
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
+        // ── 加注 ──────────────────────────────────────────────────────
+        OmahaAction::Raise { amount } => {
+            let mut state = OMAHA_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Omaha })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            if matches!(state.stage, OmahaStage::Showdown) {
+                return Err(ContractError::InvalidAction {
+                    reason: "Game is at Showdown, cannot raise",
+                });
+            }
 
-    // 生成 3×3 = 9 格符号
-    let mut grid = [ScratchSymbol::Lemon; 9];
-    for i in 0..9usize {
-        let salt = format!("scratch_cell_{}", i);
-        let rand = utils::generate_random_number(&info, &env, salt.as_bytes());
-        grid[i] = ScratchSymbol::from_rand(rand);
-    }
+            // 附带的资金必须与加注额完全一致（多付的部分不会计入底池）
+            let sent = sent_in_denom(&info.funds, &state.denom);
 
-    // 评估中奖
-    let (total_multiplier, winning_lines) = evaluate_scratch_card(&grid);
+            if sent != amount || amount == 0 {
+                return Err(ContractError::FundsMismatch {
+                    action: "raise",
+                    denom: state.denom,
+                });
+            }
 
-    // 构建格子展示
-    let mut response = Response::new()
-        .add_attribute("action", "play_scratch_card")
-        .add_attribute("card_type", format!("{:?}", card_type))
-        .add_attribute("bet_amount", bet.to_string());
+            let min_raise = game_config(deps.storage, GameKind::Omaha)?.min_raise.u128();
+            let min_raise = STATE.load(deps.storage)?.denom_config(&state.denom)?.scale(min_raise);
+            if amount < min_raise {
+                return Err(ContractError::RaiseTooSmall {
+                    min: min_raise,
+                    denom: state.denom,
+                });
+            }
 
-    // 输出 3×3 格子
-    for row in 0..3usize {
-        for col in 0..3usize {
-            let idx = row * 3 + col;
-            let key = format!("cell_{}_{}", row + 1, col + 1);
-            response = response.add_attribute(key, grid[idx].name());
+            // 推进阶段并揭示公共牌
+            let (new_stage, community) = advance_stage(deps.storage, &state)?;
+
+            state.player_total_bet += Uint128::from(amount);
+            state.current_call_amount += Uint128::from(amount);
+            state.stage = new_stage.clone();
+            state.community_cards = community.clone();
+
+            add_multi_step_stake(
+                deps.storage,
+                GameKind::Omaha,
+                &state.denom,
+                &mut state.reserved,
+                amount,
+                state.player_total_bet,
+            )?;
+
+            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "omaha_raise")
+                .add_attribute("raise_amount", amount.to_string())
+                .add_attribute("total_bet", state.player_total_bet.to_string())
+                .add_attribute("stage", format!("{:?}", new_stage))
+                .add_attribute("community_cards", format_cards(&community)))
         }
-    }
 
-    // 输出格子 emoji 行（便于阅读）
-    for row in 0..3usize {
-        let row_str: Vec<&str> = (0..3)
-            .map(|col| grid[row * 3 + col].emoji())
-            .collect();
-        response = response.add_attribute(
-            format!("row_{}", row + 1),
-            row_str.join(" "),
-        );
-    }
+        // ── 跟注 ──────────────────────────────────────────────────────
+        OmahaAction::Call => {
+            let mut state = OMAHA_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Omaha })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            if matches!(state.stage, OmahaStage::Showdown) {
+                return Err(ContractError::InvalidAction {
+                    reason: "Game is at Showdown, use Showdown action",
+                });
+            }
 
-    // 中奖线数
-    let win_count = winning_lines.len() as u32;
-    response = response.add_attribute("winning_lines", win_count.to_string());
+            // 需要补齐的差额
+            let call_diff = state
+                .current_call_amount
+                .saturating_sub(state.player_total_bet)
+                .u128();
 
-    if total_multiplier > 0 {
-        // 有中奖（扣除抽水）
-        let gross = bet * total_multiplier;
-        let payout = apply_house_edge(gross, bet, state.house_edge_bps);
+            // 附带的资金必须与需补齐的差额完全一致，追加下注只能通过 Raise
+            let sent = sent_in_denom(&info.funds, &state.denom);
+            if sent != call_diff {
+                return Err(ContractError::FundsMismatch {
+                    action: "call",
+                    denom: state.denom,
+                });
+            }
 
-        // 输出每条中奖线
-        let win_desc: Vec<String> = winning_lines
-            .iter()
-            .map(|(line, sym)| format!("{}:{}", line, sym.name()))
-            .collect();
+            // 推进阶段
+            let (new_stage, community) = advance_stage(deps.storage, &state)?;
 
-        state.locked_amount = state.locked_amount.saturating_sub(payout);
-        response = response
-            .add_attribute("result", "win")
-            .add_attribute("win_desc", win_desc.join(","))
-            .add_attribute("total_multiplier", total_multiplier.to_string())
-            .add_attribute("payout", payout.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout),
-                }],
-            });
-    } else {
-        response = response
-            .add_attribute("result", "lose")
-            .add_attribute("payout", "0");
-    }
+            state.player_total_bet += Uint128::from(call_diff);
+            state.stage = new_stage.clone();
+            state.community_cards = community.clone();
 
-    STATE.save(deps.storage, &state)?;
-    Ok(response)
+            if call_diff > 0 {
+                add_multi_step_stake(
+                    deps.storage,
+                    GameKind::Omaha,
+                    &state.denom,
+                    &mut state.reserved,
+                    call_diff,
+                    state.player_total_bet,
+                )?;
+            }
+
+            OMAHA_STATE.save(deps.storage, &info.sender, &state)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "omaha_call")
+                .add_attribute("call_amount", call_diff.to_string())
+                .add_attribute("total_bet", state.player_total_bet.to_string())
+                .add_attribute("stage", format!("{:?}", new_stage))
+                .add_attribute("community_cards", format_cards(&community)))
+        }
+
+        // ── 弃牌 ──────────────────────────────────────────────────────
+        OmahaAction::Fold => {
+            let state = OMAHA_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Omaha })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            ensure_no_funds(&info.funds, "fold", &state.denom)?;
+            fold_omaha(deps, &info.sender, state)
+        }
+
+        // ── 摊牌结算 ───────────────────────────────────────────────────
+        OmahaAction::Showdown => {
+            let state = OMAHA_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Omaha })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            ensure_no_funds(&info.funds, "showdown", &state.denom)?;
+            settle_omaha(deps, env, &info.sender, state)
+        }
+    }
 }
 
-// ──────────────────────────────────────────────────────────────────────────────
-// 斗牛（Bull Bull / Niu Niu）
-//
-// 单局游戏：双方各发 5 张牌，比较牌型
-// 特殊牌型：五小牛(8×) > 四炸(7×) > 五花牛(6×) > 牛牛(4×) > 牛9/8(3×) > 牛1-7(2×) > 没牛(2×)
-// ──────────────────────────────────────────────────────────────────────────────
-fn play_bullfight(
+/// 奥马哈弃牌：玩家已下注金额归合约（弃牌与超时共用）
+fn fold_omaha(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-) -> StdResult<Response> {
-    // 检查下注金额
-    let bet = info
-        .funds
-        .iter()
-        .find(|c| c.denom == "uatom")
-        .map(|c| c.amount.u128())
-        .unwrap_or(0);
+    player: &Addr,
+    mut state: OmahaState,
+) -> Result<Response, ContractError> {
+    state.finished = true;
+    OMAHA_STATE.save(deps.storage, player, &state)?;
+    release_reservation(deps.storage, &state.denom, state.reserved)?;
 
-    if bet < 100_000 || bet > 10_000_000 {
-        return Err(StdError::generic_err(
-            "Bet must be between 100,000 and 10,000,000 uatom",
-        ));
-    }
+    let settlement = Settlement::lost(GameKind::Omaha, state.player_total_bet.u128());
+    Ok(Response::new()
+        .add_event(omaha_settled_event(deps.storage, &settlement, player, &state)?)
+        .add_attribute("action", "omaha_fold")
+        .add_attribute("result", "folded")
+        .add_attribute("lost_amount", state.player_total_bet.to_string()))
+}
 
-    // 更新合约锁仓金额
-    let mut state = STATE.load(deps.storage)?;
-    state.locked_amount += bet;
+/// 奥马哈摊牌：揭示全部公共牌并结算（摊牌与超时共用）
+fn settle_omaha(
+    deps: DepsMut,
+    env: &Env,
+    player: &Addr,
+    mut state: OmahaState,
+) -> Result<Response, ContractError> {
+    // 揭示全部 5 张公共牌
+    let deck = omaha_deck(deps.storage, &state)?;
+    let full_community: Vec<Card> = (8..13).map(|i| Card::from_id(deck[i])).collect();
 
-    // 洗牌并发牌：玩家 5 张 + 庄家 5 张
-    let deck = shuffle_bullfight_deck(&info, &env);
-    let player_cards: [BullCard; 5] = [
-        BullCard::from_id(deck[0]),
-        BullCard::from_id(deck[1]),
-        BullCard::from_id(deck[2]),
-        BullCard::from_id(deck[3]),
-        BullCard::from_id(deck[4]),
-    ];
-    let dealer_cards: [BullCard; 5] = [
-        BullCard::from_id(deck[5]),
-        BullCard::from_id(deck[6]),
-        BullCard::from_id(deck[7]),
-        BullCard::from_id(deck[8]),
-        BullCard::from_id(deck[9]),
-    ];
+    // 评估双方最佳手牌
+    let player_rank = best_omaha_hand_rank(&state.player_hand, &full_community);
+    let dealer_rank = best_omaha_hand_rank(&state.dealer_hand, &full_community);
 
-    // 评估双方牌力
-    let player_rank = evaluate_bull_hand(&player_cards);
-    let dealer_rank = evaluate_bull_hand(&dealer_cards);
+    let player_hand_name = hand_rank_name(player_rank);
+    let dealer_hand_name = hand_rank_name(dealer_rank);
 
-    let player_type_name = bull_hand_type_name(&player_rank.hand_type);
-    let dealer_type_name = bull_hand_type_name(&dealer_rank.hand_type);
+    let total_bet = state.player_total_bet.u128();
+    let bettor = Bettor {
+        player: player.clone(),
+        denom: state.denom.clone(),
+        source: state.source,
+    };
 
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Omaha)?;
     let mut response = Response::new()
-        .add_attribute("action", "play_bullfight")
-        .add_attribute("player_cards", format_bull_cards(&player_cards))
-        .add_attribute("dealer_cards", format_bull_cards(&dealer_cards))
-        .add_attribute("player_hand_type", player_type_name)
-        .add_attribute("dealer_hand_type", dealer_type_name)
-        .add_attribute("player_score", player_rank.score.to_string())
-        .add_attribute("dealer_score", dealer_rank.score.to_string());
-
-    if player_rank.score > dealer_rank.score {
-        // 玩家赢（扣除抽水）
-        let multiplier = bull_payout_multiplier(&player_rank.hand_type);
-        let gross = bet * multiplier;
-        let payout = apply_house_edge(gross, bet, state.house_edge_bps);
+        .add_attribute("action", "omaha_showdown")
+        .add_attribute("player_hand", format_cards(&state.player_hand))
+        .add_attribute("dealer_hand", format_cards(&state.dealer_hand))
+        .add_attribute("community_cards", format_cards(&full_community))
+        .add_attribute("player_rank_name", player_hand_name)
+        .add_attribute("dealer_rank_name", dealer_hand_name)
+        .add_attribute("player_rank", player_rank.to_string())
+        .add_attribute("dealer_rank", dealer_rank.to_string());
 
-        state.locked_amount = state.locked_amount.saturating_sub(payout);
+    let settlement = if player_rank > dealer_rank {
+        // 玩家赢：获得 2× 下注额（扣除抽水）
+        let gross = total_bet * 2;
+        let payout = apply_house_edge(gross, total_bet, house_edge_bps);
         response = response
-            .add_attribute("result", "win")
-            .add_attribute("payout_multiplier", multiplier.to_string())
-            .add_attribute("payout", payout.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(payout),
-                }],
-            });
-    } else if dealer_rank.score > player_rank.score {
-        // 庄家赢
+            .add_attribute("result", "player_win")
+            .add_attribute("payout", payout.to_string());
+        Settlement::new(GameKind::Omaha, total_bet, gross, payout)
+    } else if dealer_rank > player_rank {
+        // 庄家赢：玩家损失下注额（已留在合约中）
         response = response
-            .add_attribute("result", "lose")
+            .add_attribute("result", "dealer_win")
             .add_attribute("payout", "0");
+        Settlement::lost(GameKind::Omaha, total_bet)
     } else {
-        // 平局，退还本金
-        state.locked_amount = state.locked_amount.saturating_sub(bet);
+        // 平局：退还下注额
         response = response
             .add_attribute("result", "tie")
-            .add_attribute("payout", bet.to_string())
-            .add_message(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: vec![Coin {
-                    denom: "uatom".to_string(),
-                    amount: Uint128::from(bet),
-                }],
-            });
-    }
+            .add_attribute("payout", total_bet.to_string());
+        Settlement::new(GameKind::Omaha, total_bet, total_bet, total_bet)
+    };
 
-    STATE.save(deps.storage, &state)?;
-    Ok(response)
+    state.finished = true;
+    state.community_cards = full_community;
+    OMAHA_STATE.save(deps.storage, player, &state)?;
+    release_reservation(deps.storage, &bettor.denom, state.reserved)?;
+
+    Ok(response
+        .add_event(omaha_settled_event(deps.storage, &settlement, player, &state)?)
+        .add_messages(settle_payout(deps, env, &bettor, settlement.payout)?))
 }
 
-/// 斗牛洗牌
-fn shuffle_bullfight_deck(info: &MessageInfo, env: &Env) -> Vec<u8> {
-    let mut deck: Vec<u8> = (0u8..52).collect();
-    let mut weights: Vec<u32> = (0u8..52)
-        .map(|i| {
-            let salt = format!("bullfight_deck_{}", i);
-            utils::generate_random_number(info, env, salt.as_bytes())
-        })
-        .collect();
+/// 奥马哈结算事件：outcome 为结束时的牌面（弃牌时只含已揭示的公共牌）
+fn omaha_settled_event(
+    storage: &dyn Storage,
+    settlement: &Settlement,
+    player: &Addr,
+    state: &OmahaState,
+) -> Result<Event, ContractError> {
+    let outcome = GameOutcome::Omaha {
+        deck: omaha_deck(storage, state)?,
+        player_hand: state.player_hand.clone(),
+        dealer_hand: state.dealer_hand.clone(),
+        community_cards: state.community_cards.clone(),
+    };
+    Ok(settlement.event(state.bet_id, player, &state.denom, &outcome)?)
+}
 
-    for i in (1..52usize).rev() {
-        let j = (weights[i] as usize + i * 97) % (i + 1);
-        deck.swap(i, j);
-        weights.swap(i, j);
+/// 奥马哈开局（开奖后由 settle_bet 调用）
+///
+/// 若结算时玩家已有进行中的牌局（同时挂了多笔 Start 下注），退还本次下注
+fn play_omaha_start(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    reserved: Uint128,
+    bet_id: u64,
+    seed: &[u8],
+) -> Result<Response, ContractError> {
+    if let Ok(existing) = OMAHA_STATE.load(deps.storage, &bettor.player) {
+        if !existing.finished {
+            let event = refund_event(GameKind::Omaha, bet_id, bettor, bet)?;
+            let response = refund_stake(deps, env, bettor, bet, reserved)?;
+            return Ok(response
+                .add_event(event)
+                .add_attribute("action", "omaha_start")
+                .add_attribute("result", "refunded"));
+        }
     }
-    deck
+
+    // 生成洗牌后的牌组（0..=51 随机排列）
+    let deck = shuffled_deck(seed, b"omaha_deck");
+
+    // 发牌：玩家 4 张 (pos 0-3)，庄家 4 张 (pos 4-7)
+    // 公共牌 5 张 (pos 8-12)，先全部生成但按阶段揭示
+    let player_hand: Vec<Card> = (0..4).map(|i| Card::from_id(deck[i])).collect();
+    let dealer_hand: Vec<Card> = (4..8).map(|i| Card::from_id(deck[i])).collect();
+    // 公共牌在 deck[8..13]，按阶段通过 advance_stage 重新洗牌后揭示
+    let state = OmahaState {
+        player_hand: player_hand.clone(),
+        dealer_hand,
+        community_cards: vec![],
+        player_total_bet: Uint128::from(bet),
+        current_call_amount: Uint128::from(bet),
+        stage: OmahaStage::PreFlop,
+        finished: false,
+        deck_pos: 13, // 前 13 张已用
+        source: bettor.source,
+        denom: bettor.denom.clone(),
+        reserved,
+        bet_id,
+        start_height: env.block.height,
+        start_time: env.block.time.seconds(),
+    };
+
+    OMAHA_STATE.save(deps.storage, &bettor.player, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "omaha_start")
+        .add_attribute("stage", "PreFlop")
+        .add_attribute("player_hand", format_cards(&player_hand))
+        .add_attribute("community_cards", "[]")
+        .add_attribute("initial_bet", bet.to_string()))
 }
 
-/// 格式化斗牛牌面
-fn format_bull_cards(cards: &[BullCard]) -> String {
+/// 奥马哈本局的牌序：按开局下注的种子重新洗出（牌序不在链上保存）
+fn omaha_deck(storage: &dyn Storage, state: &OmahaState) -> Result<Vec<u8>, ContractError> {
+    Ok(shuffled_deck(&bet_seed(storage, state.bet_id)?, b"omaha_deck"))
+}
+
+/// 根据当前阶段推进到下一阶段，并返回应揭示的公共牌列表
+fn advance_stage(
+    storage: &dyn Storage,
+    state: &OmahaState,
+) -> Result<(OmahaStage, Vec<Card>), ContractError> {
+    let deck = omaha_deck(storage, state)?;
+    Ok(match state.stage {
+        OmahaStage::PreFlop => {
+            // 翻牌：揭示 3 张公共牌（deck[8..11]）
+            let community = (8..11).map(|i| Card::from_id(deck[i])).collect();
+            (OmahaStage::Flop, community)
+        }
+        OmahaStage::Flop => {
+            // 转牌：揭示 4 张（deck[8..12]）
+            let community = (8..12).map(|i| Card::from_id(deck[i])).collect();
+            (OmahaStage::Turn, community)
+        }
+        OmahaStage::Turn => {
+            // 河牌：揭示全部 5 张（deck[8..13]）
+            let community = (8..13).map(|i| Card::from_id(deck[i])).collect();
+            (OmahaStage::River, community)
+        }
+        OmahaStage::River | OmahaStage::Showdown => {
+            // 已经揭示完毕，保持不变
+            let community = state.community_cards.clone();
+            (OmahaStage::Showdown, community)
+        }
+    })
+}
+
+/// 格式化 Card 列表为可读字符串
+fn format_cards(cards: &[Card]) -> String {
     let parts: Vec<String> = cards
         .iter()
         .map(|c| {
             let rank_str = match c.rank {
-                1 => "A".to_string(),
                 11 => "J".to_string(),
                 12 => "Q".to_string(),
                 13 => "K".to_string(),
+                14 => "A".to_string(),
                 n => n.to_string(),
             };
             let suit_str = match c.suit {
-                bullfight::Suit::Spades   => "♠",
-                bullfight::Suit::Hearts   => "♥",
-                bullfight::Suit::Diamonds => "♦",
-                bullfight::Suit::Clubs    => "♣",
+                omaha::Suit::Spades   => "♠",
+                omaha::Suit::Hearts   => "♥",
+                omaha::Suit::Diamonds => "♦",
+                omaha::Suit::Clubs    => "♣",
             };
             format!("{}{}", rank_str, suit_str)
         })
@@ -2784,499 +4098,2659 @@ fn format_bull_cards(cards: &[BullCard]) -> String {
     format!("[{}]", parts.join(","))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{attr, coins, from_json};
-
-    #[test]
-    pub fn test_init() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!("init", res.attributes[0].value);
-        assert_eq!("creator", res.attributes[1].value);
-        assert_eq!("10000000000", res.attributes[2].value)
+// ──────────────────────────────────────────────────────────────────────────────
+// 德州扑克（Texas Hold'em）
+//
+// 流程：
+//   1. Start        → 玩家下注底注，发 2 张手牌（庄家也发 2 张），进入 PreFlop
+//   2. Raise/Call/Check → 在 PreFlop / Flop / Turn / River 阶段操作
+//   3. AllIn        → 全押，直接进入 Showdown 自动揭示所有公共牌
+//   4. Showdown     → 任意阶段直接摊牌结算
+//   5. Fold         → 放弃本局，损失已押注金额
+//
+// 公共牌揭示节奏：
+//   PreFlop  → Flop（3 张）→ Turn（+1 张）→ River（+1 张）→ Showdown
+//
+// 德州规则：从 2 张手牌 + 5 张公共牌中选最佳 5 张组合
+// ──────────────────────────────────────────────────────────────────────────────
+fn play_texas(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    action: TexasAction,
+) -> Result<Response, ContractError> {
+    match action {
+        // ── 开始游戏 ──────────────────────────────────────────────────
+        // 开局需先下注并等待开奖，由 settle_bet 调用 play_texas_start
+        TexasAction::Start => Err(ContractError::StartRequiresBet { game: GameKind::Texas }),
+
+        // ── 加注 ──────────────────────────────────────────────────────
+        TexasAction::Raise { amount } => {
+            let mut state = TEXAS_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Texas })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            if state.all_in {
+                return Err(ContractError::InvalidAction {
+                    reason: "Already all-in, cannot raise",
+                });
+            }
+            if matches!(state.stage, TexasStage::Showdown) {
+                return Err(ContractError::InvalidAction {
+                    reason: "Game is at Showdown, cannot raise",
+                });
+            }
+
+            let sent = sent_in_denom(&info.funds, &state.denom);
+
+            if sent != amount || amount == 0 {
+                return Err(ContractError::FundsMismatch {
+                    action: "raise",
+                    denom: state.denom,
+                });
+            }
+
+            let min_raise = game_config(deps.storage, GameKind::Texas)?.min_raise.u128();
+            let min_raise = STATE.load(deps.storage)?.denom_config(&state.denom)?.scale(min_raise);
+            if amount < min_raise {
+                return Err(ContractError::RaiseTooSmall {
+                    min: min_raise,
+                    denom: state.denom,
+                });
+            }
+
+            let (new_stage, community) = advance_texas_stage(deps.storage, &state)?;
+
+            state.player_total_bet += Uint128::from(amount);
+            state.current_call_amount += Uint128::from(amount);
+            state.stage = new_stage.clone();
+            state.community_cards = community.clone();
+
+            add_multi_step_stake(
+                deps.storage,
+                GameKind::Texas,
+                &state.denom,
+                &mut state.reserved,
+                amount,
+                state.player_total_bet,
+            )?;
+
+            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "texas_raise")
+                .add_attribute("raise_amount", amount.to_string())
+                .add_attribute("total_bet", state.player_total_bet.to_string())
+                .add_attribute("stage", format!("{:?}", new_stage))
+                .add_attribute("community_cards", format_texas_cards(&community)))
+        }
+
+        // ── 跟注 ──────────────────────────────────────────────────────
+        TexasAction::Call => {
+            let mut state = TEXAS_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Texas })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            if state.all_in {
+                return Err(ContractError::InvalidAction {
+                    reason: "Already all-in, cannot call",
+                });
+            }
+            if matches!(state.stage, TexasStage::Showdown) {
+                return Err(ContractError::InvalidAction {
+                    reason: "Game is at Showdown, use Showdown action",
+                });
+            }
+
+            let call_diff = state
+                .current_call_amount
+                .saturating_sub(state.player_total_bet)
+                .u128();
+
+            // 附带的资金必须与需补齐的差额完全一致，追加下注只能通过 Raise
+            let sent = sent_in_denom(&info.funds, &state.denom);
+            if sent != call_diff {
+                return Err(ContractError::FundsMismatch {
+                    action: "call",
+                    denom: state.denom,
+                });
+            }
+
+            let (new_stage, community) = advance_texas_stage(deps.storage, &state)?;
+
+            state.player_total_bet += Uint128::from(call_diff);
+            state.stage = new_stage.clone();
+            state.community_cards = community.clone();
+
+            if call_diff > 0 {
+                add_multi_step_stake(
+                    deps.storage,
+                    GameKind::Texas,
+                    &state.denom,
+                    &mut state.reserved,
+                    call_diff,
+                    state.player_total_bet,
+                )?;
+            }
+
+            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "texas_call")
+                .add_attribute("call_amount", call_diff.to_string())
+                .add_attribute("total_bet", state.player_total_bet.to_string())
+                .add_attribute("stage", format!("{:?}", new_stage))
+                .add_attribute("community_cards", format_texas_cards(&community)))
+        }
+
+        // ── 过牌 ──────────────────────────────────────────────────────
+        TexasAction::Check => {
+            let mut state = TEXAS_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Texas })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            ensure_no_funds(&info.funds, "check", &state.denom)?;
+            if state.all_in {
+                return Err(ContractError::InvalidAction {
+                    reason: "Already all-in, cannot check",
+                });
+            }
+            if matches!(state.stage, TexasStage::Showdown) {
+                return Err(ContractError::InvalidAction {
+                    reason: "Game is at Showdown, use Showdown action",
+                });
+            }
+
+            // Check 仅在差额为 0 时允许（即无需补齐）
+            let diff = state
+                .current_call_amount
+                .saturating_sub(state.player_total_bet)
+                .u128();
+            if diff > 0 {
+                return Err(ContractError::InvalidAction {
+                    reason: "Cannot check when you owe a call amount. Use Call or Raise instead.",
+                });
+            }
+
+            let (new_stage, community) = advance_texas_stage(deps.storage, &state)?;
+
+            state.stage = new_stage.clone();
+            state.community_cards = community.clone();
+
+            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+
+            Ok(Response::new()
+                .add_attribute("action", "texas_check")
+                .add_attribute("total_bet", state.player_total_bet.to_string())
+                .add_attribute("stage", format!("{:?}", new_stage))
+                .add_attribute("community_cards", format_texas_cards(&community)))
+        }
+
+        // ── 全押 ──────────────────────────────────────────────────────
+        TexasAction::AllIn { amount } => {
+            let mut state = TEXAS_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Texas })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            if state.all_in {
+                return Err(ContractError::InvalidAction {
+                    reason: "Already all-in",
+                });
+            }
+            if matches!(state.stage, TexasStage::Showdown) {
+                return Err(ContractError::InvalidAction {
+                    reason: "Game is at Showdown, cannot all-in",
+                });
+            }
+
+            let sent = sent_in_denom(&info.funds, &state.denom);
+
+            if sent != amount || amount == 0 {
+                return Err(ContractError::FundsMismatch {
+                    action: "all-in",
+                    denom: state.denom,
+                });
+            }
+
+            // 全押额不低于开局底注下限
+            let min_all_in = game_config(deps.storage, GameKind::Texas)?.min_bet.u128();
+            let min_all_in =
+                STATE.load(deps.storage)?.denom_config(&state.denom)?.scale(min_all_in);
+            if amount < min_all_in {
+                return Err(ContractError::AllInTooSmall {
+                    min: min_all_in,
+                    denom: state.denom,
+                });
+            }
+
+            // 全押：揭示全部公共牌，进入 Showdown
+            let deck = texas_deck(deps.storage, &state)?;
+            let full_community: Vec<texas::Card> =
+                (4..9).map(|i| texas::Card::from_id(deck[i])).collect();
+
+            state.player_total_bet += Uint128::from(amount);
+            state.current_call_amount += Uint128::from(amount);
+            state.all_in = true;
+            state.community_cards = full_community.clone();
+            state.stage = TexasStage::Showdown;
+
+            add_multi_step_stake(
+                deps.storage,
+                GameKind::Texas,
+                &state.denom,
+                &mut state.reserved,
+                amount,
+                state.player_total_bet,
+            )?;
+
+            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+
+            // 自动进入 Showdown 结算
+            settle_texas(deps, env, &info.sender, state)
+        }
+
+        // ── 弃牌 ──────────────────────────────────────────────────────
+        TexasAction::Fold => {
+            let state = TEXAS_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Texas })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            ensure_no_funds(&info.funds, "fold", &state.denom)?;
+            fold_texas(deps, &info.sender, state)
+        }
+
+        // ── 摊牌结算 ─────────────────────────────────────────────────
+        TexasAction::Showdown => {
+            let mut state = TEXAS_STATE
+                .may_load(deps.storage, &info.sender)?
+                .ok_or(ContractError::GameNotFound { game: GameKind::Texas })?;
+            if state.finished {
+                return Err(ContractError::GameFinished);
+            }
+            ensure_no_funds(&info.funds, "showdown", &state.denom)?;
+
+            reveal_texas_board(deps.storage, &mut state)?;
+            TEXAS_STATE.save(deps.storage, &info.sender, &state)?;
+
+            settle_texas(deps, env, &info.sender, state)
+        }
+    }
+}
+
+/// 德州扑克开局（开奖后由 settle_bet 调用）
+///
+/// 若结算时玩家已有进行中的牌局（同时挂了多笔 Start 下注），退还本次下注
+fn play_texas_start(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    reserved: Uint128,
+    bet_id: u64,
+    seed: &[u8],
+) -> Result<Response, ContractError> {
+    if let Ok(existing) = TEXAS_STATE.load(deps.storage, &bettor.player) {
+        if !existing.finished {
+            let event = refund_event(GameKind::Texas, bet_id, bettor, bet)?;
+            let response = refund_stake(deps, env, bettor, bet, reserved)?;
+            return Ok(response
+                .add_event(event)
+                .add_attribute("action", "texas_start")
+                .add_attribute("result", "refunded"));
+        }
+    }
+
+    // 洗牌
+    let deck = shuffled_deck(seed, b"texas_deck");
+
+    // 发牌：玩家 2 张 (pos 0-1)，庄家 2 张 (pos 2-3)
+    // 公共牌 5 张 (pos 4-8)，按阶段揭示
+    let player_hand: Vec<texas::Card> = (0..2).map(|i| texas::Card::from_id(deck[i])).collect();
+    let dealer_hand: Vec<texas::Card> = (2..4).map(|i| texas::Card::from_id(deck[i])).collect();
+
+    let state = TexasState {
+        player_hand: player_hand.clone(),
+        dealer_hand,
+        community_cards: vec![],
+        player_total_bet: Uint128::from(bet),
+        current_call_amount: Uint128::from(bet),
+        stage: TexasStage::PreFlop,
+        finished: false,
+        all_in: false,
+        source: bettor.source,
+        denom: bettor.denom.clone(),
+        reserved,
+        bet_id,
+        start_height: env.block.height,
+        start_time: env.block.time.seconds(),
+    };
+
+    TEXAS_STATE.save(deps.storage, &bettor.player, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "texas_start")
+        .add_attribute("stage", "PreFlop")
+        .add_attribute("player_hand", format_texas_cards(&player_hand))
+        .add_attribute("community_cards", "[]")
+        .add_attribute("initial_bet", bet.to_string()))
+}
+
+/// 德州扑克结算逻辑
+fn settle_texas(
+    deps: DepsMut,
+    env: &Env,
+    player: &Addr,
+    mut state: TexasState,
+) -> Result<Response, ContractError> {
+    let full_community = &state.community_cards;
+
+    // 评估双方最佳手牌
+    let player_rank = best_texas_hand_rank(&state.player_hand, full_community);
+    let dealer_rank = best_texas_hand_rank(&state.dealer_hand, full_community);
+
+    let player_hand_name = texas::hand_rank_name(player_rank);
+    let dealer_hand_name = texas::hand_rank_name(dealer_rank);
+
+    let total_bet = state.player_total_bet.u128();
+    let bettor = Bettor {
+        player: player.clone(),
+        denom: state.denom.clone(),
+        source: state.source,
+    };
+
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Texas)?;
+    let mut response = Response::new()
+        .add_attribute("action", "texas_showdown")
+        .add_attribute("player_hand", format_texas_cards(&state.player_hand))
+        .add_attribute("dealer_hand", format_texas_cards(&state.dealer_hand))
+        .add_attribute(
+            "community_cards",
+            format_texas_cards(full_community),
+        )
+        .add_attribute("player_rank_name", player_hand_name)
+        .add_attribute("dealer_rank_name", dealer_hand_name)
+        .add_attribute("player_rank", player_rank.to_string())
+        .add_attribute("dealer_rank", dealer_rank.to_string());
+
+    let settlement = if player_rank > dealer_rank {
+        // 玩家赢：获得 2× 下注额（扣除抽水）
+        let gross = total_bet * 2;
+        let payout = apply_house_edge(gross, total_bet, house_edge_bps);
+        response = response
+            .add_attribute("result", "player_win")
+            .add_attribute("payout", payout.to_string());
+        Settlement::new(GameKind::Texas, total_bet, gross, payout)
+    } else if dealer_rank > player_rank {
+        // 庄家赢
+        response = response
+            .add_attribute("result", "dealer_win")
+            .add_attribute("payout", "0");
+        Settlement::lost(GameKind::Texas, total_bet)
+    } else {
+        // 平局：退还下注额
+        response = response
+            .add_attribute("result", "tie")
+            .add_attribute("payout", total_bet.to_string());
+        Settlement::new(GameKind::Texas, total_bet, total_bet, total_bet)
+    };
+
+    state.finished = true;
+    TEXAS_STATE.save(deps.storage, player, &state)?;
+    release_reservation(deps.storage, &bettor.denom, state.reserved)?;
+
+    Ok(response
+        .add_event(texas_settled_event(deps.storage, &settlement, player, &state)?)
+        .add_messages(settle_payout(deps, env, &bettor, settlement.payout)?))
+}
+
+/// 德州扑克弃牌：玩家已下注金额归合约（弃牌与超时共用）
+fn fold_texas(
+    deps: DepsMut,
+    player: &Addr,
+    mut state: TexasState,
+) -> Result<Response, ContractError> {
+    state.finished = true;
+    TEXAS_STATE.save(deps.storage, player, &state)?;
+    release_reservation(deps.storage, &state.denom, state.reserved)?;
+
+    let settlement = Settlement::lost(GameKind::Texas, state.player_total_bet.u128());
+    Ok(Response::new()
+        .add_event(texas_settled_event(deps.storage, &settlement, player, &state)?)
+        .add_attribute("action", "texas_fold")
+        .add_attribute("result", "folded")
+        .add_attribute("lost_amount", state.player_total_bet.to_string()))
+}
+
+/// 德州扑克本局的牌序：按开局下注的种子重新洗出（牌序不在链上保存）
+fn texas_deck(storage: &dyn Storage, state: &TexasState) -> Result<Vec<u8>, ContractError> {
+    Ok(shuffled_deck(&bet_seed(storage, state.bet_id)?, b"texas_deck"))
+}
+
+/// 德州扑克揭示全部 5 张公共牌并进入 Showdown
+fn reveal_texas_board(storage: &dyn Storage, state: &mut TexasState) -> Result<(), ContractError> {
+    let deck = texas_deck(storage, state)?;
+    state.community_cards = (4..9).map(|i| texas::Card::from_id(deck[i])).collect();
+    state.stage = TexasStage::Showdown;
+    Ok(())
+}
+
+/// 德州扑克结算事件：outcome 为结束时的牌面（弃牌时只含已揭示的公共牌）
+fn texas_settled_event(
+    storage: &dyn Storage,
+    settlement: &Settlement,
+    player: &Addr,
+    state: &TexasState,
+) -> Result<Event, ContractError> {
+    let outcome = GameOutcome::Texas {
+        deck: texas_deck(storage, state)?,
+        player_hand: state.player_hand.clone(),
+        dealer_hand: state.dealer_hand.clone(),
+        community_cards: state.community_cards.clone(),
+    };
+    Ok(settlement.event(state.bet_id, player, &state.denom, &outcome)?)
+}
+
+/// 德州扑克阶段推进
+fn advance_texas_stage(
+    storage: &dyn Storage,
+    state: &TexasState,
+) -> Result<(TexasStage, Vec<texas::Card>), ContractError> {
+    let deck = texas_deck(storage, state)?;
+    Ok(match state.stage {
+        TexasStage::PreFlop => {
+            // Flop: 揭示 3 张公共牌 (deck[4..7])
+            let community = (4..7).map(|i| texas::Card::from_id(deck[i])).collect();
+            (TexasStage::Flop, community)
+        }
+        TexasStage::Flop => {
+            // Turn: 揭示 4 张 (deck[4..8])
+            let community = (4..8).map(|i| texas::Card::from_id(deck[i])).collect();
+            (TexasStage::Turn, community)
+        }
+        TexasStage::Turn => {
+            // River: 揭示全部 5 张 (deck[4..9])
+            let community = (4..9).map(|i| texas::Card::from_id(deck[i])).collect();
+            (TexasStage::River, community)
+        }
+        TexasStage::River | TexasStage::Showdown => {
+            let community = state.community_cards.clone();
+            (TexasStage::Showdown, community)
+        }
+    })
+}
+
+/// 格式化德州扑克 Card 列表为可读字符串
+fn format_texas_cards(cards: &[texas::Card]) -> String {
+    let parts: Vec<String> = cards
+        .iter()
+        .map(|c| {
+            let rank_str = match c.rank {
+                11 => "J".to_string(),
+                12 => "Q".to_string(),
+                13 => "K".to_string(),
+                14 => "A".to_string(),
+                n => n.to_string(),
+            };
+            let suit_str = match c.suit {
+                texas::Suit::Spades   => "♠",
+                texas::Suit::Hearts   => "♥",
+                texas::Suit::Diamonds => "♦",
+                texas::Suit::Clubs    => "♣",
+            };
+            format!("{}{}", rank_str, suit_str)
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// 三公（San Gong / Three Face Cards）
+//
+// 单局游戏：下注后双方各发 3 张牌，直接比较牌力
+// 牌型：三公 > 混合九 > 普通点数 9 > ... > 0
+// 赔率：三公赢 3×，混合九/普通赢 2×，平局退还本金
+// ──────────────────────────────────────────────────────────────────────────────
+fn play_sangong(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::SanGong)?;
+
+    let (player_cards, dealer_cards) = deal_sangong(seed);
+
+    // 评估双方牌力
+    let player_rank = evaluate_sangong_hand(&player_cards);
+    let dealer_rank = evaluate_sangong_hand(&dealer_cards);
+
+    let player_type_name = hand_type_name(&player_rank.hand_type);
+    let dealer_type_name = hand_type_name(&dealer_rank.hand_type);
+
+    let player_points = match &player_rank.hand_type {
+        SanGongHandType::SanGong => 10,     // 显示用
+        SanGongHandType::MixedNine => 9,
+        SanGongHandType::Normal { points } => *points,
+    };
+    let dealer_points = match &dealer_rank.hand_type {
+        SanGongHandType::SanGong => 10,
+        SanGongHandType::MixedNine => 9,
+        SanGongHandType::Normal { points } => *points,
+    };
+
+    let mut response = Response::new()
+        .add_attribute("action", "play_sangong")
+        .add_attribute("player_cards", format_sangong_cards(&player_cards))
+        .add_attribute("dealer_cards", format_sangong_cards(&dealer_cards))
+        .add_attribute("player_hand_type", player_type_name)
+        .add_attribute("dealer_hand_type", dealer_type_name)
+        .add_attribute("player_points", player_points.to_string())
+        .add_attribute("dealer_points", dealer_points.to_string())
+        .add_attribute("player_score", player_rank.score.to_string())
+        .add_attribute("dealer_score", dealer_rank.score.to_string());
+
+    let settlement = if player_rank.score > dealer_rank.score {
+        // 玩家赢（扣除抽水）
+        let multiplier = sangong_payout_multiplier(&player_rank.hand_type);
+        let gross = bet * multiplier;
+        let payout = apply_house_edge(gross, bet, house_edge_bps);
+
+        response = response
+            .add_attribute("result", "win")
+            .add_attribute("payout_multiplier", multiplier.to_string())
+            .add_attribute("payout", payout.to_string())
+            .add_messages(settle_payout(deps, env, bettor, payout)?);
+        Settlement::new(GameKind::SanGong, bet, gross, payout)
+    } else if dealer_rank.score > player_rank.score {
+        // 庄家赢
+        response = response
+            .add_attribute("result", "lose")
+            .add_attribute("payout", "0");
+        Settlement::lost(GameKind::SanGong, bet)
+    } else {
+        // 平局，退还本金
+        response = response
+            .add_attribute("result", "tie")
+            .add_attribute("payout", bet.to_string())
+            .add_messages(settle_payout(deps, env, bettor, bet)?);
+        Settlement::new(GameKind::SanGong, bet, bet, bet)
+    };
+
+    Ok((response, settlement))
+}
+
+/// 三公洗牌并发牌：玩家 3 张 + 庄家 3 张
+fn deal_sangong(seed: &[u8]) -> ([SanGongCard; 3], [SanGongCard; 3]) {
+    let deck = shuffled_deck(seed, b"sangong_deck");
+    let player_cards: [SanGongCard; 3] = [
+        SanGongCard::from_id(deck[0]),
+        SanGongCard::from_id(deck[1]),
+        SanGongCard::from_id(deck[2]),
+    ];
+    let dealer_cards: [SanGongCard; 3] = [
+        SanGongCard::from_id(deck[3]),
+        SanGongCard::from_id(deck[4]),
+        SanGongCard::from_id(deck[5]),
+    ];
+    (player_cards, dealer_cards)
+}
+
+/// 格式化三公牌面
+fn format_sangong_cards(cards: &[SanGongCard]) -> String {
+    let parts: Vec<String> = cards
+        .iter()
+        .map(|c| {
+            let rank_str = match c.rank {
+                1 => "A".to_string(),
+                11 => "J".to_string(),
+                12 => "Q".to_string(),
+                13 => "K".to_string(),
+                n => n.to_string(),
+            };
+            let suit_str = match c.suit {
+                sangong::Suit::Spades   => "♠",
+                sangong::Suit::Hearts   => "♥",
+                sangong::Suit::Diamonds => "♦",
+                sangong::Suit::Clubs    => "♣",
+            };
+            format!("{}{}", rank_str, suit_str)
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// 骰宝（Sic Bo）
+//
+// 单局游戏：下注后摇三颗骰子，根据投注类型判定输赢
+// 投注类型：大/小、单/双、总和、三同号通选、指定三同号、双骰、单骰、两骰组合
+// ──────────────────────────────────────────────────────────────────────────────
+fn play_sicbo(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    bet_type: SicBoBetType,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::SicBo)?;
+
+    let result = roll_sicbo(seed);
+    let (die1, die2, die3) = (result.die1, result.die2, result.die3);
+
+    // 计算赔付
+    let (won, multiplier) = calculate_sicbo_payout(&bet_type, &result);
+
+    let mut response = Response::new()
+        .add_attribute("action", "play_sicbo")
+        .add_attribute("die1", die1.to_string())
+        .add_attribute("die2", die2.to_string())
+        .add_attribute("die3", die3.to_string())
+        .add_attribute("total", result.total.to_string())
+        .add_attribute("is_triple", result.is_triple.to_string())
+        .add_attribute("bet_type", format!("{:?}", bet_type));
+
+    let settlement = if won {
+        let gross = bet * multiplier;
+        let payout = apply_house_edge(gross, bet, house_edge_bps);
+
+        response = response
+            .add_attribute("result", "win")
+            .add_attribute("payout_multiplier", multiplier.to_string())
+            .add_attribute("payout", payout.to_string())
+            .add_messages(settle_payout(deps, env, bettor, payout)?);
+        Settlement::new(GameKind::SicBo, bet, gross, payout)
+    } else {
+        response = response
+            .add_attribute("result", "lose")
+            .add_attribute("payout", "0");
+        Settlement::lost(GameKind::SicBo, bet)
+    };
+
+    Ok((response, settlement))
+}
+
+/// 摇三颗骰子（每颗 1-6）
+fn roll_sicbo(seed: &[u8]) -> SicBoResult {
+    let mut rng = RngStream::new(seed, b"sicbo");
+    let die1 = rng.range_inclusive(1, 6) as u8;
+    let die2 = rng.range_inclusive(1, 6) as u8;
+    let die3 = rng.range_inclusive(1, 6) as u8;
+    SicBoResult::new(die1, die2, die3)
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// 基诺（Keno）
+//
+// 单局游戏：玩家选 1-10 个号码（1-80），系统随机开出 20 个号码
+// 命中越多赔率越高，选 10 中 10 最高赔 2000×
+// ──────────────────────────────────────────────────────────────────────────────
+fn play_keno(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    picks: Vec<u8>,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::Keno)?;
+
+    // 从 1-80 中随机抽取 20 个不重复号码并计算命中
+    let KenoResult {
+        picks,
+        drawn,
+        hits,
+        hit_count,
+    } = draw_keno(picks, seed);
+    let pick_count = picks.len() as u8;
+
+    // 计算赔率
+    let multiplier = keno_payout_multiplier(pick_count, hit_count);
+
+    let drawn_str: Vec<String> = drawn.iter().map(|n| n.to_string()).collect();
+    let picks_str: Vec<String> = picks.iter().map(|n| n.to_string()).collect();
+    let hits_str: Vec<String> = hits.iter().map(|n| n.to_string()).collect();
+
+    let mut response = Response::new()
+        .add_attribute("action", "play_keno")
+        .add_attribute("picks", format!("[{}]", picks_str.join(",")))
+        .add_attribute("drawn", format!("[{}]", drawn_str.join(",")))
+        .add_attribute("hits", format!("[{}]", hits_str.join(",")))
+        .add_attribute("pick_count", pick_count.to_string())
+        .add_attribute("hit_count", hit_count.to_string());
+
+    let settlement = if multiplier > 0 {
+        let gross = bet * multiplier;
+        let payout = apply_house_edge(gross, bet, house_edge_bps);
+
+        response = response
+            .add_attribute("result", "win")
+            .add_attribute("payout_multiplier", multiplier.to_string())
+            .add_attribute("payout", payout.to_string())
+            .add_messages(settle_payout(deps, env, bettor, payout)?);
+        Settlement::new(GameKind::Keno, bet, gross, payout)
+    } else {
+        response = response
+            .add_attribute("result", "lose")
+            .add_attribute("payout", "0");
+        Settlement::lost(GameKind::Keno, bet)
+    };
+
+    Ok((response, settlement))
+}
+
+/// 基诺开奖：抽取 20 个号码并与玩家选号比对
+fn draw_keno(picks: Vec<u8>, seed: &[u8]) -> KenoResult {
+    let drawn = draw_keno_numbers(seed);
+    let hits = calculate_hits(&picks, &drawn);
+    KenoResult {
+        picks,
+        drawn,
+        hit_count: hits.len() as u8,
+        hits,
+    }
+}
+
+/// 从 1-80 中随机抽取 20 个不重复号码
+fn draw_keno_numbers(seed: &[u8]) -> Vec<u8> {
+    // 生成 1-80 的号码池
+    let mut pool: Vec<u8> = (1u8..=80).collect();
+
+    // Fisher-Yates 均匀洗牌后取前 20 个
+    RngStream::new(seed, b"keno_draw").shuffle(&mut pool);
+
+    // 取前 20 个并排序（便于展示）
+    let mut drawn: Vec<u8> = pool[..20].to_vec();
+    drawn.sort_unstable();
+    drawn
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// 刮刮乐（Scratch Card）
+//
+// 单局游戏：购买一张 3×3 的刮刮卡，刮开后检查 8 条中奖线
+// 同行/同列/对角线三个相同符号即中奖，多线可叠加
+// 符号：💎(50×) ⭐(20×) 🍀(10×) 🔔(5×) 🍒(3×) 🍋(2×)
+// ──────────────────────────────────────────────────────────────────────────────
+fn play_scratch_card(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    card_type: ScratchCardType,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let kind = GameKind::scratch(&card_type);
+    let house_edge_bps = game_house_edge(deps.storage, kind)?;
+
+    let grid = scratch_grid(seed);
+
+    // 评估中奖
+    let (total_multiplier, winning_lines) = evaluate_scratch_card(&grid);
+
+    // 构建格子展示
+    let mut response = Response::new()
+        .add_attribute("action", "play_scratch_card")
+        .add_attribute("card_type", format!("{:?}", card_type))
+        .add_attribute("bet_amount", bet.to_string());
+
+    // 输出 3×3 格子
+    for row in 0..3usize {
+        for col in 0..3usize {
+            let idx = row * 3 + col;
+            let key = format!("cell_{}_{}", row + 1, col + 1);
+            response = response.add_attribute(key, grid[idx].name());
+        }
+    }
+
+    // 输出格子 emoji 行（便于阅读）
+    for row in 0..3usize {
+        let row_str: Vec<&str> = (0..3)
+            .map(|col| grid[row * 3 + col].emoji())
+            .collect();
+        response = response.add_attribute(
+            format!("row_{}", row + 1),
+            row_str.join(" "),
+        );
+    }
+
+    // 中奖线数
+    let win_count = winning_lines.len() as u32;
+    response = response.add_attribute("winning_lines", win_count.to_string());
+
+    let settlement = if total_multiplier > 0 {
+        // 有中奖（扣除抽水）
+        let gross = bet * total_multiplier;
+        let payout = apply_house_edge(gross, bet, house_edge_bps);
+
+        // 输出每条中奖线
+        let win_desc: Vec<String> = winning_lines
+            .iter()
+            .map(|(line, sym)| format!("{}:{}", line, sym.name()))
+            .collect();
+
+        response = response
+            .add_attribute("result", "win")
+            .add_attribute("win_desc", win_desc.join(","))
+            .add_attribute("total_multiplier", total_multiplier.to_string())
+            .add_attribute("payout", payout.to_string())
+            .add_messages(settle_payout(deps, env, bettor, payout)?);
+        Settlement::new(kind, bet, gross, payout)
+    } else {
+        response = response
+            .add_attribute("result", "lose")
+            .add_attribute("payout", "0");
+        Settlement::lost(kind, bet)
+    };
+
+    Ok((response, settlement))
+}
+
+/// 生成刮刮乐 3×3 = 9 格符号（按行排列）
+fn scratch_grid(seed: &[u8]) -> [ScratchSymbol; 9] {
+    let mut rng = RngStream::new(seed, b"scratch_card");
+    std::array::from_fn(|_| ScratchSymbol::draw(&mut rng))
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// 斗牛（Bull Bull / Niu Niu）
+//
+// 单局游戏：双方各发 5 张牌，比较牌型
+// 特殊牌型：五小牛(8×) > 四炸(7×) > 五花牛(6×) > 牛牛(4×) > 牛9/8(3×) > 牛1-7(2×) > 没牛(2×)
+// ──────────────────────────────────────────────────────────────────────────────
+fn play_bullfight(
+    deps: DepsMut,
+    env: &Env,
+    bettor: &Bettor,
+    bet: u128,
+    seed: &[u8],
+) -> Result<(Response, Settlement), ContractError> {
+    let house_edge_bps = game_house_edge(deps.storage, GameKind::BullFight)?;
+
+    let (player_cards, dealer_cards) = deal_bullfight(seed);
+
+    // 评估双方牌力
+    let player_rank = evaluate_bull_hand(&player_cards);
+    let dealer_rank = evaluate_bull_hand(&dealer_cards);
+
+    let player_type_name = bull_hand_type_name(&player_rank.hand_type);
+    let dealer_type_name = bull_hand_type_name(&dealer_rank.hand_type);
+
+    let mut response = Response::new()
+        .add_attribute("action", "play_bullfight")
+        .add_attribute("player_cards", format_bull_cards(&player_cards))
+        .add_attribute("dealer_cards", format_bull_cards(&dealer_cards))
+        .add_attribute("player_hand_type", player_type_name)
+        .add_attribute("dealer_hand_type", dealer_type_name)
+        .add_attribute("player_score", player_rank.score.to_string())
+        .add_attribute("dealer_score", dealer_rank.score.to_string());
+
+    let settlement = if player_rank.score > dealer_rank.score {
+        // 玩家赢（扣除抽水）
+        let multiplier = bull_payout_multiplier(&player_rank.hand_type);
+        let gross = bet * multiplier;
+        let payout = apply_house_edge(gross, bet, house_edge_bps);
+
+        response = response
+            .add_attribute("result", "win")
+            .add_attribute("payout_multiplier", multiplier.to_string())
+            .add_attribute("payout", payout.to_string())
+            .add_messages(settle_payout(deps, env, bettor, payout)?);
+        Settlement::new(GameKind::BullFight, bet, gross, payout)
+    } else if dealer_rank.score > player_rank.score {
+        // 庄家赢
+        response = response
+            .add_attribute("result", "lose")
+            .add_attribute("payout", "0");
+        Settlement::lost(GameKind::BullFight, bet)
+    } else {
+        // 平局，退还本金
+        response = response
+            .add_attribute("result", "tie")
+            .add_attribute("payout", bet.to_string())
+            .add_messages(settle_payout(deps, env, bettor, bet)?);
+        Settlement::new(GameKind::BullFight, bet, bet, bet)
+    };
+
+    Ok((response, settlement))
+}
+
+/// 斗牛洗牌并发牌：玩家 5 张 + 庄家 5 张
+fn deal_bullfight(seed: &[u8]) -> ([BullCard; 5], [BullCard; 5]) {
+    let deck = shuffled_deck(seed, b"bullfight_deck");
+    let player_cards: [BullCard; 5] = [
+        BullCard::from_id(deck[0]),
+        BullCard::from_id(deck[1]),
+        BullCard::from_id(deck[2]),
+        BullCard::from_id(deck[3]),
+        BullCard::from_id(deck[4]),
+    ];
+    let dealer_cards: [BullCard; 5] = [
+        BullCard::from_id(deck[5]),
+        BullCard::from_id(deck[6]),
+        BullCard::from_id(deck[7]),
+        BullCard::from_id(deck[8]),
+        BullCard::from_id(deck[9]),
+    ];
+    (player_cards, dealer_cards)
+}
+
+/// 格式化斗牛牌面
+fn format_bull_cards(cards: &[BullCard]) -> String {
+    let parts: Vec<String> = cards
+        .iter()
+        .map(|c| {
+            let rank_str = match c.rank {
+                1 => "A".to_string(),
+                11 => "J".to_string(),
+                12 => "Q".to_string(),
+                13 => "K".to_string(),
+                n => n.to_string(),
+            };
+            let suit_str = match c.suit {
+                bullfight::Suit::Spades   => "♠",
+                bullfight::Suit::Hearts   => "♥",
+                bullfight::Suit::Diamonds => "♦",
+                bullfight::Suit::Clubs    => "♣",
+            };
+            format!("{}{}", rank_str, suit_str)
+        })
+        .collect();
+    format!("[{}]", parts.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MockApi,
+        MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        attr, coins, BankMsg, ContractInfoResponse, ContractResult, Decimal, OwnedDeps,
+        SystemError, SystemResult, WasmMsg, WasmQuery,
+    };
+
+    /// 测试用服务端种子（按承诺 ID 区分）
+    fn test_server_seed(id: u64) -> String {
+        format!("test_server_seed_{}", id)
+    }
+
+    /// 初始化消息：同时提交第 1 个服务端种子承诺
+    fn init_msg(house_edge_bps: Option<u16>) -> InstantiateMsg {
+        InstantiateMsg {
+            house_edge_bps,
+            server_seed_hash: Some(hash_server_seed(&test_server_seed(1))),
+            drand: None,
+            eoa_only: None,
+            denoms: None,
+            max_payout_bps: None,
+            timelock: None,
+            game_timeout: None,
+            blackjack_rules: None,
+        }
+    }
+
+    /// 测试合约的链上余额（下注和赔付都会校验合约真实余额）
+    const CONTRACT_BALANCE: u128 = 1_000_000_000_000;
+
+    /// 合约账户持有 CONTRACT_BALANCE uatom 的测试依赖
+    fn funded_deps() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        mock_dependencies_with_balance(&coins(CONTRACT_BALANCE, "uatom"))
+    }
+
+    /// 执行消息；若是下注，则由 creator 在下一个区块揭示种子（或提交 drand 信标）后立即结算，
+    /// 返回结算响应
+    fn play(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        let res = execute(deps.branch(), env.clone(), info, msg)?;
+        let bet_id: u64 = match res.attributes.iter().find(|a| a.key == "bet_id") {
+            Some(a) => a.value.parse().unwrap(),
+            None => return Ok(res),
+        };
+
+        if let Some(a) = res.attributes.iter().find(|a| a.key == "drand_round") {
+            let round: u64 = a.value.parse().unwrap();
+            if !DRAND_BEACONS.has(deps.storage, round) {
+                execute(
+                    deps.branch(),
+                    env.clone(),
+                    mock_info("keeper", &[]),
+                    ExecuteMsg::SubmitBeacon {
+                        round,
+                        signature: mock_beacon_signature(round),
+                    },
+                )?;
+            }
+            return execute(deps, env, mock_info("keeper", &[]), ExecuteMsg::SettleBet { bet_id });
+        }
+
+        // 在下一个区块揭示并结算（与揭示同一区块的下注会被退还）
+        let mut env = env;
+        env.block.height += 1;
+        let commitment_id = ACTIVE_COMMITMENT.load(deps.storage)?;
+        execute(
+            deps.branch(),
+            env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(commitment_id),
+                next_seed_hash: hash_server_seed(&test_server_seed(commitment_id + 1)),
+            },
+        )?;
+        execute(deps, env, mock_info("keeper", &[]), ExecuteMsg::SettleBet { bet_id })
+    }
+
+    #[test]
+    pub fn test_init() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!("init", res.attributes[0].value);
+        assert_eq!("creator", res.attributes[1].value);
+        assert_eq!("10000000000", res.attributes[2].value)
+    }
+
+    #[test]
+    pub fn test_query() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLockedAmount { denom: None }).unwrap();
+        let value: LockedAmountResponse = from_json(&res).unwrap();
+        assert_eq!(
+            value,
+            LockedAmountResponse {
+                denom: "uatom".to_string(),
+                locked_amount: Uint128::from(10000000000u128)
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_play_war() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(100_000, "uatom")),
+            ExecuteMsg::PlayWar { client_seed: None },
+        )
+        .unwrap();
+
+        let attrs = res.attributes;
+        let result = attrs
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        if "win" == result.value {
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: "user".to_string(),
+                    amount: coins(200_000, "uatom")
+                }
+                .into()
+            );
+        } else if "tie" == result.value {
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: "user".to_string(),
+                    amount: coins(100_000, "uatom")
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    pub fn test_play_slot_basic() {
+        let mut deps = funded_deps();
+
+        // 初始化合约
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Basic 模式下注
+        let user_info = mock_info("user", &coins(100_000, "uatom"));
+        let env = mock_env();
+        let res = play(
+            deps.as_mut(),
+            env.clone(),
+            user_info.clone(),
+            ExecuteMsg::PlaySlot { mode: SlotMode::Basic, client_seed: None },
+        )
+        .unwrap();
+
+        let attrs = &res.attributes;
+
+        // 检查 mode 属性
+        let mode_attr = attrs.iter().find(|a| a.key == "mode").expect("mode missing");
+        assert_eq!(mode_attr.value, "Basic");
+
+        // 检查下注金额
+        let bet_attr = attrs.iter().find(|a| a.key == "bet_amount").expect("bet_amount missing");
+        assert_eq!(bet_attr.value, "100000");
+
+        // 检查三个轮盘符号存在
+        assert!(attrs.iter().any(|a| a.key == "reel1"), "reel1 missing");
+        assert!(attrs.iter().any(|a| a.key == "reel2"), "reel2 missing");
+        assert!(attrs.iter().any(|a| a.key == "reel3"), "reel3 missing");
+
+        // 检查 win_desc
+        assert!(attrs.iter().any(|a| a.key == "win_desc"), "win_desc missing");
+
+        // 应该有 result 或 payout_multiplier
+        let has_result = attrs.iter().any(|a| a.key == "result");
+        let has_payout = attrs.iter().any(|a| a.key == "payout_multiplier");
+        assert!(has_result || has_payout, "expected result or payout_multiplier");
+
+        // 投注金额下限检查
+        let too_small = play(
+            deps.as_mut(), env.clone(),
+            mock_info("user", &coins(50_000, "uatom")),
+            ExecuteMsg::PlaySlot { mode: SlotMode::Basic, client_seed: None },
+        );
+        assert!(too_small.is_err(), "should reject bet below minimum");
+    }
+
+    #[test]
+    pub fn test_play_slot_advanced() {
+        let mut deps = funded_deps();
+
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Advanced 模式下注
+        let user_info = mock_info("user", &coins(500_000, "uatom"));
+        let env = mock_env();
+        let res = play(
+            deps.as_mut(),
+            env.clone(),
+            user_info.clone(),
+            ExecuteMsg::PlaySlot { mode: SlotMode::Advanced, client_seed: None },
+        )
+        .unwrap();
+
+        let attrs = &res.attributes;
+
+        // 检查 mode 属性
+        let mode_attr = attrs.iter().find(|a| a.key == "mode").expect("mode missing");
+        assert_eq!(mode_attr.value, "Advanced");
+
+        // 检查 5 列 × 3 行 = 15 个格子都有输出
+        for col in 1..=5usize {
+            for row in 1..=3usize {
+                let key = format!("reel{}_{}", col, row);
+                assert!(
+                    attrs.iter().any(|a| a.key == key),
+                    "missing grid cell {}",
+                    key
+                );
+            }
+        }
+
+        // 检查 win_desc
+        assert!(attrs.iter().any(|a| a.key == "win_desc"), "win_desc missing");
+
+        // 应有 result 或 payout_multiplier
+        let has_result = attrs.iter().any(|a| a.key == "result");
+        let has_payout = attrs.iter().any(|a| a.key == "payout_multiplier");
+        assert!(has_result || has_payout, "expected result or payout_multiplier");
+
+        // Advanced 模式最低下注 200_000，低于时应报错
+        let too_small = play(
+            deps.as_mut(), env.clone(),
+            mock_info("user", &coins(100_000, "uatom")),
+            ExecuteMsg::PlaySlot { mode: SlotMode::Advanced, client_seed: None },
+        );
+        assert!(too_small.is_err(), "should reject bet below Advanced minimum");
+    }
+
+    #[test]
+    pub fn test_play_slot_win_payout() {
+        // 测试赢时支付金额正确（通过 execute 入口）
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let user = "player1";
+        let bet = 1_000_000u128;
+        let user_info = mock_info(user, &coins(bet, "uatom"));
+        let env = mock_env();
+
+        let res = play(
+            deps.as_mut(), env, user_info,
+            ExecuteMsg::PlaySlot { mode: SlotMode::Basic, client_seed: None },
+        ).unwrap();
+
+        let has_result = res.attributes.iter().any(|a| a.key == "result");
+        let has_payout = res.attributes.iter().any(|a| a.key == "payout_multiplier");
+        assert!(has_result || has_payout);
+
+        if has_payout {
+            // 如果赢了，支付消息必须存在
+            assert_eq!(res.messages.len(), 1);
+            let payout_mult: u128 = res
+                .attributes
+                .iter()
+                .find(|a| a.key == "payout_multiplier")
+                .unwrap()
+                .value
+                .parse()
+                .unwrap();
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(bet * payout_mult, "uatom"),
+                }
+                .into()
+            );
+        } else {
+            // 如果输了，没有支付消息
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    pub fn test_play_guess_number() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(100_000, "uatom")),
+            ExecuteMsg::GuessNumber { guess: 9, client_seed: None },
+        )
+        .unwrap();
+
+        let result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        let user_guess = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "user_guess")
+            .expect("user_guess missing");
+
+        let contract_guess = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "correct_number")
+            .expect("contract_guess missing");
+
+        // 如果用户猜的数字和 contract 生成的随机数字相同,则用户获取 10 倍奖励.
+        if "exact" == result.value {
+            assert_eq!(user_guess.value, contract_guess.value);
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: "user".to_string(),
+                    amount: coins(100_000 * 10, "uatom")
+                }
+                .into()
+            )
+        } else if "adjacent" == result.value {
+            // 如果用户猜的数字和 contract 生成的随机数字相邻, 则用户获取 1 倍奖励
+            let user_guess_num = user_guess.value.parse::<i32>().unwrap();
+            let contract_guess_num = contract_guess.value.parse::<i32>().unwrap();
+            assert_eq!((user_guess_num - contract_guess_num).abs(), 1);
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: "user".to_string(),
+                    amount: coins(100_000, "uatom")
+                }
+                .into()
+            )
+        } else {
+            // 用户猜的数字与合约的数字既不相同也不相邻则不获取任何奖励.
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    pub fn test_withdraw_funds() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        let info_clone = info.clone();
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let withdraw = ExecuteMsg::Withdraw {
+            amount: 100_000,
+            denom: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info_clone, withdraw).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(100_000, "uatom")
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_blackjack_full_flow() {
+        let mut deps = funded_deps();
+
+        // 初始化合约
+        let instantiate_msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let player = "player1";
+
+        // ----------------------------
+        // Step 1: Start 游戏
+        // ----------------------------
+        let start_msg = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: None,
+            side_bets: None,
+        };
+        let info = mock_info(player, &coins(500_000, "uatom")); // 有效下注金额
+        let res = play(deps.as_mut(), mock_env(), info.clone(), start_msg).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "play_blackjack_start"));
+
+        let mut query_msg = QueryMsg::GetBlackjackState {
+            address: player.to_string(),
+        };
+
+        let mut bin = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let mut resp: BlackjackStateResponse = from_json(&bin).unwrap();
+
+        let hand = &resp.hands[0];
+        assert_eq!(resp.hand_totals, vec![hand_total(&hand.cards)]);
+        assert_eq!(attr_value(&res, "user_card1"), format_blackjack_card(&hand.cards[0]));
+        assert_eq!(attr_value(&res, "user_card2"), format_blackjack_card(&hand.cards[1]));
+
+        // ----------------------------
+        // Step 2: 当用户的牌小于 17 点, Hit 要一张牌
+        // ----------------------------
+
+        if resp.hand_totals[0] < 17 {
+            let hit_msg = ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Hit,
+                client_seed: None,
+                side_bets: None,
+            };
+
+            let res = play(deps.as_mut(), mock_env(), mock_info(player, &[]), hit_msg).unwrap();
+
+            assert_eq!(res.attributes[0], attr("action", "blackjack_hit"));
+        }
+
+        // ----------------------------
+        // Step 3: Stand 停牌
+        // ----------------------------
+        let stand_msg = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        // 停牌不需要追加下注，附带的资金会被拒绝而不是留在合约中
+        let err = play(deps.as_mut(), mock_env(), info.clone(), stand_msg.clone()).unwrap_err();
+        let denom = "uatom".to_string();
+        assert_eq!(err, ContractError::FundsMismatch { action: "blackjack_stand", denom });
+        let res = play(deps.as_mut(), mock_env(), mock_info(player, &[]), stand_msg).unwrap();
+        assert_eq!(res.attributes[0], attr("action", "blackjack_stand"));
+
+        query_msg = QueryMsg::GetBlackjackState {
+            address: player.to_string(),
+        };
+        bin = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        resp = from_json(&bin).unwrap();
+
+        assert!(resp.finished);
+        assert!(!resp.hands[0].cards.is_empty());
+        assert!(!resp.dealer_cards.is_empty());
+    }
+
+    #[test]
+    fn test_blackjack_query_state() {
+        let mut deps = funded_deps();
+
+        // 初始化合约
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+
+        // 模拟用户参与 Blackjack 游戏
+        let user = "player1";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+
+        let start_msg = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: None,
+            side_bets: None,
+        };
+
+        let _res = play(deps.as_mut(), mock_env(), user_info.clone(), start_msg).unwrap();
+
+        // 查询 Blackjack 状态
+        let query_msg = QueryMsg::GetBlackjackState {
+            address: user.to_string(),
+        };
+
+        let bin = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let resp: BlackjackStateResponse = from_json(&bin).unwrap();
+
+        // 验证结构内容
+        assert_eq!(resp.hands.len(), 1);
+        assert_eq!(resp.hands[0].cards.len(), 2);
+        assert_eq!(resp.dealer_cards.len(), 1); // 未完成游戏,庄家暗牌被隐藏
+        assert_eq!(resp.bet, Uint128::new(1_000_000));
+        assert!(!resp.finished);
+    }
+
+    #[test]
+    fn test_play_coin_flip() {
+        let mut deps = funded_deps();
+
+        // 初始化合约
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+
+        // 模拟用户参与 coin_flip 游戏
+        let user = "player1";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+
+        let coin_flip = ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: None,
+        };
+
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), coin_flip).unwrap();
+
+        let result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        if "win" == result.value {
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(2_000_000, "uatom")
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_play_dice_guess_size() {
+        let mut deps = funded_deps();
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+        let user = "player1";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let dice_guess = ExecuteMsg::PlayDice {
+            mode: DiceGameMode::GuessSize {
+                guess_big: DiceGuessSize::Small,
+            },
+            client_seed: None,
+        };
+
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), dice_guess).unwrap();
+        let result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        let player_guess = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "player_guess")
+            .expect("player_guess missing");
+
+        let actual_result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "actual_result")
+            .expect("actual_result missing");
+
+        if result.value == "win" {
+            assert_eq!(player_guess.value, actual_result.value);
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(2_000_000, "uatom")
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_play_dice_guess_number() {
+        let mut deps = funded_deps();
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+        let user = "player1";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let dice_guess = ExecuteMsg::PlayDice {
+            mode: DiceGameMode::ExactNumber { guess_number: 6 },
+            client_seed: None,
+        };
+
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), dice_guess).unwrap();
+
+        let result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        let player_guess = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "player_guess")
+            .expect("player_guess missing");
+
+        let actual_result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "actual_result")
+            .expect("actual_result missing");
+
+        if result.value == "win" {
+            assert_eq!(player_guess.value, actual_result.value);
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(6_000_000, "uatom")
+                }
+                .into()
+            )
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_play_dice_range_bet() {
+        let mut deps = funded_deps();
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(20_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+        let user = "player1";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let dice_guess = ExecuteMsg::PlayDice {
+            mode: DiceGameMode::RangeBet { start: 1, end: 3 },
+            client_seed: None,
+        };
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), dice_guess).unwrap();
+        print!("{:?}", res);
+
+        let result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        let actual_result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "actual_result")
+            .expect("actual_result missing");
+
+        let player_start = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "player_start")
+            .expect("player_start missing");
+
+        let player_end = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "player_end")
+            .expect("player_end missing");
+
+        if result.value == "win" {
+            let actual_result_int = actual_result.value.parse::<u32>().unwrap();
+            assert!(
+                actual_result_int >= player_start.value.parse::<u32>().unwrap()
+            );
+            assert!(
+                actual_result_int <= player_end.value.parse::<u32>().unwrap()
+            );
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(2_000_000, "uatom")
+                }
+                .into()
+            )
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_play_baccarat() {
+        let mut deps = funded_deps();
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+        let user = "player1";
+
+        // 测试玩家获胜情况
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let baccarat_game = ExecuteMsg::PlayBaccarat {
+            bet_choice: BaccaratBet::Player,
+            client_seed: None,
+        };
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), baccarat_game).unwrap();
+
+        println!("{:?}", res);
+
+        let result = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        let winner = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "winner")
+            .expect("winner missing");
+
+        if result.value == "win" {
+            // 如果玩家获胜，检查是否正确赔付 (1:1 奖金 + 1:1 本金 = 2倍)
+            if winner.value == "Player" {
+                assert_eq!(
+                    res.messages[0].msg,
+                    BankMsg::Send {
+                        to_address: user.to_string(),
+                        amount: coins(2_000_000, "uatom")
+                    }
+                    .into()
+                );
+            } else if winner.value == "Banker" {
+                // 庄家获胜但是用户押注错了
+                assert_eq!(res.messages.len(), 0);
+            } else {
+                // 平局，赔率更高 (8:1 奖金 + 1:1 本金 = 9倍)
+                assert_eq!(
+                    res.messages[0].msg,
+                    BankMsg::Send {
+                        to_address: user.to_string(),
+                        amount: coins(9_000_000, "uatom")
+                    }
+                    .into()
+                );
+            }
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_play_roulette() {
+        let mut deps = funded_deps();
+        let instantiate_msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
+        let user = "player1";
+
+        // 测试单数字投注
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let roulette_game = ExecuteMsg::PlayRoulette {
+            bet_type: RouletteBetType::SingleNumber { number: 17 },
+            client_seed: None,
+        };
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), roulette_game).unwrap();
+
+        println!("{:?}", res);
+
+        let result_single_number = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        if result_single_number.value == "win" {
+            // 如果玩家获胜，检查是否正确赔付 (35:1 奖金 + 1:1 本金 = 36倍)
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(36_000_000, "uatom")
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+
+        // 测试红色投注
+        let user_info = mock_info(user, &coins(2_000_000, "uatom"));
+        let roulette_game = ExecuteMsg::PlayRoulette {
+            bet_type: RouletteBetType::Color { color: Color::Red },
+            client_seed: None,
+        };
+        let res = play(deps.as_mut(), mock_env(), user_info.clone(), roulette_game).unwrap();
+
+        println!("{:?}", res);
+
+        let result_color = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "result")
+            .expect("result missing");
+
+        if result_color.value == "win" {
+            // 如果玩家获胜，检查是否正确赔付 (1:1 奖金 + 1:1 本金 = 2倍)
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(4_000_000, "uatom")
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    // ──────────────────────────────────────────────────────────────────────
+    // Mega Slot 测试
+    // ──────────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_play_slot_mega() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Mega 模式最低投注 500_000
+        let user_info = mock_info("user", &coins(500_000, "uatom"));
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySlot { mode: SlotMode::Mega, client_seed: None },
+        )
+        .unwrap();
+        let attrs = &res.attributes;
+
+        // mode 属性
+        let mode_attr = attrs.iter().find(|a| a.key == "mode").expect("mode missing");
+        assert_eq!(mode_attr.value, "Mega");
+
+        // 6 列 × 4 行 = 24 格
+        for col in 1..=6usize {
+            for row in 1..=4usize {
+                let key = format!("reel{}_{}", col, row);
+                assert!(attrs.iter().any(|a| a.key == key), "missing {}", key);
+            }
+        }
+
+        // 必须包含 free_spin 和 jackpot 属性
+        assert!(attrs.iter().any(|a| a.key == "free_spin"), "free_spin missing");
+        assert!(attrs.iter().any(|a| a.key == "jackpot"), "jackpot missing");
+        assert!(attrs.iter().any(|a| a.key == "win_desc"), "win_desc missing");
+
+        // 低于最低投注应报错
+        let too_small = play(
+            deps.as_mut(), mock_env(),
+            mock_info("user", &coins(100_000, "uatom")),
+            ExecuteMsg::PlaySlot { mode: SlotMode::Mega, client_seed: None },
+        );
+        assert!(too_small.is_err(), "should reject bet below Mega minimum");
+    }
+
+    #[test]
+    fn test_play_slot_mega_payout() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        // Mega 最坏情况 18,000×：1,000,000 的下注需要预留 18,000,000,000
+        let info = mock_info("creator", &coins(20_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let user = "player1";
+        let bet = 1_000_000u128;
+        let res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(user, &coins(bet, "uatom")),
+            ExecuteMsg::PlaySlot { mode: SlotMode::Mega, client_seed: None },
+        ).unwrap();
+
+        let has_result = res.attributes.iter().any(|a| a.key == "result");
+        let has_payout = res.attributes.iter().any(|a| a.key == "payout_multiplier");
+        assert!(has_result || has_payout, "expected result or payout_multiplier");
+
+        if has_payout {
+            assert_eq!(res.messages.len(), 1);
+            let payout_mult: u128 = res
+                .attributes
+                .iter()
+                .find(|a| a.key == "payout_multiplier")
+                .unwrap()
+                .value
+                .parse()
+                .unwrap();
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(bet * payout_mult, "uatom"),
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    // ──────────────────────────────────────────────────────────────────────
+    // 奥马哈扑克测试
+    // ──────────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_omaha_full_flow() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let player = "omaha_player";
+        let bet = 500_000u128;
+
+        // ── Step 1: Start ──────────────────────────────────────
+        let start_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(bet, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None },
+        ).unwrap();
+
+        let stage = start_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(stage.value, "PreFlop");
+
+        let player_hand_attr = start_res.attributes.iter().find(|a| a.key == "player_hand").unwrap();
+        assert!(!player_hand_attr.value.is_empty(), "player_hand should not be empty");
+
+        // ── Step 2: Query state ────────────────────────────────
+        let bin = query(
+            deps.as_ref(), mock_env(),
+            QueryMsg::GetOmahaState { address: player.to_string() },
+        ).unwrap();
+        let state_resp: OmahaStateResponse = from_json(&bin).unwrap();
+        assert_eq!(state_resp.player_hand.len(), 4, "player should have 4 cards");
+        assert_eq!(state_resp.dealer_hand.len(), 0, "dealer hand hidden before showdown");
+        assert_eq!(state_resp.community_cards.len(), 0, "no community cards at PreFlop");
+        assert!(!state_resp.finished);
+
+        // ── Step 3: Raise (PreFlop → Flop) ───────────────────
+        let raise_amount = 200_000u128;
+        let raise_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(raise_amount, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: raise_amount }, client_seed: None },
+        ).unwrap();
+
+        let raise_stage = raise_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(raise_stage.value, "Flop");
+
+        let community_attr = raise_res.attributes.iter().find(|a| a.key == "community_cards").unwrap();
+        assert!(community_attr.value.contains(","), "flop should have 3 cards");
+
+        // 没有需要补齐的差额时跟注不能附带资金，追加下注只能通过加注
+        let err = execute(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Call, client_seed: None },
+        ).unwrap_err();
+        let denom = "uatom".to_string();
+        assert_eq!(err, ContractError::FundsMismatch { action: "call", denom });
+
+        // ── Step 4: Call (Flop → Turn) ────────────────────────
+        let call_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Call, client_seed: None },
+        ).unwrap();
+        let call_stage = call_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(call_stage.value, "Turn");
+
+        // ── Step 5: Showdown ──────────────────────────────────
+        let showdown_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Showdown, client_seed: None },
+        ).unwrap();
+
+        let result = showdown_res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        assert!(
+            result.value == "player_win" || result.value == "dealer_win" || result.value == "tie",
+            "unexpected result: {}",
+            result.value
+        );
+
+        // showdown 后庄家手牌应可见
+        let bin2 = query(
+            deps.as_ref(), mock_env(),
+            QueryMsg::GetOmahaState { address: player.to_string() },
+        ).unwrap();
+        let final_state: OmahaStateResponse = from_json(&bin2).unwrap();
+        assert!(final_state.finished);
+        assert_eq!(final_state.dealer_hand.len(), 4, "dealer hand visible after showdown");
+        assert_eq!(final_state.community_cards.len(), 5, "all 5 community cards after showdown");
     }
 
     #[test]
-    pub fn test_query() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetLockedAmount {}).unwrap();
-        let value: LockedAmountResponse = from_json(&res).unwrap();
-        assert_eq!(
-            value,
-            LockedAmountResponse {
-                locked_amount: Uint128::from(10000000000u128)
-            }
+    fn test_omaha_fold() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let player = "omaha_fold_player";
+        let bet = 300_000u128;
+
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(bet, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None },
+        ).unwrap();
+
+        // 弃牌不需要追加下注，附带资金会被拒绝
+        let err = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(bet, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Fold, client_seed: None },
+        ).unwrap_err();
+        let denom = "uatom".to_string();
+        assert_eq!(err, ContractError::FundsMismatch { action: "fold", denom });
+
+        // 弃牌
+        let fold_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Fold, client_seed: None },
+        ).unwrap();
+
+        let result = fold_res.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert_eq!(result.value, "folded");
+
+        // 弃牌后游戏结束，不能再操作
+        let err = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Showdown, client_seed: None },
+        );
+        assert!(err.is_err(), "should not be able to showdown after fold");
+    }
+
+    #[test]
+    fn test_omaha_no_duplicate_game() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let player = "omaha_dup_player";
+
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(100_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None },
+        ).unwrap();
+
+        // 重复开始应报错
+        let err = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(100_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None },
+        );
+        assert!(err.is_err(), "should not allow duplicate active game");
+    }
+
+    #[test]
+    fn test_omaha_raise_then_showdown() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let player = "omaha_raise_player";
+
+        // Start
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(500_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None },
+        ).unwrap();
+
+        // Raise 1: PreFlop → Flop
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(100_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: 100_000 }, client_seed: None },
+        ).unwrap();
+
+        // Raise 2: Flop → Turn
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(200_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: 200_000 }, client_seed: None },
+        ).unwrap();
+
+        // Raise 3: Turn → River
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(300_000, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: 300_000 }, client_seed: None },
+        ).unwrap();
+
+        // Showdown
+        let sd = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayOmaha { action: OmahaAction::Showdown, client_seed: None },
+        ).unwrap();
+
+        let result = sd.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert!(
+            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
+            "unexpected result: {}",
+            result.value
+        );
+
+        // 验证 player_rank_name 和 dealer_rank_name 存在
+        assert!(sd.attributes.iter().any(|a| a.key == "player_rank_name"));
+        assert!(sd.attributes.iter().any(|a| a.key == "dealer_rank_name"));
+    }
+
+    // ──────────────────────────────────────────────────────────────────────
+    // 德州扑克（Texas Hold'em）测试
+    // ──────────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_texas_full_flow() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let player = "texas_player";
+        let bet = 500_000u128;
+
+        // ── Step 1: Start ──────────────────────────────────────
+        let start_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(bet, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
+        ).unwrap();
+
+        let stage = start_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(stage.value, "PreFlop");
+
+        let player_hand_attr = start_res.attributes.iter().find(|a| a.key == "player_hand").unwrap();
+        assert!(!player_hand_attr.value.is_empty(), "player_hand should not be empty");
+
+        // ── Step 2: Query state ────────────────────────────────
+        let bin = query(
+            deps.as_ref(), mock_env(),
+            QueryMsg::GetTexasState { address: player.to_string() },
+        ).unwrap();
+        let state_resp: TexasStateResponse = from_json(&bin).unwrap();
+        assert_eq!(state_resp.player_hand.len(), 2, "player should have 2 cards");
+        assert_eq!(state_resp.dealer_hand.len(), 0, "dealer hand hidden before showdown");
+        assert_eq!(state_resp.community_cards.len(), 0, "no community cards at PreFlop");
+        assert!(!state_resp.finished);
+        assert!(!state_resp.all_in);
+
+        // ── Step 3: Raise (PreFlop → Flop) ───────────────────
+        let raise_amount = 200_000u128;
+        let raise_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(raise_amount, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: raise_amount }, client_seed: None },
+        ).unwrap();
+
+        let raise_stage = raise_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(raise_stage.value, "Flop");
+
+        let community_attr = raise_res.attributes.iter().find(|a| a.key == "community_cards").unwrap();
+        assert!(community_attr.value.contains(","), "flop should have 3 cards");
+
+        // 没有需要补齐的差额时跟注不能附带资金，追加下注只能通过加注
+        let err = execute(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Call, client_seed: None },
+        ).unwrap_err();
+        let denom = "uatom".to_string();
+        assert_eq!(err, ContractError::FundsMismatch { action: "call", denom });
+
+        // ── Step 4: Call (Flop → Turn) ────────────────────────
+        let call_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Call, client_seed: None },
+        ).unwrap();
+        let call_stage = call_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(call_stage.value, "Turn");
+
+        // ── Step 5: Showdown ──────────────────────────────────
+        let showdown_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Showdown, client_seed: None },
+        ).unwrap();
+
+        let result = showdown_res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        assert!(
+            result.value == "player_win" || result.value == "dealer_win" || result.value == "tie",
+            "unexpected result: {}",
+            result.value
+        );
+
+        // Showdown 后庄家手牌应可见
+        let bin2 = query(
+            deps.as_ref(), mock_env(),
+            QueryMsg::GetTexasState { address: player.to_string() },
+        ).unwrap();
+        let final_state: TexasStateResponse = from_json(&bin2).unwrap();
+        assert!(final_state.finished);
+        assert_eq!(final_state.dealer_hand.len(), 2, "dealer hand visible after showdown");
+        assert_eq!(final_state.community_cards.len(), 5, "all 5 community cards after showdown");
+    }
+
+    #[test]
+    fn test_texas_fold() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let player = "texas_fold_player";
+        let bet = 300_000u128;
+
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(bet, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
+        ).unwrap();
+
+        // 弃牌
+        let fold_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Fold, client_seed: None },
+        ).unwrap();
+
+        let result = fold_res.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert_eq!(result.value, "folded");
+
+        // 弃牌后不能再操作
+        let err = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Showdown, client_seed: None },
         );
+        assert!(err.is_err(), "should not be able to showdown after fold");
     }
 
     #[test]
-    pub fn test_play_war() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        let res = play_war(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("user", &coins(100_000, "uatom")),
-        )
-        .unwrap();
+    fn test_texas_no_duplicate_game() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let attrs = res.attributes;
-        let result = attrs
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        let player = "texas_dup_player";
 
-        if "win" == result.value {
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: "user".to_string(),
-                    amount: coins(200_000, "uatom")
-                }
-                .into()
-            );
-        } else if "tie" == result.value {
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: "user".to_string(),
-                    amount: coins(100_000, "uatom")
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
-        }
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(100_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
+        ).unwrap();
+
+        // 重复开始应报错
+        let err = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(100_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
+        );
+        assert!(err.is_err(), "should not allow duplicate active game");
     }
 
     #[test]
-    pub fn test_play_slot_basic() {
-        let mut deps = mock_dependencies();
-
-        // 初始化合约
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-        // Basic 模式下注
-        let user_info = mock_info("user", &coins(100_000, "uatom"));
-        let env = mock_env();
-        let res = play_slot(deps.as_mut(), env.clone(), user_info.clone(), SlotMode::Basic).unwrap();
+    fn test_texas_check_advance() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let attrs = &res.attributes;
+        let player = "texas_check_player";
 
-        // 检查 mode 属性
-        let mode_attr = attrs.iter().find(|a| a.key == "mode").expect("mode missing");
-        assert_eq!(mode_attr.value, "Basic");
+        // Start
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(500_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
+        ).unwrap();
 
-        // 检查下注金额
-        let bet_attr = attrs.iter().find(|a| a.key == "bet_amount").expect("bet_amount missing");
-        assert_eq!(bet_attr.value, "100000");
+        // 过牌不需要追加下注，附带资金会被拒绝
+        let err = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(500_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Check, client_seed: None },
+        ).unwrap_err();
+        let denom = "uatom".to_string();
+        assert_eq!(err, ContractError::FundsMismatch { action: "check", denom });
 
-        // 检查三个轮盘符号存在
-        assert!(attrs.iter().any(|a| a.key == "reel1"), "reel1 missing");
-        assert!(attrs.iter().any(|a| a.key == "reel2"), "reel2 missing");
-        assert!(attrs.iter().any(|a| a.key == "reel3"), "reel3 missing");
+        // Check (PreFlop → Flop) — 差额为 0，可以过牌
+        let check_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Check, client_seed: None },
+        ).unwrap();
+        let check_stage = check_res.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(check_stage.value, "Flop");
 
-        // 检查 win_desc
-        assert!(attrs.iter().any(|a| a.key == "win_desc"), "win_desc missing");
+        // Check (Flop → Turn)
+        let check2 = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Check, client_seed: None },
+        ).unwrap();
+        let check2_stage = check2.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(check2_stage.value, "Turn");
 
-        // 应该有 result 或 payout_multiplier
-        let has_result = attrs.iter().any(|a| a.key == "result");
-        let has_payout = attrs.iter().any(|a| a.key == "payout_multiplier");
-        assert!(has_result || has_payout, "expected result or payout_multiplier");
+        // Check (Turn → River)
+        let check3 = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Check, client_seed: None },
+        ).unwrap();
+        let check3_stage = check3.attributes.iter().find(|a| a.key == "stage").unwrap();
+        assert_eq!(check3_stage.value, "River");
 
-        // 投注金额下限检查
-        let too_small = play_slot(
-            deps.as_mut(), env.clone(),
-            mock_info("user", &coins(50_000, "uatom")),
-            SlotMode::Basic,
+        // Showdown
+        let sd = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Showdown, client_seed: None },
+        ).unwrap();
+        let result = sd.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert!(
+            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
+            "unexpected result: {}",
+            result.value
         );
-        assert!(too_small.is_err(), "should reject bet below minimum");
     }
 
     #[test]
-    pub fn test_play_slot_advanced() {
-        let mut deps = mock_dependencies();
-
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-        // Advanced 模式下注
-        let user_info = mock_info("user", &coins(500_000, "uatom"));
-        let env = mock_env();
-        let res = play_slot(deps.as_mut(), env.clone(), user_info.clone(), SlotMode::Advanced).unwrap();
+    fn test_texas_all_in() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let attrs = &res.attributes;
+        let player = "texas_allin_player";
 
-        // 检查 mode 属性
-        let mode_attr = attrs.iter().find(|a| a.key == "mode").expect("mode missing");
-        assert_eq!(mode_attr.value, "Advanced");
+        // Start
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(500_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
+        ).unwrap();
 
-        // 检查 5 列 × 3 行 = 15 个格子都有输出
-        for col in 1..=5usize {
-            for row in 1..=3usize {
-                let key = format!("reel{}_{}", col, row);
-                assert!(
-                    attrs.iter().any(|a| a.key == key),
-                    "missing grid cell {}",
-                    key
-                );
-            }
-        }
+        // All-In 直接进入 Showdown
+        let allin_res = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(2_000_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::AllIn { amount: 2_000_000 }, client_seed: None },
+        ).unwrap();
 
-        // 检查 win_desc
-        assert!(attrs.iter().any(|a| a.key == "win_desc"), "win_desc missing");
+        let result = allin_res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        assert!(
+            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
+            "unexpected result: {}",
+            result.value
+        );
 
-        // 应有 result 或 payout_multiplier
-        let has_result = attrs.iter().any(|a| a.key == "result");
-        let has_payout = attrs.iter().any(|a| a.key == "payout_multiplier");
-        assert!(has_result || has_payout, "expected result or payout_multiplier");
+        // 验证 rank_name 属性存在
+        assert!(allin_res.attributes.iter().any(|a| a.key == "player_rank_name"));
+        assert!(allin_res.attributes.iter().any(|a| a.key == "dealer_rank_name"));
 
-        // Advanced 模式最低下注 200_000，低于时应报错
-        let too_small = play_slot(
-            deps.as_mut(), env.clone(),
-            mock_info("user", &coins(100_000, "uatom")),
-            SlotMode::Advanced,
-        );
-        assert!(too_small.is_err(), "should reject bet below Advanced minimum");
+        // 验证游戏已结束
+        let bin = query(
+            deps.as_ref(), mock_env(),
+            QueryMsg::GetTexasState { address: player.to_string() },
+        ).unwrap();
+        let final_state: TexasStateResponse = from_json(&bin).unwrap();
+        assert!(final_state.finished);
+        assert!(final_state.all_in);
+        assert_eq!(final_state.community_cards.len(), 5);
+        assert_eq!(final_state.dealer_hand.len(), 2);
     }
 
     #[test]
-    pub fn test_play_slot_win_payout() {
-        // 测试赢时支付金额正确（通过 execute 入口）
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    fn test_texas_raise_then_showdown() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let user = "player1";
-        let bet = 1_000_000u128;
-        let user_info = mock_info(user, &coins(bet, "uatom"));
-        let env = mock_env();
+        let player = "texas_raise_player";
 
-        let res = execute(
-            deps.as_mut(), env, user_info,
-            ExecuteMsg::PlaySlot { mode: SlotMode::Basic },
+        // Start
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(500_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Start, client_seed: None },
         ).unwrap();
 
-        let has_result = res.attributes.iter().any(|a| a.key == "result");
-        let has_payout = res.attributes.iter().any(|a| a.key == "payout_multiplier");
-        assert!(has_result || has_payout);
+        // Raise 1: PreFlop → Flop
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(100_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: 100_000 }, client_seed: None },
+        ).unwrap();
 
-        if has_payout {
-            // 如果赢了，支付消息必须存在
-            assert_eq!(res.messages.len(), 1);
-            let payout_mult: u128 = res
-                .attributes
-                .iter()
-                .find(|a| a.key == "payout_multiplier")
-                .unwrap()
-                .value
-                .parse()
-                .unwrap();
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(bet * payout_mult, "uatom"),
-                }
-                .into()
-            );
-        } else {
-            // 如果输了，没有支付消息
-            assert_eq!(res.messages.len(), 0);
-        }
+        // Raise 2: Flop → Turn
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(200_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: 200_000 }, client_seed: None },
+        ).unwrap();
+
+        // Raise 3: Turn → River
+        play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(300_000, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: 300_000 }, client_seed: None },
+        ).unwrap();
+
+        // Showdown
+        let sd = play(
+            deps.as_mut(), mock_env(),
+            mock_info(player, &coins(0, "uatom")),
+            ExecuteMsg::PlayTexas { action: TexasAction::Showdown, client_seed: None },
+        ).unwrap();
+
+        let result = sd.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert!(
+            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
+            "unexpected result: {}",
+            result.value
+        );
+
+        assert!(sd.attributes.iter().any(|a| a.key == "player_rank_name"));
+        assert!(sd.attributes.iter().any(|a| a.key == "dealer_rank_name"));
     }
 
+    // ──────────────────────────────────────────────────────────────────────
+    // 三公（San Gong）测试
+    // ──────────────────────────────────────────────────────────────────────
+
     #[test]
-    pub fn test_play_guess_number() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    fn test_play_sangong() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let res = play_guess_number(
+        let user = "sangong_player";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(100_000, "uatom")),
-            9,
+            user_info.clone(),
+            ExecuteMsg::PlaySanGong { client_seed: None },
         )
         .unwrap();
 
-        let result = res
+        // 检查基本属性
+        let action = res
             .attributes
             .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+            .find(|a| a.key == "action")
+            .expect("action missing");
+        assert_eq!(action.value, "play_sangong");
 
-        let user_guess = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "user_guess")
-            .expect("user_guess missing");
+        assert!(
+            res.attributes.iter().any(|a| a.key == "player_cards"),
+            "player_cards missing"
+        );
+        assert!(
+            res.attributes.iter().any(|a| a.key == "dealer_cards"),
+            "dealer_cards missing"
+        );
+        assert!(
+            res.attributes.iter().any(|a| a.key == "player_hand_type"),
+            "player_hand_type missing"
+        );
+        assert!(
+            res.attributes.iter().any(|a| a.key == "dealer_hand_type"),
+            "dealer_hand_type missing"
+        );
+        assert!(
+            res.attributes.iter().any(|a| a.key == "player_points"),
+            "player_points missing"
+        );
+        assert!(
+            res.attributes.iter().any(|a| a.key == "dealer_points"),
+            "dealer_points missing"
+        );
 
-        let contract_guess = res
+        let result = res
             .attributes
             .iter()
-            .find(|a| a.key == "correct_number")
-            .expect("contract_guess missing");
+            .find(|a| a.key == "result")
+            .expect("result missing");
 
-        // 如果用户猜的数字和 contract 生成的随机数字相同,则用户获取 10 倍奖励.
-        if "exact" == result.value {
-            assert_eq!(user_guess.value, contract_guess.value);
+        if result.value == "win" {
+            assert_eq!(res.messages.len(), 1, "should have payout message on win");
+            // 检查 payout_multiplier 属性存在
+            let mult = res
+                .attributes
+                .iter()
+                .find(|a| a.key == "payout_multiplier")
+                .expect("payout_multiplier missing");
+            let mult_val: u128 = mult.value.parse().unwrap();
+            assert!(
+                (2..=3).contains(&mult_val),
+                "multiplier should be 2 or 3, got {}",
+                mult_val
+            );
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
-                    to_address: "user".to_string(),
-                    amount: coins(100_000 * 10, "uatom")
+                    to_address: user.to_string(),
+                    amount: coins(1_000_000 * mult_val, "uatom"),
                 }
                 .into()
-            )
-        } else if "adjacent" == result.value {
-            // 如果用户猜的数字和 contract 生成的随机数字相邻, 则用户获取 1 倍奖励
-            let user_guess_num = user_guess.value.parse::<i32>().unwrap();
-            let contract_guess_num = contract_guess.value.parse::<i32>().unwrap();
-            assert_eq!((user_guess_num - contract_guess_num).abs(), 1);
+            );
+        } else if result.value == "tie" {
+            // 平局退还本金
+            assert_eq!(res.messages.len(), 1, "should refund on tie");
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
-                    to_address: "user".to_string(),
-                    amount: coins(100_000 * 1, "uatom")
+                    to_address: user.to_string(),
+                    amount: coins(1_000_000, "uatom"),
                 }
                 .into()
-            )
+            );
         } else {
-            // 用户猜的数字与合约的数字既不相同也不相邻则不获取任何奖励.
-            assert_eq!(res.messages.len(), 0);
-        }
-    }
-
-    #[test]
-    pub fn test_withdraw_funds() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        let info_clone = info.clone();
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        let res = withdraw_funds(deps.as_mut(), info_clone, 100_000).unwrap();
-        assert_eq!(
-            res.messages[0].msg,
-            BankMsg::Send {
-                to_address: "creator".to_string(),
-                amount: coins(100_000, "uatom")
-            }
-            .into()
-        );
-    }
-
-    #[test]
-    fn test_blackjack_full_flow() {
-        let mut deps = mock_dependencies();
-
-        // 初始化合约
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-
-        let player = "player1";
-
-        // ----------------------------
-        // Step 1: Start 游戏
-        // ----------------------------
-        let start_msg = ExecuteMsg::PlayBlackjack {
-            action: BlackjackAction::Start,
-        };
-        let info = mock_info(player, &coins(500_000, "uatom")); // 有效下注金额
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), start_msg).unwrap();
-        assert_eq!(res.attributes[0], attr("action", "play_blackjack_start"));
-
-        let mut query_msg = QueryMsg::GetBlackjackState {
-            address: player.to_string(),
-        };
-
-        let mut bin = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let mut resp: BlackjackStateResponse = from_json(&bin).unwrap();
-
-        let user_total_by_resp: u32 = utils::calculate_blackjack_total(&resp.user_cards);
-        let user_card1 = &res
-            .attributes
-            .iter()
-            .find(|a| a.key == "user_card1")
-            .unwrap()
-            .value;
-        let user_card2 = &res
-            .attributes
-            .iter()
-            .find(|a| a.key == "user_card2")
-            .unwrap()
-            .value;
-        let user_total_by_attr: u32 = utils::calculate_blackjack_total(&*vec![
-            user_card1.parse::<u32>().unwrap(),
-            user_card2.parse::<u32>().unwrap(),
-        ]);
-        assert_eq!(user_total_by_resp, user_total_by_attr);
-
-        // ----------------------------
-        // Step 2: 当用户的牌小于 17 点, Hit 要一张牌
-        // ----------------------------
-
-        if user_total_by_attr < 17 {
-            let hit_msg = ExecuteMsg::PlayBlackjack {
-                action: BlackjackAction::Hit,
-            };
-
-            let res = execute(deps.as_mut(), mock_env(), info.clone(), hit_msg).unwrap();
-
-            assert_eq!(res.attributes[0], attr("action", "blackjack_hit"));
+            assert_eq!(result.value, "lose");
+            assert_eq!(res.messages.len(), 0, "should have no payout on loss");
         }
-
-        // ----------------------------
-        // Step 3: Stand 停牌
-        // ----------------------------
-        let stand_msg = ExecuteMsg::PlayBlackjack {
-            action: BlackjackAction::Stand,
-        };
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), stand_msg).unwrap();
-        assert_eq!(res.attributes[0], attr("action", "blackjack_stand"));
-
-        query_msg = QueryMsg::GetBlackjackState {
-            address: player.to_string(),
-        };
-        bin = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        resp = from_json(&bin).unwrap();
-
-        assert!(resp.finished);
-        assert!(!resp.user_cards.is_empty());
-        assert!(!resp.dealer_cards.is_empty());
     }
 
     #[test]
-    fn test_blackjack_query_state() {
-        let mut deps = mock_dependencies();
-
-        // 初始化合约
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
+    fn test_sangong_bet_limits() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-
-        // 模拟用户参与 Blackjack 游戏
-        let user = "player1";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
-
-        let start_msg = ExecuteMsg::PlayBlackjack {
-            action: BlackjackAction::Start,
-        };
-
-        let _res = execute(deps.as_mut(), mock_env(), user_info.clone(), start_msg).unwrap();
-
-        // 查询 Blackjack 状态
-        let query_msg = QueryMsg::GetBlackjackState {
-            address: user.to_string(),
-        };
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let bin = query(deps.as_ref(), mock_env(), query_msg).unwrap();
-        let resp: BlackjackStateResponse = from_json(&bin).unwrap();
+        // 低于最小下注
+        let too_small = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(50_000, "uatom")),
+            ExecuteMsg::PlaySanGong { client_seed: None },
+        );
+        assert!(too_small.is_err(), "should reject bet below 100,000");
 
-        // 验证结构内容
-        assert_eq!(resp.user_cards.len(), 2);
-        assert_eq!(resp.dealer_cards.len(), 2);
-        assert_eq!(resp.dealer_cards[0], 0); // 未完成游戏,庄家第一张牌被隐藏
-        assert_eq!(resp.bet, Uint128::new(1_000_000));
-        assert_eq!(resp.finished, false);
+        // 高于最大下注
+        let too_big = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(20_000_000, "uatom")),
+            ExecuteMsg::PlaySanGong { client_seed: None },
+        );
+        assert!(too_big.is_err(), "should reject bet above 10,000,000");
     }
 
     #[test]
-    fn test_play_coin_flip() {
-        let mut deps = mock_dependencies();
-
-        // 初始化合约
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-
-        // 模拟用户参与 coin_flip 游戏
-        let user = "player1";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+    fn test_sangong_hand_evaluation() {
+        use crate::sangong::{evaluate_sangong_hand, SanGongCard, SanGongHandType, Suit};
 
-        let coin_flip = ExecuteMsg::PlayCoinFlip {
-            choice: CoinSide::Heads,
-        };
+        // 三公：三张全是公牌 (K, Q, J)
+        let san_gong = [
+            SanGongCard { rank: 13, suit: Suit::Spades },
+            SanGongCard { rank: 12, suit: Suit::Hearts },
+            SanGongCard { rank: 11, suit: Suit::Diamonds },
+        ];
+        let rank = evaluate_sangong_hand(&san_gong);
+        assert_eq!(rank.hand_type, SanGongHandType::SanGong);
+        assert!(rank.score >= 30_000, "san gong should have highest score");
 
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), coin_flip).unwrap();
+        // 混合九：含公牌且点数为 9（K + 4 + 5 = 0+4+5 = 9）
+        let mixed_nine = [
+            SanGongCard { rank: 13, suit: Suit::Spades },
+            SanGongCard { rank: 4, suit: Suit::Hearts },
+            SanGongCard { rank: 5, suit: Suit::Diamonds },
+        ];
+        let rank2 = evaluate_sangong_hand(&mixed_nine);
+        assert_eq!(rank2.hand_type, SanGongHandType::MixedNine);
+        assert!(rank2.score >= 20_000 && rank2.score < 30_000);
 
-        let result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        // 普通 7 点 (3 + 2 + 2 = 7)
+        let normal = [
+            SanGongCard { rank: 3, suit: Suit::Spades },
+            SanGongCard { rank: 2, suit: Suit::Hearts },
+            SanGongCard { rank: 2, suit: Suit::Diamonds },
+        ];
+        let rank3 = evaluate_sangong_hand(&normal);
+        assert_eq!(rank3.hand_type, SanGongHandType::Normal { points: 7 });
+        assert!(rank3.score < 20_000);
 
-        if "win" == result.value {
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(2_000_000, "uatom")
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
-        }
+        // 三公 > 混合九 > 普通
+        assert!(rank.score > rank2.score);
+        assert!(rank2.score > rank3.score);
     }
 
+    // ──────────────────────────────────────────────────────────────────────
+    // 骰宝（Sic Bo）测试
+    // ──────────────────────────────────────────────────────────────────────
+
     #[test]
-    fn test_play_dice_guess_size() {
-        let mut deps = mock_dependencies();
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_sicbo_big() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-        let user = "player1";
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+
+        let user = "sicbo_player";
         let user_info = mock_info(user, &coins(1_000_000, "uatom"));
-        let dice_guess = ExecuteMsg::PlayDice {
-            mode: DiceGameMode::GuessSize {
-                guess_big: DiceGuessSize::Small,
-            },
-        };
 
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), dice_guess).unwrap();
-        let result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Big,
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        let player_guess = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "player_guess")
-            .expect("player_guess missing");
+        // 检查基本属性
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
+            "play_sicbo"
+        );
+        assert!(res.attributes.iter().any(|a| a.key == "die1"));
+        assert!(res.attributes.iter().any(|a| a.key == "die2"));
+        assert!(res.attributes.iter().any(|a| a.key == "die3"));
+        assert!(res.attributes.iter().any(|a| a.key == "total"));
+        assert!(res.attributes.iter().any(|a| a.key == "is_triple"));
 
-        let actual_result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "actual_result")
-            .expect("actual_result missing");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        let total: u8 = res.attributes.iter().find(|a| a.key == "total").unwrap().value.parse().unwrap();
+        let is_triple: bool = res.attributes.iter().find(|a| a.key == "is_triple").unwrap().value.parse().unwrap();
 
         if result.value == "win" {
-            assert_eq!(player_guess.value, actual_result.value);
+            assert!((11..=17).contains(&total) && !is_triple, "Big win requires total 11-17, no triple");
+            assert_eq!(res.messages.len(), 1);
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(2_000_000, "uatom")
+                    amount: coins(2_000_000, "uatom"),
                 }
                 .into()
             );
@@ -3286,231 +6760,159 @@ mod tests {
     }
 
     #[test]
-    fn test_play_dice_guess_number() {
-        let mut deps = mock_dependencies();
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_sicbo_small() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-        let user = "player1";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
-        let dice_guess = ExecuteMsg::PlayDice {
-            mode: DiceGameMode::ExactNumber { guess_number: 6 },
-        };
-
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), dice_guess).unwrap();
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        let user = "sicbo_small_player";
+        let user_info = mock_info(user, &coins(500_000, "uatom"));
 
-        let player_guess = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "player_guess")
-            .expect("player_guess missing");
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Small,
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        let actual_result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "actual_result")
-            .expect("actual_result missing");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        let total: u8 = res.attributes.iter().find(|a| a.key == "total").unwrap().value.parse().unwrap();
+        let is_triple: bool = res.attributes.iter().find(|a| a.key == "is_triple").unwrap().value.parse().unwrap();
 
         if result.value == "win" {
-            assert_eq!(player_guess.value, actual_result.value);
+            assert!((4..=10).contains(&total) && !is_triple, "Small win requires total 4-10, no triple");
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(6_000_000, "uatom")
+                    amount: coins(1_000_000, "uatom"),
                 }
                 .into()
-            )
+            );
         } else {
             assert_eq!(res.messages.len(), 0);
         }
     }
 
     #[test]
-    fn test_play_dice_range_bet() {
-        let mut deps = mock_dependencies();
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(20_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-        let user = "player1";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
-        let dice_guess = ExecuteMsg::PlayDice {
-            mode: DiceGameMode::RangeBet { start: 1, end: 3 },
-        };
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), dice_guess).unwrap();
-        print!("{:?}", res);
-
-        let result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+    fn test_play_sicbo_total() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let actual_result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "actual_result")
-            .expect("actual_result missing");
+        let user = "sicbo_total_player";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
 
-        let player_start = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "player_start")
-            .expect("player_start missing");
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Total { value: 10 },
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        let player_end = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "player_end")
-            .expect("player_end missing");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        let total: u8 = res.attributes.iter().find(|a| a.key == "total").unwrap().value.parse().unwrap();
 
         if result.value == "win" {
-            let actual_result_int = actual_result.value.parse::<u32>().unwrap();
-            assert_eq!(
-                true,
-                actual_result_int >= player_start.value.parse::<u32>().unwrap()
-            );
-            assert_eq!(
-                true,
-                actual_result_int <= player_end.value.parse::<u32>().unwrap()
-            );
+            assert_eq!(total, 10);
+            // 总和 10 赔率 7×
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(2_000_000, "uatom")
+                    amount: coins(7_000_000, "uatom"),
                 }
                 .into()
-            )
-        } else {
-            assert_eq!(res.messages.len(), 0);
-        }
-    }
-
-    #[test]
-    fn test_play_baccarat() {
-        let mut deps = mock_dependencies();
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-        let user = "player1";
-
-        // 测试玩家获胜情况
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
-        let baccarat_game = ExecuteMsg::PlayBaccarat {
-            bet_choice: BaccaratBet::Player,
-        };
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), baccarat_game).unwrap();
-
-        println!("{:?}", res);
-
-        let result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
-
-        let winner = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "winner")
-            .expect("winner missing");
-
-        if result.value == "win" {
-            // 如果玩家获胜，检查是否正确赔付 (1:1 奖金 + 1:1 本金 = 2倍)
-            if winner.value == "Player" {
-                assert_eq!(
-                    res.messages[0].msg,
-                    BankMsg::Send {
-                        to_address: user.to_string(),
-                        amount: coins(2_000_000, "uatom")
-                    }
-                    .into()
-                );
-            } else if winner.value == "Banker" {
-                // 庄家获胜但是用户押注错了
-                assert_eq!(res.messages.len(), 0);
-            } else {
-                // 平局，赔率更高 (8:1 奖金 + 1:1 本金 = 9倍)
-                assert_eq!(
-                    res.messages[0].msg,
-                    BankMsg::Send {
-                        to_address: user.to_string(),
-                        amount: coins(9_000_000, "uatom")
-                    }
-                    .into()
-                );
-            }
+            );
         } else {
             assert_eq!(res.messages.len(), 0);
         }
     }
 
     #[test]
-    fn test_play_roulette() {
-        let mut deps = mock_dependencies();
-        let instantiate_msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_sicbo_any_triple() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, instantiate_msg).unwrap();
-        let user = "player1";
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        // 测试单数字投注
+        let user = "sicbo_triple_player";
         let user_info = mock_info(user, &coins(1_000_000, "uatom"));
-        let roulette_game = ExecuteMsg::PlayRoulette {
-            bet_type: RouletteBetType::SingleNumber { number: 17 },
-        };
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), roulette_game).unwrap();
 
-        println!("{:?}", res);
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::AnyTriple,
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        let result_single_number = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        let is_triple: bool = res.attributes.iter().find(|a| a.key == "is_triple").unwrap().value.parse().unwrap();
 
-        if result_single_number.value == "win" {
-            // 如果玩家获胜，检查是否正确赔付 (35:1 奖金 + 1:1 本金 = 36倍)
+        if result.value == "win" {
+            assert!(is_triple, "AnyTriple win requires all three dice the same");
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(36_000_000, "uatom")
+                    amount: coins(31_000_000, "uatom"),
                 }
                 .into()
             );
         } else {
             assert_eq!(res.messages.len(), 0);
         }
+    }
 
-        // 测试红色投注
-        let user_info = mock_info(user, &coins(2_000_000, "uatom"));
-        let roulette_game = ExecuteMsg::PlayRoulette {
-            bet_type: RouletteBetType::Color { color: Color::Red },
-        };
-        let res = execute(deps.as_mut(), mock_env(), user_info.clone(), roulette_game).unwrap();
+    #[test]
+    fn test_play_sicbo_single_die() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        println!("{:?}", res);
+        let user = "sicbo_single_player";
+        let bet = 1_000_000u128;
+        let user_info = mock_info(user, &coins(bet, "uatom"));
 
-        let result_color = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::SingleDie { number: 3 },
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        if result_color.value == "win" {
-            // 如果玩家获胜，检查是否正确赔付 (1:1 奖金 + 1:1 本金 = 2倍)
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+
+        if result.value == "win" {
+            let payout_mult: u128 = res.attributes.iter()
+                .find(|a| a.key == "payout_multiplier")
+                .unwrap()
+                .value.parse().unwrap();
+            assert!((2..=4).contains(&payout_mult), "single die multiplier 2-4");
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(4_000_000, "uatom")
+                    amount: coins(bet * payout_mult, "uatom"),
                 }
                 .into()
             );
@@ -3519,82 +6921,35 @@ mod tests {
         }
     }
 
-    // ──────────────────────────────────────────────────────────────────────
-    // Mega Slot 测试
-    // ──────────────────────────────────────────────────────────────────────
-
     #[test]
-    fn test_play_slot_mega() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-        // Mega 模式最低投注 500_000
-        let user_info = mock_info("user", &coins(500_000, "uatom"));
-        let res = play_slot(deps.as_mut(), mock_env(), user_info, SlotMode::Mega).unwrap();
-        let attrs = &res.attributes;
-
-        // mode 属性
-        let mode_attr = attrs.iter().find(|a| a.key == "mode").expect("mode missing");
-        assert_eq!(mode_attr.value, "Mega");
-
-        // 6 列 × 4 行 = 24 格
-        for col in 1..=6usize {
-            for row in 1..=4usize {
-                let key = format!("reel{}_{}", col, row);
-                assert!(attrs.iter().any(|a| a.key == key), "missing {}", key);
-            }
-        }
-
-        // 必须包含 free_spin 和 jackpot 属性
-        assert!(attrs.iter().any(|a| a.key == "free_spin"), "free_spin missing");
-        assert!(attrs.iter().any(|a| a.key == "jackpot"), "jackpot missing");
-        assert!(attrs.iter().any(|a| a.key == "win_desc"), "win_desc missing");
-
-        // 低于最低投注应报错
-        let too_small = play_slot(
-            deps.as_mut(), mock_env(),
-            mock_info("user", &coins(100_000, "uatom")),
-            SlotMode::Mega,
-        );
-        assert!(too_small.is_err(), "should reject bet below Mega minimum");
-    }
+    fn test_play_sicbo_combo() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-    #[test]
-    fn test_play_slot_mega_payout() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let user = "sicbo_combo_player";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
 
-        let user = "player1";
-        let bet = 1_000_000u128;
-        let res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(user, &coins(bet, "uatom")),
-            ExecuteMsg::PlaySlot { mode: SlotMode::Mega },
-        ).unwrap();
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Combo { first: 1, second: 6 },
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        let has_result = res.attributes.iter().any(|a| a.key == "result");
-        let has_payout = res.attributes.iter().any(|a| a.key == "payout_multiplier");
-        assert!(has_result || has_payout, "expected result or payout_multiplier");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
 
-        if has_payout {
-            assert_eq!(res.messages.len(), 1);
-            let payout_mult: u128 = res
-                .attributes
-                .iter()
-                .find(|a| a.key == "payout_multiplier")
-                .unwrap()
-                .value
-                .parse()
-                .unwrap();
+        if result.value == "win" {
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(bet * payout_mult, "uatom"),
+                    amount: coins(7_000_000, "uatom"),
                 }
                 .into()
             );
@@ -3603,764 +6958,1077 @@ mod tests {
         }
     }
 
-    // ──────────────────────────────────────────────────────────────────────
-    // 奥马哈扑克测试
-    // ──────────────────────────────────────────────────────────────────────
-
     #[test]
-    fn test_omaha_full_flow() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_sicbo_bet_validation() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "omaha_player";
-        let bet = 500_000u128;
+        // 总和超出范围
+        let err = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Total { value: 20 },
+                client_seed: None,
+            },
+        );
+        assert!(err.is_err(), "should reject total > 17");
 
-        // ── Step 1: Start ──────────────────────────────────────
-        let start_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(bet, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Start },
-        ).unwrap();
+        let err2 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Total { value: 2 },
+                client_seed: None,
+            },
+        );
+        assert!(err2.is_err(), "should reject total < 4");
 
-        let stage = start_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(stage.value, "PreFlop");
+        // 骰子点数超出范围
+        let err3 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::SingleDie { number: 7 },
+                client_seed: None,
+            },
+        );
+        assert!(err3.is_err(), "should reject die number > 6");
 
-        let player_hand_attr = start_res.attributes.iter().find(|a| a.key == "player_hand").unwrap();
-        assert!(!player_hand_attr.value.is_empty(), "player_hand should not be empty");
+        // 两骰组合相同数字
+        let err4 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Combo { first: 3, second: 3 },
+                client_seed: None,
+            },
+        );
+        assert!(err4.is_err(), "should reject combo with same numbers");
 
-        // ── Step 2: Query state ────────────────────────────────
-        let bin = query(
-            deps.as_ref(), mock_env(),
-            QueryMsg::GetOmahaState { address: player.to_string() },
-        ).unwrap();
-        let state_resp: OmahaStateResponse = from_json(&bin).unwrap();
-        assert_eq!(state_resp.player_hand.len(), 4, "player should have 4 cards");
-        assert_eq!(state_resp.dealer_hand.len(), 0, "dealer hand hidden before showdown");
-        assert_eq!(state_resp.community_cards.len(), 0, "no community cards at PreFlop");
-        assert!(!state_resp.finished);
+        // 下注金额过小
+        let err5 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(50_000, "uatom")),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Big,
+                client_seed: None,
+            },
+        );
+        assert!(err5.is_err(), "should reject bet below 100,000");
 
-        // ── Step 3: Raise (PreFlop → Flop) ───────────────────
-        let raise_amount = 200_000u128;
-        let raise_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(raise_amount, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: raise_amount } },
-        ).unwrap();
+        // 下注金额过大
+        let err6 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(20_000_000, "uatom")),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Big,
+                client_seed: None,
+            },
+        );
+        assert!(err6.is_err(), "should reject bet above 10,000,000");
+    }
 
-        let raise_stage = raise_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(raise_stage.value, "Flop");
+    #[test]
+    fn test_sicbo_payout_logic() {
+        use crate::sicbo::{calculate_sicbo_payout, SicBoBetType, SicBoResult};
+
+        // 大：总和 12，非三同号 → 赢
+        let r = SicBoResult::new(4, 4, 4); // triple 12
+        let (won, _) = calculate_sicbo_payout(&SicBoBetType::Big, &r);
+        assert!(!won, "triple should lose Big bet");
+
+        let r2 = SicBoResult::new(3, 4, 5); // total 12, no triple
+        let (won2, mult2) = calculate_sicbo_payout(&SicBoBetType::Big, &r2);
+        assert!(won2, "total 12 no triple should win Big");
+        assert_eq!(mult2, 2);
+
+        // 小：总和 8，非三同号 → 赢
+        let r3 = SicBoResult::new(1, 3, 4); // total 8
+        let (won3, mult3) = calculate_sicbo_payout(&SicBoBetType::Small, &r3);
+        assert!(won3);
+        assert_eq!(mult3, 2);
+
+        // 单双
+        let r4 = SicBoResult::new(1, 2, 4); // total 7 (odd)
+        let (odd_won, _) = calculate_sicbo_payout(&SicBoBetType::Odd, &r4);
+        assert!(odd_won);
+        let (even_won, _) = calculate_sicbo_payout(&SicBoBetType::Even, &r4);
+        assert!(!even_won);
+
+        // 指定三同号
+        let r5 = SicBoResult::new(6, 6, 6);
+        let (triple_won, triple_mult) = calculate_sicbo_payout(
+            &SicBoBetType::SpecificTriple { number: 6 },
+            &r5,
+        );
+        assert!(triple_won);
+        assert_eq!(triple_mult, 181);
+
+        let (wrong_triple, _) = calculate_sicbo_payout(
+            &SicBoBetType::SpecificTriple { number: 5 },
+            &r5,
+        );
+        assert!(!wrong_triple);
 
-        let community_attr = raise_res.attributes.iter().find(|a| a.key == "community_cards").unwrap();
-        assert!(community_attr.value.contains(","), "flop should have 3 cards");
+        // 双骰
+        let r6 = SicBoResult::new(3, 3, 5);
+        let (double_won, double_mult) = calculate_sicbo_payout(
+            &SicBoBetType::DoubleBet { number: 3 },
+            &r6,
+        );
+        assert!(double_won);
+        assert_eq!(double_mult, 12);
 
-        // ── Step 4: Call (Flop → Turn) ────────────────────────
-        let call_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Call },
-        ).unwrap();
-        let call_stage = call_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(call_stage.value, "Turn");
+        // 单骰：出现 1 次
+        let r7 = SicBoResult::new(2, 4, 6);
+        let (single_won, single_mult) = calculate_sicbo_payout(
+            &SicBoBetType::SingleDie { number: 4 },
+            &r7,
+        );
+        assert!(single_won);
+        assert_eq!(single_mult, 2);
 
-        // ── Step 5: Showdown ──────────────────────────────────
-        let showdown_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Showdown },
-        ).unwrap();
+        // 单骰：出现 2 次
+        let r8 = SicBoResult::new(4, 4, 6);
+        let (s2_won, s2_mult) = calculate_sicbo_payout(
+            &SicBoBetType::SingleDie { number: 4 },
+            &r8,
+        );
+        assert!(s2_won);
+        assert_eq!(s2_mult, 3);
 
-        let result = showdown_res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        assert!(
-            result.value == "player_win" || result.value == "dealer_win" || result.value == "tie",
-            "unexpected result: {}",
-            result.value
+        // 两骰组合
+        let r9 = SicBoResult::new(1, 3, 6);
+        let (combo_won, combo_mult) = calculate_sicbo_payout(
+            &SicBoBetType::Combo { first: 1, second: 6 },
+            &r9,
         );
+        assert!(combo_won);
+        assert_eq!(combo_mult, 7);
 
-        // showdown 后庄家手牌应可见
-        let bin2 = query(
-            deps.as_ref(), mock_env(),
-            QueryMsg::GetOmahaState { address: player.to_string() },
-        ).unwrap();
-        let final_state: OmahaStateResponse = from_json(&bin2).unwrap();
-        assert!(final_state.finished);
-        assert_eq!(final_state.dealer_hand.len(), 4, "dealer hand visible after showdown");
-        assert_eq!(final_state.community_cards.len(), 5, "all 5 community cards after showdown");
+        let (combo_fail, _) = calculate_sicbo_payout(
+            &SicBoBetType::Combo { first: 2, second: 5 },
+            &r9,
+        );
+        assert!(!combo_fail);
     }
 
+    // ──────────────────────────────────────────────────────────────────────
+    // 基诺（Keno）测试
+    // ──────────────────────────────────────────────────────────────────────
+
     #[test]
-    fn test_omaha_fold() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_keno_basic() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "omaha_fold_player";
-        let bet = 300_000u128;
-
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(bet, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Start },
-        ).unwrap();
-
-        // 弃牌
-        let fold_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Fold },
-        ).unwrap();
+        let user = "keno_player";
+        let bet = 1_000_000u128;
+        let user_info = mock_info(user, &coins(bet, "uatom"));
 
-        let result = fold_res.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert_eq!(result.value, "folded");
+        // 选 5 个号码
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlayKeno {
+                picks: vec![3, 17, 28, 42, 65],
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        // 弃牌后游戏结束，不能再操作
-        let err = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Showdown },
+        // 检查基本属性
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
+            "play_keno"
         );
-        assert!(err.is_err(), "should not be able to showdown after fold");
-    }
+        assert!(res.attributes.iter().any(|a| a.key == "picks"));
+        assert!(res.attributes.iter().any(|a| a.key == "drawn"));
+        assert!(res.attributes.iter().any(|a| a.key == "hits"));
+        assert!(res.attributes.iter().any(|a| a.key == "pick_count"));
+        assert!(res.attributes.iter().any(|a| a.key == "hit_count"));
 
-    #[test]
-    fn test_omaha_no_duplicate_game() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+        let pick_count: u8 = res
+            .attributes.iter().find(|a| a.key == "pick_count").unwrap()
+            .value.parse().unwrap();
+        assert_eq!(pick_count, 5);
 
-        let player = "omaha_dup_player";
+        let hit_count: u8 = res
+            .attributes.iter().find(|a| a.key == "hit_count").unwrap()
+            .value.parse().unwrap();
+        assert!(hit_count <= 5);
 
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(100_000, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Start },
-        ).unwrap();
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
 
-        // 重复开始应报错
-        let err = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(100_000, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Start },
-        );
-        assert!(err.is_err(), "should not allow duplicate active game");
+        if result.value == "win" {
+            assert!(res.messages.len() == 1);
+            let mult: u128 = res.attributes.iter()
+                .find(|a| a.key == "payout_multiplier").unwrap()
+                .value.parse().unwrap();
+            assert!(mult > 0);
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(bet * mult, "uatom"),
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(result.value, "lose");
+            assert_eq!(res.messages.len(), 0);
+        }
     }
 
     #[test]
-    fn test_omaha_raise_then_showdown() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_keno_single_pick() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "omaha_raise_player";
+        let user = "keno_single_player";
+        let user_info = mock_info(user, &coins(500_000, "uatom"));
 
-        // Start
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(500_000, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Start },
-        ).unwrap();
+        // 只选 1 个号码
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlayKeno {
+                picks: vec![42],
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        // Raise 1: PreFlop → Flop
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(100_000, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: 100_000 } },
-        ).unwrap();
+        let hit_count: u8 = res
+            .attributes.iter().find(|a| a.key == "hit_count").unwrap()
+            .value.parse().unwrap();
+        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
 
-        // Raise 2: Flop → Turn
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(200_000, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: 200_000 } },
-        ).unwrap();
+        if hit_count == 1 {
+            assert_eq!(result.value, "win");
+            // 选1中1 赔率 3×
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(1_500_000, "uatom"),
+                }
+                .into()
+            );
+        } else {
+            assert_eq!(result.value, "lose");
+        }
+    }
 
-        // Raise 3: Turn → River
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(300_000, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Raise { amount: 300_000 } },
-        ).unwrap();
+    #[test]
+    fn test_play_keno_max_picks() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(100_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        // Showdown
-        let sd = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayOmaha { action: OmahaAction::Showdown },
-        ).unwrap();
+        let user = "keno_max_player";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
 
-        let result = sd.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert!(
-            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
-            "unexpected result: {}",
-            result.value
-        );
+        // 选 10 个号码
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlayKeno {
+                picks: vec![1, 10, 20, 30, 40, 50, 60, 70, 75, 80],
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        // 验证 player_rank_name 和 dealer_rank_name 存在
-        assert!(sd.attributes.iter().any(|a| a.key == "player_rank_name"));
-        assert!(sd.attributes.iter().any(|a| a.key == "dealer_rank_name"));
-    }
+        let pick_count: u8 = res
+            .attributes.iter().find(|a| a.key == "pick_count").unwrap()
+            .value.parse().unwrap();
+        assert_eq!(pick_count, 10);
 
-    // ──────────────────────────────────────────────────────────────────────
-    // 德州扑克（Texas Hold'em）测试
-    // ──────────────────────────────────────────────────────────────────────
+        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert!(result.value == "win" || result.value == "lose");
+    }
 
     #[test]
-    fn test_texas_full_flow() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_keno_validation() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "texas_player";
-        let bet = 500_000u128;
+        // 空选号
+        let err1 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayKeno { picks: vec![], client_seed: None },
+        );
+        assert!(err1.is_err(), "should reject empty picks");
 
-        // ── Step 1: Start ──────────────────────────────────────
-        let start_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(bet, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
-        ).unwrap();
+        // 超过 10 个号码
+        let err2 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayKeno {
+                picks: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+                client_seed: None,
+            },
+        );
+        assert!(err2.is_err(), "should reject more than 10 picks");
 
-        let stage = start_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(stage.value, "PreFlop");
+        // 号码超出范围
+        let err3 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayKeno { picks: vec![0, 42], client_seed: None },
+        );
+        assert!(err3.is_err(), "should reject number 0");
 
-        let player_hand_attr = start_res.attributes.iter().find(|a| a.key == "player_hand").unwrap();
-        assert!(!player_hand_attr.value.is_empty(), "player_hand should not be empty");
+        let err4 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayKeno { picks: vec![81], client_seed: None },
+        );
+        assert!(err4.is_err(), "should reject number > 80");
 
-        // ── Step 2: Query state ────────────────────────────────
-        let bin = query(
-            deps.as_ref(), mock_env(),
-            QueryMsg::GetTexasState { address: player.to_string() },
-        ).unwrap();
-        let state_resp: TexasStateResponse = from_json(&bin).unwrap();
-        assert_eq!(state_resp.player_hand.len(), 2, "player should have 2 cards");
-        assert_eq!(state_resp.dealer_hand.len(), 0, "dealer hand hidden before showdown");
-        assert_eq!(state_resp.community_cards.len(), 0, "no community cards at PreFlop");
-        assert!(!state_resp.finished);
-        assert!(!state_resp.all_in);
+        // 重复号码
+        let err5 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayKeno { picks: vec![5, 5, 10], client_seed: None },
+        );
+        assert!(err5.is_err(), "should reject duplicate numbers");
 
-        // ── Step 3: Raise (PreFlop → Flop) ───────────────────
-        let raise_amount = 200_000u128;
-        let raise_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(raise_amount, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: raise_amount } },
-        ).unwrap();
+        // 下注金额过小
+        let err6 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(50_000, "uatom")),
+            ExecuteMsg::PlayKeno { picks: vec![1], client_seed: None },
+        );
+        assert!(err6.is_err(), "should reject bet below 100,000");
 
-        let raise_stage = raise_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(raise_stage.value, "Flop");
+        // 下注金额过大
+        let err7 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(20_000_000, "uatom")),
+            ExecuteMsg::PlayKeno { picks: vec![1], client_seed: None },
+        );
+        assert!(err7.is_err(), "should reject bet above 10,000,000");
+    }
 
-        let community_attr = raise_res.attributes.iter().find(|a| a.key == "community_cards").unwrap();
-        assert!(community_attr.value.contains(","), "flop should have 3 cards");
+    #[test]
+    fn test_keno_payout_table() {
+        use crate::keno::keno_payout_multiplier;
 
-        // ── Step 4: Call (Flop → Turn) ────────────────────────
-        let call_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Call },
-        ).unwrap();
-        let call_stage = call_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(call_stage.value, "Turn");
+        // 选1
+        assert_eq!(keno_payout_multiplier(1, 0), 0);
+        assert_eq!(keno_payout_multiplier(1, 1), 3);
 
-        // ── Step 5: Showdown ──────────────────────────────────
-        let showdown_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Showdown },
-        ).unwrap();
+        // 选3
+        assert_eq!(keno_payout_multiplier(3, 0), 0);
+        assert_eq!(keno_payout_multiplier(3, 1), 0);
+        assert_eq!(keno_payout_multiplier(3, 2), 2);
+        assert_eq!(keno_payout_multiplier(3, 3), 16);
 
-        let result = showdown_res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        assert!(
-            result.value == "player_win" || result.value == "dealer_win" || result.value == "tie",
-            "unexpected result: {}",
-            result.value
-        );
+        // 选5
+        assert_eq!(keno_payout_multiplier(5, 2), 0);
+        assert_eq!(keno_payout_multiplier(5, 3), 2);
+        assert_eq!(keno_payout_multiplier(5, 4), 12);
+        assert_eq!(keno_payout_multiplier(5, 5), 50);
 
-        // Showdown 后庄家手牌应可见
-        let bin2 = query(
-            deps.as_ref(), mock_env(),
-            QueryMsg::GetTexasState { address: player.to_string() },
-        ).unwrap();
-        let final_state: TexasStateResponse = from_json(&bin2).unwrap();
-        assert!(final_state.finished);
-        assert_eq!(final_state.dealer_hand.len(), 2, "dealer hand visible after showdown");
-        assert_eq!(final_state.community_cards.len(), 5, "all 5 community cards after showdown");
+        // 选10
+        assert_eq!(keno_payout_multiplier(10, 4), 0);
+        assert_eq!(keno_payout_multiplier(10, 5), 2);
+        assert_eq!(keno_payout_multiplier(10, 10), 2000);
     }
 
     #[test]
-    fn test_texas_fold() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
-
-        let player = "texas_fold_player";
-        let bet = 300_000u128;
+    fn test_keno_draw_no_duplicates() {
+        // 验证开出的 20 个号码无重复且在 1-80 范围内
+        let seed = derive_bet_seed("keno_server_seed", "", &Addr::unchecked("test_user"), 1, 1);
+        let drawn = draw_keno_numbers(&seed);
 
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(bet, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
-        ).unwrap();
+        assert_eq!(drawn.len(), 20, "should draw exactly 20 numbers");
 
-        // 弃牌
-        let fold_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Fold },
-        ).unwrap();
+        for &n in &drawn {
+            assert!((1..=80).contains(&n), "number {} out of range", n);
+        }
 
-        let result = fold_res.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert_eq!(result.value, "folded");
+        // 检查无重复
+        let mut unique = drawn.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 20, "drawn numbers should be unique");
 
-        // 弃牌后不能再操作
-        let err = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Showdown },
-        );
-        assert!(err.is_err(), "should not be able to showdown after fold");
+        // 验证已排序
+        let mut sorted = drawn.clone();
+        sorted.sort_unstable();
+        assert_eq!(drawn, sorted, "drawn numbers should be sorted");
     }
 
+    // ──────────────────────────────────────────────────────────────────────
+    // 刮刮乐（Scratch Card）测试
+    // ──────────────────────────────────────────────────────────────────────
+
     #[test]
-    fn test_texas_no_duplicate_game() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_scratch_card_classic() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "texas_dup_player";
+        let user = "scratch_player";
+        let bet = 500_000u128;
+        let user_info = mock_info(user, &coins(bet, "uatom"));
 
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(100_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
-        ).unwrap();
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Classic,
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        // 重复开始应报错
-        let err = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(100_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
+        // 检查基本属性
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
+            "play_scratch_card"
+        );
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "card_type").unwrap().value,
+            "Classic"
         );
-        assert!(err.is_err(), "should not allow duplicate active game");
-    }
 
-    #[test]
-    fn test_texas_check_advance() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+        // 检查 3×3 = 9 个格子都有输出
+        for row in 1..=3usize {
+            for col in 1..=3usize {
+                let key = format!("cell_{}_{}", row, col);
+                assert!(
+                    res.attributes.iter().any(|a| a.key == key),
+                    "missing {}",
+                    key
+                );
+            }
+        }
 
-        let player = "texas_check_player";
+        // 检查行 emoji 展示
+        assert!(res.attributes.iter().any(|a| a.key == "row_1"));
+        assert!(res.attributes.iter().any(|a| a.key == "row_2"));
+        assert!(res.attributes.iter().any(|a| a.key == "row_3"));
 
-        // Start
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(500_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
-        ).unwrap();
+        // 检查中奖线数
+        assert!(res.attributes.iter().any(|a| a.key == "winning_lines"));
 
-        // Check (PreFlop → Flop) — 差额为 0，可以过牌
-        let check_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Check },
-        ).unwrap();
-        let check_stage = check_res.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(check_stage.value, "Flop");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
 
-        // Check (Flop → Turn)
-        let check2 = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Check },
-        ).unwrap();
-        let check2_stage = check2.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(check2_stage.value, "Turn");
+        if result.value == "win" {
+            assert!(res.messages.len() == 1);
+            let mult: u128 = res.attributes.iter()
+                .find(|a| a.key == "total_multiplier").unwrap()
+                .value.parse().unwrap();
+            assert!(mult >= 2, "multiplier should be at least 2");
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: user.to_string(),
+                    amount: coins(bet * mult, "uatom"),
+                }
+                .into()
+            );
+            // 检查 win_desc 存在
+            assert!(res.attributes.iter().any(|a| a.key == "win_desc"));
+        } else {
+            assert_eq!(result.value, "lose");
+            assert_eq!(res.messages.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_play_scratch_card_premium() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
+        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        // Check (Turn → River)
-        let check3 = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Check },
-        ).unwrap();
-        let check3_stage = check3.attributes.iter().find(|a| a.key == "stage").unwrap();
-        assert_eq!(check3_stage.value, "River");
+        let user = "scratch_premium_player";
+        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
 
-        // Showdown
-        let sd = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Showdown },
-        ).unwrap();
-        let result = sd.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert!(
-            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
-            "unexpected result: {}",
-            result.value
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Premium,
+                client_seed: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "card_type").unwrap().value,
+            "Premium"
         );
+        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
+        assert!(result.value == "win" || result.value == "lose");
     }
 
     #[test]
-    fn test_texas_all_in() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_scratch_card_deluxe() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "texas_allin_player";
-
-        // Start
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(500_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
-        ).unwrap();
+        let user = "scratch_deluxe_player";
+        let user_info = mock_info(user, &coins(2_000_000, "uatom"));
 
-        // All-In 直接进入 Showdown
-        let allin_res = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(2_000_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::AllIn { amount: 2_000_000 } },
-        ).unwrap();
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user_info,
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Deluxe,
+                client_seed: None,
+            },
+        )
+        .unwrap();
 
-        let result = allin_res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        assert!(
-            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
-            "unexpected result: {}",
-            result.value
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "card_type").unwrap().value,
+            "Deluxe"
         );
-
-        // 验证 rank_name 属性存在
-        assert!(allin_res.attributes.iter().any(|a| a.key == "player_rank_name"));
-        assert!(allin_res.attributes.iter().any(|a| a.key == "dealer_rank_name"));
-
-        // 验证游戏已结束
-        let bin = query(
-            deps.as_ref(), mock_env(),
-            QueryMsg::GetTexasState { address: player.to_string() },
-        ).unwrap();
-        let final_state: TexasStateResponse = from_json(&bin).unwrap();
-        assert!(final_state.finished);
-        assert!(final_state.all_in);
-        assert_eq!(final_state.community_cards.len(), 5);
-        assert_eq!(final_state.dealer_hand.len(), 2);
     }
 
     #[test]
-    fn test_texas_raise_then_showdown() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_scratch_card_bet_limits() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let player = "texas_raise_player";
+        // Classic 下注过小
+        let err1 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(50_000, "uatom")),
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Classic,
+                client_seed: None,
+            },
+        );
+        assert!(err1.is_err(), "Classic should reject bet below 100,000");
 
-        // Start
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(500_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Start },
-        ).unwrap();
+        // Classic 下注过大
+        let err2 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(3_000_000, "uatom")),
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Classic,
+                client_seed: None,
+            },
+        );
+        assert!(err2.is_err(), "Classic should reject bet above 2,000,000");
 
-        // Raise 1: PreFlop → Flop
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(100_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: 100_000 } },
-        ).unwrap();
+        // Premium 下注过小
+        let err3 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(200_000, "uatom")),
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Premium,
+                client_seed: None,
+            },
+        );
+        assert!(err3.is_err(), "Premium should reject bet below 500,000");
 
-        // Raise 2: Flop → Turn
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(200_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: 200_000 } },
-        ).unwrap();
+        // Deluxe 下注过小
+        let err4 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(500_000, "uatom")),
+            ExecuteMsg::PlayScratchCard {
+                card_type: ScratchCardType::Deluxe,
+                client_seed: None,
+            },
+        );
+        assert!(err4.is_err(), "Deluxe should reject bet below 1,000,000");
+    }
 
-        // Raise 3: Turn → River
-        execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(300_000, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Raise { amount: 300_000 } },
-        ).unwrap();
+    #[test]
+    fn test_scratch_card_evaluation_logic() {
+        use crate::scratch::{evaluate_scratch_card, ScratchSymbol};
 
-        // Showdown
-        let sd = execute(
-            deps.as_mut(), mock_env(),
-            mock_info(player, &coins(0, "uatom")),
-            ExecuteMsg::PlayTexas { action: TexasAction::Showdown },
-        ).unwrap();
+        // 全部相同 → 8 条线全中
+        let all_diamond = [ScratchSymbol::Diamond; 9];
+        let (mult, lines) = evaluate_scratch_card(&all_diamond);
+        assert_eq!(lines.len(), 8, "all same should win 8 lines");
+        assert_eq!(mult, 50 * 8, "8 lines × 50× each");
 
-        let result = sd.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert!(
-            ["player_win", "dealer_win", "tie"].contains(&result.value.as_str()),
-            "unexpected result: {}",
-            result.value
-        );
+        // 第一行全 Cherry → 1 条线
+        let mut grid = [ScratchSymbol::Lemon; 9];
+        grid[0] = ScratchSymbol::Cherry;
+        grid[1] = ScratchSymbol::Cherry;
+        grid[2] = ScratchSymbol::Cherry;
+        let (mult2, lines2) = evaluate_scratch_card(&grid);
+        // row1 中奖 Cherry(3×)，row2 和 row3 全 Lemon(2×)，列和对角线不一定
+        // row2: [Lemon, Lemon, Lemon] → 中，row3: [Lemon, Lemon, Lemon] → 中
+        // col1: [Cherry, Lemon, Lemon] → 不中
+        // 需要精确计算
+        assert!(lines2.iter().any(|l| l.0 == "row1"), "row1 should win");
+        assert!(lines2.iter().any(|l| l.0 == "row2"), "row2 (all Lemon) should win");
+        assert!(lines2.iter().any(|l| l.0 == "row3"), "row3 (all Lemon) should win");
+        assert_eq!(mult2, 3 + 2 + 2, "Cherry 3× + Lemon 2× + Lemon 2× = 7×");
 
-        assert!(sd.attributes.iter().any(|a| a.key == "player_rank_name"));
-        assert!(sd.attributes.iter().any(|a| a.key == "dealer_rank_name"));
+        // 无任何三连 → 0
+        let no_match = [
+            ScratchSymbol::Diamond, ScratchSymbol::Star,    ScratchSymbol::Clover,
+            ScratchSymbol::Bell,    ScratchSymbol::Cherry,  ScratchSymbol::Lemon,
+            ScratchSymbol::Star,    ScratchSymbol::Diamond, ScratchSymbol::Bell,
+        ];
+        let (mult3, lines3) = evaluate_scratch_card(&no_match);
+        assert_eq!(mult3, 0);
+        assert_eq!(lines3.len(), 0);
+
+        // 对角线中奖
+        let mut diag_grid = [ScratchSymbol::Lemon; 9];
+        diag_grid[0] = ScratchSymbol::Star;
+        diag_grid[4] = ScratchSymbol::Star;
+        diag_grid[8] = ScratchSymbol::Star;
+        // grid: Star Lemon Lemon / Lemon Star Lemon / Lemon Lemon Star
+        // diag1: [0,4,8] = Star → 中
+        // row2: [Lemon, Star, Lemon] → 不中
+        // 但 row1 和 row3 没有全 Lemon（因为有 Star 在角上）
+        // row1: [Star, Lemon, Lemon] → 不中
+        let (mult4, lines4) = evaluate_scratch_card(&diag_grid);
+        assert!(lines4.iter().any(|l| l.0 == "diag1"), "diag1 should win");
+        // diag1 中 Star(20×) 只有这一条
+        assert!(mult4 >= 20, "at least Star 20× for diag1");
     }
 
     // ──────────────────────────────────────────────────────────────────────
-    // 三公（San Gong）测试
+    // 斗牛（Bull Fight / Niu Niu）测试
     // ──────────────────────────────────────────────────────────────────────
 
     #[test]
-    fn test_play_sangong() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_play_bullfight() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        let user = "sangong_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let user = "bull_player";
+        let bet = 1_000_000u128;
+        let user_info = mock_info(user, &coins(bet, "uatom"));
 
-        let res = execute(
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            user_info.clone(),
-            ExecuteMsg::PlaySanGong {},
+            user_info,
+            ExecuteMsg::PlayBullFight { client_seed: None },
         )
         .unwrap();
 
         // 检查基本属性
-        let action = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "action")
-            .expect("action missing");
-        assert_eq!(action.value, "play_sangong");
-
-        assert!(
-            res.attributes.iter().any(|a| a.key == "player_cards"),
-            "player_cards missing"
-        );
-        assert!(
-            res.attributes.iter().any(|a| a.key == "dealer_cards"),
-            "dealer_cards missing"
-        );
-        assert!(
-            res.attributes.iter().any(|a| a.key == "player_hand_type"),
-            "player_hand_type missing"
-        );
-        assert!(
-            res.attributes.iter().any(|a| a.key == "dealer_hand_type"),
-            "dealer_hand_type missing"
-        );
-        assert!(
-            res.attributes.iter().any(|a| a.key == "player_points"),
-            "player_points missing"
-        );
-        assert!(
-            res.attributes.iter().any(|a| a.key == "dealer_points"),
-            "dealer_points missing"
+        assert_eq!(
+            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
+            "play_bullfight"
         );
+        assert!(res.attributes.iter().any(|a| a.key == "player_cards"));
+        assert!(res.attributes.iter().any(|a| a.key == "dealer_cards"));
+        assert!(res.attributes.iter().any(|a| a.key == "player_hand_type"));
+        assert!(res.attributes.iter().any(|a| a.key == "dealer_hand_type"));
+        assert!(res.attributes.iter().any(|a| a.key == "player_score"));
+        assert!(res.attributes.iter().any(|a| a.key == "dealer_score"));
 
-        let result = res
-            .attributes
-            .iter()
-            .find(|a| a.key == "result")
-            .expect("result missing");
+        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
 
         if result.value == "win" {
-            assert_eq!(res.messages.len(), 1, "should have payout message on win");
-            // 检查 payout_multiplier 属性存在
-            let mult = res
-                .attributes
-                .iter()
-                .find(|a| a.key == "payout_multiplier")
-                .expect("payout_multiplier missing");
-            let mult_val: u128 = mult.value.parse().unwrap();
-            assert!(
-                mult_val >= 2 && mult_val <= 3,
-                "multiplier should be 2 or 3, got {}",
-                mult_val
-            );
+            assert_eq!(res.messages.len(), 1);
+            let mult: u128 = res.attributes.iter()
+                .find(|a| a.key == "payout_multiplier").unwrap()
+                .value.parse().unwrap();
+            assert!((2..=8).contains(&mult), "multiplier should be 2-8, got {}", mult);
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(1_000_000 * mult_val, "uatom"),
+                    amount: coins(bet * mult, "uatom"),
                 }
                 .into()
             );
         } else if result.value == "tie" {
-            // 平局退还本金
-            assert_eq!(res.messages.len(), 1, "should refund on tie");
+            assert_eq!(res.messages.len(), 1);
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(1_000_000, "uatom"),
+                    amount: coins(bet, "uatom"),
                 }
                 .into()
             );
         } else {
             assert_eq!(result.value, "lose");
-            assert_eq!(res.messages.len(), 0, "should have no payout on loss");
+            assert_eq!(res.messages.len(), 0);
         }
     }
 
     #[test]
-    fn test_sangong_bet_limits() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_bullfight_bet_limits() {
+        let mut deps = funded_deps();
+        let msg = init_msg(None);
         let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
 
-        // 低于最小下注
-        let too_small = execute(
+        // 下注过小
+        let err1 = play(
             deps.as_mut(),
             mock_env(),
             mock_info("user", &coins(50_000, "uatom")),
-            ExecuteMsg::PlaySanGong {},
+            ExecuteMsg::PlayBullFight { client_seed: None },
         );
-        assert!(too_small.is_err(), "should reject bet below 100,000");
+        assert!(err1.is_err(), "should reject bet below 100,000");
 
-        // 高于最大下注
-        let too_big = execute(
+        // 下注过大
+        let err2 = play(
             deps.as_mut(),
             mock_env(),
             mock_info("user", &coins(20_000_000, "uatom")),
-            ExecuteMsg::PlaySanGong {},
+            ExecuteMsg::PlayBullFight { client_seed: None },
         );
-        assert!(too_big.is_err(), "should reject bet above 10,000,000");
+        assert!(err2.is_err(), "should reject bet above 10,000,000");
     }
 
     #[test]
-    fn test_sangong_hand_evaluation() {
-        use crate::sangong::{evaluate_sangong_hand, SanGongCard, SanGongHandType, Suit};
+    fn test_bullfight_hand_evaluation() {
+        use crate::bullfight::{evaluate_bull_hand, BullCard, BullHandType, Suit};
 
-        // 三公：三张全是公牌 (K, Q, J)
-        let san_gong = [
-            SanGongCard { rank: 13, suit: Suit::Spades },
-            SanGongCard { rank: 12, suit: Suit::Hearts },
-            SanGongCard { rank: 11, suit: Suit::Diamonds },
+        // ── 五小牛：A A A 2 3 → 全 ≤5，总和=1+1+1+2+3=8 ≤10 ──
+        let wu_xiao = [
+            BullCard { rank: 1, suit: Suit::Spades },
+            BullCard { rank: 1, suit: Suit::Hearts },
+            BullCard { rank: 1, suit: Suit::Diamonds },
+            BullCard { rank: 2, suit: Suit::Clubs },
+            BullCard { rank: 3, suit: Suit::Spades },
         ];
-        let rank = evaluate_sangong_hand(&san_gong);
-        assert_eq!(rank.hand_type, SanGongHandType::SanGong);
-        assert!(rank.score >= 30_000, "san gong should have highest score");
+        let r1 = evaluate_bull_hand(&wu_xiao);
+        assert_eq!(r1.hand_type, BullHandType::WuXiaoNiu);
+        assert!(r1.score >= 90_000);
 
-        // 混合九：含公牌且点数为 9（K + 4 + 5 = 0+4+5 = 9）
-        let mixed_nine = [
-            SanGongCard { rank: 13, suit: Suit::Spades },
-            SanGongCard { rank: 4, suit: Suit::Hearts },
-            SanGongCard { rank: 5, suit: Suit::Diamonds },
+        // ── 四炸：K K K K 5 ──
+        let si_zha = [
+            BullCard { rank: 13, suit: Suit::Spades },
+            BullCard { rank: 13, suit: Suit::Hearts },
+            BullCard { rank: 13, suit: Suit::Diamonds },
+            BullCard { rank: 13, suit: Suit::Clubs },
+            BullCard { rank: 5, suit: Suit::Spades },
         ];
-        let rank2 = evaluate_sangong_hand(&mixed_nine);
-        assert_eq!(rank2.hand_type, SanGongHandType::MixedNine);
-        assert!(rank2.score >= 20_000 && rank2.score < 30_000);
+        let r2 = evaluate_bull_hand(&si_zha);
+        assert!(matches!(r2.hand_type, BullHandType::SiZha { rank: 13 }));
+        assert!(r2.score >= 80_000);
 
-        // 普通 7 点 (3 + 2 + 2 = 7)
-        let normal = [
-            SanGongCard { rank: 3, suit: Suit::Spades },
-            SanGongCard { rank: 2, suit: Suit::Hearts },
-            SanGongCard { rank: 2, suit: Suit::Diamonds },
+        // ── 五花牛：J Q K J Q ──
+        let wu_hua = [
+            BullCard { rank: 11, suit: Suit::Spades },
+            BullCard { rank: 12, suit: Suit::Hearts },
+            BullCard { rank: 13, suit: Suit::Diamonds },
+            BullCard { rank: 11, suit: Suit::Clubs },
+            BullCard { rank: 12, suit: Suit::Diamonds },
         ];
-        let rank3 = evaluate_sangong_hand(&normal);
-        assert_eq!(rank3.hand_type, SanGongHandType::Normal { points: 7 });
-        assert!(rank3.score < 20_000);
+        let r3 = evaluate_bull_hand(&wu_hua);
+        assert_eq!(r3.hand_type, BullHandType::WuHuaNiu);
+        assert!(r3.score >= 70_000);
 
-        // 三公 > 混合九 > 普通
-        assert!(rank.score > rank2.score);
-        assert!(rank2.score > rank3.score);
+        // ── 牛牛：10 10 10 J K → 三张 10+10+10=30(10倍), 剩余 10+10=20(10倍) ──
+        let niu_niu = [
+            BullCard { rank: 10, suit: Suit::Spades },
+            BullCard { rank: 10, suit: Suit::Hearts },
+            BullCard { rank: 10, suit: Suit::Diamonds },
+            BullCard { rank: 11, suit: Suit::Clubs },
+            BullCard { rank: 13, suit: Suit::Spades },
+        ];
+        let r4 = evaluate_bull_hand(&niu_niu);
+        assert_eq!(r4.hand_type, BullHandType::NiuNiu);
+        assert!(r4.score >= 60_000);
+
+        // ── 牛 7：10 J 10 3 4 → 10+10+10=30(牛), 3+4=7 ──
+        let niu7 = [
+            BullCard { rank: 10, suit: Suit::Spades },
+            BullCard { rank: 11, suit: Suit::Hearts },
+            BullCard { rank: 10, suit: Suit::Diamonds },
+            BullCard { rank: 3, suit: Suit::Clubs },
+            BullCard { rank: 4, suit: Suit::Spades },
+        ];
+        let r5 = evaluate_bull_hand(&niu7);
+        assert_eq!(r5.hand_type, BullHandType::NiuN { n: 7 });
+        assert!(r5.score >= 50_000);
+
+        // ── 没牛：A 2 6 8 K → 无法选 3 张凑成 10 的倍数 ──
+        // 检查：1+2+6=9, 1+2+10=13, 1+6+8=15, 1+6+10=17, 1+8+10=19
+        //       2+6+8=16, 2+6+10=18, 2+8+10=20✓ → 有牛，剩余 1+6=7 → 牛7
+        // 用 A 3 6 8 K: 1+3+6=10✓ → 牛8+10=18%10=8 → 牛8
+        // 用另一组: A 2 3 6 8: 1+2+3=6, 1+2+6=9, 1+2+8=11, 1+3+6=10✓ → 牛 2+8=10%10=0 → 牛牛
+        // 真正没牛的例子：A 2 3 7 K: 1+2+3=6, 1+2+7=10✓ → 有牛，3+10=13%10=3 → 牛3
+        // 更好的没牛：A 2 4 7 K: 1+2+4=7, 1+2+7=10✓ → 牛 4+10=14%10=4
+        // 直接构造：3 4 6 7 8 → 3+4+6=13, 3+4+7=14, 3+4+8=15, 3+6+7=16, 3+6+8=17,
+        //                       3+7+8=18, 4+6+7=17, 4+6+8=18, 4+7+8=19, 6+7+8=21
+        // 没有 10 的倍数 → 没牛！
+        let no_niu = [
+            BullCard { rank: 3, suit: Suit::Spades },
+            BullCard { rank: 4, suit: Suit::Hearts },
+            BullCard { rank: 6, suit: Suit::Diamonds },
+            BullCard { rank: 7, suit: Suit::Clubs },
+            BullCard { rank: 8, suit: Suit::Spades },
+        ];
+        let r6 = evaluate_bull_hand(&no_niu);
+        assert_eq!(r6.hand_type, BullHandType::NoNiu);
+        assert!(r6.score < 50_000);
+
+        // ── 牌型强弱排序 ──
+        assert!(r1.score > r2.score, "五小牛 > 四炸");
+        assert!(r2.score > r3.score, "四炸 > 五花牛");
+        assert!(r3.score > r4.score, "五花牛 > 牛牛");
+        assert!(r4.score > r5.score, "牛牛 > 牛7");
+        assert!(r5.score > r6.score, "牛7 > 没牛");
+    }
+
+    #[test]
+    fn test_bullfight_payout_multipliers() {
+        use crate::bullfight::{bull_payout_multiplier, BullHandType};
+
+        assert_eq!(bull_payout_multiplier(&BullHandType::WuXiaoNiu), 8);
+        assert_eq!(bull_payout_multiplier(&BullHandType::SiZha { rank: 10 }), 7);
+        assert_eq!(bull_payout_multiplier(&BullHandType::WuHuaNiu), 6);
+        assert_eq!(bull_payout_multiplier(&BullHandType::NiuNiu), 4);
+        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 9 }), 3);
+        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 8 }), 3);
+        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 7 }), 2);
+        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 1 }), 2);
+        assert_eq!(bull_payout_multiplier(&BullHandType::NoNiu), 2);
+    }
+
+    // ──────────────────────────────────────────────────────────────────────
+    // 庄家抽水（House Edge）测试
+    // ──────────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_apply_house_edge_function() {
+        // 无抽水
+        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 0), 2_000_000);
+
+        // 5% 抽水（500 bps）：净赢利 1_000_000，抽水 50_000
+        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 500), 1_950_000);
+
+        // 10% 抽水（1000 bps）：净赢利 1_000_000，抽水 100_000
+        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 1000), 1_900_000);
+
+        // 1% 抽水（100 bps）：净赢利 1_000_000，抽水 10_000
+        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 100), 1_990_000);
+
+        // 平局退还本金时不抽水
+        assert_eq!(apply_house_edge(1_000_000, 1_000_000, 500), 1_000_000);
+
+        // 高倍率赢利：10x 赢利，5% 抽水
+        // gross=10_000_000, bet=1_000_000, net_winnings=9_000_000, rake=450_000
+        assert_eq!(apply_house_edge(10_000_000, 1_000_000, 500), 9_550_000);
+
+        // 0 payout 不受影响
+        assert_eq!(apply_house_edge(0, 0, 500), 0);
+    }
+
+    #[test]
+    fn test_init_with_house_edge() {
+        let mut deps = funded_deps();
+
+        // 使用 5% 抽水初始化
+        let msg = init_msg(Some(500));
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        assert_eq!(res.attributes[0].value, "init");
+        assert_eq!(res.attributes[3].value, "500"); // house_edge_bps
+
+        // 查询抽水比例
+        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
+        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
+        assert_eq!(resp.house_edge_bps, 500);
+    }
+
+    #[test]
+    fn test_init_with_default_house_edge() {
+        let mut deps = funded_deps();
+
+        // 不设置抽水（默认 0）
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
+        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
+        assert_eq!(resp.house_edge_bps, 0);
+    }
+
+    #[test]
+    fn test_init_house_edge_exceeds_max() {
+        let mut deps = funded_deps();
+
+        // 超过 10% 应失败
+        let msg = init_msg(Some(1001));
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg);
+        assert!(err.is_err(), "house edge > 1000 bps should be rejected");
     }
 
-    // ──────────────────────────────────────────────────────────────────────
-    // 骰宝（Sic Bo）测试
-    // ──────────────────────────────────────────────────────────────────────
+    #[test]
+    fn test_update_house_edge() {
+        let mut deps = funded_deps();
+
+        // 初始化合约，抽水 0
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // owner 修改抽水到 3%（300 bps）
+        let update_res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &coins(0, "uatom")),
+            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 300 },
+        ).unwrap();
+
+        assert_eq!(
+            update_res.attributes.iter().find(|a| a.key == "action").unwrap().value,
+            "update_house_edge"
+        );
+        assert_eq!(
+            update_res.attributes.iter().find(|a| a.key == "old_house_edge_bps").unwrap().value,
+            "0"
+        );
+        assert_eq!(
+            update_res.attributes.iter().find(|a| a.key == "new_house_edge_bps").unwrap().value,
+            "300"
+        );
+
+        // 验证查询结果
+        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
+        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
+        assert_eq!(resp.house_edge_bps, 300);
+    }
 
     #[test]
-    fn test_play_sicbo_big() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_update_house_edge_unauthorized() {
+        let mut deps = funded_deps();
 
-        let user = "sicbo_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = execute(
+        // 非 owner 尝试修改抽水
+        let err = play(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Big,
-            },
-        )
-        .unwrap();
+            mock_info("hacker", &coins(0, "uatom")),
+            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 500 },
+        );
+        assert!(err.is_err(), "non-owner should not be able to update house edge");
+    }
 
-        // 检查基本属性
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
-            "play_sicbo"
+    #[test]
+    fn test_update_house_edge_exceeds_max() {
+        let mut deps = funded_deps();
+
+        let msg = init_msg(None);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 尝试设置超过 10% 的抽水
+        let err = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &coins(0, "uatom")),
+            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 1001 },
         );
-        assert!(res.attributes.iter().any(|a| a.key == "die1"));
-        assert!(res.attributes.iter().any(|a| a.key == "die2"));
-        assert!(res.attributes.iter().any(|a| a.key == "die3"));
-        assert!(res.attributes.iter().any(|a| a.key == "total"));
-        assert!(res.attributes.iter().any(|a| a.key == "is_triple"));
+        assert!(err.is_err(), "house edge > 1000 bps should be rejected");
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        let total: u8 = res.attributes.iter().find(|a| a.key == "total").unwrap().value.parse().unwrap();
-        let is_triple: bool = res.attributes.iter().find(|a| a.key == "is_triple").unwrap().value.parse().unwrap();
+        // 设置最大值 1000 应成功
+        let ok = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &coins(0, "uatom")),
+            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 1000 },
+        );
+        assert!(ok.is_ok(), "house edge 1000 bps (10%) should be accepted");
 
-        if result.value == "win" {
-            assert!(total >= 11 && total <= 17 && !is_triple, "Big win requires total 11-17, no triple");
-            assert_eq!(res.messages.len(), 1);
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(2_000_000, "uatom"),
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
-        }
+        // 设置 0 应成功
+        let ok2 = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &coins(0, "uatom")),
+            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 0 },
+        );
+        assert!(ok2.is_ok(), "house edge 0 bps should be accepted");
     }
 
     #[test]
-    fn test_play_sicbo_small() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_coin_flip_with_house_edge() {
+        let mut deps = funded_deps();
 
-        let user = "sicbo_small_player";
-        let user_info = mock_info(user, &coins(500_000, "uatom"));
+        // 初始化合约，5% 抽水
+        let msg = init_msg(Some(500));
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = execute(
+        let user = "player1";
+        let bet = 1_000_000u128;
+        let user_info = mock_info(user, &coins(bet, "uatom"));
+
+        let res = play(
             deps.as_mut(),
             mock_env(),
             user_info,
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Small,
-            },
-        )
-        .unwrap();
+            ExecuteMsg::PlayCoinFlip { choice: CoinSide::Heads, client_seed: None },
+        ).unwrap();
 
         let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        let total: u8 = res.attributes.iter().find(|a| a.key == "total").unwrap().value.parse().unwrap();
-        let is_triple: bool = res.attributes.iter().find(|a| a.key == "is_triple").unwrap().value.parse().unwrap();
 
         if result.value == "win" {
-            assert!(total >= 4 && total <= 10 && !is_triple, "Small win requires total 4-10, no triple");
+            // 5% 抽水：gross=2_000_000, net_winnings=1_000_000, rake=50_000
+            // 实际支付 = 2_000_000 - 50_000 = 1_950_000
+            let payout_attr = res.attributes.iter().find(|a| a.key == "payout").unwrap();
+            let payout_val: u128 = payout_attr.value.parse().unwrap();
+            assert_eq!(payout_val, 1_950_000, "payout should reflect 5% house edge");
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
                     to_address: user.to_string(),
-                    amount: coins(1_000_000, "uatom"),
-                }
-                .into()
+                    amount: coins(1_950_000, "uatom"),
+                }.into()
             );
         } else {
             assert_eq!(res.messages.len(), 0);
@@ -4368,38 +8036,41 @@ mod tests {
     }
 
     #[test]
-    fn test_play_sicbo_total() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_war_with_house_edge() {
+        let mut deps = funded_deps();
 
-        let user = "sicbo_total_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        // 初始化合约，10% 抽水
+        let msg = init_msg(Some(1000));
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = execute(
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Total { value: 10 },
-            },
-        )
-        .unwrap();
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayWar { client_seed: None },
+        ).unwrap();
 
         let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        let total: u8 = res.attributes.iter().find(|a| a.key == "total").unwrap().value.parse().unwrap();
 
         if result.value == "win" {
-            assert_eq!(total, 10);
-            // 总和 10 赔率 7×
+            // 10% 抽水：gross=2_000_000, net_winnings=1_000_000, rake=100_000
+            // 实际支付 = 2_000_000 - 100_000 = 1_900_000
             assert_eq!(
                 res.messages[0].msg,
                 BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(7_000_000, "uatom"),
-                }
-                .into()
+                    to_address: "user".to_string(),
+                    amount: coins(1_900_000, "uatom"),
+                }.into()
+            );
+        } else if result.value == "tie" {
+            // 平局不抽水
+            assert_eq!(
+                res.messages[0].msg,
+                BankMsg::Send {
+                    to_address: "user".to_string(),
+                    amount: coins(1_000_000, "uatom"),
+                }.into()
             );
         } else {
             assert_eq!(res.messages.len(), 0);
@@ -4407,1293 +8078,3547 @@ mod tests {
     }
 
     #[test]
-    fn test_play_sicbo_any_triple() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_house_edge_locked_amount_accounting() {
+        let mut deps = funded_deps();
 
-        let user = "sicbo_triple_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        // 初始化合约，5% 抽水
+        let msg = init_msg(Some(500));
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = execute(
+        let initial_locked = 10_000_000_000u128;
+
+        // 玩硬币翻转游戏
+        let bet = 1_000_000u128;
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::AnyTriple,
-            },
-        )
-        .unwrap();
+            mock_info("player", &coins(bet, "uatom")),
+            ExecuteMsg::PlayCoinFlip { choice: CoinSide::Heads, client_seed: None },
+        ).unwrap();
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-        let is_triple: bool = res.attributes.iter().find(|a| a.key == "is_triple").unwrap().value.parse().unwrap();
+        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
+
+        // 查询锁仓金额
+        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetLockedAmount { denom: None }).unwrap();
+        let resp: LockedAmountResponse = from_json(&bin).unwrap();
 
         if result.value == "win" {
-            assert!(is_triple, "AnyTriple win requires all three dice the same");
+            // 赢了：locked = initial + bet - payout
+            // payout = 1_950_000（扣除 5% 抽水后）
+            // locked = 10_000_000_000 + 1_000_000 - 1_950_000 = 10_000_050_000
+            // 庄家额外多留了 50_000 的抽水
             assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(31_000_000, "uatom"),
-                }
-                .into()
+                resp.locked_amount.u128(),
+                initial_locked + bet - 1_950_000,
+                "locked amount should reflect house edge"
             );
         } else {
-            assert_eq!(res.messages.len(), 0);
+            // 输了：locked = initial + bet
+            assert_eq!(
+                resp.locked_amount.u128(),
+                initial_locked + bet,
+                "locked amount should include lost bet"
+            );
         }
     }
 
     #[test]
-    fn test_play_sicbo_single_die() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_query_house_edge() {
+        let mut deps = funded_deps();
 
-        let user = "sicbo_single_player";
-        let bet = 1_000_000u128;
-        let user_info = mock_info(user, &coins(bet, "uatom"));
+        // 初始化合约
+        let msg = init_msg(Some(250));
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = execute(
+        // 查询
+        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
+        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
+        assert_eq!(resp.house_edge_bps, 250);
+
+        // 更新后再查询
+        play(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::SingleDie { number: 3 },
-            },
-        )
-        .unwrap();
+            mock_info("creator", &coins(0, "uatom")),
+            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 800 },
+        ).unwrap();
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        let bin2 = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
+        let resp2: HouseEdgeResponse = from_json(&bin2).unwrap();
+        assert_eq!(resp2.house_edge_bps, 800);
+    }
 
-        if result.value == "win" {
-            let payout_mult: u128 = res.attributes.iter()
-                .find(|a| a.key == "payout_multiplier")
-                .unwrap()
-                .value.parse().unwrap();
-            assert!(payout_mult >= 2 && payout_mult <= 4, "single die multiplier 2-4");
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(bet * payout_mult, "uatom"),
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
+    // ──────────────────────────────────────────────────────────────────────
+    // 承诺-揭示（Commit–Reveal）测试
+    // ──────────────────────────────────────────────────────────────────────
+
+    fn coin_flip_msg() -> ExecuteMsg {
+        ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: Some("lucky".to_string()),
         }
     }
 
     #[test]
-    fn test_play_sicbo_combo() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
-
-        let user = "sicbo_combo_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+    fn test_bet_pending_until_reveal() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
+        // 下注：只锁仓，不开奖
         let res = execute(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Combo { first: 1, second: 6 },
-            },
+            mock_info("user", &coins(1_000_000, "uatom")),
+            coin_flip_msg(),
         )
         .unwrap();
+        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.attributes[0], attr("action", "place_bet"));
+        assert_eq!(res.attributes[1], attr("bet_id", "1"));
+        assert_eq!(
+            res.attributes[5],
+            attr("seed_hash", hash_server_seed(&test_server_seed(1)))
+        );
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        let locked: LockedAmountResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetLockedAmount { denom: None }).unwrap())
+                .unwrap();
+        assert_eq!(locked.locked_amount, Uint128::from(10_001_000_000u128));
 
-        if result.value == "win" {
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(7_000_000, "uatom"),
-                }
+        let bet: BetRecord =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetBet { bet_id: 1 }).unwrap())
+                .unwrap();
+        assert_eq!(bet.player, Addr::unchecked("user"));
+        assert_eq!(bet.client_seed, "lucky");
+        assert_eq!(bet.entropy, BetEntropy::Commitment { id: 1 });
+        assert!(!bet.settled);
+
+        // 种子未揭示前不能结算
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::SettleBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Server seed not revealed yet"));
+    }
+
+    #[test]
+    fn test_bet_in_reveal_block_refunded() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+
+        // 与揭示同一区块、排在揭示之前的下注：结算时原路退还，不开奖
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, coin_flip_msg()).unwrap();
+        let reveal = ExecuteMsg::RevealServerSeed {
+            server_seed: test_server_seed(1),
+            next_seed_hash: hash_server_seed(&test_server_seed(2)),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), reveal).unwrap();
+        let settle = ExecuteMsg::SettleBet { bet_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), settle).unwrap();
+        assert_eq!(attr_value(&res, "action"), "refund_bet");
+        assert_eq!(attr_value(&res, "reason"), "placed_in_reveal_block");
+        assert_eq!(event_attr(settled_event(&res), "outcome"), "\"refund\"");
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send { to_address: "user".to_string(), amount: coins(1_000_000, "uatom") }
                 .into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
-        }
+        );
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::zero());
+        let settle = ExecuteMsg::SettleBet { bet_id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), settle).unwrap_err();
+        assert_eq!(err, ContractError::BetAlreadySettled);
     }
 
     #[test]
-    fn test_sicbo_bet_validation() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_reveal_server_seed_validation() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        // 总和超出范围
+        let next_seed_hash = hash_server_seed(&test_server_seed(2));
+
+        // 非所有者不能揭示
         let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Total { value: 20 },
+            mock_info("user", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(1),
+                next_seed_hash: next_seed_hash.clone(),
             },
-        );
-        assert!(err.is_err(), "should reject total > 17");
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
 
-        let err2 = execute(
+        // 种子与承诺不符
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Total { value: 2 },
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: "forged_seed".to_string(),
+                next_seed_hash: next_seed_hash.clone(),
             },
-        );
-        assert!(err2.is_err(), "should reject total < 4");
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("does not match commitment"));
 
-        // 骰子点数超出范围
-        let err3 = execute(
+        // 下一轮承诺格式非法
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::SingleDie { number: 7 },
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(1),
+                next_seed_hash: "not_a_hash".to_string(),
             },
-        );
-        assert!(err3.is_err(), "should reject die number > 6");
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("64-character hex"));
 
-        // 两骰组合相同数字
-        let err4 = execute(
+        // 正确揭示后轮换到下一个承诺
+        execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Combo { first: 3, second: 3 },
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(1),
+                next_seed_hash: next_seed_hash.clone(),
             },
-        );
-        assert!(err4.is_err(), "should reject combo with same numbers");
+        )
+        .unwrap();
 
-        // 下注金额过小
-        let err5 = execute(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("user", &coins(50_000, "uatom")),
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Big,
-            },
-        );
-        assert!(err5.is_err(), "should reject bet below 100,000");
+        let revealed: SeedCommitment = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetSeedCommitment { id: Some(1) }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(revealed.server_seed, Some(test_server_seed(1)));
 
-        // 下注金额过大
-        let err6 = execute(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("user", &coins(20_000_000, "uatom")),
-            ExecuteMsg::PlaySicBo {
-                bet_type: SicBoBetType::Big,
-            },
-        );
-        assert!(err6.is_err(), "should reject bet above 10,000,000");
+        let active: SeedCommitment = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetSeedCommitment { id: None }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(active.id, 2);
+        assert_eq!(active.seed_hash, next_seed_hash);
+        assert_eq!(active.server_seed, None);
     }
 
     #[test]
-    fn test_sicbo_payout_logic() {
-        use crate::sicbo::{calculate_sicbo_payout, SicBoBetType, SicBoResult};
-
-        // 大：总和 12，非三同号 → 赢
-        let r = SicBoResult::new(4, 4, 4); // triple 12
-        let (won, _) = calculate_sicbo_payout(&SicBoBetType::Big, &r);
-        assert!(!won, "triple should lose Big bet");
-
-        let r2 = SicBoResult::new(3, 4, 5); // total 12, no triple
-        let (won2, mult2) = calculate_sicbo_payout(&SicBoBetType::Big, &r2);
-        assert!(won2, "total 12 no triple should win Big");
-        assert_eq!(mult2, 2);
-
-        // 小：总和 8，非三同号 → 赢
-        let r3 = SicBoResult::new(1, 3, 4); // total 8
-        let (won3, mult3) = calculate_sicbo_payout(&SicBoBetType::Small, &r3);
-        assert!(won3);
-        assert_eq!(mult3, 2);
-
-        // 单双
-        let r4 = SicBoResult::new(1, 2, 4); // total 7 (odd)
-        let (odd_won, _) = calculate_sicbo_payout(&SicBoBetType::Odd, &r4);
-        assert!(odd_won);
-        let (even_won, _) = calculate_sicbo_payout(&SicBoBetType::Even, &r4);
-        assert!(!even_won);
-
-        // 指定三同号
-        let r5 = SicBoResult::new(6, 6, 6);
-        let (triple_won, triple_mult) = calculate_sicbo_payout(
-            &SicBoBetType::SpecificTriple { number: 6 },
-            &r5,
-        );
-        assert!(triple_won);
-        assert_eq!(triple_mult, 181);
-
-        let (wrong_triple, _) = calculate_sicbo_payout(
-            &SicBoBetType::SpecificTriple { number: 5 },
-            &r5,
-        );
-        assert!(!wrong_triple);
-
-        // 双骰
-        let r6 = SicBoResult::new(3, 3, 5);
-        let (double_won, double_mult) = calculate_sicbo_payout(
-            &SicBoBetType::DoubleBet { number: 3 },
-            &r6,
-        );
-        assert!(double_won);
-        assert_eq!(double_mult, 12);
-
-        // 单骰：出现 1 次
-        let r7 = SicBoResult::new(2, 4, 6);
-        let (single_won, single_mult) = calculate_sicbo_payout(
-            &SicBoBetType::SingleDie { number: 4 },
-            &r7,
-        );
-        assert!(single_won);
-        assert_eq!(single_mult, 2);
+    fn test_settle_bet_is_reproducible() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        // 单骰：出现 2 次
-        let r8 = SicBoResult::new(4, 4, 6);
-        let (s2_won, s2_mult) = calculate_sicbo_payout(
-            &SicBoBetType::SingleDie { number: 4 },
-            &r8,
-        );
-        assert!(s2_won);
-        assert_eq!(s2_mult, 3);
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            coin_flip_msg(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "coin_flip"));
 
-        // 两骰组合
-        let r9 = SicBoResult::new(1, 3, 6);
-        let (combo_won, combo_mult) = calculate_sicbo_payout(
-            &SicBoBetType::Combo { first: 1, second: 6 },
-            &r9,
-        );
-        assert!(combo_won);
-        assert_eq!(combo_mult, 7);
+        // 任何人都可以用揭示的种子复算结果
+        let seed = derive_bet_seed(&test_server_seed(1), "lucky", &Addr::unchecked("user"), 1, 1);
+        let expected = if RngStream::new(&seed, b"coin_flip").below(2) == 0 {
+            CoinSide::Heads
+        } else {
+            CoinSide::Tails
+        };
+        let actual = res.attributes.iter().find(|a| a.key == "actual_result").unwrap();
+        assert_eq!(actual.value, format!("{:?}", expected));
+        assert!(res.attributes.contains(&attr("server_seed", test_server_seed(1))));
 
-        let (combo_fail, _) = calculate_sicbo_payout(
-            &SicBoBetType::Combo { first: 2, second: 5 },
-            &r9,
-        );
-        assert!(!combo_fail);
+        // 重复结算被拒绝
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::SettleBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Bet already settled"));
     }
 
-    // ──────────────────────────────────────────────────────────────────────
-    // 基诺（Keno）测试
-    // ──────────────────────────────────────────────────────────────────────
-
     #[test]
-    fn test_play_keno_basic() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
-
-        let user = "keno_player";
-        let bet = 1_000_000u128;
-        let user_info = mock_info(user, &coins(bet, "uatom"));
+    fn test_refund_bet_after_reveal_timeout() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        // 选 5 个号码
-        let res = execute(
+        execute(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlayKeno {
-                picks: vec![3, 17, 28, 42, 65],
-            },
+            mock_info("user", &coins(1_000_000, "uatom")),
+            coin_flip_msg(),
         )
         .unwrap();
 
-        // 检查基本属性
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
-            "play_keno"
-        );
-        assert!(res.attributes.iter().any(|a| a.key == "picks"));
-        assert!(res.attributes.iter().any(|a| a.key == "drawn"));
-        assert!(res.attributes.iter().any(|a| a.key == "hits"));
-        assert!(res.attributes.iter().any(|a| a.key == "pick_count"));
-        assert!(res.attributes.iter().any(|a| a.key == "hit_count"));
-
-        let pick_count: u8 = res
-            .attributes.iter().find(|a| a.key == "pick_count").unwrap()
-            .value.parse().unwrap();
-        assert_eq!(pick_count, 5);
-
-        let hit_count: u8 = res
-            .attributes.iter().find(|a| a.key == "hit_count").unwrap()
-            .value.parse().unwrap();
-        assert!(hit_count <= 5);
-
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
-
-        if result.value == "win" {
-            assert!(res.messages.len() == 1);
-            let mult: u128 = res.attributes.iter()
-                .find(|a| a.key == "payout_multiplier").unwrap()
-                .value.parse().unwrap();
-            assert!(mult > 0);
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(bet * mult, "uatom"),
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(result.value, "lose");
-            assert_eq!(res.messages.len(), 0);
-        }
-    }
+        // 超时前不能退款
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &[]),
+            ExecuteMsg::RefundBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("can be refunded from block"));
 
-    #[test]
-    fn test_play_keno_single_pick() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+        let mut late_env = mock_env();
+        late_env.block.height += REVEAL_TIMEOUT_BLOCKS;
 
-        let user = "keno_single_player";
-        let user_info = mock_info(user, &coins(500_000, "uatom"));
+        // 只有下注玩家可以退款
+        let err = execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("someone_else", &[]),
+            ExecuteMsg::RefundBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
 
-        // 只选 1 个号码
         let res = execute(
             deps.as_mut(),
-            mock_env(),
-            user_info,
-            ExecuteMsg::PlayKeno {
-                picks: vec![42],
-            },
+            late_env.clone(),
+            mock_info("user", &[]),
+            ExecuteMsg::RefundBet { bet_id: 1 },
         )
         .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "user".to_string(),
+                amount: coins(1_000_000, "uatom"),
+            }
+            .into()
+        );
+        // 退款同样发出结算事件，outcome 为 refund
+        let event = settled_event(&res);
+        assert_eq!(event_attr(event, "game"), "coin_flip");
+        assert_eq!(event_attr(event, "bet_id"), "1");
+        assert_eq!(event_attr(event, "wager"), "1000000");
+        assert_eq!(event_attr(event, "payout"), "1000000");
+        assert_eq!(event_attr(event, "house_rake"), "0");
+        let outcome: String = from_json(event_attr(event, "outcome")).unwrap();
+        assert_eq!(outcome, REFUND_OUTCOME);
+
+        let locked: LockedAmountResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetLockedAmount { denom: None }).unwrap())
+                .unwrap();
+        assert_eq!(locked.locked_amount, Uint128::from(10_000_000_000u128));
 
-        let hit_count: u8 = res
-            .attributes.iter().find(|a| a.key == "hit_count").unwrap()
-            .value.parse().unwrap();
-        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
-
-        if hit_count == 1 {
-            assert_eq!(result.value, "win");
-            // 选1中1 赔率 3×
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(1_500_000, "uatom"),
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(result.value, "lose");
-        }
+        // 退款后即使揭示种子也不能再结算
+        execute(
+            deps.as_mut(),
+            late_env.clone(),
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(1),
+                next_seed_hash: hash_server_seed(&test_server_seed(2)),
+            },
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            late_env,
+            mock_info("keeper", &[]),
+            ExecuteMsg::SettleBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Bet already settled"));
     }
 
     #[test]
-    fn test_play_keno_max_picks() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(100_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_player_nonce_increments_per_bet() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        let user = "keno_max_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        let nonce_of = |deps: Deps, address: &str| -> u64 {
+            let res: PlayerNonceResponse = from_json(
+                query(deps, mock_env(), QueryMsg::GetPlayerNonce { address: address.to_string() })
+                    .unwrap(),
+            )
+            .unwrap();
+            res.nonce
+        };
+        assert_eq!(nonce_of(deps.as_ref(), "user"), 0);
+
+        // 同一区块内两笔完全相同的下注
+        for expected_nonce in 1..=2u64 {
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("user", &coins(1_000_000, "uatom")),
+                coin_flip_msg(),
+            )
+            .unwrap();
+            assert!(res.attributes.contains(&attr("nonce", expected_nonce.to_string())));
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &coins(1_000_000, "uatom")),
+            coin_flip_msg(),
+        )
+        .unwrap();
+        assert_eq!(nonce_of(deps.as_ref(), "user"), 2);
+        assert_eq!(nonce_of(deps.as_ref(), "other"), 1);
 
-        // 选 10 个号码
-        let res = execute(
+        execute(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlayKeno {
-                picks: vec![1, 10, 20, 30, 40, 50, 60, 70, 75, 80],
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(1),
+                next_seed_hash: hash_server_seed(&test_server_seed(2)),
             },
         )
         .unwrap();
 
-        let pick_count: u8 = res
-            .attributes.iter().find(|a| a.key == "pick_count").unwrap()
-            .value.parse().unwrap();
-        assert_eq!(pick_count, 10);
-
-        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert!(result.value == "win" || result.value == "lose");
+        // 序号混入种子：两笔相同下注得到不同的本局种子
+        let first = verify_outcome_query(deps.as_ref(), 1);
+        let second = verify_outcome_query(deps.as_ref(), 2);
+        assert_eq!((first.nonce, second.nonce), (1, 2));
+        assert_ne!(first.seed, second.seed);
+        let expected =
+            derive_bet_seed(&test_server_seed(1), "lucky", &Addr::unchecked("user"), 2, 2);
+        assert_eq!(second.seed.as_slice(), expected.as_slice());
     }
 
     #[test]
-    fn test_keno_validation() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_commit_server_seed() {
+        let mut deps = funded_deps();
+        let msg = InstantiateMsg {
+            house_edge_bps: None,
+            server_seed_hash: None,
+            drand: None,
+            eoa_only: None,
+            denoms: None,
+            max_payout_bps: None,
+            timelock: None,
+            game_timeout: None,
+            blackjack_rules: None,
+        };
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // 空选号
-        let err1 = execute(
+        // 没有承诺时不能下注
+        let err = execute(
             deps.as_mut(),
             mock_env(),
             mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlayKeno { picks: vec![] },
-        );
-        assert!(err1.is_err(), "should reject empty picks");
+            coin_flip_msg(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("No active server seed commitment"));
 
-        // 超过 10 个号码
-        let err2 = execute(
+        let seed_hash = hash_server_seed(&test_server_seed(1));
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlayKeno {
-                picks: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
-            },
-        );
-        assert!(err2.is_err(), "should reject more than 10 picks");
+            mock_info("user", &[]),
+            ExecuteMsg::CommitServerSeed { seed_hash: seed_hash.clone() },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
 
-        // 号码超出范围
-        let err3 = execute(
+        let res = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlayKeno { picks: vec![0, 42] },
-        );
-        assert!(err3.is_err(), "should reject number 0");
+            mock_info("creator", &[]),
+            ExecuteMsg::CommitServerSeed { seed_hash: seed_hash.clone() },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1], attr("commitment_id", "1"));
+
+        // 已有生效承诺时只能通过揭示轮换
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            ExecuteMsg::CommitServerSeed { seed_hash },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("An active commitment exists"));
 
-        let err4 = execute(
+        let res = play(
             deps.as_mut(),
             mock_env(),
             mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlayKeno { picks: vec![81] },
+            coin_flip_msg(),
+        )
+        .unwrap();
+        assert!(res.attributes.contains(&attr("bet_id", "1")));
+    }
+
+    // ──────────────────────────────────────────────────────────────────────
+    // drand 信标测试（本地模拟信标：固定私钥签名，与 quicknet 相同的签名方案）
+    // ──────────────────────────────────────────────────────────────────────
+
+    use crate::drand::{DrandConfig, QUICKNET_GENESIS_TIME};
+    use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+    use bls12_381::{G1Affine, G1Projective, G2Affine, Scalar};
+    use sha2::{Digest, Sha256};
+
+    fn mock_beacon_secret() -> Scalar {
+        Scalar::from(0x5eed_u64)
+    }
+
+    /// 模拟信标：创世时间早于 mock_env 区块时间，3 秒一轮
+    fn mock_drand_config() -> DrandConfig {
+        let pubkey = G2Affine::from(G2Affine::generator() * mock_beacon_secret());
+        DrandConfig {
+            pubkey: HexBinary::from(pubkey.to_compressed().to_vec()),
+            genesis_time: mock_env().block.time.seconds() - 300,
+            period: 3,
+        }
+    }
+
+    /// 模拟信标对某轮次的签名：H(sha256(round_be)) * sk，G1 压缩点
+    fn mock_beacon_signature(round: u64) -> HexBinary {
+        let msg = Sha256::digest(&round.to_be_bytes());
+        let point = <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
+            msg,
+            b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_",
         );
-        assert!(err4.is_err(), "should reject number > 80");
+        let signature = G1Affine::from(point * mock_beacon_secret());
+        HexBinary::from(signature.to_compressed().to_vec())
+    }
 
-        // 重复号码
-        let err5 = execute(
+    fn instantiate_with_drand(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            drand: Some(mock_drand_config()),
+            ..init_msg(None)
+        };
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps, mock_env(), info, msg).unwrap();
+    }
+
+    #[test]
+    fn test_drand_round_after() {
+        let config = DrandConfig::quicknet();
+        // 第 1 轮在创世时间发布
+        assert_eq!(config.round_after(0), 1);
+        assert_eq!(config.round_after(QUICKNET_GENESIS_TIME - 1), 1);
+        assert_eq!(config.round_after(QUICKNET_GENESIS_TIME), 2);
+        assert_eq!(config.round_after(QUICKNET_GENESIS_TIME + 2), 2);
+        assert_eq!(config.round_after(QUICKNET_GENESIS_TIME + 3), 3);
+    }
+
+    #[test]
+    fn test_drand_verify_quicknet_round() {
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let signature = HexBinary::from_hex(
+            "b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92",
+        )
+        .unwrap();
+        let config = DrandConfig::quicknet();
+        let randomness = config.verify(123, signature.as_slice()).unwrap();
+        assert_eq!(randomness, <[u8; 32]>::from(Sha256::digest(signature.as_slice())));
+
+        // 同一签名不能冒充其它轮次
+        let err = config.verify(124, signature.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("Invalid drand signature for round 124"));
+    }
+
+    #[test]
+    fn test_drand_invalid_config_rejected() {
+        let mut deps = funded_deps();
+        let msg = InstantiateMsg {
+            drand: Some(DrandConfig {
+                pubkey: HexBinary::from(vec![0u8; 96]),
+                ..DrandConfig::quicknet()
+            }),
+            ..init_msg(None)
+        };
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(err.to_string().contains("Invalid drand pubkey"));
+    }
+
+    #[test]
+    fn test_drand_bet_flow() {
+        let mut deps = funded_deps();
+        instantiate_with_drand(deps.as_mut());
+
+        let config = mock_drand_config();
+        let expected_round =
+            config.round_after(mock_env().block.time.seconds() + BEACON_SAFETY_MARGIN_SECS);
+
+        // 下注锁定未来的 drand 轮次
+        let res = execute(
             deps.as_mut(),
             mock_env(),
             mock_info("user", &coins(1_000_000, "uatom")),
-            ExecuteMsg::PlayKeno { picks: vec![5, 5, 10] },
+            coin_flip_msg(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "place_bet"));
+        assert_eq!(res.attributes[4], attr("drand_round", expected_round.to_string()));
+
+        let bet: BetRecord =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetBet { bet_id: 1 }).unwrap())
+                .unwrap();
+        assert_eq!(bet.entropy, BetEntropy::Drand { round: expected_round });
+
+        let pending: Vec<PendingBeaconBets> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetPendingBeaconBets { address: "user".to_string() },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            pending,
+            vec![PendingBeaconBets { round: expected_round, bet_ids: vec![1] }]
         );
-        assert!(err5.is_err(), "should reject duplicate numbers");
 
-        // 下注金额过小
-        let err6 = execute(
+        // 信标未提交前不能结算
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(50_000, "uatom")),
-            ExecuteMsg::PlayKeno { picks: vec![1] },
-        );
-        assert!(err6.is_err(), "should reject bet below 100,000");
+            mock_info("keeper", &[]),
+            ExecuteMsg::SettleBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not submitted yet"));
 
-        // 下注金额过大
-        let err7 = execute(
+        // drand 下注不能退款
+        let mut late_env = mock_env();
+        late_env.block.height += REVEAL_TIMEOUT_BLOCKS;
+        let err = execute(
+            deps.as_mut(),
+            late_env,
+            mock_info("user", &[]),
+            ExecuteMsg::RefundBet { bet_id: 1 },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Drand bets cannot be refunded"));
+
+        // 其它轮次的签名被拒绝
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(20_000_000, "uatom")),
-            ExecuteMsg::PlayKeno { picks: vec![1] },
-        );
-        assert!(err7.is_err(), "should reject bet above 10,000,000");
-    }
+            mock_info("keeper", &[]),
+            ExecuteMsg::SubmitBeacon {
+                round: expected_round,
+                signature: mock_beacon_signature(expected_round + 1),
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Invalid drand signature"));
 
-    #[test]
-    fn test_keno_payout_table() {
-        use crate::keno::keno_payout_multiplier;
+        let signature = mock_beacon_signature(expected_round);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::SubmitBeacon { round: expected_round, signature: signature.clone() },
+        )
+        .unwrap();
 
-        // 选1
-        assert_eq!(keno_payout_multiplier(1, 0), 0);
-        assert_eq!(keno_payout_multiplier(1, 1), 3);
+        // 同一轮次只能提交一次
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::SubmitBeacon { round: expected_round, signature: signature.clone() },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("already submitted"));
 
-        // 选3
-        assert_eq!(keno_payout_multiplier(3, 0), 0);
-        assert_eq!(keno_payout_multiplier(3, 1), 0);
-        assert_eq!(keno_payout_multiplier(3, 2), 2);
-        assert_eq!(keno_payout_multiplier(3, 3), 16);
+        let randomness: Option<HexBinary> = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetBeacon { round: expected_round })
+                .unwrap(),
+        )
+        .unwrap();
+        let randomness = randomness.unwrap();
+        assert_eq!(randomness.as_slice(), &Sha256::digest(signature.as_slice())[..]);
 
-        // 选5
-        assert_eq!(keno_payout_multiplier(5, 2), 0);
-        assert_eq!(keno_payout_multiplier(5, 3), 2);
-        assert_eq!(keno_payout_multiplier(5, 4), 12);
-        assert_eq!(keno_payout_multiplier(5, 5), 50);
+        // 任何人都可以结算，结果可由 drand 随机数复算
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::SettleBet { bet_id: 1 },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "coin_flip"));
+        assert!(res.attributes.contains(&attr("drand_randomness", randomness.to_hex())));
 
-        // 选10
-        assert_eq!(keno_payout_multiplier(10, 4), 0);
-        assert_eq!(keno_payout_multiplier(10, 5), 2);
-        assert_eq!(keno_payout_multiplier(10, 10), 2000);
+        let seed = derive_bet_seed(&randomness.to_hex(), "lucky", &Addr::unchecked("user"), 1, 1);
+        let expected = if RngStream::new(&seed, b"coin_flip").below(2) == 0 {
+            CoinSide::Heads
+        } else {
+            CoinSide::Tails
+        };
+        let actual = res.attributes.iter().find(|a| a.key == "actual_result").unwrap();
+        assert_eq!(actual.value, format!("{:?}", expected));
+
+        // 结算后从待开奖索引移除
+        let pending: Vec<PendingBeaconBets> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetPendingBeaconBets { address: "user".to_string() },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(pending.is_empty());
     }
 
     #[test]
-    fn test_keno_draw_no_duplicates() {
-        // 验证开出的 20 个号码无重复且在 1-80 范围内
-        let info = mock_info("test_user", &coins(1_000_000, "uatom"));
-        let env = mock_env();
-        let drawn = draw_keno_numbers(&info, &env);
-
-        assert_eq!(drawn.len(), 20, "should draw exactly 20 numbers");
-
-        for &n in &drawn {
-            assert!(n >= 1 && n <= 80, "number {} out of range", n);
-        }
-
-        // 检查无重复
-        let mut unique = drawn.clone();
-        unique.sort_unstable();
-        unique.dedup();
-        assert_eq!(unique.len(), 20, "drawn numbers should be unique");
+    fn test_drand_single_shot_games_settle() {
+        let mut deps = funded_deps();
+        instantiate_with_drand(deps.as_mut());
 
-        // 验证已排序
-        let mut sorted = drawn.clone();
-        sorted.sort_unstable();
-        assert_eq!(drawn, sorted, "drawn numbers should be sorted");
-    }
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayRoulette {
+                bet_type: RouletteBetType::Color { color: Color::Red },
+                client_seed: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "play_roulette"));
 
-    // ──────────────────────────────────────────────────────────────────────
-    // 刮刮乐（Scratch Card）测试
-    // ──────────────────────────────────────────────────────────────────────
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayKeno {
+                picks: vec![1, 2, 3, 4, 5],
+                client_seed: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "play_keno"));
+    }
 
     #[test]
-    fn test_play_scratch_card_classic() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
-
-        let user = "scratch_player";
-        let bet = 500_000u128;
-        let user_info = mock_info(user, &coins(bet, "uatom"));
+    fn test_drand_multi_step_games_use_commitment() {
+        let mut deps = funded_deps();
+        instantiate_with_drand(deps.as_mut());
 
         let res = execute(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Classic,
+            mock_info("user", &coins(1_000_000, "uatom")),
+            ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Start,
+                client_seed: None,
+                side_bets: None,
             },
         )
         .unwrap();
+        assert_eq!(res.attributes[4], attr("commitment_id", "1"));
+        assert!(!res.attributes.iter().any(|a| a.key == "drand_round"));
+    }
 
-        // 检查基本属性
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
-            "play_scratch_card"
-        );
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "card_type").unwrap().value,
-            "Classic"
-        );
-
-        // 检查 3×3 = 9 个格子都有输出
-        for row in 1..=3usize {
-            for col in 1..=3usize {
-                let key = format!("cell_{}_{}", row, col);
-                assert!(
-                    res.attributes.iter().any(|a| a.key == key),
-                    "missing {}",
-                    key
-                );
-            }
-        }
-
-        // 检查行 emoji 展示
-        assert!(res.attributes.iter().any(|a| a.key == "row_1"));
-        assert!(res.attributes.iter().any(|a| a.key == "row_2"));
-        assert!(res.attributes.iter().any(|a| a.key == "row_3"));
-
-        // 检查中奖线数
-        assert!(res.attributes.iter().any(|a| a.key == "winning_lines"));
+    // ──────────────────────────────────────────────────────────────────────
+    // 开奖复算（VerifyOutcome）测试
+    // ──────────────────────────────────────────────────────────────────────
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+    fn verify_outcome_query(deps: Deps, bet_id: u64) -> VerifyOutcomeResponse {
+        from_json(query(deps, mock_env(), QueryMsg::VerifyOutcome { bet_id }).unwrap()).unwrap()
+    }
 
-        if result.value == "win" {
-            assert!(res.messages.len() == 1);
-            let mult: u128 = res.attributes.iter()
-                .find(|a| a.key == "total_multiplier").unwrap()
-                .value.parse().unwrap();
-            assert!(mult >= 2, "multiplier should be at least 2");
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(bet * mult, "uatom"),
-                }
-                .into()
-            );
-            // 检查 win_desc 存在
-            assert!(res.attributes.iter().any(|a| a.key == "win_desc"));
-        } else {
-            assert_eq!(result.value, "lose");
-            assert_eq!(res.messages.len(), 0);
-        }
+    fn attr_value(res: &Response, key: &str) -> String {
+        res.attributes.iter().find(|a| a.key == key).unwrap().value.clone()
     }
 
     #[test]
-    fn test_play_scratch_card_premium() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_verify_outcome_requires_revealed_seed() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        let user = "scratch_premium_player";
-        let user_info = mock_info(user, &coins(1_000_000, "uatom"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            coin_flip_msg(),
+        )
+        .unwrap();
 
-        let res = execute(
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::VerifyOutcome { bet_id: 1 })
+            .unwrap_err();
+        assert!(err.to_string().contains("Server seed not revealed yet"));
+
+        execute(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Premium,
+            mock_info("creator", &[]),
+            ExecuteMsg::RevealServerSeed {
+                server_seed: test_server_seed(1),
+                next_seed_hash: hash_server_seed(&test_server_seed(2)),
             },
         )
         .unwrap();
 
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "card_type").unwrap().value,
-            "Premium"
-        );
-        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
-        assert!(result.value == "win" || result.value == "lose");
+        let verified = verify_outcome_query(deps.as_ref(), 1);
+        let seed = derive_bet_seed(&test_server_seed(1), "lucky", &Addr::unchecked("user"), 1, 1);
+        assert_eq!(verified.entropy, BetEntropy::Commitment { id: 1 });
+        assert_eq!(verified.entropy_value, test_server_seed(1));
+        assert_eq!(verified.seed.as_slice(), seed.as_slice());
+        assert!(!verified.settled);
     }
 
     #[test]
-    fn test_play_scratch_card_deluxe() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
-
-        let user = "scratch_deluxe_player";
-        let user_info = mock_info(user, &coins(2_000_000, "uatom"));
+    fn test_verify_outcome_matches_settlement() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        let user = mock_info("user", &coins(1_000_000, "uatom"));
 
-        let res = execute(
+        // 基诺
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            user_info,
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Deluxe,
+            user.clone(),
+            ExecuteMsg::PlayKeno {
+                picks: vec![3, 17, 25, 42, 80],
+                client_seed: Some("keno".to_string()),
             },
         )
         .unwrap();
+        let verified = verify_outcome_query(deps.as_ref(), 1);
+        assert!(verified.settled);
+        match verified.outcome {
+            GameOutcome::Keno(result) => {
+                let drawn: Vec<String> = result.drawn.iter().map(|n| n.to_string()).collect();
+                assert_eq!(attr_value(&res, "drawn"), format!("[{}]", drawn.join(",")));
+                assert_eq!(attr_value(&res, "hit_count"), result.hit_count.to_string());
+                assert_eq!(result.picks, vec![3, 17, 25, 42, 80]);
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
 
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "card_type").unwrap().value,
-            "Deluxe"
-        );
-    }
-
-    #[test]
-    fn test_scratch_card_bet_limits() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
-
-        // Classic 下注过小
-        let err1 = execute(
+        // 骰宝
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(50_000, "uatom")),
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Classic,
+            user.clone(),
+            ExecuteMsg::PlaySicBo {
+                bet_type: SicBoBetType::Big,
+                client_seed: None,
             },
-        );
-        assert!(err1.is_err(), "Classic should reject bet below 100,000");
+        )
+        .unwrap();
+        match verify_outcome_query(deps.as_ref(), 2).outcome {
+            GameOutcome::SicBo(result) => {
+                assert_eq!(attr_value(&res, "die1"), result.die1.to_string());
+                assert_eq!(attr_value(&res, "die2"), result.die2.to_string());
+                assert_eq!(attr_value(&res, "die3"), result.die3.to_string());
+                assert_eq!(attr_value(&res, "total"), result.total.to_string());
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
 
-        // Classic 下注过大
-        let err2 = execute(
+        // 轮盘
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(3_000_000, "uatom")),
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Classic,
+            user.clone(),
+            ExecuteMsg::PlayRoulette {
+                bet_type: RouletteBetType::SingleNumber { number: 7 },
+                client_seed: None,
             },
-        );
-        assert!(err2.is_err(), "Classic should reject bet above 2,000,000");
+        )
+        .unwrap();
+        match verify_outcome_query(deps.as_ref(), 3).outcome {
+            GameOutcome::Roulette(result) => {
+                assert_eq!(attr_value(&res, "winning_number"), result.winning_number.to_string());
+                assert_eq!(
+                    attr_value(&res, "winning_color"),
+                    format!("{:?}", result.winning_color)
+                );
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
 
-        // Premium 下注过小
-        let err3 = execute(
+        // 老虎机（Advanced 5×3）
+        let res = play(
             deps.as_mut(),
             mock_env(),
             mock_info("user", &coins(200_000, "uatom")),
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Premium,
+            ExecuteMsg::PlaySlot {
+                mode: SlotMode::Advanced,
+                client_seed: None,
+            },
+        )
+        .unwrap();
+        match verify_outcome_query(deps.as_ref(), 4).outcome {
+            GameOutcome::Slot { reels } => {
+                assert_eq!(reels.len(), 5);
+                for (col, reel) in reels.iter().enumerate() {
+                    assert_eq!(reel.len(), 3);
+                    for (row, symbol) in reel.iter().enumerate() {
+                        let key = format!("reel{}_{}", col + 1, row + 1);
+                        assert_eq!(attr_value(&res, &key), format!("{:?}", symbol));
+                    }
+                }
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+
+        // 骰子猜点数
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            user.clone(),
+            ExecuteMsg::PlayDice {
+                mode: DiceGameMode::ExactNumber { guess_number: 3 },
+                client_seed: None,
             },
+        )
+        .unwrap();
+        assert_eq!(
+            verify_outcome_query(deps.as_ref(), 5).outcome,
+            GameOutcome::Dice {
+                roll: attr_value(&res, "actual_result").parse().unwrap()
+            }
         );
-        assert!(err3.is_err(), "Premium should reject bet below 500,000");
 
-        // Deluxe 下注过小
-        let err4 = execute(
+        // 奥马哈开局：牌局结束前不返回暗牌与牌序，弃牌后复算的手牌与开局发牌一致
+        let res = play(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(500_000, "uatom")),
-            ExecuteMsg::PlayScratchCard {
-                card_type: ScratchCardType::Deluxe,
+            user,
+            ExecuteMsg::PlayOmaha {
+                action: OmahaAction::Start,
+                client_seed: None,
             },
+        )
+        .unwrap();
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::VerifyOutcome { bet_id: 6 })
+            .unwrap_err();
+        assert_eq!(err, ContractError::GameNotFinished { game: GameKind::Omaha });
+        let fold = ExecuteMsg::PlayOmaha { action: OmahaAction::Fold, client_seed: None };
+        execute(deps.as_mut(), mock_env(), mock_info("user", &[]), fold).unwrap();
+        match verify_outcome_query(deps.as_ref(), 6).outcome {
+            GameOutcome::Omaha {
+                player_hand,
+                community_cards,
+                deck,
+                ..
+            } => {
+                assert_eq!(attr_value(&res, "player_hand"), format_cards(&player_hand));
+                assert_eq!(community_cards.len(), 5);
+                assert_eq!(deck.len(), 52);
+                // 牌序不在链上保存，按开局下注的种子重新洗出的牌序与复算一致
+                let state = OMAHA_STATE.load(&deps.storage, &Addr::unchecked("user")).unwrap();
+                assert_eq!(omaha_deck(&deps.storage, &state).unwrap(), deck);
+                let stored = deps.storage.get(&OMAHA_STATE.key(&Addr::unchecked("user"))).unwrap();
+                assert!(!String::from_utf8(stored).unwrap().contains(r#""deck""#));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_verify_outcome_drand_bet() {
+        let mut deps = funded_deps();
+        instantiate_with_drand(deps.as_mut());
+
+        let res = play(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &coins(1_000_000, "uatom")),
+            coin_flip_msg(),
+        )
+        .unwrap();
+
+        let verified = verify_outcome_query(deps.as_ref(), 1);
+        let randomness = attr_value(&res, "drand_randomness");
+        assert_eq!(verified.entropy_value, randomness);
+        assert_eq!(
+            verified.outcome,
+            GameOutcome::CoinFlip {
+                result: if attr_value(&res, "actual_result") == "Heads" {
+                    CoinSide::Heads
+                } else {
+                    CoinSide::Tails
+                },
+            }
         );
-        assert!(err4.is_err(), "Deluxe should reject bet below 1,000,000");
+    }
+
+    // ──────────────────────────────────────────────────────────────────────
+    // RngStream 均匀性测试（卡方检验，显著性 0.001；种子固定，结果确定）
+    // ──────────────────────────────────────────────────────────────────────
+
+    fn chi_square(counts: &[u64], expected: &[f64]) -> f64 {
+        counts
+            .iter()
+            .zip(expected)
+            .map(|(&observed, &e)| (observed as f64 - e).powi(2) / e)
+            .sum()
+    }
+
+    fn uniform_chi_square(counts: &[u64]) -> f64 {
+        let total: u64 = counts.iter().sum();
+        let expected = vec![total as f64 / counts.len() as f64; counts.len()];
+        chi_square(counts, &expected)
+    }
+
+    /// 第 i 局的测试种子
+    fn sample_seed(i: u64) -> [u8; 32] {
+        Sha256::digest(&i.to_be_bytes()).into()
     }
 
     #[test]
-    fn test_scratch_card_evaluation_logic() {
-        use crate::scratch::{evaluate_scratch_card, ScratchSymbol};
+    fn test_rng_stream_is_deterministic() {
+        let mut a = RngStream::new(b"seed", b"domain");
+        let mut b = RngStream::new(b"seed", b"domain");
+        let mut c = RngStream::new(b"seed", b"other_domain");
+        let xs: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let zs: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+
+        // 拒绝率接近 50% 的区间也能得到合法结果
+        let n = (1u64 << 63) + 1;
+        for _ in 0..100 {
+            assert!(a.below(n) < n);
+        }
+        assert_eq!(a.below(1), 0);
+        assert_eq!(a.range_inclusive(7, 7), 7);
+    }
 
-        // 全部相同 → 8 条线全中
-        let all_diamond = [ScratchSymbol::Diamond; 9];
-        let (mult, lines) = evaluate_scratch_card(&all_diamond);
-        assert_eq!(lines.len(), 8, "all same should win 8 lines");
-        assert_eq!(mult, 50 * 8, "8 lines × 50× each");
+    #[test]
+    fn test_rng_stream_below_is_uniform() {
+        // 37 个格子（轮盘），df = 36，临界值 67.99
+        let mut rng = RngStream::new(b"uniformity", b"below_37");
+        let mut counts = [0u64; 37];
+        for _ in 0..37 * 2_000 {
+            counts[rng.below(37) as usize] += 1;
+        }
+        assert!(uniform_chi_square(&counts) < 67.99);
 
-        // 第一行全 Cherry → 1 条线
-        let mut grid = [ScratchSymbol::Lemon; 9];
-        grid[0] = ScratchSymbol::Cherry;
-        grid[1] = ScratchSymbol::Cherry;
-        grid[2] = ScratchSymbol::Cherry;
-        let (mult2, lines2) = evaluate_scratch_card(&grid);
-        // row1 中奖 Cherry(3×)，row2 和 row3 全 Lemon(2×)，列和对角线不一定
-        // row2: [Lemon, Lemon, Lemon] → 中，row3: [Lemon, Lemon, Lemon] → 中
-        // col1: [Cherry, Lemon, Lemon] → 不中
-        // 需要精确计算
-        assert!(lines2.iter().any(|l| l.0 == "row1"), "row1 should win");
-        assert!(lines2.iter().any(|l| l.0 == "row2"), "row2 (all Lemon) should win");
-        assert!(lines2.iter().any(|l| l.0 == "row3"), "row3 (all Lemon) should win");
-        assert_eq!(mult2, 3 + 2 + 2, "Cherry 3× + Lemon 2× + Lemon 2× = 7×");
+        // 1-6 骰子，df = 5，临界值 20.52
+        let mut counts = [0u64; 6];
+        for _ in 0..6 * 5_000 {
+            counts[rng.range_inclusive(1, 6) as usize - 1] += 1;
+        }
+        assert!(uniform_chi_square(&counts) < 20.52);
+    }
 
-        // 无任何三连 → 0
-        let no_match = [
-            ScratchSymbol::Diamond, ScratchSymbol::Star,    ScratchSymbol::Clover,
-            ScratchSymbol::Bell,    ScratchSymbol::Cherry,  ScratchSymbol::Lemon,
-            ScratchSymbol::Star,    ScratchSymbol::Diamond, ScratchSymbol::Bell,
-        ];
-        let (mult3, lines3) = evaluate_scratch_card(&no_match);
-        assert_eq!(mult3, 0);
-        assert_eq!(lines3.len(), 0);
+    #[test]
+    fn test_roulette_and_dice_uniform_across_seeds() {
+        // 轮盘 0-36，df = 36，临界值 67.99
+        let mut roulette = [0u64; 37];
+        // 骰宝三颗骰子 1-6，df = 5，临界值 20.52
+        let mut dice = [0u64; 6];
+        // 21 点初始牌 1-10（10/J/Q/K 都记 10 点），df = 9，临界值 27.88
+        let mut cards = [0u64; 10];
+        for i in 0..20_000 {
+            let seed = sample_seed(i);
+            roulette[spin_roulette(&seed).winning_number as usize] += 1;
+            let result = roll_sicbo(&seed);
+            for die in [result.die1, result.die2, result.die3] {
+                dice[die as usize - 1] += 1;
+            }
+            let shoe = blackjack_shoe(&seed, blackjack::DEFAULT_SHOE_DECKS);
+            let (user_cards, dealer_cards) = deal_blackjack(&shoe);
+            for card in user_cards.iter().chain(dealer_cards.iter()) {
+                cards[card.value() as usize - 1] += 1;
+            }
+        }
+        assert!(uniform_chi_square(&roulette) < 67.99);
+        assert!(uniform_chi_square(&dice) < 20.52);
+        let total_cards: u64 = cards.iter().sum();
+        let expected: Vec<f64> = (1..=10)
+            .map(|value| {
+                let ranks = if value == 10 { 4.0 } else { 1.0 };
+                total_cards as f64 * ranks / 13.0
+            })
+            .collect();
+        assert!(chi_square(&cards, &expected) < 27.88);
+    }
 
-        // 对角线中奖
-        let mut diag_grid = [ScratchSymbol::Lemon; 9];
-        diag_grid[0] = ScratchSymbol::Star;
-        diag_grid[4] = ScratchSymbol::Star;
-        diag_grid[8] = ScratchSymbol::Star;
-        // grid: Star Lemon Lemon / Lemon Star Lemon / Lemon Lemon Star
-        // diag1: [0,4,8] = Star → 中
-        // row2: [Lemon, Star, Lemon] → 不中
-        // 但 row1 和 row3 没有全 Lemon（因为有 Star 在角上）
-        // row1: [Star, Lemon, Lemon] → 不中
-        let (mult4, lines4) = evaluate_scratch_card(&diag_grid);
-        assert!(lines4.iter().any(|l| l.0 == "diag1"), "diag1 should win");
-        // diag1 中 Star(20×) 只有这一条
-        assert!(mult4 >= 20, "at least Star 20× for diag1");
+    #[test]
+    fn test_rng_stream_shuffle_is_uniform() {
+        // 4 个元素的 24 种排列出现频率一致，df = 23，临界值 49.73
+        let mut rng = RngStream::new(b"uniformity", b"shuffle");
+        let mut counts = [0u64; 24];
+        for _ in 0..24 * 1_000 {
+            let mut items = [0u8, 1, 2, 3];
+            rng.shuffle(&mut items);
+            // 排列编码（Lehmer code）
+            let mut index = 0usize;
+            for i in 0..4 {
+                let smaller = items[i + 1..].iter().filter(|&&x| x < items[i]).count();
+                index = index * (4 - i) + smaller;
+            }
+            counts[index] += 1;
+        }
+        assert!(uniform_chi_square(&counts) < 49.73);
+
+        // 52 张牌洗牌后每张牌出现在首位的频率一致，df = 51，临界值 87.97
+        let mut first = [0u64; 52];
+        for i in 0..52 * 200 {
+            first[shuffled_deck(&sample_seed(i), b"omaha_deck")[0] as usize] += 1;
+        }
+        assert!(uniform_chi_square(&first) < 87.97);
+    }
+
+    #[test]
+    fn test_rng_stream_weighted_index_matches_weights() {
+        // 老虎机符号权重，df = 8，临界值 26.12
+        let mut rng = RngStream::new(b"uniformity", b"weighted");
+        let samples = 100_000u64;
+        let mut counts = [0u64; 9];
+        for _ in 0..samples {
+            counts[rng.weighted_index(&slot::SYMBOL_WEIGHTS)] += 1;
+        }
+        let expected: Vec<f64> = slot::SYMBOL_WEIGHTS
+            .iter()
+            .map(|&w| samples as f64 * w as f64 / 100.0)
+            .collect();
+        assert!(chi_square(&counts, &expected) < 26.12);
+
+        // 权重为 0 的项永远不会被选中
+        for _ in 0..1_000 {
+            assert_eq!(rng.weighted_index(&[0, 5, 0]), 1);
+        }
     }
 
     // ──────────────────────────────────────────────────────────────────────
-    // 斗牛（Bull Fight / Niu Niu）测试
+    // 牌组 / 牌靴测试
     // ──────────────────────────────────────────────────────────────────────
 
     #[test]
-    fn test_play_bullfight() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_shoe_composition() {
+        let shoe = ShoeSpec::decks(6);
+        assert_eq!(shoe.size(), 312);
+
+        let mut rng = RngStream::new(b"shoe", b"composition");
+        let mut cards = shoe.shuffle(&mut rng);
+        assert_eq!(cards.len(), 312);
+        assert_ne!(cards, shoe.cards());
+        cards.sort_unstable();
+        assert_eq!(cards, {
+            let mut sorted = shoe.cards();
+            sorted.sort_unstable();
+            sorted
+        });
 
-        let user = "bull_player";
-        let bet = 1_000_000u128;
-        let user_info = mock_info(user, &coins(bet, "uatom"));
+        // 带大小王：每副 54 张
+        let with_jokers = ShoeSpec { decks: 2, jokers: true };
+        let cards = with_jokers.shuffle(&mut rng);
+        assert_eq!(cards.len(), 108);
+        assert_eq!(cards.iter().filter(|&&id| deck::JOKER_IDS.contains(&id)).count(), 4);
 
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            user_info,
-            ExecuteMsg::PlayBullFight {},
-        )
-        .unwrap();
+        // 部分发牌不会重复发出同一张物理牌
+        let dealt = ShoeSpec::SINGLE_DECK.deal(&mut rng, 20);
+        let mut unique = dealt.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 20);
+    }
 
-        // 检查基本属性
-        assert_eq!(
-            res.attributes.iter().find(|a| a.key == "action").unwrap().value,
-            "play_bullfight"
-        );
-        assert!(res.attributes.iter().any(|a| a.key == "player_cards"));
-        assert!(res.attributes.iter().any(|a| a.key == "dealer_cards"));
-        assert!(res.attributes.iter().any(|a| a.key == "player_hand_type"));
-        assert!(res.attributes.iter().any(|a| a.key == "dealer_hand_type"));
-        assert!(res.attributes.iter().any(|a| a.key == "player_score"));
-        assert!(res.attributes.iter().any(|a| a.key == "dealer_score"));
+    #[test]
+    fn test_deck_permutation_frequencies() {
+        // 每张牌出现在任意位置的频率一致：检查首、中、尾三个位置，df = 51，临界值 87.97
+        let samples = 52 * 300;
+        let mut positions = [[0u64; 52]; 3];
+        for i in 0..samples {
+            let deck = shuffled_deck(&sample_seed(i), b"deck_test");
+            for (counts, pos) in positions.iter_mut().zip([0usize, 25, 51]) {
+                counts[deck[pos] as usize] += 1;
+            }
+        }
+        for counts in positions.iter() {
+            assert!(uniform_chi_square(counts) < 87.97);
+        }
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        // 3 张牌的相对顺序 6 种排列出现频率一致，df = 5，临界值 20.52
+        let mut orders = [0u64; 6];
+        for i in 0..6 * 2_000 {
+            let deck = shuffled_deck(&sample_seed(i), b"deck_order");
+            let pos = |card: u8| deck.iter().position(|&c| c == card).unwrap();
+            let (a, b, c) = (pos(0), pos(1), pos(2));
+            let index = match (a < b, b < c, a < c) {
+                (true, true, _) => 0,
+                (true, false, true) => 1,
+                (true, false, false) => 2,
+                (false, true, true) => 3,
+                (false, true, false) => 4,
+                (false, false, _) => 5,
+            };
+            orders[index] += 1;
+        }
+        assert!(uniform_chi_square(&orders) < 20.52);
 
-        if result.value == "win" {
-            assert_eq!(res.messages.len(), 1);
-            let mult: u128 = res.attributes.iter()
-                .find(|a| a.key == "payout_multiplier").unwrap()
-                .value.parse().unwrap();
-            assert!(mult >= 2 && mult <= 8, "multiplier should be 2-8, got {}", mult);
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(bet * mult, "uatom"),
-                }
-                .into()
-            );
-        } else if result.value == "tie" {
-            assert_eq!(res.messages.len(), 1);
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(bet, "uatom"),
-                }
-                .into()
-            );
-        } else {
-            assert_eq!(result.value, "lose");
-            assert_eq!(res.messages.len(), 0);
+        // 8 副牌的部分发牌：首张牌的分布一致，df = 51，临界值 87.97
+        let mut first = [0u64; 52];
+        for i in 0..52 * 200 {
+            let dealt = ShoeSpec::decks(8).deal(&mut RngStream::new(&sample_seed(i), b"shoe"), 6);
+            first[dealt[0] as usize] += 1;
         }
+        assert!(uniform_chi_square(&first) < 87.97);
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 调用方限制
+    // ─────────────────────────────────────────────────────────────
+
+    /// 让 mock querier 把 `contract` 识别为合约地址
+    fn mock_contract_caller(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        contract: &str,
+    ) {
+        let contract = contract.to_string();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::ContractInfo { contract_addr } if *contract_addr == contract => {
+                let mut info = ContractInfoResponse::default();
+                info.code_id = 1;
+                info.creator = "creator".to_string();
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract {
+                addr: "unknown".to_string(),
+            }),
+        });
     }
 
     #[test]
-    fn test_bullfight_bet_limits() {
-        let mut deps = mock_dependencies();
-        let msg = InstantiateMsg { house_edge_bps: None };
-        let creator_info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+    fn test_contract_caller_rejected_unless_allowed() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        mock_contract_caller(&mut deps, "sniper");
 
-        // 下注过小
-        let err1 = execute(
+        let bet = ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: None,
+        };
+        let stake = coins(1_000_000, "uatom");
+
+        // 合约下注被拒绝
+        let err = execute(deps.as_mut(), mock_env(), mock_info("sniper", &stake), bet.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("not allowed to play"));
+
+        // 外部账户照常下注
+        execute(deps.as_mut(), mock_env(), mock_info("user", &stake), bet.clone()).unwrap();
+
+        // 只有所有者可以修改白名单
+        let allow = ExecuteMsg::AllowContract {
+            address: "sniper".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), allow.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+
+        // 加入白名单后可以下注
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), allow).unwrap();
+        let guard: CallerGuardResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetCallerGuard {}).unwrap())
+                .unwrap();
+        assert!(guard.eoa_only);
+        assert_eq!(guard.allowed_contracts, vec![Addr::unchecked("sniper")]);
+        execute(deps.as_mut(), mock_env(), mock_info("sniper", &stake), bet.clone()).unwrap();
+
+        // 移出白名单后再次被拒绝
+        let disallow = ExecuteMsg::DisallowContract {
+            address: "sniper".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), disallow).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("sniper", &stake), bet.clone())
+            .unwrap_err();
+
+        // 关闭仅外部账户模式后合约也可以下注
+        let open = ExecuteMsg::SetEoaOnly { enabled: false };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), open).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("sniper", &stake), bet).unwrap();
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 玩家余额
+    // ─────────────────────────────────────────────────────────────
+
+    fn player_balance(deps: Deps, address: &str) -> Uint128 {
+        let msg = QueryMsg::GetPlayerBalance {
+            address: address.to_string(),
+        };
+        let res: PlayerBalanceResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.balances
+            .iter()
+            .find(|c| c.denom == "uatom")
+            .map(|c| c.amount)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_play_from_balance() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+
+        // 存入余额，不计入锁仓
+        let res = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(50_000, "uatom")),
-            ExecuteMsg::PlayBullFight {},
-        );
-        assert!(err1.is_err(), "should reject bet below 100,000");
+            mock_info("user", &coins(5_000_000, "uatom")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+        assert_eq!(attr_value(&res, "balance"), "5000000");
+        assert_eq!(player_balance(deps.as_ref(), "user"), Uint128::new(5_000_000));
+        assert_eq!(STATE.load(&deps.storage).unwrap().locked("uatom"), 10_000_000_000);
+
+        // 余额下注：赔付记回余额，不发送 BankMsg
+        let mut balance = 5_000_000u128;
+        for i in 0..4 {
+            let bet = ExecuteMsg::PlayFromBalance {
+                amount: Uint128::new(1_000_000),
+                denom: None,
+                game: Box::new(ExecuteMsg::PlayCoinFlip {
+                    choice: CoinSide::Heads,
+                    client_seed: Some(format!("balance-{}", i)),
+                }),
+            };
+            let res = play(deps.as_mut(), mock_env(), mock_info("user", &[]), bet).unwrap();
+            assert!(res.messages.is_empty());
+            let payout = match attr_value(&res, "result").as_str() {
+                "win" => attr_value(&res, "payout").parse::<u128>().unwrap(),
+                _ => 0,
+            };
+            balance = balance - 1_000_000 + payout;
+            assert_eq!(player_balance(deps.as_ref(), "user"), Uint128::new(balance));
+        }
 
-        // 下注过大
-        let err2 = execute(
+        // 多步游戏：开局来自余额，停牌赔付同样记回余额
+        let start = ExecuteMsg::PlayFromBalance {
+            amount: Uint128::new(1_000_000),
+            denom: None,
+            game: Box::new(ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Start,
+                client_seed: None,
+                side_bets: None,
+            }),
+        };
+        play(deps.as_mut(), mock_env(), mock_info("user", &[]), start).unwrap();
+        balance -= 1_000_000;
+        let stand = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), stand).unwrap();
+        assert!(res.messages.is_empty());
+        if let Some(payout) = res.attributes.iter().find(|a| a.key == "payout") {
+            balance += payout.value.parse::<u128>().unwrap();
+        }
+        assert_eq!(player_balance(deps.as_ref(), "user"), Uint128::new(balance));
+
+        // 余额不足、附带资金、非游戏消息都会被拒绝
+        let too_much = ExecuteMsg::PlayFromBalance {
+            amount: Uint128::new(balance + 1),
+            denom: None,
+            game: Box::new(ExecuteMsg::PlayWar { client_seed: None }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), too_much).unwrap_err();
+        assert!(err.to_string().contains("Insufficient balance"));
+
+        let with_funds = ExecuteMsg::PlayFromBalance {
+            amount: Uint128::new(1_000_000),
+            denom: None,
+            game: Box::new(ExecuteMsg::PlayWar { client_seed: None }),
+        };
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, with_funds).unwrap_err();
+
+        let not_a_game = ExecuteMsg::PlayFromBalance {
+            amount: Uint128::new(1_000_000),
+            denom: None,
+            game: Box::new(ExecuteMsg::Withdraw {
+                amount: 1,
+                denom: None,
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("user", &[]), not_a_game).unwrap_err();
+
+        // 提取余额
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("user", &coins(20_000_000, "uatom")),
-            ExecuteMsg::PlayBullFight {},
+            mock_info("user", &[]),
+            ExecuteMsg::WithdrawBalance {
+                amount: Some(Uint128::new(balance + 1)),
+                denom: None,
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Insufficient balance"));
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &[]),
+            ExecuteMsg::WithdrawBalance {
+                amount: None,
+                denom: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "user".to_string(),
+                amount: coins(balance, "uatom"),
+            }
+            .into()
         );
-        assert!(err2.is_err(), "should reject bet above 10,000,000");
+        assert_eq!(player_balance(deps.as_ref(), "user"), Uint128::zero());
     }
 
-    #[test]
-    fn test_bullfight_hand_evaluation() {
-        use crate::bullfight::{evaluate_bull_hand, BullCard, BullHandType, Suit};
+    // ─────────────────────────────────────────────────────────────
+    // 多币种下注
+    // ─────────────────────────────────────────────────────────────
 
-        // ── 五小牛：A A A 2 3 → 全 ≤5，总和=1+1+1+2+3=8 ≤10 ──
-        let wu_xiao = [
-            BullCard { rank: 1, suit: Suit::Spades },
-            BullCard { rank: 1, suit: Suit::Hearts },
-            BullCard { rank: 1, suit: Suit::Diamonds },
-            BullCard { rank: 2, suit: Suit::Clubs },
-            BullCard { rank: 3, suit: Suit::Spades },
-        ];
-        let r1 = evaluate_bull_hand(&wu_xiao);
-        assert_eq!(r1.hand_type, BullHandType::WuXiaoNiu);
-        assert!(r1.score >= 90_000);
+    const IBC_DENOM: &str = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
 
-        // ── 四炸：K K K K 5 ──
-        let si_zha = [
-            BullCard { rank: 13, suit: Suit::Spades },
-            BullCard { rank: 13, suit: Suit::Hearts },
-            BullCard { rank: 13, suit: Suit::Diamonds },
-            BullCard { rank: 13, suit: Suit::Clubs },
-            BullCard { rank: 5, suit: Suit::Spades },
-        ];
-        let r2 = evaluate_bull_hand(&si_zha);
-        assert!(matches!(r2.hand_type, BullHandType::SiZha { rank: 13 }));
-        assert!(r2.score >= 80_000);
+    fn locked_in(deps: Deps, denom: &str) -> u128 {
+        let msg = QueryMsg::GetLockedAmount {
+            denom: Some(denom.to_string()),
+        };
+        let res: LockedAmountResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.locked_amount.u128()
+    }
 
-        // ── 五花牛：J Q K J Q ──
-        let wu_hua = [
-            BullCard { rank: 11, suit: Suit::Spades },
-            BullCard { rank: 12, suit: Suit::Hearts },
-            BullCard { rank: 13, suit: Suit::Diamonds },
-            BullCard { rank: 11, suit: Suit::Clubs },
-            BullCard { rank: 12, suit: Suit::Diamonds },
+    #[test]
+    fn test_multi_denom_bets() {
+        let mut deps = funded_deps();
+        let denoms = vec![
+            DenomConfig::uatom(),
+            DenomConfig {
+                denom: IBC_DENOM.to_string(),
+                bet_scale: Decimal::percent(1),
+                cw20: false,
+            },
         ];
-        let r3 = evaluate_bull_hand(&wu_hua);
-        assert_eq!(r3.hand_type, BullHandType::WuHuaNiu);
-        assert!(r3.score >= 70_000);
 
-        // ── 牛牛：10 10 10 J K → 三张 10+10+10=30(10倍), 剩余 10+10=20(10倍) ──
-        let niu_niu = [
-            BullCard { rank: 10, suit: Suit::Spades },
-            BullCard { rank: 10, suit: Suit::Hearts },
-            BullCard { rank: 10, suit: Suit::Diamonds },
-            BullCard { rank: 11, suit: Suit::Clubs },
-            BullCard { rank: 13, suit: Suit::Spades },
-        ];
-        let r4 = evaluate_bull_hand(&niu_niu);
-        assert_eq!(r4.hand_type, BullHandType::NiuNiu);
-        assert!(r4.score >= 60_000);
+        // 不可下注的币种、重复币种都不能用于初始化
+        let mut msg = init_msg(None);
+        msg.denoms = Some(denoms.clone());
+        let info = mock_info(
+            "creator",
+            &[Coin::new(10_000_000_000, "uatom"), Coin::new(1_000, "uosmo")],
+        );
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported denom: uosmo"));
+        let mut duplicated = msg.clone();
+        duplicated.denoms = Some(vec![DenomConfig::uatom(), DenomConfig::uatom()]);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, duplicated).unwrap_err();
 
-        // ── 牛 7：10 J 10 3 4 → 10+10+10=30(牛), 3+4=7 ──
-        let niu7 = [
-            BullCard { rank: 10, suit: Suit::Spades },
-            BullCard { rank: 11, suit: Suit::Hearts },
-            BullCard { rank: 10, suit: Suit::Diamonds },
-            BullCard { rank: 3, suit: Suit::Clubs },
-            BullCard { rank: 4, suit: Suit::Spades },
-        ];
-        let r5 = evaluate_bull_hand(&niu7);
-        assert_eq!(r5.hand_type, BullHandType::NiuN { n: 7 });
-        assert!(r5.score >= 50_000);
+        let info = mock_info(
+            "creator",
+            &[Coin::new(10_000_000_000, "uatom"), Coin::new(1_000_000_000, IBC_DENOM)],
+        );
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier.update_balance(
+            MOCK_CONTRACT_ADDR,
+            vec![Coin::new(CONTRACT_BALANCE, "uatom"), Coin::new(1_000_000_000, IBC_DENOM)],
+        );
+        let res: Vec<DenomConfig> =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetDenoms {}).unwrap()).unwrap();
+        assert_eq!(res, denoms);
+        assert_eq!(locked_in(deps.as_ref(), "uatom"), 10_000_000_000);
+        assert_eq!(locked_in(deps.as_ref(), IBC_DENOM), 1_000_000_000);
+
+        // 限额按 bet_scale 换算：1% → 1,000 – 100,000
+        let coin_flip = |i: u32| ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: Some(format!("ibc-{}", i)),
+        };
+        let info = mock_info("user", &coins(999, IBC_DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip(0)).unwrap_err();
+        assert!(err.to_string().contains(&format!("between 1000 and 100000 {}", IBC_DENOM)));
+        let info = mock_info("user", &coins(100_001, IBC_DENOM));
+        execute(deps.as_mut(), mock_env(), info, coin_flip(0)).unwrap_err();
+
+        // 赔付与锁仓都按下注币种结算，主币种锁仓不受影响
+        let mut locked = 1_000_000_000u128;
+        for i in 0..4 {
+            let info = mock_info("user", &coins(10_000, IBC_DENOM));
+            let res = play(deps.as_mut(), mock_env(), info, coin_flip(i)).unwrap();
+            locked += 10_000;
+            if attr_value(&res, "result") == "win" {
+                let payout = attr_value(&res, "payout").parse::<u128>().unwrap();
+                assert_eq!(
+                    res.messages[0].msg,
+                    BankMsg::Send {
+                        to_address: "user".to_string(),
+                        amount: coins(payout, IBC_DENOM),
+                    }
+                    .into()
+                );
+                locked -= payout;
+            }
+            assert_eq!(locked_in(deps.as_ref(), IBC_DENOM), locked);
+        }
+        assert_eq!(locked_in(deps.as_ref(), "uatom"), 10_000_000_000);
+
+        // 未配置的币种、一次附带多个币种都会被拒绝
+        let info = mock_info("user", &coins(100_000, "uosmo"));
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip(9)).unwrap_err();
+        assert!(err.to_string().contains("Unsupported denom: uosmo"));
+        let info = mock_info(
+            "user",
+            &[Coin::new(100_000, "uatom"), Coin::new(10_000, IBC_DENOM)],
+        );
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip(9)).unwrap_err();
+        assert!(err.to_string().contains("single denom"));
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetLockedAmount {
+                denom: Some("uosmo".to_string()),
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unsupported denom"));
+
+        // 所有者按币种提取
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::Withdraw {
+            amount: 1_000,
+            denom: Some(IBC_DENOM.to_string()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1_000, IBC_DENOM),
+            }
+            .into()
+        );
+        assert_eq!(locked_in(deps.as_ref(), IBC_DENOM), locked - 1_000);
+    }
 
-        // ── 没牛：A 2 6 8 K → 无法选 3 张凑成 10 的倍数 ──
-        // 检查：1+2+6=9, 1+2+10=13, 1+6+8=15, 1+6+10=17, 1+8+10=19
-        //       2+6+8=16, 2+6+10=18, 2+8+10=20✓ → 有牛，剩余 1+6=7 → 牛7
-        // 用 A 3 6 8 K: 1+3+6=10✓ → 牛8+10=18%10=8 → 牛8
-        // 用另一组: A 2 3 6 8: 1+2+3=6, 1+2+6=9, 1+2+8=11, 1+3+6=10✓ → 牛 2+8=10%10=0 → 牛牛
-        // 真正没牛的例子：A 2 3 7 K: 1+2+3=6, 1+2+7=10✓ → 有牛，3+10=13%10=3 → 牛3
-        // 更好的没牛：A 2 4 7 K: 1+2+4=7, 1+2+7=10✓ → 牛 4+10=14%10=4
-        // 直接构造：3 4 6 7 8 → 3+4+6=13, 3+4+7=14, 3+4+8=15, 3+6+7=16, 3+6+8=17,
-        //                       3+7+8=18, 4+6+7=17, 4+6+8=18, 4+7+8=19, 6+7+8=21
-        // 没有 10 的倍数 → 没牛！
-        let no_niu = [
-            BullCard { rank: 3, suit: Suit::Spades },
-            BullCard { rank: 4, suit: Suit::Hearts },
-            BullCard { rank: 6, suit: Suit::Diamonds },
-            BullCard { rank: 7, suit: Suit::Clubs },
-            BullCard { rank: 8, suit: Suit::Spades },
-        ];
-        let r6 = evaluate_bull_hand(&no_niu);
-        assert_eq!(r6.hand_type, BullHandType::NoNiu);
-        assert!(r6.score < 50_000);
+    // ─────────────────────────────────────────────────────────────
+    // CW20 代币下注
+    // ─────────────────────────────────────────────────────────────
 
-        // ── 牌型强弱排序 ──
-        assert!(r1.score > r2.score, "五小牛 > 四炸");
-        assert!(r2.score > r3.score, "四炸 > 五花牛");
-        assert!(r3.score > r4.score, "五花牛 > 牛牛");
-        assert!(r4.score > r5.score, "牛牛 > 牛7");
-        assert!(r5.score > r6.score, "牛7 > 没牛");
+    const CW20_TOKEN: &str = "community_token";
+
+    /// 代币合约以 Receive 回调转发玩家 Send 的代币与内嵌消息
+    fn cw20_send(sender: &str, amount: u128, msg: &ExecuteMsg) -> ExecuteMsg {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(msg).unwrap(),
+        })
+    }
+
+    /// 模拟代币合约的 Balance 查询：本合约持有 `balance` 个代币
+    fn mock_cw20_balance(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, balance: u128) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == CW20_TOKEN => {
+                let res = Cw20BalanceResponse {
+                    balance: Uint128::new(balance),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract {
+                addr: "unknown".to_string(),
+            }),
+        });
+    }
+
+    fn cw20_transfer(recipient: &str, amount: u128) -> CosmosMsg {
+        WasmMsg::Execute {
+            contract_addr: CW20_TOKEN.to_string(),
+            msg: to_json_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }
+        .into()
     }
 
     #[test]
-    fn test_bullfight_payout_multipliers() {
-        use crate::bullfight::{bull_payout_multiplier, BullHandType};
-
-        assert_eq!(bull_payout_multiplier(&BullHandType::WuXiaoNiu), 8);
-        assert_eq!(bull_payout_multiplier(&BullHandType::SiZha { rank: 10 }), 7);
-        assert_eq!(bull_payout_multiplier(&BullHandType::WuHuaNiu), 6);
-        assert_eq!(bull_payout_multiplier(&BullHandType::NiuNiu), 4);
-        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 9 }), 3);
-        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 8 }), 3);
-        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 7 }), 2);
-        assert_eq!(bull_payout_multiplier(&BullHandType::NiuN { n: 1 }), 2);
-        assert_eq!(bull_payout_multiplier(&BullHandType::NoNiu), 2);
+    fn test_cw20_bets() {
+        let mut deps = funded_deps();
+        let mut msg = init_msg(None);
+        msg.denoms = Some(vec![
+            DenomConfig::uatom(),
+            DenomConfig {
+                denom: CW20_TOKEN.to_string(),
+                bet_scale: Decimal::one(),
+                cw20: true,
+            },
+        ]);
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        mock_cw20_balance(&mut deps, 1_000_000_000);
+        let token = mock_info(CW20_TOKEN, &[]);
+
+        // 部署者经由代币合约注入流动性，其他人不行
+        let add = ExecuteMsg::AddLiquidity {};
+        let err = execute(deps.as_mut(), mock_env(), token.clone(), cw20_send("user", 1, &add))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let msg = cw20_send("creator", 1_000_000_000, &add);
+        let res = execute(deps.as_mut(), mock_env(), token.clone(), msg).unwrap();
+        assert_eq!(attr_value(&res, "locked_amount"), "1000000000");
+        assert_eq!(locked_in(deps.as_ref(), CW20_TOKEN), 1_000_000_000);
+
+        // 内嵌 Play* 消息下注，赢了通过 Transfer 赔付代币
+        let mut locked = 1_000_000_000u128;
+        for i in 0..4 {
+            let game = ExecuteMsg::PlayCoinFlip {
+                choice: CoinSide::Tails,
+                client_seed: Some(format!("cw20-{}", i)),
+            };
+            let msg = cw20_send("user", 1_000_000, &game);
+            let res = play(deps.as_mut(), mock_env(), token.clone(), msg).unwrap();
+            locked += 1_000_000;
+            if attr_value(&res, "result") == "win" {
+                let payout = attr_value(&res, "payout").parse::<u128>().unwrap();
+                assert_eq!(res.messages[0].msg, cw20_transfer("user", payout));
+                locked -= payout;
+            } else {
+                assert!(res.messages.is_empty());
+            }
+            assert_eq!(locked_in(deps.as_ref(), CW20_TOKEN), locked);
+        }
+        assert_eq!(locked_in(deps.as_ref(), "uatom"), 10_000_000_000);
+
+        // 限额同样适用；非 Play* 消息、未配置的代币合约、冒充的原生资金都会被拒绝
+        let game = ExecuteMsg::PlayWar { client_seed: None };
+        let err = execute(deps.as_mut(), mock_env(), token.clone(), cw20_send("user", 10, &game))
+            .unwrap_err();
+        assert!(err.to_string().contains("Bet must be between"));
+        let withdraw = ExecuteMsg::WithdrawBalance {
+            amount: None,
+            denom: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), token, cw20_send("user", 100_000, &withdraw))
+            .unwrap_err();
+        assert!(err.to_string().contains("Only Play* messages"));
+        let fake_token = mock_info("fake_token", &[]);
+        let err = execute(deps.as_mut(), mock_env(), fake_token, cw20_send("user", 100_000, &game))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unsupported CW20 token"));
+        let info = mock_info("user", &coins(100_000, CW20_TOKEN));
+        let err = execute(deps.as_mut(), mock_env(), info, game).unwrap_err();
+        assert!(err.to_string().contains("must be sent through the token contract"));
+
+        // 部署者提取代币流动性
+        let msg = ExecuteMsg::Withdraw {
+            amount: 1_000,
+            denom: Some(CW20_TOKEN.to_string()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, cw20_transfer("creator", 1_000));
+        assert_eq!(locked_in(deps.as_ref(), CW20_TOKEN), locked - 1_000);
     }
 
-    // ──────────────────────────────────────────────────────────────────────
-    // 庄家抽水（House Edge）测试
-    // ──────────────────────────────────────────────────────────────────────
+    // ─────────────────────────────────────────────────────────────
+    // 多步游戏赔付敞口
+    // ─────────────────────────────────────────────────────────────
+
+    fn exposure(deps: Deps) -> ExposureResponse {
+        let msg = QueryMsg::GetExposure { denom: None };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
 
     #[test]
-    fn test_apply_house_edge_function() {
-        // 无抽水
-        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 0), 2_000_000);
+    fn test_multi_step_liability_reservation() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        // 5% 抽水（500 bps）：净赢利 1_000_000，抽水 50_000
-        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 500), 1_950_000);
+        // 只留 104,000,000 uatom 锁仓
+        let withdraw = ExecuteMsg::Withdraw {
+            amount: 9_896_000_000,
+            denom: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), withdraw).unwrap();
 
-        // 10% 抽水（1000 bps）：净赢利 1_000_000，抽水 100_000
-        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 1000), 1_900_000);
+        // 21 点开局按 3× 下注额预留（天然 21 点 2.5× 返还，向上取整）
+        let start = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: None,
+            side_bets: None,
+        };
+        let info = mock_info("bj_player", &coins(1_000_000, "uatom"));
+        play(deps.as_mut(), mock_env(), info, start).unwrap();
+        let res = exposure(deps.as_ref());
+        assert_eq!(res.locked_amount, Uint128::new(105_000_000));
+        assert_eq!(res.reserved_amount, Uint128::new(3_000_000));
+        assert_eq!(res.free_amount, Uint128::new(102_000_000));
+
+        // 奥马哈开局同样预留
+        let player = "omaha_player";
+        let start = ExecuteMsg::PlayOmaha {
+            action: OmahaAction::Start,
+            client_seed: None,
+        };
+        play(deps.as_mut(), mock_env(), mock_info(player, &coins(1_000_000, "uatom")), start)
+            .unwrap();
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(5_000_000));
+
+        // 加注按累计下注额校验玩法的静态上限（奥马哈默认 5,000,000）
+        let raise = |amount: u128| ExecuteMsg::PlayOmaha {
+            action: OmahaAction::Raise { amount },
+            client_seed: None,
+        };
+        let info = mock_info(player, &coins(4_500_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, raise(4_500_000)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TotalStakeExceedsMaxBet {
+                game: GameKind::Omaha,
+                max: 5_000_000,
+                denom: "uatom".to_string(),
+            }
+        );
+        let config = GameConfig {
+            max_bet: Uint128::new(1_000_000_000),
+            ..query_game_config(deps.as_ref(), GameKind::Omaha)
+        };
+        let update = ExecuteMsg::UpdateGameConfig { game: GameKind::Omaha, config };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update).unwrap();
 
-        // 1% 抽水（100 bps）：净赢利 1_000_000，抽水 10_000
-        assert_eq!(apply_house_edge(2_000_000, 1_000_000, 100), 1_990_000);
+        // 累计下注的最坏赔付超出动态上限（可用资金 + 本局预留）时拒绝，状态不变
+        let info = mock_info(player, &coins(150_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, raise(150_000_000)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::BetExceedsMaxPayout {
+                max_bet: 51_500_000,
+                denom: "uatom".to_string(),
+                multiplier: 2,
+                max_payout_bps: 10_000,
+            }
+        );
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(5_000_000));
 
-        // 平局退还本金时不抽水
-        assert_eq!(apply_house_edge(1_000_000, 1_000_000, 500), 1_000_000);
+        // 附带的资金必须与加注额一致，多付的部分不会被吞掉
+        let info = mock_info(player, &coins(50_000_001, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, raise(50_000_000)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FundsMismatch { action: "raise", denom: "uatom".to_string() }
+        );
 
-        // 高倍率赢利：10x 赢利，5% 抽水
-        // gross=10_000_000, bet=1_000_000, net_winnings=9_000_000, rake=450_000
-        assert_eq!(apply_house_edge(10_000_000, 1_000_000, 500), 9_550_000);
+        // 加注 50,000,000：预留补足到 2 × 51,000,000
+        let info = mock_info(player, &coins(50_000_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, raise(50_000_000)).unwrap();
+        let res = exposure(deps.as_ref());
+        assert_eq!(res.locked_amount, Uint128::new(156_000_000));
+        assert_eq!(res.reserved_amount, Uint128::new(105_000_000));
+        assert_eq!(res.free_amount, Uint128::new(51_000_000));
+
+        // 单注赔付比例降到 50% 后，可用资金足以预留的加注也会被动态上限拒绝
+        let update = ExecuteMsg::UpdateMaxPayout { max_payout_bps: 5_000 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update).unwrap();
+        let info = mock_info(player, &coins(10_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, raise(10_000_000)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::BetExceedsMaxPayout {
+                max_bet: 38_250_000,
+                denom: "uatom".to_string(),
+                multiplier: 2,
+                max_payout_bps: 5_000,
+            }
+        );
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(105_000_000));
 
-        // 0 payout 不受影响
-        assert_eq!(apply_house_edge(0, 0, 500), 0);
+        // 部署者不能提取预留部分
+        let withdraw = ExecuteMsg::Withdraw {
+            amount: 51_000_001,
+            denom: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), withdraw)
+            .unwrap_err();
+        assert!(err.to_string().contains("Insufficient locked funds"));
+
+        // 弃牌、停牌结算后释放
+        let fold = ExecuteMsg::PlayOmaha {
+            action: OmahaAction::Fold,
+            client_seed: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(player, &[]), fold).unwrap();
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(3_000_000));
+        let stand = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bj_player", &[]), stand).unwrap();
+        let res = exposure(deps.as_ref());
+        assert_eq!(res.reserved_amount, Uint128::zero());
+        assert_eq!(res.free_amount, res.locked_amount);
     }
+    // ─────────────────────────────────────────────────────────────
+    // 统一赔付结算（偿付能力校验）
+    // ─────────────────────────────────────────────────────────────
 
     #[test]
-    fn test_init_with_house_edge() {
+    fn test_uniform_solvency_check() {
+        // 账面锁仓 10,000,000,000，但合约链上没有余额：开奖前拒绝下注
         let mut deps = mock_dependencies();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        let coin_flip = |i: u32| ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: Some(format!("solvency-{}", i)),
+        };
+        let stake = coins(1_000_000, "uatom");
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &stake), coin_flip(0))
+            .unwrap_err();
+        assert!(err.to_string().contains("Insufficient contract balance"));
+
+        // 单局游戏同样按最坏情况计算：选 10 中 10 赔 2000×，最坏赔付超出可用资金时拒绝
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(CONTRACT_BALANCE, "uatom"));
+        let keno = ExecuteMsg::PlayKeno {
+            picks: (1..=10).collect(),
+            client_seed: None,
+        };
+        let info = mock_info("user", &coins(10_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, keno).unwrap_err();
+        assert!(err.to_string().contains("Bet exceeds the current max bet"));
+
+        // 猜硬币每笔预留 2× 下注额，开奖后释放
+        let mut bet_ids = vec![];
+        for i in 0..8 {
+            let info = mock_info("user", &stake);
+            let res = execute(deps.as_mut(), mock_env(), info, coin_flip(i)).unwrap();
+            bet_ids.push(attr_value(&res, "bet_id").parse::<u64>().unwrap());
+        }
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(16_000_000));
+        let reveal = ExecuteMsg::RevealServerSeed {
+            server_seed: test_server_seed(1),
+            next_seed_hash: hash_server_seed(&test_server_seed(2)),
+        };
+        let mut next_block = mock_env();
+        next_block.block.height += 1;
+        execute(deps.as_mut(), next_block, mock_info("creator", &[]), reveal).unwrap();
+        let (winners, losers): (Vec<u64>, Vec<u64>) = bet_ids.into_iter().partition(|id| {
+            verify_outcome_query(deps.as_ref(), *id).outcome
+                == GameOutcome::CoinFlip {
+                    result: CoinSide::Heads,
+                }
+        });
+        assert!(winners.len() >= 2 && !losers.is_empty());
+
+        // 合约余额被转走后：输的下注照常结算，赢的下注因真实余额不足而回滚（保持待开奖）
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, vec![]);
+        let settle = |bet_id: u64| ExecuteMsg::SettleBet { bet_id };
+        let keeper = mock_info("keeper", &[]);
+        for bet_id in losers {
+            let res = execute(deps.as_mut(), mock_env(), keeper.clone(), settle(bet_id)).unwrap();
+            assert_eq!(attr_value(&res, "result"), "lose");
+        }
+        let err = execute(deps.as_mut(), mock_env(), keeper.clone(), settle(winners[0]))
+            .unwrap_err();
+        assert!(err.to_string().contains("Insufficient contract balance"));
+
+        // 补充余额后赢的下注可以结算
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(CONTRACT_BALANCE, "uatom"));
+        for bet_id in &winners[1..] {
+            let res = execute(deps.as_mut(), mock_env(), keeper.clone(), settle(*bet_id)).unwrap();
+            assert_eq!(attr_value(&res, "result"), "win");
+        }
+    }
 
-        // 使用 5% 抽水初始化
-        let msg = InstantiateMsg { house_edge_bps: Some(500) };
+    #[test]
+    fn test_solvency_check_excludes_player_deposits() {
+        let mut deps = mock_dependencies();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
 
-        assert_eq!(res.attributes[0].value, "init");
-        assert_eq!(res.attributes[3].value, "500"); // house_edge_bps
+        // 合约链上只有玩家存入的 50,000,000 加上本次下注，庄家自己的资金不足以预留 2×
+        let info = mock_info("depositor", &coins(50_000_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Deposit {}).unwrap();
+        let coin_flip = |i: u32| ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: Some(format!("deposits-{}", i)),
+        };
+        let stake = coins(1_000_000, "uatom");
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(51_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &stake), coin_flip(0))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InsufficientContractBalance {
+                denom: "uatom".to_string(),
+                held: 1_000_000,
+                required: 2_000_000,
+            }
+        );
 
-        // 查询抽水比例
-        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
-        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
-        assert_eq!(resp.house_edge_bps, 500);
+        // 余额足够时下注，之后庄家资金被转走：存款足以支付，但赢的下注仍因庄家资金不足而回滚
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(66_000_000, "uatom"));
+        let mut bet_ids = vec![];
+        for i in 0..8 {
+            let info = mock_info("user", &stake);
+            let res = execute(deps.as_mut(), mock_env(), info, coin_flip(i)).unwrap();
+            bet_ids.push(attr_value(&res, "bet_id").parse::<u64>().unwrap());
+        }
+        let reveal = ExecuteMsg::RevealServerSeed {
+            server_seed: test_server_seed(1),
+            next_seed_hash: hash_server_seed(&test_server_seed(2)),
+        };
+        let mut next_block = mock_env();
+        next_block.block.height += 1;
+        execute(deps.as_mut(), next_block, mock_info("creator", &[]), reveal).unwrap();
+        let winners: Vec<u64> = bet_ids
+            .into_iter()
+            .filter(|id| {
+                verify_outcome_query(deps.as_ref(), *id).outcome
+                    == GameOutcome::CoinFlip { result: CoinSide::Heads }
+            })
+            .collect();
+        assert!(winners.len() >= 2);
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(51_000_000, "uatom"));
+        let settle = |bet_id: u64| ExecuteMsg::SettleBet { bet_id };
+        let keeper = mock_info("keeper", &[]);
+        let err = execute(deps.as_mut(), mock_env(), keeper.clone(), settle(winners[0]))
+            .unwrap_err();
+        assert!(err.to_string().contains("Insufficient contract balance"));
+
+        // 玩家提取存款后不再计入；庄家补足资金后可以结算
+        let withdraw = ExecuteMsg::WithdrawBalance { amount: None, denom: None };
+        execute(deps.as_mut(), mock_env(), mock_info("depositor", &[]), withdraw).unwrap();
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(2_000_000, "uatom"));
+        let res = execute(deps.as_mut(), mock_env(), keeper, settle(winners[1])).unwrap();
+        assert_eq!(attr_value(&res, "result"), "win");
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 单注风险上限（Kelly 式）
+    // ─────────────────────────────────────────────────────────────
+
+    fn bet_limits(deps: Deps, game: ExecuteMsg) -> BetLimitsResponse {
+        let msg = QueryMsg::GetBetLimits {
+            game: Box::new(game),
+            denom: None,
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
     }
 
     #[test]
-    fn test_init_with_default_house_edge() {
-        let mut deps = mock_dependencies();
-
-        // 不设置抽水（默认 0）
-        let msg = InstantiateMsg { house_edge_bps: None };
+    fn test_bet_limits() {
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        let coin_flip = ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: None,
+        };
+        let keno = ExecuteMsg::PlayKeno {
+            picks: (1..=10).collect(),
+            client_seed: None,
+        };
 
-        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
-        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
-        assert_eq!(resp.house_edge_bps, 0);
+        // 默认 100%：猜硬币 10,000,000,000 / 2 远超静态上限，选 10 中 10 按 2000× 收窄
+        let res = bet_limits(deps.as_ref(), coin_flip.clone());
+        assert_eq!(res.max_payout_bps, 10_000);
+        assert_eq!(res.max_payout, Uint128::new(10_000_000_000));
+        assert_eq!(res.max_multiplier, Uint128::new(2));
+        assert_eq!(res.min_bet, Uint128::new(100_000));
+        assert_eq!(res.max_bet, Uint128::new(10_000_000));
+        assert_eq!(res.static_max_bet, Uint128::new(10_000_000));
+        let res = bet_limits(deps.as_ref(), keno.clone());
+        assert_eq!(res.max_multiplier, Uint128::new(2_000));
+        assert_eq!(res.max_bet, Uint128::new(5_000_000));
+
+        // 只有所有者能修改，比例必须在 1 – 10000 基点之间
+        let update = |bps: u16| ExecuteMsg::UpdateMaxPayout { max_payout_bps: bps };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), update(100))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        for bps in [0, 10_001] {
+            let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update(bps))
+                .unwrap_err();
+            assert!(err.to_string().contains("Max payout must be between 1 and 10000 bps"));
+        }
+        let res =
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update(1)).unwrap();
+        assert_eq!(attr_value(&res, "old_max_payout_bps"), "10000");
+        assert_eq!(attr_value(&res, "new_max_payout_bps"), "1");
+
+        // 1 基点：单注最多赢 1,000,000，猜硬币上限降到 500,000
+        let res = bet_limits(deps.as_ref(), coin_flip.clone());
+        assert_eq!(res.max_payout, Uint128::new(1_000_000));
+        assert_eq!(res.max_bet, Uint128::new(500_000));
+        let info = mock_info("user", &coins(500_001, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip.clone()).unwrap_err();
+        assert!(err.to_string().contains("Bet exceeds the current max bet of 500000 uatom"));
+        let info = mock_info("user", &coins(500_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, coin_flip.clone()).unwrap();
+
+        // 高赔率玩法的动态上限低于最低下注额时无法下注
+        assert_eq!(bet_limits(deps.as_ref(), keno.clone()).max_bet, Uint128::new(499));
+        let info = mock_info("user", &coins(100_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, keno).unwrap_err();
+        assert!(err.to_string().contains("Bet exceeds the current max bet"));
+
+        // 下注计入锁仓、预留 2× 占用可用资金，上限随之下降
+        let res = bet_limits(deps.as_ref(), coin_flip);
+        assert_eq!(res.max_payout, Uint128::new(999_950));
+        assert_eq!(res.max_bet, Uint128::new(499_975));
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 玩法配置
+    // ─────────────────────────────────────────────────────────────
+
+    fn query_game_config(deps: Deps, game: GameKind) -> GameConfig {
+        let msg = QueryMsg::GetGameConfig { game };
+        from_json::<GameConfigResponse>(query(deps, mock_env(), msg).unwrap()).unwrap().config
     }
 
     #[test]
-    fn test_init_house_edge_exceeds_max() {
-        let mut deps = mock_dependencies();
-
-        // 超过 10% 应失败
-        let msg = InstantiateMsg { house_edge_bps: Some(1001) };
+    fn test_game_config() {
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        let err = instantiate(deps.as_mut(), mock_env(), info, msg);
-        assert!(err.is_err(), "house edge > 1000 bps should be rejected");
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+
+        // 未修改时返回原有限额
+        let omaha = query_game_config(deps.as_ref(), GameKind::Omaha);
+        assert_eq!(omaha, GameKind::Omaha.default_config());
+        assert_eq!((omaha.min_bet.u128(), omaha.max_bet.u128()), (100_000, 5_000_000));
+        assert_eq!(omaha.min_raise, Uint128::new(50_000));
+        let scratch = query_game_config(deps.as_ref(), GameKind::ScratchDeluxe);
+        assert_eq!((scratch.min_bet.u128(), scratch.max_bet.u128()), (1_000_000, 10_000_000));
+        let res: GameConfigsResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListGameConfigs {}).unwrap())
+                .unwrap();
+        assert_eq!(res.configs.len(), GameKind::ALL.len());
+
+        // 只有所有者能修改，配置须合法
+        let coin_flip_config = GameConfig {
+            enabled: true,
+            min_bet: Uint128::new(200_000),
+            max_bet: Uint128::new(1_000_000),
+            house_edge_bps: Some(500),
+            paytable_version: 2,
+            min_raise: Uint128::zero(),
+        };
+        let update = |config: GameConfig| ExecuteMsg::UpdateGameConfig {
+            game: GameKind::CoinFlip,
+            config,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user", &[]),
+            update(coin_flip_config.clone()),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let invalid = GameConfig {
+            min_bet: Uint128::new(2_000_000),
+            ..coin_flip_config.clone()
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update(invalid))
+            .unwrap_err();
+        assert!(err.to_string().contains("Min bet must be greater than 0 and not exceed max bet"));
+        let invalid = GameConfig {
+            house_edge_bps: Some(1001),
+            ..coin_flip_config.clone()
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update(invalid))
+            .unwrap_err();
+        assert!(err.to_string().contains("House edge must not exceed 1000 bps"));
+        let info = mock_info("creator", &[]);
+        execute(deps.as_mut(), mock_env(), info, update(coin_flip_config.clone())).unwrap();
+        assert_eq!(query_game_config(deps.as_ref(), GameKind::CoinFlip), coin_flip_config);
+
+        // 新限额立即生效
+        let coin_flip = |i: u32| ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: Some(format!("config-{}", i)),
+        };
+        let info = mock_info("user", &coins(100_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip(0)).unwrap_err();
+        assert!(err.to_string().contains("Bet must be between 200000 and 1000000 uatom"));
+        let limits = bet_limits(deps.as_ref(), coin_flip(0));
+        assert_eq!(limits.max_bet, Uint128::new(1_000_000));
+
+        // 抽水覆盖：全局抽水为 0，猜硬币按 5% 抽水
+        let mut won = false;
+        for i in 0..16 {
+            let info = mock_info("user", &coins(1_000_000, "uatom"));
+            let res = play(deps.as_mut(), mock_env(), info, coin_flip(i)).unwrap();
+            if attr_value(&res, "result") == "win" {
+                assert_eq!(attr_value(&res, "payout"), "1950000");
+                won = true;
+                break;
+            }
+        }
+        assert!(won);
+
+        // 关闭玩法后拒绝新下注
+        let keno_config = GameConfig {
+            enabled: false,
+            ..GameKind::Keno.default_config()
+        };
+        let msg = ExecuteMsg::UpdateGameConfig {
+            game: GameKind::Keno,
+            config: keno_config,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let keno = ExecuteMsg::PlayKeno {
+            picks: vec![1, 2, 3],
+            client_seed: None,
+        };
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, keno).unwrap_err();
+        assert!(err.to_string().contains("Game keno is disabled"));
     }
 
+    // ─────────────────────────────────────────────────────────────
+    // 熔断（紧急暂停 / 玩法开关）
+    // ─────────────────────────────────────────────────────────────
+
     #[test]
-    fn test_update_house_edge() {
-        let mut deps = mock_dependencies();
+    fn test_circuit_breaker() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        let owner = mock_info("creator", &[]);
+        let pauser = mock_info("pauser", &[]);
+
+        // 只有所有者能授予暂停人角色
+        let set_pauser = ExecuteMsg::GrantRole {
+            role: Role::Pauser,
+            address: "pauser".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), pauser.clone(), set_pauser.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        execute(deps.as_mut(), mock_env(), owner.clone(), set_pauser).unwrap();
 
-        // 初始化合约，抽水 0
-        let msg = InstantiateMsg { house_edge_bps: None };
+        // 暂停前：一局进行中的 21 点和两笔待开奖的猜硬币
+        let start = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: None,
+            side_bets: None,
+        };
+        let info = mock_info("bj_player", &coins(500_000, "uatom"));
+        play(deps.as_mut(), mock_env(), info, start).unwrap();
+        let coin_flip = |i: u32| ExecuteMsg::PlayCoinFlip {
+            choice: CoinSide::Heads,
+            client_seed: Some(format!("pause-{}", i)),
+        };
+        let mut bet_ids = vec![];
+        for i in 0..2 {
+            let info = mock_info("user", &coins(1_000_000, "uatom"));
+            let res = execute(deps.as_mut(), mock_env(), info, coin_flip(i)).unwrap();
+            bet_ids.push(attr_value(&res, "bet_id").parse::<u64>().unwrap());
+        }
+
+        // 暂停人可以暂停，其他人不行
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), ExecuteMsg::Pause {})
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        execute(deps.as_mut(), mock_env(), pauser.clone(), ExecuteMsg::Pause {}).unwrap();
+
+        // 暂停期间拒绝新下注
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip(2)).unwrap_err();
+        assert!(err.to_string().contains("Contract is paused"));
+
+        // 种子未揭示的下注可立即退款
+        let refund = ExecuteMsg::RefundBet { bet_id: bet_ids[0] };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), refund).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "user".to_string(),
+                amount: coins(1_000_000, "uatom"),
+            }
+            .into()
+        );
+
+        // 种子揭示后暂停期间仍可结算已下的注
+        let mut next_block = mock_env();
+        next_block.block.height += 1;
+        let commitment_id = ACTIVE_COMMITMENT.load(deps.as_ref().storage).unwrap();
+        let reveal = ExecuteMsg::RevealServerSeed {
+            server_seed: test_server_seed(commitment_id),
+            next_seed_hash: hash_server_seed(&test_server_seed(commitment_id + 1)),
+        };
+        execute(deps.as_mut(), next_block.clone(), owner.clone(), reveal).unwrap();
+        let settle = ExecuteMsg::SettleBet { bet_id: bet_ids[1] };
+        let res = execute(deps.as_mut(), next_block, mock_info("keeper", &[]), settle).unwrap();
+        assert_eq!(attr_value(&res, "bet_id"), bet_ids[1].to_string());
+
+        // 进行中的 21 点仍可停牌结束
+        let stand = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("bj_player", &[]), stand).unwrap();
+
+        // 只有所有者能解除暂停
+        let err = execute(deps.as_mut(), mock_env(), pauser.clone(), ExecuteMsg::Unpause {})
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        execute(deps.as_mut(), mock_env(), owner.clone(), ExecuteMsg::Unpause {}).unwrap();
+
+        // 暂停人可以关闭单个玩法，重新开放只能由所有者操作
+        let set_enabled = |enabled: bool| ExecuteMsg::SetGameEnabled {
+            game: GameKind::CoinFlip,
+            enabled,
+        };
+        execute(deps.as_mut(), mock_env(), pauser.clone(), set_enabled(false)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), pauser, set_enabled(true)).unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, coin_flip(3)).unwrap_err();
+        assert!(err.to_string().contains("Game coin_flip is disabled"));
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::PlayWar { client_seed: None })
+            .unwrap();
+
+        let res: CircuitBreakerResponse =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetCircuitBreaker {}).unwrap())
+                .unwrap();
+        assert!(!res.paused);
+        assert_eq!(res.disabled_games, vec![GameKind::CoinFlip]);
+
+        // 风控不能借修改玩法配置重新开放被关闭的玩法
+        let grant = ExecuteMsg::GrantRole {
+            role: Role::RiskManager,
+            address: "risk".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), grant).unwrap();
+        let update = ExecuteMsg::UpdateGameConfig {
+            game: GameKind::CoinFlip,
+            config: GameKind::CoinFlip.default_config(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("risk", &[]), update).unwrap_err();
+        assert_eq!(err, ContractError::GameReenableRequiresOwner { game: GameKind::CoinFlip });
+        assert!(!query_game_config(deps.as_ref(), GameKind::CoinFlip).enabled);
+
+        execute(deps.as_mut(), mock_env(), owner, set_enabled(true)).unwrap();
+        let info = mock_info("user", &coins(1_000_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, coin_flip(3)).unwrap();
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 角色与所有权
+    // ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_roles_and_ownership() {
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        let grant = |role: Role, address: &str| ExecuteMsg::GrantRole {
+            role,
+            address: address.to_string(),
+        };
 
-        // owner 修改抽水到 3%（300 bps）
-        let update_res = execute(
+        // 只有所有者能授予角色
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("creator", &coins(0, "uatom")),
-            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 300 },
-        ).unwrap();
+            mock_info("ops", &[]),
+            grant(Role::RiskManager, "ops"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        for (role, address) in [(Role::Treasurer, "treasury"), (Role::RiskManager, "ops")] {
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), grant(role, address))
+                .unwrap();
+        }
+        let res: AddressRolesResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetRoles { address: "ops".into() })
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!((res.is_owner, res.roles), (false, vec![Role::RiskManager]));
+        let res: RoleMembersResponse = from_json(
+            query(deps.as_ref(), mock_env(), QueryMsg::GetRoleMembers { role: Role::Treasurer })
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.members, vec![Addr::unchecked("treasury")]);
+
+        // 风控只能改风控参数，不能提款
+        let edge = ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 200 };
+        execute(deps.as_mut(), mock_env(), mock_info("ops", &[]), edge).unwrap();
+        let withdraw = ExecuteMsg::Withdraw {
+            amount: 1_000_000,
+            denom: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ops", &[]), withdraw.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
 
+        // 财务提款转入所有者地址
+        let res =
+            execute(deps.as_mut(), mock_env(), mock_info("treasury", &[]), withdraw.clone())
+                .unwrap();
         assert_eq!(
-            update_res.attributes.iter().find(|a| a.key == "action").unwrap().value,
-            "update_house_edge"
-        );
-        assert_eq!(
-            update_res.attributes.iter().find(|a| a.key == "old_house_edge_bps").unwrap().value,
-            "0"
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1_000_000, "uatom"),
+            }
+            .into()
         );
+
+        // 撤销后失去权限
+        let revoke = ExecuteMsg::RevokeRole {
+            role: Role::Treasurer,
+            address: "treasury".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), revoke).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("treasury", &[]), withdraw.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+
+        // 两步转移所有权：提议后原所有者仍有效，只有被提议的地址能接受
+        let transfer = ExecuteMsg::TransferOwnership {
+            new_owner: "multisig".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ops", &[]), transfer.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), transfer).unwrap();
+        let ownership = |deps: Deps| -> OwnershipResponse {
+            from_json(query(deps, mock_env(), QueryMsg::GetOwnership {}).unwrap()).unwrap()
+        };
+        let res = ownership(deps.as_ref());
+        assert_eq!(res.owner, Addr::unchecked("creator"));
+        assert_eq!(res.pending_owner, Some(Addr::unchecked("multisig")));
+        let accept = ExecuteMsg::AcceptOwnership {};
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ops", &[]), accept.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Caller is not the pending owner"));
+        execute(deps.as_mut(), mock_env(), mock_info("multisig", &[]), accept.clone()).unwrap();
+        let res = ownership(deps.as_ref());
+        assert_eq!(res.owner, Addr::unchecked("multisig"));
+        assert_eq!(res.pending_owner, None);
+
+        // 原所有者失去全部权限，角色成员不受影响
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), withdraw.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("multisig", &[]), withdraw).unwrap();
         assert_eq!(
-            update_res.attributes.iter().find(|a| a.key == "new_house_edge_bps").unwrap().value,
-            "300"
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "multisig".to_string(),
+                amount: coins(1_000_000, "uatom"),
+            }
+            .into()
         );
+        let edge = ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 300 };
+        execute(deps.as_mut(), mock_env(), mock_info("ops", &[]), edge).unwrap();
 
-        // 验证查询结果
-        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
-        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
-        assert_eq!(resp.house_edge_bps, 300);
+        // 取消尚未接受的转移
+        let transfer = ExecuteMsg::TransferOwnership {
+            new_owner: "creator".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("multisig", &[]), transfer).unwrap();
+        let cancel = ExecuteMsg::CancelOwnershipTransfer {};
+        execute(deps.as_mut(), mock_env(), mock_info("multisig", &[]), cancel.clone()).unwrap();
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), accept).unwrap_err();
+        assert!(err.to_string().contains("Caller is not the pending owner"));
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("multisig", &[]), cancel).unwrap_err();
+        assert!(err.to_string().contains("No pending ownership transfer"));
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 管理操作时间锁
+    // ─────────────────────────────────────────────────────────────
+
+    fn pending_admin_actions(deps: Deps) -> Vec<PendingAdminAction> {
+        let msg = QueryMsg::ListPendingAdminActions {};
+        from_json::<PendingAdminActionsResponse>(query(deps, mock_env(), msg).unwrap())
+            .unwrap()
+            .actions
     }
 
-    #[test]
-    fn test_update_house_edge_unauthorized() {
-        let mut deps = mock_dependencies();
+    fn house_edge(deps: Deps) -> u16 {
+        let msg = QueryMsg::GetHouseEdge {};
+        let res: HouseEdgeResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.house_edge_bps
+    }
 
-        let msg = InstantiateMsg { house_edge_bps: None };
+    #[test]
+    fn test_admin_timelock() {
+        let mut deps = funded_deps();
+        let msg = InstantiateMsg {
+            timelock: Some(TimelockConfig {
+                delay_blocks: 100,
+                delay_seconds: 600,
+            }),
+            ..init_msg(None)
+        };
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let owner = mock_info("creator", &[]);
 
-        // 非 owner 尝试修改抽水
-        let err = execute(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("hacker", &coins(0, "uatom")),
-            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 500 },
+        // 提款和修改抽水先排队，不立即生效；参数在提议时校验
+        let withdraw = ExecuteMsg::Withdraw {
+            amount: 1_000_000,
+            denom: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), owner.clone(), withdraw).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(attr_value(&res, "admin_action_id"), "1");
+        let edge = |bps: u16| ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: bps };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), edge(2_000)).unwrap_err();
+        assert!(err.to_string().contains("House edge must not exceed 1000 bps"));
+        execute(deps.as_mut(), mock_env(), owner.clone(), edge(500)).unwrap();
+        assert_eq!(house_edge(deps.as_ref()), 0);
+
+        // 玩家可以看到即将发生的变更
+        let actions = pending_admin_actions(deps.as_ref());
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[1].action, AdminAction::UpdateHouseEdge { new_house_edge_bps: 500 });
+        assert_eq!(actions[1].ready_height, mock_env().block.height + 100);
+        assert_eq!(actions[1].ready_time, mock_env().block.time.seconds() + 600);
+
+        // 区块数与秒数都满足后才能执行
+        let execute_action = |id: u64| ExecuteMsg::ExecuteAdminAction { id };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), execute_action(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("Admin action 1 is not ready"));
+        let mut env = mock_env();
+        env.block.height += 100;
+        env.block.time = env.block.time.plus_seconds(300);
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), execute_action(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("Admin action 1 is not ready"));
+        env.block.time = env.block.time.plus_seconds(300);
+
+        // 执行需要相应角色，提款转入所有者地址
+        let err = execute(deps.as_mut(), env.clone(), mock_info("user", &[]), execute_action(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), execute_action(1)).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1_000_000, "uatom"),
+            }
+            .into()
         );
-        assert!(err.is_err(), "non-owner should not be able to update house edge");
+        let err = execute(deps.as_mut(), env.clone(), owner.clone(), execute_action(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("Admin action 1 not found"));
+
+        // 暂停人可以取消待执行的操作
+        let grant = ExecuteMsg::GrantRole {
+            role: Role::Pauser,
+            address: "pauser".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), grant).unwrap();
+        let cancel = ExecuteMsg::CancelAdminAction { id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), cancel.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        execute(deps.as_mut(), mock_env(), mock_info("pauser", &[]), cancel).unwrap();
+        assert!(pending_admin_actions(deps.as_ref()).is_empty());
+        assert_eq!(house_edge(deps.as_ref()), 0);
+
+        // 修改时间锁本身也要等待当前延迟，之后的操作立即生效
+        let update = ExecuteMsg::UpdateTimelock {
+            delay_blocks: 0,
+            delay_seconds: 0,
+        };
+        let res = execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap();
+        assert_eq!(attr_value(&res, "admin_action_id"), "3");
+        execute(deps.as_mut(), env, owner.clone(), execute_action(3)).unwrap();
+        let timelock: TimelockConfig =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetTimelock {}).unwrap())
+                .unwrap();
+        assert!(timelock.is_zero());
+        execute(deps.as_mut(), mock_env(), owner, edge(500)).unwrap();
+        assert_eq!(house_edge(deps.as_ref()), 500);
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 合约迁移
+    // ─────────────────────────────────────────────────────────────
+
+    fn contract_version(deps: Deps) -> ContractVersionResponse {
+        let msg = QueryMsg::GetContractVersion {};
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_migrate() {
+        use crate::migrate::{BlackjackStateV1, StateV1};
+
+        let mut deps = funded_deps();
+        let player = Addr::unchecked("player");
+
+        // 版本 1 的存储：单一锁仓、没有任何版本号，一局进行中的 21 点和一局进行中的奥马哈
+        let legacy = StateV1 {
+            owner: Addr::unchecked("creator"),
+            locked_amount: 10_000_000_000,
+            house_edge_bps: 200,
+        };
+        STATE_V1.save(deps.as_mut().storage, &legacy).unwrap();
+        let hand = BlackjackStateV1 {
+            user_cards: vec![10, 6],
+            dealer_cards: vec![9, 7],
+            bet: Uint128::new(1_000_000),
+            finished: false,
+        };
+        BLACKJACK_STATE_V1.save(deps.as_mut().storage, &player, &hand).unwrap();
+        let deck: Vec<u8> = (0..52).collect();
+        let omaha = OmahaState {
+            player_hand: deck[0..4].iter().map(|id| Card::from_id(*id)).collect(),
+            dealer_hand: deck[4..8].iter().map(|id| Card::from_id(*id)).collect(),
+            community_cards: vec![],
+            player_total_bet: Uint128::new(2_000_000),
+            current_call_amount: Uint128::new(2_000_000),
+            stage: OmahaStage::PreFlop,
+            finished: false,
+            deck_pos: 13,
+            source: FundsSource::Attached,
+            denom: "uatom".to_string(),
+            reserved: Uint128::zero(),
+            bet_id: 0,
+            start_height: 0,
+            start_time: 0,
+        };
+        // 旧结构没有资金来源、币种和预留字段，并存储了完整牌序
+        let json = String::from_utf8(to_json_binary(&omaha).unwrap().to_vec()).unwrap();
+        let new_fields = r#","source":"attached","denom":"uatom","reserved":"0""#;
+        let legacy_deck = format!(r#""deck":{:?},"deck_pos""#, deck);
+        let legacy_json = json.replace(new_fields, "").replace(r#""deck_pos""#, &legacy_deck);
+        assert!(json.contains(new_fields) && !json.contains(r#""deck":"#));
+        deps.storage.set(&OMAHA_STATE.key(&player), legacy_json.as_bytes());
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(attr_value(&res, "from_state_version"), "1");
+        assert_eq!(attr_value(&res, "to_state_version"), "2");
+        assert_eq!(attr_value(&res, "refunded_poker_games"), "1");
+        assert_eq!(attr_value(&res, "refunded_blackjack_games"), "1");
+        // 进行中的奥马哈没有下注记录、无法重新洗牌，进行中的 21 点无法继续发牌，都退还下注
+        let refund = |amount| {
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: player.to_string(),
+                amount: coins(amount, "uatom"),
+            })
+        };
+        assert_eq!(res.messages[0].msg, refund(2_000_000));
+        assert_eq!(res.messages[1].msg, refund(1_000_000));
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.owner, Addr::unchecked("creator"));
+        assert_eq!(state.house_edge_bps, 200);
+        assert_eq!(state.locked("uatom"), 10_000_000_000 - 3_000_000);
+        assert!(BLACKJACK_STATE.load(deps.as_ref().storage, &player).unwrap().finished);
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::zero());
+
+        // 奥马哈牌局已结束，存储中不再保留牌序
+        let game = OMAHA_STATE.load(deps.as_ref().storage, &player).unwrap();
+        assert!(game.finished);
+        let stored = deps.storage.get(&OMAHA_STATE.key(&player)).unwrap();
+        assert!(!String::from_utf8(stored).unwrap().contains(r#""deck""#));
+
+        let version = contract_version(deps.as_ref());
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+        assert_eq!(version.state_version, CURRENT_STATE_VERSION);
+
+        // 已是当前版本：再次迁移不做任何改动
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(attr_value(&res, "from_state_version"), "2");
+
+        // 拒绝其他合约和降级
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(err.to_string().contains("Cannot migrate from contract crates.io:other"));
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(err.to_string().contains("to older version"));
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, CONTRACT_VERSION).unwrap();
+        STATE_VERSION.save(deps.as_mut().storage, &(CURRENT_STATE_VERSION + 1)).unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(err.to_string().contains("older state version"));
+
+        // 新部署的合约直接记录当前版本
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        assert_eq!(contract_version(deps.as_ref()).state_version, CURRENT_STATE_VERSION);
     }
 
-    #[test]
-    fn test_update_house_edge_exceeds_max() {
-        let mut deps = mock_dependencies();
+    // ─────────────────────────────────────────────────────────────
+    // 错误类型
+    // ─────────────────────────────────────────────────────────────
 
-        let msg = InstantiateMsg { house_edge_bps: None };
+    #[test]
+    fn test_contract_errors() {
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(None)).unwrap();
+        let player = mock_info("player", &coins(1_000_000, "uatom"));
 
-        // 尝试设置超过 10% 的抽水
-        let err = execute(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("creator", &coins(0, "uatom")),
-            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 1001 },
+        // 参数校验：基诺号码与骰宝下注直接返回对应变体
+        let keno = ExecuteMsg::PlayKeno { picks: vec![5, 5], client_seed: None };
+        let err = execute(deps.as_mut(), mock_env(), player.clone(), keno).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidPicks { reason: "Duplicate numbers are not allowed" }
+        );
+        let sicbo = ExecuteMsg::PlaySicBo {
+            bet_type: SicBoBetType::Total { value: 20 },
+            client_seed: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), player.clone(), sicbo).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidSicBoBet { reason: "Total bet must be between 4 and 17" }
         );
-        assert!(err.is_err(), "house edge > 1000 bps should be rejected");
 
-        // 设置最大值 1000 应成功
-        let ok = execute(
+        // 下注额超出范围
+        let small = mock_info("player", &coins(1_000, "uatom"));
+        let msg = ExecuteMsg::PlayWar { client_seed: None };
+        let err = execute(deps.as_mut(), mock_env(), small, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidBet {
+                game: GameKind::War,
+                min: 100_000,
+                max: 10_000_000,
+                denom: "uatom".to_string(),
+            }
+        );
+
+        // 没有进行中的牌局
+        let hit = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Hit,
+            client_seed: None,
+            side_bets: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), player.clone(), hit).unwrap_err();
+        assert_eq!(err, ContractError::GameNotFound { game: GameKind::Blackjack });
+
+        // 权限
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("creator", &coins(0, "uatom")),
-            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 1000 },
-        );
-        assert!(ok.is_ok(), "house edge 1000 bps (10%) should be accepted");
+            player,
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
 
-        // 设置 0 应成功
-        let ok2 = execute(
+        // 底层存储错误包装为 Std
+        let err = execute(
             deps.as_mut(),
             mock_env(),
-            mock_info("creator", &coins(0, "uatom")),
-            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 0 },
-        );
-        assert!(ok2.is_ok(), "house edge 0 bps should be accepted");
+            mock_info("keeper", &[]),
+            ExecuteMsg::SettleBet { bet_id: 42 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(cosmwasm_std::StdError::NotFound { .. })));
+
+        // 查询时地址不合法返回错误而不是 panic
+        let query_msg = QueryMsg::GetBlackjackState { address: "Invalid_Address".to_string() };
+        let err = query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(cosmwasm_std::StdError::GenericErr { .. })));
     }
 
-    #[test]
-    fn test_coin_flip_with_house_edge() {
-        let mut deps = mock_dependencies();
+    // ─────────────────────────────────────────────────────────────
+    // 结算事件
+    // ─────────────────────────────────────────────────────────────
 
-        // 初始化合约，5% 抽水
-        let msg = InstantiateMsg { house_edge_bps: Some(500) };
+    fn settled_event(res: &Response) -> &Event {
+        res.events.iter().find(|e| e.ty == "game_settled").unwrap()
+    }
+
+    fn event_attr(event: &Event, key: &str) -> String {
+        event.attributes.iter().find(|a| a.key == key).unwrap().value.clone()
+    }
+
+    #[test]
+    fn test_game_settled_event() {
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(Some(500))).unwrap();
+
+        // 单局游戏：赢局扣除 5% 赢利抽水，输局赔付为 0
+        let (mut wins, mut losses) = (0, 0);
+        for _ in 0..20 {
+            let msg = ExecuteMsg::PlayCoinFlip { choice: CoinSide::Heads, client_seed: None };
+            let player = mock_info("player", &coins(1_000_000, "uatom"));
+            let res = play(deps.as_mut(), mock_env(), player, msg).unwrap();
+            let event = settled_event(&res);
+            assert_eq!(event_attr(event, "game"), "coin_flip");
+            assert_eq!(event_attr(event, "bet_id"), attr_value(&res, "bet_id"));
+            assert_eq!(event_attr(event, "player"), "player");
+            assert_eq!(event_attr(event, "denom"), "uatom");
+            assert_eq!(event_attr(event, "wager"), "1000000");
+
+            let outcome: GameOutcome = from_json(event_attr(event, "outcome")).unwrap();
+            let GameOutcome::CoinFlip { result } = outcome else {
+                panic!("unexpected outcome {:?}", outcome);
+            };
+            if result == CoinSide::Heads {
+                wins += 1;
+                assert_eq!(event_attr(event, "result"), "win");
+                assert_eq!(event_attr(event, "payout"), "1950000");
+                assert_eq!(event_attr(event, "multiplier"), "1.95");
+                assert_eq!(event_attr(event, "house_rake"), "50000");
+            } else {
+                losses += 1;
+                assert_eq!(event_attr(event, "result"), "lose");
+                assert_eq!(event_attr(event, "payout"), "0");
+                assert_eq!(event_attr(event, "multiplier"), "0");
+                assert_eq!(event_attr(event, "house_rake"), "0");
+            }
+        }
+        assert!(wins > 0 && losses > 0);
 
-        let user = "player1";
-        let bet = 1_000_000u128;
-        let user_info = mock_info(user, &coins(bet, "uatom"));
+        // 多步游戏：开局不发事件，结束时按开局下注的 ID 发出
+        let player = mock_info("bj_player", &coins(1_000_000, "uatom"));
+        let start = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: Some("bj-stand".to_string()),
+            side_bets: None,
+        };
+        let res = play(deps.as_mut(), mock_env(), player.clone(), start).unwrap();
+        assert!(res.events.is_empty());
+        let bet_id = attr_value(&res, "bet_id");
 
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            user_info,
-            ExecuteMsg::PlayCoinFlip { choice: CoinSide::Heads },
-        ).unwrap();
+        let stand = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bj_player", &[]), stand).unwrap();
+        let event = settled_event(&res);
+        assert_eq!(event_attr(event, "game"), "blackjack");
+        assert_eq!(event_attr(event, "bet_id"), bet_id);
+        assert_eq!(event_attr(event, "wager"), "1000000");
+        let outcome: GameOutcome = from_json(event_attr(event, "outcome")).unwrap();
+        let state: BlackjackStateResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetBlackjackState { address: "bj_player".to_string() },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let GameOutcome::Blackjack { user_hands, dealer_cards, .. } = outcome else {
+            panic!("unexpected outcome {:?}", outcome);
+        };
+        assert_eq!(user_hands, vec![state.hands[0].cards.clone()]);
+        assert_eq!(dealer_cards, state.dealer_cards);
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        // 弃牌按输掉全部投入结算
+        let player = mock_info("omaha_player", &coins(500_000, "uatom"));
+        let start = ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None };
+        play(deps.as_mut(), mock_env(), player, start).unwrap();
+        let fold = ExecuteMsg::PlayOmaha { action: OmahaAction::Fold, client_seed: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("omaha_player", &[]), fold).unwrap();
+        let event = settled_event(&res);
+        assert_eq!(event_attr(event, "game"), "omaha");
+        assert_eq!(event_attr(event, "result"), "lose");
+        assert_eq!(event_attr(event, "wager"), "500000");
+        assert_eq!(event_attr(event, "payout"), "0");
+    }
 
-        if result.value == "win" {
-            // 5% 抽水：gross=2_000_000, net_winnings=1_000_000, rake=50_000
-            // 实际支付 = 2_000_000 - 50_000 = 1_950_000
-            let payout_attr = res.attributes.iter().find(|a| a.key == "payout").unwrap();
-            let payout_val: u128 = payout_attr.value.parse().unwrap();
-            assert_eq!(payout_val, 1_950_000, "payout should reflect 5% house edge");
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: user.to_string(),
-                    amount: coins(1_950_000, "uatom"),
-                }.into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
-        }
+    // ─────────────────────────────────────────────────────────────
+    // 多步游戏超时
+    // ─────────────────────────────────────────────────────────────
+
+    fn game_timeout(deps: Deps) -> GameTimeout {
+        from_json(query(deps, mock_env(), QueryMsg::GetGameTimeout {}).unwrap()).unwrap()
     }
 
     #[test]
-    fn test_war_with_house_edge() {
-        let mut deps = mock_dependencies();
-
-        // 初始化合约，10% 抽水
-        let msg = InstantiateMsg { house_edge_bps: Some(1000) };
+    fn test_expire_game() {
+        let mut deps = funded_deps();
+        let msg = InstantiateMsg {
+            game_timeout: Some(GameTimeout {
+                timeout_blocks: 100,
+                timeout_seconds: 600,
+            }),
+            ..init_msg(None)
+        };
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let keeper = mock_info("keeper", &[]);
+        let expire = |game: GameKind, player: &str| ExecuteMsg::ExpireGame {
+            game,
+            player: player.to_string(),
+        };
 
-        let res = play_war(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("user", &coins(1_000_000, "uatom")),
-        ).unwrap();
+        // 21 点开局后玩家不再操作
+        let start = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: None,
+            side_bets: None,
+        };
+        let player = mock_info("bj_player", &coins(1_000_000, "uatom"));
+        let res = play(deps.as_mut(), mock_env(), player, start).unwrap();
+        let bet_id = attr_value(&res, "bet_id");
+        assert!(!exposure(deps.as_ref()).reserved_amount.is_zero());
+
+        // 未到超时：区块数与秒数都要满足（play 在下一个区块开奖开局）
+        let mut start_env = mock_env();
+        start_env.block.height += 1;
+        let mut early_env = start_env.clone();
+        early_env.block.height += 99;
+        let msg = expire(GameKind::Blackjack, "bj_player");
+        let err = execute(deps.as_mut(), early_env, keeper.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::GameNotExpired {
+                game: GameKind::Blackjack,
+                height: start_env.block.height + 100,
+                time: start_env.block.time.seconds() + 600,
+            }
+        );
 
-        let result = res.attributes.iter().find(|a| a.key == "result").expect("result missing");
+        // 超时后任何人都可以结算：庄家补牌比牌，删除牌局并释放预留
+        let mut late_env = start_env.clone();
+        late_env.block.height += 100;
+        late_env.block.time = late_env.block.time.plus_seconds(600);
+        let res = execute(deps.as_mut(), late_env.clone(), keeper.clone(), msg.clone()).unwrap();
+        let expired = res.events.iter().find(|e| e.ty == "game_expired").unwrap();
+        assert_eq!(event_attr(expired, "game"), "blackjack");
+        assert_eq!(event_attr(expired, "bet_id"), bet_id);
+        assert_eq!(event_attr(expired, "player"), "bj_player");
+        assert_eq!(event_attr(expired, "resolution"), "dealer_plays_out");
+        assert_eq!(event_attr(expired, "expired_by"), "keeper");
+        assert_eq!(event_attr(settled_event(&res), "bet_id"), bet_id);
+        assert!(BLACKJACK_STATE
+            .may_load(deps.as_ref().storage, &Addr::unchecked("bj_player"))
+            .unwrap()
+            .is_none());
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::zero());
+        let err = execute(deps.as_mut(), late_env.clone(), keeper.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::GameNotFound { game: GameKind::Blackjack });
+
+        // 奥马哈：注额已跟齐，自动摊牌
+        let start = ExecuteMsg::PlayOmaha { action: OmahaAction::Start, client_seed: None };
+        let player = mock_info("omaha_player", &coins(500_000, "uatom"));
+        play(deps.as_mut(), mock_env(), player, start).unwrap();
+        let msg = expire(GameKind::Omaha, "omaha_player");
+        let res = execute(deps.as_mut(), late_env.clone(), keeper.clone(), msg).unwrap();
+        let expired = res.events.iter().find(|e| e.ty == "game_expired").unwrap();
+        assert_eq!(event_attr(expired, "resolution"), "showdown");
+        assert_eq!(attr_value(&res, "action"), "omaha_showdown");
+        assert!(OMAHA_STATE
+            .may_load(deps.as_ref().storage, &Addr::unchecked("omaha_player"))
+            .unwrap()
+            .is_none());
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::zero());
+
+        // 单局游戏没有超时
+        let msg = expire(GameKind::War, "bj_player");
+        let err = execute(deps.as_mut(), late_env, keeper, msg).unwrap_err();
+        assert_eq!(err, ContractError::NotAMultiStepGame { game: GameKind::War });
+
+        // 风控修改超时（无时间锁时立即生效），不能为 0
+        assert_eq!(game_timeout(deps.as_ref()).timeout_blocks, 100);
+        let owner = mock_info("creator", &[]);
+        let update = ExecuteMsg::UpdateGameTimeout {
+            timeout_blocks: 0,
+            timeout_seconds: 600,
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap_err();
+        assert_eq!(err, ContractError::InvalidGameTimeout);
+        let update = ExecuteMsg::UpdateGameTimeout {
+            timeout_blocks: 200,
+            timeout_seconds: 1_200,
+        };
+        execute(deps.as_mut(), mock_env(), owner, update.clone()).unwrap();
+        assert_eq!(
+            game_timeout(deps.as_ref()),
+            GameTimeout {
+                timeout_blocks: 200,
+                timeout_seconds: 1_200,
+            }
+        );
+        let err = execute(deps.as_mut(), mock_env(), mock_info("player", &[]), update).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
 
-        if result.value == "win" {
-            // 10% 抽水：gross=2_000_000, net_winnings=1_000_000, rake=100_000
-            // 实际支付 = 2_000_000 - 100_000 = 1_900_000
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: "user".to_string(),
-                    amount: coins(1_900_000, "uatom"),
-                }.into()
-            );
-        } else if result.value == "tie" {
-            // 平局不抽水
-            assert_eq!(
-                res.messages[0].msg,
-                BankMsg::Send {
-                    to_address: "user".to_string(),
-                    amount: coins(1_000_000, "uatom"),
-                }.into()
-            );
-        } else {
-            assert_eq!(res.messages.len(), 0);
+    // ─────────────────────────────────────────────────────────────
+    // 21 点牌靴与天然 21 点
+    // ─────────────────────────────────────────────────────────────
+
+    fn blackjack_rules(deps: Deps) -> BlackjackRules {
+        from_json(query(deps, mock_env(), QueryMsg::GetBlackjackRules {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_blackjack_shoe_and_naturals() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(Some(0))).unwrap();
+        let owner = mock_info("creator", &[]);
+
+        // 默认 6 副牌，可配置 1-8 副
+        let rules = blackjack_rules(deps.as_ref());
+        assert_eq!(rules.decks, blackjack::DEFAULT_SHOE_DECKS);
+        for decks in [0, 9] {
+            let update = ExecuteMsg::UpdateBlackjackRules {
+                rules: BlackjackRules { decks, ..rules.clone() },
+            };
+            let err = execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap_err();
+            assert_eq!(err, ContractError::InvalidShoeDecks { max: 8 });
+        }
+        let update = ExecuteMsg::UpdateBlackjackRules {
+            rules: BlackjackRules { decks: 2, ..rules },
+        };
+        execute(deps.as_mut(), mock_env(), owner, update).unwrap();
+
+        // 逐局开局，直到分别遇到玩家天然 21 点、庄家天然 21 点和需要继续要牌的牌局
+        let (mut player_natural, mut dealer_natural, mut in_play) = (false, false, false);
+        for i in 0..200 {
+            let player = format!("bj_{}", i);
+            let start = ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Start,
+                client_seed: Some(format!("natural-{}", i)),
+                side_bets: None,
+            };
+            let info = mock_info(&player, &coins(1_000_000, "uatom"));
+            let res = play(deps.as_mut(), mock_env(), info, start).unwrap();
+            let bet_id = attr_value(&res, "bet_id").parse::<u64>().unwrap();
+
+            // 牌靴按下注时配置的 2 副牌洗好，不在链上保存；牌局结束后复算结果与实际发牌一致，
+            // 结束前不返回暗牌与牌靴
+            let addr = Addr::unchecked(&player);
+            let game = BLACKJACK_STATE.load(deps.as_ref().storage, &addr).unwrap();
+            let stored = deps.storage.get(&BLACKJACK_STATE.key(&addr)).unwrap();
+            assert!(!String::from_utf8(stored).unwrap().contains("shoe"));
+            let game_shoe = blackjack_game_shoe(deps.as_ref().storage, &game).unwrap();
+            let (user_cards, dealer_cards) = deal_blackjack(&game_shoe);
+            assert_eq!(game_shoe.len(), 104);
+            assert_eq!(attr_value(&res, "user_card1"), format_blackjack_card(&user_cards[0]));
+            assert_eq!(attr_value(&res, "dealer_card2"), format_blackjack_card(&dealer_cards[1]));
+            if game.finished {
+                let outcome = verify_outcome_query(deps.as_ref(), bet_id).outcome;
+                let GameOutcome::Blackjack { shoe, .. } = outcome else {
+                    panic!("unexpected outcome {:?}", outcome);
+                };
+                assert_eq!(shoe, game_shoe);
+            } else {
+                let verify = QueryMsg::VerifyOutcome { bet_id };
+                let err = query(deps.as_ref(), mock_env(), verify).unwrap_err();
+                assert_eq!(err, ContractError::GameNotFinished { game: GameKind::Blackjack });
+            }
+
+            let hit = ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Hit,
+                client_seed: None,
+                side_bets: None,
+            };
+            if dealer_cards[1].is_ace() {
+                // 庄家明牌为 A：先给玩家买保险的机会，第一个操作时才看暗牌
+                assert!(res.events.is_empty());
+                assert!(!game.finished && !game.peeked);
+            } else if is_natural(&dealer_cards) {
+                // 庄家明牌为 10 点牌时当场看暗牌，天然 21 点当场结算，玩家不能再要牌
+                let (result, payout) = if is_natural(&user_cards) {
+                    ("draw", "1000000")
+                } else {
+                    ("dealer_blackjack", "0")
+                };
+                assert_eq!(attr_value(&res, "result"), result);
+                assert_eq!(attr_value(&res, "payout"), payout);
+                assert!(game.finished);
+                let err = execute(deps.as_mut(), mock_env(), mock_info(&player, &[]), hit)
+                    .unwrap_err();
+                assert_eq!(err, ContractError::GameFinished);
+                dealer_natural = true;
+            } else if is_natural(&user_cards) {
+                // 玩家天然 21 点按 3:2 赔付
+                assert_eq!(attr_value(&res, "result"), "blackjack");
+                assert_eq!(attr_value(&res, "payout"), "2500000");
+                let event = settled_event(&res);
+                assert_eq!(event_attr(event, "multiplier"), "2.5");
+                assert_eq!(event_attr(event, "result"), "win");
+                assert!(game.finished);
+                player_natural = true;
+            } else {
+                // 没有天然 21 点：牌局继续，结束时才发出结算事件
+                assert!(res.events.is_empty());
+                assert!(!game.finished);
+                assert_eq!(game.hands[0].cards, user_cards);
+                in_play = true;
+            }
+            assert_eq!(game.reserved, Uint128::new(3_000_000));
+            if player_natural && dealer_natural && in_play {
+                break;
+            }
+        }
+        assert!(player_natural && dealer_natural && in_play);
+
+        // 比牌：天然 21 点大于多张牌凑成的 21 点
+        let rules = BlackjackRules::default();
+        let cards = |ids: &[u8]| ids.iter().map(|id| BlackjackCard::from_id(*id)).collect();
+        let natural: Vec<BlackjackCard> = cards(&[0, 40]); // A♠ J♠
+        let three_card_21: Vec<BlackjackCard> = cards(&[20, 16, 36]); // 6♠ 5♠ 10♠
+        assert_eq!(hand_total(&three_card_21), 21);
+        let settle = |user: &Vec<BlackjackCard>, dealer: &Vec<BlackjackCard>| {
+            BlackjackHand::new(user.clone(), Uint128::new(1_000)).settle(dealer, &rules)
+        };
+        assert_eq!(settle(&natural, &three_card_21), ("blackjack", 2_500));
+        assert_eq!(settle(&three_card_21, &natural), ("dealer_blackjack", 0));
+        assert_eq!(settle(&natural, &natural), ("draw", 1_000));
+        assert_eq!(settle(&three_card_21, &three_card_21), ("draw", 1_000));
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 21 点加倍、分牌、保险与投降
+    // ─────────────────────────────────────────────────────────────
+
+    /// 按开局下注 ID 指定的 21 点牌靴，代替按种子重新洗出的牌靴（见 rig_blackjack）
+    pub(super) const RIGGED_SHOES: cw_storage_plus::Map<u64, Vec<u8>> =
+        cw_storage_plus::Map::new("rigged_shoes");
+
+    /// 开局一局未结束的 21 点，再换成指定的牌靴：前 4 张依次为玩家 2 张、庄家暗牌、庄家明牌
+    fn rig_blackjack(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        player: &str,
+        shoe: &[u8],
+    ) {
+        for i in 0.. {
+            let start = ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Start,
+                client_seed: Some(format!("rig-{}", i)),
+                side_bets: None,
+            };
+            let info = mock_info(player, &coins(1_000_000, "uatom"));
+            play(deps.as_mut(), mock_env(), info, start).unwrap();
+            let addr = Addr::unchecked(player);
+            let mut game = BLACKJACK_STATE.load(deps.as_ref().storage, &addr).unwrap();
+            if game.finished {
+                continue;
+            }
+            let (user_cards, dealer_cards) = deal_blackjack(shoe);
+            game.hands = vec![BlackjackHand::new(user_cards, game.bet)];
+            game.peeked = !dealer_cards[1].is_ace();
+            game.dealer_cards = dealer_cards;
+            BLACKJACK_STATE.save(deps.as_mut().storage, &addr, &game).unwrap();
+            RIGGED_SHOES.save(deps.as_mut().storage, game.bet_id, &shoe.to_vec()).unwrap();
+            return;
         }
     }
 
     #[test]
-    fn test_house_edge_locked_amount_accounting() {
-        let mut deps = mock_dependencies();
+    fn test_blackjack_start_while_in_progress() {
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(Some(0))).unwrap();
+        let start = |client_seed: &str| ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Start,
+            client_seed: Some(client_seed.to_string()),
+            side_bets: None,
+        };
+        let with = |amount: u128| mock_info("player", &coins(amount, "uatom"));
+
+        // 开局前挂一笔 Start 下注，随后另一笔开局成功，牌局未结束
+        let res = execute(deps.as_mut(), mock_env(), with(1_000_000), start("pending")).unwrap();
+        let pending_id: u64 = attr_value(&res, "bet_id").parse().unwrap();
+        rig_blackjack(&mut deps, "player", &[36, 28, 32, 37]);
+        let addr = Addr::unchecked("player");
+        let game = BLACKJACK_STATE.load(deps.as_ref().storage, &addr).unwrap();
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(6_000_000));
+
+        // 牌局未结束时不能再下开局注
+        let err = execute(deps.as_mut(), mock_env(), with(1_000_000), start("again")).unwrap_err();
+        assert_eq!(err, ContractError::GameInProgress { game: GameKind::Blackjack });
+
+        // 先挂上的开局注结算时退还，不覆盖进行中的牌局
+        let mut next_block = mock_env();
+        next_block.block.height += 1;
+        let settle = ExecuteMsg::SettleBet { bet_id: pending_id };
+        let res = execute(deps.as_mut(), next_block, mock_info("keeper", &[]), settle).unwrap();
+        assert_eq!(attr_value(&res, "result"), "refunded");
+        assert_eq!(event_attr(settled_event(&res), "outcome"), "\"refund\"");
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "player".to_string(),
+                amount: coins(1_000_000, "uatom"),
+            }
+            .into()
+        );
+        assert_eq!(BLACKJACK_STATE.load(deps.as_ref().storage, &addr).unwrap(), game);
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(3_000_000));
 
-        // 初始化合约，5% 抽水
-        let msg = InstantiateMsg { house_edge_bps: Some(500) };
+        // 牌局结束后可以再开局
+        let stand = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("player", &[]), stand).unwrap();
+        play(deps.as_mut(), mock_env(), with(1_000_000), start("again")).unwrap();
+    }
+
+    #[test]
+    fn test_blackjack_player_actions() {
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(Some(0))).unwrap();
+        let act = |action| ExecuteMsg::PlayBlackjack { action, client_seed: None, side_bets: None };
+        let with = |player: &str, amount: u128| mock_info(player, &coins(amount, "uatom"));
+
+        // 分牌：8♠ 8♥ 对庄家 10♠ 10♥，第一手补 2♠ 后加倍补 10♦ 得 20，第二手补 Q♠ 得 18
+        rig_blackjack(&mut deps, "splitter", &[28, 29, 36, 37, 4, 44, 38]);
+        let split = act(BlackjackAction::Split);
+        let err = execute(deps.as_mut(), mock_env(), with("splitter", 500_000), split).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FundsMismatch { action: "blackjack_split", denom: "uatom".to_string() }
+        );
+        let split = act(BlackjackAction::Split);
+        let res = execute(deps.as_mut(), mock_env(), with("splitter", 1_000_000), split).unwrap();
+        assert_eq!(attr_value(&res, "user_cards"), "[8♠,2♠],[8♥,Q♠]");
+        assert_eq!(attr_value(&res, "active_hand"), "0");
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(4_000_000));
+        let split = act(BlackjackAction::Split);
+        let err =
+            execute(deps.as_mut(), mock_env(), with("splitter", 1_000_000), split).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAction { .. }));
+        let double = act(BlackjackAction::DoubleDown);
+        let res = execute(deps.as_mut(), mock_env(), with("splitter", 1_000_000), double).unwrap();
+        assert_eq!(attr_value(&res, "current_total"), "20");
+        assert_eq!(attr_value(&res, "active_hand"), "1");
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(6_000_000));
+        let stand = act(BlackjackAction::Stand);
+        let res = execute(deps.as_mut(), mock_env(), with("splitter", 0), stand).unwrap();
+        assert_eq!(attr_value(&res, "result"), "draw,dealer_win");
+        assert_eq!(attr_value(&res, "user_total"), "20,18");
+        assert_eq!(attr_value(&res, "payout"), "2000000");
+        assert_eq!(event_attr(settled_event(&res), "wager"), "3000000");
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::zero());
+
+        // A 分牌后各只补一张牌并直接比牌，分牌得到的 A + K 不算天然 21 点
+        rig_blackjack(&mut deps, "aces", &[0, 1, 32, 25, 48, 16, 20]);
+        let split = act(BlackjackAction::Split);
+        let res = execute(deps.as_mut(), mock_env(), with("aces", 1_000_000), split).unwrap();
+        assert_eq!(attr_value(&res, "user_cards"), "[A♠,K♠],[A♥,5♠]");
+        assert_eq!(attr_value(&res, "dealer_cards"), "[9♠,7♥,6♠]");
+        assert_eq!(attr_value(&res, "result"), "dealer_busted,dealer_busted");
+        assert_eq!(attr_value(&res, "payout"), "4000000");
+
+        // 保险：庄家明牌 A，看暗牌前买入开局下注的一半，庄家天然 21 点赔 2:1
+        rig_blackjack(&mut deps, "insured", &[36, 32, 48, 1]);
+        let insurance = act(BlackjackAction::Insurance);
+        let err =
+            execute(deps.as_mut(), mock_env(), with("insured", 1_000_000), insurance).unwrap_err();
+        assert!(matches!(err, ContractError::FundsMismatch { action: "blackjack_insurance", .. }));
+        let insurance = act(BlackjackAction::Insurance);
+        let res = execute(deps.as_mut(), mock_env(), with("insured", 500_000), insurance).unwrap();
+        assert_eq!(attr_value(&res, "result"), "dealer_blackjack");
+        assert_eq!(attr_value(&res, "insurance_result"), "won");
+        assert_eq!(attr_value(&res, "payout"), "1500000");
+        assert_eq!(event_attr(settled_event(&res), "result"), "push");
+
+        // 庄家没有天然 21 点时保险输掉，牌局继续；看过暗牌后不能再买保险
+        rig_blackjack(&mut deps, "uninsured", &[36, 28, 24, 1]);
+        let insurance = act(BlackjackAction::Insurance);
+        let res =
+            execute(deps.as_mut(), mock_env(), with("uninsured", 500_000), insurance).unwrap();
+        assert_eq!(attr_value(&res, "insurance"), "500000");
+        assert!(res.events.is_empty());
+        assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(3_500_000));
+        let insurance = act(BlackjackAction::Insurance);
+        let err =
+            execute(deps.as_mut(), mock_env(), with("uninsured", 500_000), insurance).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAction { .. }));
+        let stand = act(BlackjackAction::Stand);
+        let res = execute(deps.as_mut(), mock_env(), with("uninsured", 0), stand).unwrap();
+        assert_eq!(attr_value(&res, "result"), "draw");
+        assert_eq!(attr_value(&res, "insurance_result"), "lost");
+        assert_eq!(attr_value(&res, "payout"), "1000000");
+        assert_eq!(event_attr(settled_event(&res), "wager"), "1500000");
+
+        // 明牌 A 时加倍先看暗牌：庄家天然 21 点，加倍的下注原样退还
+        rig_blackjack(&mut deps, "doubler", &[16, 20, 44, 2]);
+        let double = act(BlackjackAction::DoubleDown);
+        let res = execute(deps.as_mut(), mock_env(), with("doubler", 1_000_000), double).unwrap();
+        assert_eq!(attr_value(&res, "result"), "dealer_blackjack");
+        assert_eq!(attr_value(&res, "payout"), "1000000");
+        assert_eq!(event_attr(settled_event(&res), "wager"), "2000000");
+
+        // 投降：只能作为第一个决定，退还一半下注
+        rig_blackjack(&mut deps, "quitter", &[36, 20, 32, 37]);
+        let surrender = act(BlackjackAction::Surrender);
+        let res = execute(deps.as_mut(), mock_env(), with("quitter", 0), surrender).unwrap();
+        assert_eq!(attr_value(&res, "result"), "surrender");
+        assert_eq!(attr_value(&res, "dealer_cards"), "[9♠,10♥]");
+        assert_eq!(attr_value(&res, "payout"), "500000");
+        rig_blackjack(&mut deps, "quitter", &[4, 8, 36, 33, 12]);
+        let res = execute(deps.as_mut(), mock_env(), with("quitter", 0), act(BlackjackAction::Hit))
+            .unwrap();
+        assert_eq!(attr_value(&res, "current_total"), "9");
+        for action in [BlackjackAction::Surrender, BlackjackAction::DoubleDown] {
+            let err = execute(deps.as_mut(), mock_env(), with("quitter", 1_000_000), act(action))
+                .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidAction { .. }));
+        }
+    }
 
-        let initial_locked = 10_000_000_000u128;
+    // ─────────────────────────────────────────────────────────────
+    // 21 点庄规
+    // ─────────────────────────────────────────────────────────────
 
-        // 玩硬币翻转游戏
-        let bet = 1_000_000u128;
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("player", &coins(bet, "uatom")),
-            ExecuteMsg::PlayCoinFlip { choice: CoinSide::Heads },
-        ).unwrap();
+    #[test]
+    fn test_blackjack_rules() {
+        use crate::blackjack::BlackjackPayout;
 
-        let result = res.attributes.iter().find(|a| a.key == "result").unwrap();
+        let mut deps = funded_deps();
+        let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(Some(0))).unwrap();
+        let owner = mock_info("creator", &[]);
+        assert_eq!(blackjack_rules(deps.as_ref()), BlackjackRules::default());
+        let act = |action| ExecuteMsg::PlayBlackjack { action, client_seed: None, side_bets: None };
+        let with = |player: &str, amount: u128| mock_info(player, &coins(amount, "uatom"));
+
+        // 逐一验证每种庄规组合：软 17、副数、分牌后加倍、投降、天然 21 点赔率
+        for combo in 0..16u8 {
+            let rules = BlackjackRules {
+                dealer_hits_soft_17: combo & 1 != 0,
+                decks: 1 + combo / 2,
+                double_after_split: combo & 2 != 0,
+                surrender_allowed: combo & 4 != 0,
+                blackjack_payout: if combo & 8 != 0 {
+                    BlackjackPayout::SixToFive
+                } else {
+                    BlackjackPayout::ThreeToTwo
+                },
+            };
+            let update = ExecuteMsg::UpdateBlackjackRules { rules: rules.clone() };
+            execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap();
+            let player = format!("rules_{}", combo);
+
+            // 软 17：10♠ 8♠ 对庄家 A♠ 6♠，H17 时庄家补 4♠ 得 21，S17 时停在 17
+            rig_blackjack(&mut deps, &player, &[36, 28, 0, 20, 12]);
+            let state: BlackjackStateResponse = from_json(
+                query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::GetBlackjackState { address: player.clone() },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(state.rules, rules);
+            let stand = act(BlackjackAction::Stand);
+            let res = execute(deps.as_mut(), mock_env(), with(&player, 0), stand).unwrap();
+            let (result, dealer_cards, payout) = if rules.dealer_hits_soft_17 {
+                ("dealer_win", "[A♠,6♠,4♠]", "0")
+            } else {
+                ("player_win", "[A♠,6♠]", "2000000")
+            };
+            assert_eq!(attr_value(&res, "result"), result);
+            assert_eq!(attr_value(&res, "dealer_cards"), dealer_cards);
+            assert_eq!(attr_value(&res, "payout"), payout);
+
+            // 天然 21 点：3:2 为 2.5×，6:5 为 2.2×
+            rig_blackjack(&mut deps, &player, &[0, 48, 36, 37]);
+            let stand = act(BlackjackAction::Stand);
+            let res = execute(deps.as_mut(), mock_env(), with(&player, 0), stand).unwrap();
+            assert_eq!(attr_value(&res, "result"), "blackjack");
+            let (payout, multiplier) = match rules.blackjack_payout {
+                BlackjackPayout::ThreeToTwo => ("2500000", "2.5"),
+                BlackjackPayout::SixToFive => ("2200000", "2.2"),
+            };
+            assert_eq!(attr_value(&res, "payout"), payout);
+            assert_eq!(event_attr(settled_event(&res), "multiplier"), multiplier);
+
+            // 分牌后加倍（牌局未打完，换一个玩家）
+            let splitter = format!("{}_split", player);
+            rig_blackjack(&mut deps, &splitter, &[28, 29, 36, 37, 4, 44, 38]);
+            let split = act(BlackjackAction::Split);
+            execute(deps.as_mut(), mock_env(), with(&splitter, 1_000_000), split).unwrap();
+            let double = act(BlackjackAction::DoubleDown);
+            let res = execute(deps.as_mut(), mock_env(), with(&splitter, 1_000_000), double);
+            if rules.double_after_split {
+                assert_eq!(attr_value(&res.unwrap(), "current_total"), "20");
+            } else {
+                let reason = "Double after split is not allowed";
+                assert_eq!(res.unwrap_err(), ContractError::InvalidAction { reason });
+            }
 
-        // 查询锁仓金额
-        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetLockedAmount {}).unwrap();
-        let resp: LockedAmountResponse = from_json(&bin).unwrap();
+            // 投降（不允许投降时牌局未结束，放在最后）
+            rig_blackjack(&mut deps, &player, &[36, 20, 32, 37]);
+            let surrender = act(BlackjackAction::Surrender);
+            let res = execute(deps.as_mut(), mock_env(), with(&player, 0), surrender);
+            if rules.surrender_allowed {
+                assert_eq!(attr_value(&res.unwrap(), "payout"), "500000");
+            } else {
+                let reason = "Surrender is not allowed";
+                assert_eq!(res.unwrap_err(), ContractError::InvalidAction { reason });
+            }
+        }
 
-        if result.value == "win" {
-            // 赢了：locked = initial + bet - payout
-            // payout = 1_950_000（扣除 5% 抽水后）
-            // locked = 10_000_000_000 + 1_000_000 - 1_950_000 = 10_000_050_000
-            // 庄家额外多留了 50_000 的抽水
-            assert_eq!(
-                resp.locked_amount.u128(),
-                initial_locked + bet - 1_950_000,
-                "locked amount should reflect house edge"
-            );
-        } else {
-            // 输了：locked = initial + bet
-            assert_eq!(
-                resp.locked_amount.u128(),
-                initial_locked + bet,
-                "locked amount should include lost bet"
-            );
+        // 庄规在开局时随牌局保存：开局后改为 H17 不影响进行中的牌局
+        let s17 = BlackjackRules::default();
+        let update = ExecuteMsg::UpdateBlackjackRules { rules: s17.clone() };
+        execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap();
+        rig_blackjack(&mut deps, "snapshot", &[36, 28, 0, 20, 12]);
+        let h17 = BlackjackRules { dealer_hits_soft_17: true, ..s17 };
+        let update = ExecuteMsg::UpdateBlackjackRules { rules: h17.clone() };
+        execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap();
+        assert_eq!(blackjack_rules(deps.as_ref()), h17);
+        let stand = act(BlackjackAction::Stand);
+        let res = execute(deps.as_mut(), mock_env(), with("snapshot", 0), stand).unwrap();
+        assert_eq!(attr_value(&res, "result"), "player_win");
+
+        // 整套庄规在下注时确定：下注后、开奖前修改庄规不影响该局
+        let start = act(BlackjackAction::Start);
+        let res = execute(deps.as_mut(), mock_env(), with("pending", 1_000_000), start).unwrap();
+        let bet_id: u64 = attr_value(&res, "bet_id").parse().unwrap();
+        let six_to_five = BlackjackRules {
+            decks: 1,
+            surrender_allowed: false,
+            blackjack_payout: BlackjackPayout::SixToFive,
+            ..h17.clone()
+        };
+        let update = ExecuteMsg::UpdateBlackjackRules { rules: six_to_five };
+        execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap();
+        let mut env = mock_env();
+        env.block.height += 1;
+        let commitment_id = ACTIVE_COMMITMENT.load(deps.as_ref().storage).unwrap();
+        let reveal = ExecuteMsg::RevealServerSeed {
+            server_seed: test_server_seed(commitment_id),
+            next_seed_hash: hash_server_seed(&test_server_seed(commitment_id + 1)),
+        };
+        execute(deps.as_mut(), env.clone(), owner.clone(), reveal).unwrap();
+        let settle = ExecuteMsg::SettleBet { bet_id };
+        execute(deps.as_mut(), env, mock_info("keeper", &[]), settle).unwrap();
+        let addr = Addr::unchecked("pending");
+        let game = BLACKJACK_STATE.load(deps.as_ref().storage, &addr).unwrap();
+        assert_eq!(game.rules, h17);
+
+        // 庄家不再看玩家的牌：硬 17 对玩家 20 停牌，庄家爆牌单独标记
+        rig_blackjack(&mut deps, "no_peek", &[36, 44, 36, 24]);
+        let stand = act(BlackjackAction::Stand);
+        let res = execute(deps.as_mut(), mock_env(), with("no_peek", 0), stand).unwrap();
+        assert_eq!(attr_value(&res, "dealer_cards"), "[10♠,7♠]");
+        assert_eq!(attr_value(&res, "result"), "player_win");
+        rig_blackjack(&mut deps, "no_peek", &[36, 44, 36, 20, 40]);
+        let stand = act(BlackjackAction::Stand);
+        let res = execute(deps.as_mut(), mock_env(), with("no_peek", 0), stand).unwrap();
+        assert_eq!(attr_value(&res, "dealer_cards"), "[10♠,6♠,J♠]");
+        assert_eq!(attr_value(&res, "result"), "dealer_busted");
+
+        // 只有风控可以修改庄规
+        let update = ExecuteMsg::UpdateBlackjackRules { rules: BlackjackRules::default() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("player", &[]), update).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized);
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    // 21 点边注
+    // ─────────────────────────────────────────────────────────────
+
+    /// 边注在 decks 副牌的牌靴中的庄家优势（基点，四舍五入）：穷举所有有序的 3 张牌
+    fn side_bet_house_edge_bps(decks: u128) -> (u128, u128) {
+        use crate::blackjack::{evaluate_3card, perfect_pairs};
+
+        let cards: Vec<BlackjackCard> = (0..52).map(BlackjackCard::from_id).collect();
+        let shoe = 52 * decks;
+        let total = shoe * (shoe - 1) * (shoe - 2);
+        let (mut pairs_return, mut three_card_return) = (0, 0);
+        for (i, a) in cards.iter().enumerate() {
+            for (j, b) in cards.iter().enumerate() {
+                for (k, c) in cards.iter().enumerate() {
+                    // 牌靴中同一张牌各有 decks 张，已发出的相同牌要扣除
+                    let ways = decks
+                        * (decks - u128::from(j == i))
+                        * decks.saturating_sub(u128::from(k == i) + u128::from(k == j));
+                    pairs_return += ways * perfect_pairs(a, b).multiplier();
+                    three_card_return += ways * evaluate_3card(&[*a, *b, *c]).multiplier();
+                }
+            }
         }
+        let edge_bps = |returned: u128| ((total - returned) * 10_000 + total / 2) / total;
+        (edge_bps(pairs_return), edge_bps(three_card_return))
     }
 
     #[test]
-    fn test_query_house_edge() {
-        let mut deps = mock_dependencies();
+    fn test_blackjack_side_bet_paytables() {
+        use crate::blackjack::{evaluate_3card, perfect_pairs, PerfectPairsHand, ThreeCardHand};
+
+        let card = BlackjackCard::from_id;
+
+        // Perfect Pairs：8♠ 8♠ 完美对子、8♥ 8♦ 同色对子、8♠ 8♥ 混色对子
+        assert_eq!(perfect_pairs(&card(28), &card(28)), PerfectPairsHand::Perfect);
+        assert_eq!(perfect_pairs(&card(29), &card(30)), PerfectPairsHand::Coloured);
+        assert_eq!(perfect_pairs(&card(28), &card(31)), PerfectPairsHand::Coloured);
+        assert_eq!(perfect_pairs(&card(28), &card(29)), PerfectPairsHand::Mixed);
+        assert_eq!(perfect_pairs(&card(28), &card(32)), PerfectPairsHand::Nothing);
+
+        // 21+3：A 可作 A-2-3 或 Q-K-A，但不能 K-A-2
+        let hand = |ids: [u8; 3]| evaluate_3card(&ids.map(card));
+        assert_eq!(hand([24, 24, 24]), ThreeCardHand::SuitedTrips);
+        assert_eq!(hand([0, 4, 8]), ThreeCardHand::StraightFlush);
+        assert_eq!(hand([44, 0, 48]), ThreeCardHand::StraightFlush);
+        assert_eq!(hand([24, 25, 26]), ThreeCardHand::Trips);
+        assert_eq!(hand([45, 50, 0]), ThreeCardHand::Straight);
+        assert_eq!(hand([9, 0, 6]), ThreeCardHand::Straight);
+        assert_eq!(hand([48, 0, 4]), ThreeCardHand::Flush);
+        assert_eq!(hand([7, 31, 39]), ThreeCardHand::Flush);
+        assert_eq!(hand([48, 1, 6]), ThreeCardHand::Nothing);
+
+        // 各副数牌靴下边注的庄家优势：副数越少，完美对子和同花三条越难出现
+        let expected = [
+            (4706, 1821),
+            (2233, 1117),
+            (1419, 807),
+            (1014, 639),
+            (772, 534),
+            (611, 462),
+            (496, 410),
+            (410, 370),
+        ];
+        for (decks, edge) in (1..=8).zip(expected) {
+            assert_eq!(side_bet_house_edge_bps(decks), edge, "{} decks", decks);
+        }
+    }
 
-        // 初始化合约
-        let msg = InstantiateMsg { house_edge_bps: Some(250) };
+    #[test]
+    fn test_blackjack_side_bets() {
+        use crate::blackjack::{evaluate_3card, perfect_pairs, PerfectPairsHand, ThreeCardHand};
+
+        let mut deps = funded_deps();
         let info = mock_info("creator", &coins(10_000_000_000, "uatom"));
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        instantiate(deps.as_mut(), mock_env(), info, init_msg(Some(0))).unwrap();
+        let start = |client_seed: String, perfect_pairs: u128, twenty_one_plus_three: u128| {
+            ExecuteMsg::PlayBlackjack {
+                action: BlackjackAction::Start,
+                client_seed: Some(client_seed),
+                side_bets: Some(BlackjackSideBets {
+                    perfect_pairs: Uint128::new(perfect_pairs),
+                    twenty_one_plus_three: Uint128::new(twenty_one_plus_three),
+                }),
+            }
+        };
 
-        // 查询
-        let bin = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
-        let resp: HouseEdgeResponse = from_json(&bin).unwrap();
-        assert_eq!(resp.house_edge_bps, 250);
+        // 每个边注都不能超过主注：附带 1,100,000 下 600,000 边注，主注只剩 500,000
+        let info = mock_info("player", &coins(1_100_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, start("x".into(), 600_000, 0))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SideBetExceedsBet { main_bet: 500_000, denom: "uatom".to_string() }
+        );
 
-        // 更新后再查询
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            mock_info("creator", &coins(0, "uatom")),
-            ExecuteMsg::UpdateHouseEdge { new_house_edge_bps: 800 },
-        ).unwrap();
+        // 边注只能在开局时下
+        rig_blackjack(&mut deps, "rigged", &[36, 28, 32, 37]);
+        let hit = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Hit,
+            client_seed: None,
+            side_bets: Some(BlackjackSideBets::default()),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("rigged", &[]), hit).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAction { .. }));
+        let stand = ExecuteMsg::PlayBlackjack {
+            action: BlackjackAction::Stand,
+            client_seed: None,
+            side_bets: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("rigged", &[]), stand).unwrap();
+
+        // 主注 1,000,000 + Perfect Pairs 100,000 + 21+3 200,000：发牌后边注当场结算，
+        // 主注照常进行，结算事件计入边注
+        let (mut pairs_won, mut three_card_won) = (false, false);
+        for i in 0.. {
+            if pairs_won && three_card_won {
+                break;
+            }
+            assert!(i < 1_000, "no winning side bets dealt");
+            let info = mock_info("player", &coins(1_300_000, "uatom"));
+            let msg = start(format!("side-{}", i), 100_000, 200_000);
+            let res = play(deps.as_mut(), mock_env(), info, msg).unwrap();
+            let game =
+                BLACKJACK_STATE.load(deps.as_ref().storage, &Addr::unchecked("player")).unwrap();
+            let shoe = blackjack_game_shoe(deps.as_ref().storage, &game).unwrap();
+            let (user_cards, dealer_cards) = deal_blackjack(&shoe);
+            let pairs = perfect_pairs(&user_cards[0], &user_cards[1]);
+            let three_card = evaluate_3card(&[user_cards[0], user_cards[1], dealer_cards[1]]);
+            let side_payout = 100_000 * pairs.multiplier() + 200_000 * three_card.multiplier();
+            assert_eq!(attr_value(&res, "perfect_pairs_result"), pairs.name());
+            assert_eq!(attr_value(&res, "twenty_one_plus_three_result"), three_card.name());
+            assert_eq!(attr_value(&res, "side_payout"), side_payout.to_string());
+            assert_eq!(game.bet, Uint128::new(1_000_000));
+            assert_eq!(game.side_payout, Uint128::new(side_payout));
+            if side_payout > 0 {
+                let paid: CosmosMsg = BankMsg::Send {
+                    to_address: "player".to_string(),
+                    amount: coins(side_payout, "uatom"),
+                }
+                .into();
+                assert!(res.messages.iter().any(|m| m.msg == paid));
+            }
+            pairs_won |= pairs != PerfectPairsHand::Nothing;
+            three_card_won |= three_card != ThreeCardHand::Nothing;
 
-        let bin2 = query(deps.as_ref(), mock_env(), QueryMsg::GetHouseEdge {}).unwrap();
-        let resp2: HouseEdgeResponse = from_json(&bin2).unwrap();
-        assert_eq!(resp2.house_edge_bps, 800);
+            // 边注的预留敞口已释放，只剩主注的 3×
+            let res = if game.finished {
+                res
+            } else {
+                assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::new(3_000_000));
+                let stand = ExecuteMsg::PlayBlackjack {
+                    action: BlackjackAction::Stand,
+                    client_seed: None,
+                    side_bets: None,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info("player", &[]), stand).unwrap()
+            };
+            let main_payout: u128 = attr_value(&res, "payout").parse().unwrap();
+            let event = settled_event(&res);
+            assert_eq!(event_attr(event, "wager"), "1300000");
+            assert_eq!(event_attr(event, "payout"), (main_payout + side_payout).to_string());
+            assert_eq!(exposure(deps.as_ref()).reserved_amount, Uint128::zero());
+        }
+
+        // 边注的最高赔付与主注共用单注可赢取额：1 基点时约 1,000,000，
+        // 主注 100,000 的 3× 可以下，再加 10,000 的 21+3（最高 101×）则超出
+        let update = ExecuteMsg::UpdateMaxPayout { max_payout_bps: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), update).unwrap();
+        let info = mock_info("player", &coins(110_000, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, start("y".into(), 0, 10_000))
+            .unwrap_err();
+        assert!(err.to_string().contains("Bet exceeds the current max bet"));
+        let info = mock_info("player", &coins(100_000, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, start("y".into(), 0, 0)).unwrap();
     }
 }
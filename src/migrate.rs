@@ -0,0 +1,79 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+// ─────────────────────────────────────────────────────────────
+// 合约迁移
+//
+// 合约名与版本号按 cw2 规范记录；存储结构另有独立的版本号（STATE_VERSION），
+// 每次不兼容地修改已存储的结构（State、各牌局状态）都要递增版本号，并在 lib.rs 中
+// 增加一个从上一版本升级的迁移例程。migrate 时从已存储的版本逐级升级到当前版本。
+// 带 #[serde(default)] 且默认值含义正确的新增字段可直接读出旧数据，不需要迁移。
+//
+// 版本 1：最初部署的结构，未记录任何版本号
+//   - State 只有单一的 uatom 锁仓金额 locked_amount
+//   - 21 点状态没有牌靴（每张牌独立随机生成），无法在新逻辑下继续发牌
+// 版本 2：多币种锁仓、赔付预留、角色 / 暂停等字段；多步游戏记录开局区块高度与时间
+//   （超时结算，默认值 0 会让牌局立即超时）；21 点改为开局洗好的多副牌牌靴，
+//   牌带花色，支持分牌（多手牌）、加倍、保险与投降
+//   多步游戏的牌序不再存储，每次操作按开局下注的种子重新洗出；旧的进行中奥马哈 / 德州
+//   牌局没有下注记录，迁移时退还
+// ─────────────────────────────────────────────────────────────
+
+/// cw2 合约名
+pub const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+
+/// cw2 合约版本
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 当前存储结构版本
+pub const CURRENT_STATE_VERSION: u32 = 2;
+
+/// 未记录版本号的存储视为最初部署的版本 1
+pub const LEGACY_STATE_VERSION: u32 = 1;
+
+/// 版本 1 的全局状态
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateV1 {
+    pub owner: Addr,
+    pub locked_amount: u128,
+    pub house_edge_bps: u16,
+}
+
+/// 版本 1 的 21 点状态（没有牌靴）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackStateV1 {
+    pub user_cards: Vec<u32>,
+    pub dealer_cards: Vec<u32>,
+    pub bet: Uint128,
+    pub finished: bool,
+}
+
+// 版本 1 的全局状态（与 STATE 同一存储键）
+pub const STATE_V1: Item<StateV1> = Item::new("state");
+
+// 版本 1 的 21 点状态（与 BLACKJACK_STATE 同一存储键）
+pub const BLACKJACK_STATE_V1: Map<&Addr, BlackjackStateV1> = Map::new("blackjack_state");
+
+/// 解析 "major.minor.patch" 版本号，用于拒绝降级
+pub fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let invalid = || ContractError::InvalidVersion {
+        version: version.to_string(),
+    };
+    let mut parts = version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse::<u64>().map_err(|_| invalid()));
+    let major = parts.next().ok_or_else(invalid)??;
+    let minor = parts.next().ok_or_else(invalid)??;
+    let patch = parts.next().ok_or_else(invalid)??;
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok((major, minor, patch))
+}
@@ -1,5 +1,5 @@
 use crate::baccarat::BaccaratBet;
-use crate::blackjack::BlackjackAction;
+use crate::blackjack::{BlackjackAction, BlackjackRules, BlackjackSideBets};
 use crate::coin::CoinSide;
 use crate::roulette::RouletteBetType;
 use crate::scratch::ScratchCardType;
@@ -10,57 +10,161 @@ use crate::texas::TexasAction;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use crate::dice::DiceGameMode;
+use crate::drand::DrandConfig;
+use crate::expiry::GameTimeout;
+use crate::game_config::{GameConfig, GameKind};
+use crate::state::{DenomConfig, Role};
+use crate::timelock::TimelockConfig;
+use cosmwasm_std::{HexBinary, Uint128};
+use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// 庄家抽水比例（基点），100 = 1%，最高 1000 = 10%，默认 0
     pub house_edge_bps: Option<u16>,
+    /// 初始服务端种子承诺：sha256(server_seed) 的十六进制，可选，之后可通过 CommitServerSeed 提交
+    pub server_seed_hash: Option<String>,
+    /// drand 信标配置，可选；配置后单局游戏使用 drand 轮次开奖
+    pub drand: Option<DrandConfig>,
+    /// 是否拒绝来自合约的下注（防止合约在同一交易内看到结果后回滚），默认 true
+    pub eoa_only: Option<bool>,
+    /// 可下注币种，第一个为主币种；默认仅 uatom
+    pub denoms: Option<Vec<DenomConfig>>,
+    /// 单注最多可赢取可用资金的比例（基点），1-10000，默认 10000 = 100%
+    pub max_payout_bps: Option<u16>,
+    /// 提款、修改抽水和玩法配置的时间锁延迟，默认不延迟
+    pub timelock: Option<TimelockConfig>,
+    /// 多步游戏超时，默认 14,400 区块且 86,400 秒
+    pub game_timeout: Option<GameTimeout>,
+    /// 21 点庄规，默认 6 副牌、软 17 停牌、分牌后可加倍、允许投降、天然 21 点赔 3:2
+    pub blackjack_rules: Option<BlackjackRules>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    // 所有 Play* 下注都会进入待开奖状态，client_seed 为玩家提供的客户端种子（多步游戏仅在 Start 时使用）
     // 比大小游戏
-    PlayWar {},
+    PlayWar { client_seed: Option<String> },
     // 老虎机游戏（Basic = 3轮1线，Advanced = 5轮5线）
-    PlaySlot { mode: SlotMode },
+    PlaySlot { mode: SlotMode, client_seed: Option<String> },
     // 猜数字游戏
-    GuessNumber { guess: u8 },
-    // 黑杰克游戏
-    PlayBlackjack { action: BlackjackAction },
+    GuessNumber { guess: u8, client_seed: Option<String> },
+    // 黑杰克游戏（side_bets 为开局时可选的 Perfect Pairs / 21+3 边注，计入附带资金）
+    PlayBlackjack {
+        action: BlackjackAction,
+        client_seed: Option<String>,
+        side_bets: Option<BlackjackSideBets>,
+    },
     // 硬币翻转游戏
-    PlayCoinFlip { choice: CoinSide },
+    PlayCoinFlip { choice: CoinSide, client_seed: Option<String> },
     // 骰子游戏
-    PlayDice { mode: DiceGameMode },
+    PlayDice { mode: DiceGameMode, client_seed: Option<String> },
     // 百家乐游戏
-    PlayBaccarat { bet_choice: BaccaratBet },
+    PlayBaccarat { bet_choice: BaccaratBet, client_seed: Option<String> },
     // 轮盘游戏
-    PlayRoulette { bet_type: RouletteBetType },
+    PlayRoulette { bet_type: RouletteBetType, client_seed: Option<String> },
     // 奥马哈扑克游戏（支持加注）
-    PlayOmaha { action: OmahaAction },
+    PlayOmaha { action: OmahaAction, client_seed: Option<String> },
     // 德州扑克游戏（支持加注、过牌、全押）
-    PlayTexas { action: TexasAction },
+    PlayTexas { action: TexasAction, client_seed: Option<String> },
     // 三公游戏（三张牌比点数）
-    PlaySanGong {},
+    PlaySanGong { client_seed: Option<String> },
     // 骰宝游戏（三颗骰子，多种投注方式）
-    PlaySicBo { bet_type: SicBoBetType },
+    PlaySicBo { bet_type: SicBoBetType, client_seed: Option<String> },
     // 基诺游戏（从 1-80 选号，系统开 20 个号）
-    PlayKeno { picks: Vec<u8> },
+    PlayKeno { picks: Vec<u8>, client_seed: Option<String> },
     // 刮刮乐游戏（3×3 格子，8 条中奖线）
-    PlayScratchCard { card_type: ScratchCardType },
+    PlayScratchCard { card_type: ScratchCardType, client_seed: Option<String> },
     // 斗牛游戏（五张牌比牛，含五小牛/四炸/五花牛等特殊牌型）
-    PlayBullFight {},
-    // 部署者提款
-    Withdraw { amount: u128 },
-    // 部署者修改抽水比例（基点），100 = 1%，最高 1000 = 10%
+    PlayBullFight { client_seed: Option<String> },
+    // 使用合约内余额下注：扣除 amount 作为 game（任一 Play* 消息）的附带资金，赔付记回余额
+    // denom 为空时使用主币种
+    PlayFromBalance { amount: Uint128, denom: Option<String>, game: Box<ExecuteMsg> },
+    // 存入余额：附带的可下注币种记入玩家在合约内的余额
+    Deposit {},
+    // 提取余额（amount 为空时全部提取，denom 为空时使用主币种）
+    WithdrawBalance { amount: Option<Uint128>, denom: Option<String> },
+    // 财务提款，转入所有者地址（denom 为空时使用主币种，受时间锁约束）
+    Withdraw { amount: u128, denom: Option<String> },
+    // 财务注入流动性：附带的可下注币种计入锁仓
+    AddLiquidity {},
+    // CW20 代币经由 Send 进入合约：msg 为内嵌的 Play* 消息（下注）或 AddLiquidity
+    Receive(Cw20ReceiveMsg),
+    // 风控修改抽水比例（基点），100 = 1%，最高 1000 = 10%（受时间锁约束）
     UpdateHouseEdge { new_house_edge_bps: u16 },
+    // 风控修改单注最多可赢取可用资金的比例（基点），1-10000
+    UpdateMaxPayout { max_payout_bps: u16 },
+    // 风控修改某玩法的配置（开放状态、下注上下限、抽水覆盖、赔率表版本、最低加注额，受时间锁约束）
+    UpdateGameConfig { game: GameKind, config: GameConfig },
+    // 运维提交服务端种子承诺 sha256(server_seed)（仅在没有生效承诺时使用）
+    CommitServerSeed { seed_hash: String },
+    // 运维揭示当前服务端种子，并提交下一轮承诺
+    RevealServerSeed { server_seed: String, next_seed_hash: String },
+    // 结算一笔种子已揭示的下注（任何人都可调用）
+    SettleBet { bet_id: u64 },
+    // 种子超时未揭示时，玩家取回下注
+    RefundBet { bet_id: u64 },
+    // 风控开启 / 关闭「仅外部账户可下注」
+    SetEoaOnly { enabled: bool },
+    // 风控将集成方合约加入白名单
+    AllowContract { address: String },
+    // 风控将合约移出白名单
+    DisallowContract { address: String },
+    // 提交某 drand 轮次的签名（任何人都可调用，合约验签后保存随机数）
+    SubmitBeacon { round: u64, signature: HexBinary },
+    // 紧急暂停（暂停人或所有者）：只拒绝新下注与加注；结算、停牌、弃牌、摊牌、
+    // 退款和超时结算照常进行
+    Pause {},
+    // 解除暂停（仅所有者）
+    Unpause {},
+    // 单个玩法的开关：暂停人或所有者可以关闭，仅所有者可以重新开放
+    SetGameEnabled { game: GameKind, enabled: bool },
+    // 所有者授予某地址角色
+    GrantRole { role: Role, address: String },
+    // 所有者撤销某地址的角色
+    RevokeRole { role: Role, address: String },
+    // 所有者提议转移所有权（新所有者接受后生效）
+    TransferOwnership { new_owner: String },
+    // 被提议的新所有者接受所有权
+    AcceptOwnership {},
+    // 所有者取消尚未接受的所有权转移
+    CancelOwnershipTransfer {},
+    // 所有者修改时间锁延迟（受当前时间锁约束）
+    UpdateTimelock { delay_blocks: u64, delay_seconds: u64 },
+    // 执行一项已到期的管理操作（需具备该操作的角色）
+    ExecuteAdminAction { id: u64 },
+    // 取消一项待执行的管理操作（该操作的角色或暂停人）
+    CancelAdminAction { id: u64 },
+    // 风控修改多步游戏超时（受时间锁约束）
+    UpdateGameTimeout { timeout_blocks: u64, timeout_seconds: u64 },
+    // 风控修改 21 点庄规（受时间锁约束，只影响之后开局的牌局）
+    UpdateBlackjackRules { rules: BlackjackRules },
+    // 结算超时未完成的多步游戏（任何人都可调用）：21 点庄家补牌比牌，扑克自动摊牌或弃牌
+    ExpireGame { game: GameKind, player: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
 pub enum QueryMsg {
-    // 查询当前合约的锁仓金额
-    GetLockedAmount {},
+    // 查询当前合约某币种的锁仓金额（denom 为空时查询主币种）
+    GetLockedAmount { denom: Option<String> },
+
+    // 查询可下注币种
+    GetDenoms {},
+
+    // 查询某币种的赔付敞口：锁仓、已预留与可用资金（denom 为空时查询主币种）
+    GetExposure { denom: Option<String> },
+
+    // 查询某玩法（任一 Play* 下注消息）当前的下注限额（denom 为空时查询主币种）
+    GetBetLimits { game: Box<ExecuteMsg>, denom: Option<String> },
+
+    // 查询某玩法的配置
+    GetGameConfig { game: GameKind },
+
+    // 查询全部玩法的配置
+    ListGameConfigs {},
     
     // 查询某用户当前 Blackjack 游戏状态
     GetBlackjackState { address: String },
@@ -73,4 +177,147 @@ pub enum QueryMsg {
 
     // 查询当前庄家抽水比例
     GetHouseEdge {},
-}
\ No newline at end of file
+
+    // 查询服务端种子承诺（不传 id 时返回当前生效的承诺）
+    GetSeedCommitment { id: Option<u64> },
+
+    // 查询下注记录
+    GetBet { bet_id: u64 },
+
+    // 查询 drand 信标配置
+    GetDrandConfig {},
+
+    // 查询已提交的 drand 轮次随机数
+    GetBeacon { round: u64 },
+
+    // 查询某用户等待 drand 开奖的下注（按轮次分组）
+    GetPendingBeaconBets { address: String },
+
+    // 查询调用方限制（仅外部账户模式与合约白名单）
+    GetCallerGuard {},
+
+    // 查询熔断状态（全局暂停、已关闭的玩法）
+    GetCircuitBreaker {},
+
+    // 查询所有者与待接受的新所有者
+    GetOwnership {},
+
+    // 查询某角色的全部成员
+    GetRoleMembers { role: Role },
+
+    // 查询某地址拥有的角色
+    GetRoles { address: String },
+
+    // 查询时间锁延迟
+    GetTimelock {},
+
+    // 查询全部待执行的管理操作
+    ListPendingAdminActions {},
+
+    // 查询多步游戏超时
+    GetGameTimeout {},
+
+    // 查询 21 点庄规
+    GetBlackjackRules {},
+
+    // 查询某用户在合约内的各币种余额
+    GetPlayerBalance { address: String },
+
+    // 查询某用户的下注序号（已下注次数）
+    GetPlayerNonce { address: String },
+
+    // 用下注记录的种子复算开奖结果（需种子已揭示或信标已提交）
+    VerifyOutcome { bet_id: u64 },
+
+    // 查询合约名、合约版本与存储结构版本
+    GetContractVersion {},
+}
+
+/// 迁移消息：升级合约代码时使用，存储结构按版本逐级升级
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+impl ExecuteMsg {
+    /// 是否为游戏消息（Play* 系列，包括多步游戏的后续操作）
+    pub fn is_play(&self) -> bool {
+        matches!(
+            self,
+            ExecuteMsg::PlayWar { .. }
+                | ExecuteMsg::PlaySlot { .. }
+                | ExecuteMsg::GuessNumber { .. }
+                | ExecuteMsg::PlayBlackjack { .. }
+                | ExecuteMsg::PlayCoinFlip { .. }
+                | ExecuteMsg::PlayDice { .. }
+                | ExecuteMsg::PlayBaccarat { .. }
+                | ExecuteMsg::PlayRoulette { .. }
+                | ExecuteMsg::PlayOmaha { .. }
+                | ExecuteMsg::PlayTexas { .. }
+                | ExecuteMsg::PlaySanGong { .. }
+                | ExecuteMsg::PlaySicBo { .. }
+                | ExecuteMsg::PlayKeno { .. }
+                | ExecuteMsg::PlayScratchCard { .. }
+                | ExecuteMsg::PlayBullFight { .. }
+                | ExecuteMsg::PlayFromBalance { .. }
+        )
+    }
+
+    /// 是否为多步游戏（开局后还要继续发牌 / 加注，赔付敞口随牌局转移）
+    pub fn is_multi_step(&self) -> bool {
+        matches!(
+            self,
+            ExecuteMsg::PlayBlackjack { .. }
+                | ExecuteMsg::PlayOmaha { .. }
+                | ExecuteMsg::PlayTexas { .. }
+        )
+    }
+
+    /// 是否会新增下注（开局，或多步游戏中的加注 / 跟注 / 全押）
+    pub fn adds_stake(&self) -> bool {
+        match self {
+            ExecuteMsg::PlayBlackjack { action, .. } => !matches!(
+                action,
+                BlackjackAction::Hit | BlackjackAction::Stand | BlackjackAction::Surrender
+            ),
+            ExecuteMsg::PlayOmaha { action, .. } => !matches!(
+                action,
+                OmahaAction::Fold | OmahaAction::Showdown
+            ),
+            ExecuteMsg::PlayTexas { action, .. } => !matches!(
+                action,
+                TexasAction::Check | TexasAction::Fold | TexasAction::Showdown
+            ),
+            ExecuteMsg::PlayFromBalance { game, .. } => game.adds_stake(),
+            other => other.is_play(),
+        }
+    }
+
+    /// 21 点开局附带的边注（其他消息返回 None）
+    pub fn blackjack_side_bets(&self) -> Option<&BlackjackSideBets> {
+        match self {
+            ExecuteMsg::PlayBlackjack { side_bets, .. } => side_bets.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// 下注消息附带的客户端种子（非下注消息返回 None）
+    pub fn client_seed(&self) -> Option<&str> {
+        match self {
+            ExecuteMsg::PlayWar { client_seed }
+            | ExecuteMsg::PlaySlot { client_seed, .. }
+            | ExecuteMsg::GuessNumber { client_seed, .. }
+            | ExecuteMsg::PlayBlackjack { client_seed, .. }
+            | ExecuteMsg::PlayCoinFlip { client_seed, .. }
+            | ExecuteMsg::PlayDice { client_seed, .. }
+            | ExecuteMsg::PlayBaccarat { client_seed, .. }
+            | ExecuteMsg::PlayRoulette { client_seed, .. }
+            | ExecuteMsg::PlayOmaha { client_seed, .. }
+            | ExecuteMsg::PlayTexas { client_seed, .. }
+            | ExecuteMsg::PlaySanGong { client_seed }
+            | ExecuteMsg::PlaySicBo { client_seed, .. }
+            | ExecuteMsg::PlayKeno { client_seed, .. }
+            | ExecuteMsg::PlayScratchCard { client_seed, .. }
+            | ExecuteMsg::PlayBullFight { client_seed } => client_seed.as_deref(),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use cosmwasm_std::Uint128;
+use crate::state::{default_denom, FundsSource};
 
 // ─────────────────────────────────────────────
 // 奥马哈扑克（Omaha Hold'em）数据类型
@@ -67,7 +68,7 @@ pub enum OmahaAction {
     Start,
     /// 加注（追加金额）
     Raise { amount: u128 },
-    /// 跟注（按当前最高注额跟进）
+    /// 跟注（按当前最高注额跟进，附带的资金必须等于需补齐的差额）
     Call,
     /// 弃牌（放弃本局，损失已下注金额）
     Fold,
@@ -95,10 +96,26 @@ pub struct OmahaState {
     pub stage: OmahaStage,
     /// 游戏是否结束
     pub finished: bool,
-    /// 初始 52 张牌的洗牌顺序（按 card_id）存储以便后续阶段续发
-    pub deck: Vec<u8>,
-    /// 当前发到第几张牌（索引）
+    /// 当前发到第几张牌（索引）；牌序不在链上保存，后续阶段按开局下注的种子重新洗出
     pub deck_pos: u8,
+    /// 开局下注的资金来源
+    #[serde(default)]
+    pub source: FundsSource,
+    /// 下注币种
+    #[serde(default = "default_denom")]
+    pub denom: String,
+    /// 为本局预留的最坏情况赔付
+    #[serde(default)]
+    pub reserved: Uint128,
+    /// 开局下注的 ID（结算事件使用）
+    #[serde(default)]
+    pub bet_id: u64,
+    /// 开局区块高度（超时判断）
+    #[serde(default)]
+    pub start_height: u64,
+    /// 开局时间（秒，超时判断）
+    #[serde(default)]
+    pub start_time: u64,
 }
 
 // ─────────────────────────────────────────────
@@ -0,0 +1,74 @@
+use crate::error::ContractError;
+use crate::baccarat::BaccaratBet;
+use crate::blackjack::MAX_PAYOUT_MULTIPLIER as BLACKJACK_MAX_MULTIPLIER;
+use crate::bullfight::{bull_payout_multiplier, BullHandType};
+use crate::dice::{range_multiplier as dice_range_multiplier, DiceGameMode};
+use crate::keno::max_payout_multiplier as keno_max_multiplier;
+use crate::msg::ExecuteMsg;
+use crate::roulette::RouletteBetType;
+use crate::sangong::{payout_multiplier as sangong_payout_multiplier, SanGongHandType};
+use crate::scratch::max_multiplier as scratch_max_multiplier;
+use crate::sicbo::max_payout_multiplier as sicbo_max_multiplier;
+use crate::slot::max_multiplier as slot_max_multiplier;
+
+// ─────────────────────────────────────────────────────────────
+// 单注风险上限（Kelly 式）
+//
+// 部署者设置单笔下注最多可赢取可用资金（锁仓 - 已预留）的比例 max_payout_bps，
+// 每个玩法按自己的最高赔率倍数换算出随资金池变化的最高下注额：
+//   max_bet = free × max_payout_bps / 10_000 / max_multiplier
+// 实际上限取它与该玩法静态上限中的较小者：资金池越小、赔率越高，可下注额越低。
+// ─────────────────────────────────────────────────────────────
+
+/// max_payout_bps 的上限，也是默认值：10_000 = 100%（仅受赔付预留约束）
+pub const MAX_PAYOUT_BPS_CAP: u16 = 10_000;
+
+/// 奥马哈 / 德州最坏情况下的赔付倍率：玩家赢时获得 2× 总下注额
+pub const MULTI_STEP_MAX_MULTIPLIER: u128 = 2;
+
+pub fn default_max_payout_bps() -> u16 {
+    MAX_PAYOUT_BPS_CAP
+}
+
+/// 下注消息可能的最高赔率倍数（含本金、抽水前）
+pub fn max_payout_multiplier(msg: &ExecuteMsg) -> Result<u128, ContractError> {
+    let multiplier = match msg {
+        ExecuteMsg::PlayWar { .. } | ExecuteMsg::PlayCoinFlip { .. } => 2,
+        ExecuteMsg::PlaySlot { mode, .. } => slot_max_multiplier(mode) as u128,
+        ExecuteMsg::GuessNumber { .. } => 10,
+        ExecuteMsg::PlayDice { mode, .. } => match mode {
+            DiceGameMode::GuessSize { .. } => 2,
+            DiceGameMode::ExactNumber { .. } => 6,
+            DiceGameMode::RangeBet { start, end } => dice_range_multiplier(*start, *end)?,
+        },
+        ExecuteMsg::PlayBaccarat { bet_choice, .. } => match bet_choice {
+            BaccaratBet::Tie => 9,
+            _ => 2,
+        },
+        ExecuteMsg::PlayRoulette { bet_type, .. } => match bet_type {
+            RouletteBetType::SingleNumber { .. } => 36,
+            _ => 2,
+        },
+        ExecuteMsg::PlaySanGong { .. } => sangong_payout_multiplier(&SanGongHandType::SanGong),
+        ExecuteMsg::PlaySicBo { bet_type, .. } => sicbo_max_multiplier(bet_type),
+        ExecuteMsg::PlayKeno { picks, .. } => keno_max_multiplier(picks.len() as u8),
+        ExecuteMsg::PlayScratchCard { .. } => scratch_max_multiplier(),
+        ExecuteMsg::PlayBullFight { .. } => bull_payout_multiplier(&BullHandType::WuXiaoNiu),
+        ExecuteMsg::PlayBlackjack { .. } => BLACKJACK_MAX_MULTIPLIER,
+        ExecuteMsg::PlayOmaha { .. } | ExecuteMsg::PlayTexas { .. } => MULTI_STEP_MAX_MULTIPLIER,
+        _ => return Err(ContractError::NotABet),
+    };
+    Ok(multiplier)
+}
+
+/// 单注最多可赢取的金额：可用资金 × max_payout_bps / 10_000
+pub fn max_payout(free: u128, max_payout_bps: u16) -> u128 {
+    free * max_payout_bps as u128 / 10_000
+}
+
+/// 按最高赔率倍数换算的动态最高下注额（倍数为 0 的非法玩法返回 0）
+pub fn dynamic_max_bet(free: u128, max_payout_bps: u16, max_multiplier: u128) -> u128 {
+    max_payout(free, max_payout_bps)
+        .checked_div(max_multiplier)
+        .unwrap_or(0)
+}
@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::RngStream;
+
 // ─────────────────────────────────────────────────────────────
 // 刮刮乐（Scratch Card）数据类型
 //
@@ -49,18 +51,23 @@ pub enum ScratchSymbol {
     Lemon,   // 🍋 最低奖
 }
 
+/// 全部符号，与 SCRATCH_SYMBOL_WEIGHTS 一一对应
+const SCRATCH_SYMBOLS: [ScratchSymbol; 6] = [
+    ScratchSymbol::Diamond,
+    ScratchSymbol::Star,
+    ScratchSymbol::Clover,
+    ScratchSymbol::Bell,
+    ScratchSymbol::Cherry,
+    ScratchSymbol::Lemon,
+];
+
+/// 符号出现权重（合计 100，即百分比）：3% / 6% / 10% / 15% / 22% / 44%
+pub const SCRATCH_SYMBOL_WEIGHTS: [u32; 6] = [3, 6, 10, 15, 22, 44];
+
 impl ScratchSymbol {
-    /// 从随机数映射到符号（加权概率）
-    /// Diamond 出现概率最低，Lemon 最高
-    pub fn from_rand(val: u32) -> Self {
-        match val % 100 {
-            0..=2 => ScratchSymbol::Diamond,     // 3%
-            3..=8 => ScratchSymbol::Star,        // 6%
-            9..=18 => ScratchSymbol::Clover,     // 10%
-            19..=33 => ScratchSymbol::Bell,      // 15%
-            34..=55 => ScratchSymbol::Cherry,    // 22%
-            _ => ScratchSymbol::Lemon,           // 44%
-        }
+    /// 按权重抽取一个符号（Diamond 出现概率最低，Lemon 最高）
+    pub fn draw(rng: &mut RngStream) -> Self {
+        SCRATCH_SYMBOLS[rng.weighted_index(&SCRATCH_SYMBOL_WEIGHTS)]
     }
 
     /// 该符号中奖时的赔率倍数（含本金）
@@ -151,3 +158,7 @@ pub fn evaluate_scratch_card(grid: &[ScratchSymbol; 9]) -> (u128, Vec<(String, S
     (total_multiplier, winning_lines)
 }
 
+/// 单张卡可能的最高赔率倍数（含本金）：9 格全是 Diamond 时 8 条线同时中奖
+pub fn max_multiplier() -> u128 {
+    8 * ScratchSymbol::Diamond.multiplier()
+}
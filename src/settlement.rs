@@ -0,0 +1,90 @@
+use cosmwasm_std::{to_json_string, Addr, Decimal, Event, StdResult};
+use serde::Serialize;
+
+use crate::game_config::GameKind;
+
+// ─────────────────────────────────────────────────────────────
+// 结算事件
+//
+// 每局结束时发出统一的 game_settled 事件（链上显示为 wasm-game_settled），
+// 索引器只需解析这一种结构，不必再按游戏解析各自的属性。
+// outcome 为 JSON 编码的开奖结果（与 VerifyOutcome 返回的 GameOutcome 结构相同），
+// 未开奖而原路退款的下注 outcome 为 "refund"。
+// ─────────────────────────────────────────────────────────────
+
+/// 结算事件类型
+pub const GAME_SETTLED_EVENT: &str = "game_settled";
+
+/// 退款的 outcome
+pub const REFUND_OUTCOME: &str = "refund";
+
+/// 一局的资金结算
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settlement {
+    pub game: GameKind,
+    /// 玩家本局投入的总额
+    pub wager: u128,
+    /// 实际支付给玩家的金额（含本金，已扣抽水）
+    pub payout: u128,
+    /// 庄家从赢利中抽取的金额
+    pub house_rake: u128,
+}
+
+impl Settlement {
+    /// gross_payout 为扣抽水前的应付金额，payout 为实际支付金额
+    pub fn new(game: GameKind, wager: u128, gross_payout: u128, payout: u128) -> Self {
+        Settlement {
+            game,
+            wager,
+            payout,
+            house_rake: gross_payout.saturating_sub(payout),
+        }
+    }
+
+    /// 玩家输掉全部投入
+    pub fn lost(game: GameKind, wager: u128) -> Self {
+        Settlement::new(game, wager, 0, 0)
+    }
+
+    /// 未开奖，原路退还全部投入
+    pub fn refunded(game: GameKind, wager: u128) -> Self {
+        Settlement::new(game, wager, wager, wager)
+    }
+
+    /// 标准化的输赢：按实际赔付与投入比较
+    pub fn result(&self) -> &'static str {
+        match self.payout.cmp(&self.wager) {
+            std::cmp::Ordering::Greater => "win",
+            std::cmp::Ordering::Equal => "push",
+            std::cmp::Ordering::Less => "lose",
+        }
+    }
+
+    /// 实际赔付倍率 payout / wager
+    pub fn multiplier(&self) -> Decimal {
+        if self.wager == 0 {
+            return Decimal::zero();
+        }
+        Decimal::from_ratio(self.payout, self.wager)
+    }
+
+    pub fn event(
+        &self,
+        bet_id: u64,
+        player: &Addr,
+        denom: &str,
+        outcome: &impl Serialize,
+    ) -> StdResult<Event> {
+        Ok(Event::new(GAME_SETTLED_EVENT)
+            .add_attribute("game", self.game.to_string())
+            .add_attribute("bet_id", bet_id.to_string())
+            .add_attribute("player", player)
+            .add_attribute("denom", denom)
+            .add_attribute("wager", self.wager.to_string())
+            .add_attribute("payout", self.payout.to_string())
+            .add_attribute("multiplier", self.multiplier().to_string())
+            .add_attribute("house_rake", self.house_rake.to_string())
+            .add_attribute("result", self.result())
+            .add_attribute("outcome", to_json_string(outcome)?))
+    }
+}
@@ -1,5 +1,6 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use crate::error::ContractError;
 
 // ─────────────────────────────────────────────────────────────
 // 骰宝（Sic Bo / 大小）数据类型
@@ -107,7 +108,7 @@ pub fn calculate_sicbo_payout(bet: &SicBoBetType, result: &SicBoResult) -> (bool
             (won, 2)
         }
         SicBoBetType::Even => {
-            let won = !result.is_triple && result.total % 2 == 0;
+            let won = !result.is_triple && result.total.is_multiple_of(2);
             (won, 2)
         }
 
@@ -167,30 +168,43 @@ pub fn calculate_sicbo_payout(bet: &SicBoBetType, result: &SicBoResult) -> (bool
 }
 
 /// 验证投注类型参数是否合法
-pub fn validate_bet(bet: &SicBoBetType) -> Result<(), &'static str> {
+pub fn validate_bet(bet: &SicBoBetType) -> Result<(), ContractError> {
+    let invalid = |reason| Err(ContractError::InvalidSicBoBet { reason });
     match bet {
-        SicBoBetType::Total { value } => {
-            if *value < 4 || *value > 17 {
-                return Err("Total bet must be between 4 and 17");
-            }
+        SicBoBetType::Total { value } if !(4..=17).contains(value) => {
+            invalid("Total bet must be between 4 and 17")
         }
         SicBoBetType::SpecificTriple { number }
         | SicBoBetType::DoubleBet { number }
-        | SicBoBetType::SingleDie { number } => {
-            if *number < 1 || *number > 6 {
-                return Err("Die number must be between 1 and 6");
-            }
+        | SicBoBetType::SingleDie { number }
+            if !(1..=6).contains(number) =>
+        {
+            invalid("Die number must be between 1 and 6")
         }
         SicBoBetType::Combo { first, second } => {
-            if *first < 1 || *first > 6 || *second < 1 || *second > 6 {
-                return Err("Combo numbers must be between 1 and 6");
+            if !(1..=6).contains(first) || !(1..=6).contains(second) {
+                return invalid("Combo numbers must be between 1 and 6");
             }
             if first == second {
-                return Err("Combo numbers must be different");
+                return invalid("Combo numbers must be different");
             }
+            Ok(())
         }
-        _ => {}
+        _ => Ok(()),
     }
-    Ok(())
 }
 
+
+/// 该投注类型可能的最高赔率倍数（含本金）：遍历全部 216 种开奖结果
+pub fn max_payout_multiplier(bet: &SicBoBetType) -> u128 {
+    (1..=6u8)
+        .flat_map(|d1| (1..=6u8).flat_map(move |d2| (1..=6u8).map(move |d3| (d1, d2, d3))))
+        .filter_map(|(d1, d2, d3)| {
+            match calculate_sicbo_payout(bet, &SicBoResult::new(d1, d2, d3)) {
+                (true, multiplier) => Some(multiplier),
+                (false, _) => None,
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::RngStream;
+
 // ─────────────────────────────────────────────
 // 游戏模式
 // ─────────────────────────────────────────────
@@ -35,21 +37,28 @@ pub enum Symbol {
     Scatter,
 }
 
+/// 全部符号，与 SYMBOL_WEIGHTS 一一对应
+const SYMBOLS: [Symbol; 9] = [
+    Symbol::Apple,
+    Symbol::Orange,
+    Symbol::Cherry,
+    Symbol::Lemon,
+    Symbol::Bell,
+    Symbol::Seven,
+    Symbol::Bar,
+    Symbol::Wild,
+    Symbol::Scatter,
+];
+
+/// 符号出现权重（合计 100，即百分比）
+///   Apple 15% / Orange 12% / Cherry 11% / Lemon 11% / Bell 10%
+///   Seven 10% / Bar 8% / Wild 12%（方便触发）/ Scatter 11%
+pub const SYMBOL_WEIGHTS: [u32; 9] = [15, 12, 11, 11, 10, 10, 8, 12, 11];
+
 impl Symbol {
-    /// 根据随机数映射符号（含 Wild / Scatter 低概率）
-    /// 输入已经是 0..=99 的随机值
-    pub fn from_u8(value: u32) -> Self {
-        match value % 100 {
-            0..=14 => Symbol::Apple,    // 15%
-            15..=26 => Symbol::Orange,  // 12%
-            27..=37 => Symbol::Cherry,  // 11%
-            38..=48 => Symbol::Lemon,   // 11%
-            49..=58 => Symbol::Bell,    // 10%
-            59..=68 => Symbol::Seven,   // 10%
-            69..=76 => Symbol::Bar,     //  8%
-            77..=88 => Symbol::Wild,    // 12%  Wild 出现频率较高方便触发
-            _       => Symbol::Scatter, //  11%
-        }
+    /// 按权重抽取一个符号（含 Wild / Scatter 低概率）
+    pub fn draw(rng: &mut RngStream) -> Self {
+        SYMBOLS[rng.weighted_index(&SYMBOL_WEIGHTS)].clone()
     }
 
     /// 三连或以上的基础倍率（Basic 模式 / Advanced 中每条赢线）
@@ -381,3 +390,23 @@ fn mega_scatter_bonus(count: usize) -> u64 {
     }
 }
 
+
+// ─────────────────────────────────────────────
+// 最高倍率（下注时按此预留赔付敞口）
+// ─────────────────────────────────────────────
+
+/// 各模式总倍率的上界：每条赢线都是最高倍符号的最长连线，再加上最大 Scatter 奖励；
+/// Mega 模式再乘以最高免费旋转倍率（10×），并与 Jackpot 保底倍率取大
+pub fn max_multiplier(mode: &SlotMode) -> u64 {
+    let top = SYMBOLS.iter().map(Symbol::payout_multiplier).max().unwrap_or(0);
+    match mode {
+        SlotMode::Basic => top,
+        SlotMode::Advanced => {
+            top * 5 * paylines().len() as u64 + scatter_bonus_multiplier(usize::MAX)
+        }
+        SlotMode::Mega => {
+            let lines = top * 10 * mega_paylines().len() as u64 + mega_scatter_bonus(usize::MAX);
+            (lines * 10).max(10_000)
+        }
+    }
+}
@@ -1,31 +1,317 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{
+    to_json_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, HexBinary, StdResult,
+    Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
 use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use crate::blackjack::{BlackjackHand, BlackjackRules, BlackjackSideBets, Card};
+use crate::drand::DrandConfig;
+use crate::error::ContractError;
+use crate::expiry::GameTimeout;
+use crate::fairness::{BetRecord, SeedCommitment};
+use crate::game_config::{GameConfig, GameKind};
 use crate::omaha::OmahaState;
+use crate::risk::default_max_payout_bps;
 use crate::texas::TexasState;
+use crate::timelock::{PendingAdminAction, TimelockConfig};
+
+/// 默认下注币种
+pub const DEFAULT_DENOM: &str = "uatom";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
-    pub owner: Addr,           // 合约所有者（部署者）
-    pub locked_amount: u128,   // 锁仓金额
-    pub house_edge_bps: u16,   // 庄家抽水（基点，100 = 1%，最高 1000 = 10%）
+    pub owner: Addr,                // 合约所有者（初始为部署者，拥有全部角色）
+    pub locked_amounts: Vec<Coin>,  // 各币种的锁仓金额
+    pub house_edge_bps: u16,        // 庄家抽水（基点，100 = 1%，最高 1000 = 10%）
+    pub denoms: Vec<DenomConfig>,   // 可下注币种，第一个为主币种（最低锁仓要求按主币种计算）
+    #[serde(default)]
+    pub reserved_amounts: Vec<Coin>, // 未结下注预留的最坏情况赔付（各币种）
+    #[serde(default = "default_max_payout_bps")]
+    pub max_payout_bps: u16,        // 单注最多可赢取可用资金的比例（基点，10_000 = 100%）
+    #[serde(default)]
+    pub paused: bool,               // 紧急暂停：拒绝新下注和加注，已下的注照常结算
+    #[serde(default)]
+    pub pending_owner: Option<Addr>, // 待接受的新所有者（两步转移）
+}
+
+impl State {
+    /// 某币种的锁仓金额
+    pub fn locked(&self, denom: &str) -> u128 {
+        coin_amount(&self.locked_amounts, denom)
+    }
+
+    /// 增加某币种的锁仓
+    pub fn lock(&mut self, denom: &str, amount: u128) {
+        add_coin(&mut self.locked_amounts, denom, amount);
+    }
+
+    /// 减少某币种的锁仓（不足时归零）
+    pub fn unlock(&mut self, denom: &str, amount: u128) {
+        sub_coin(&mut self.locked_amounts, denom, amount);
+    }
+
+    /// 某币种已预留的赔付敞口
+    pub fn reserved(&self, denom: &str) -> u128 {
+        coin_amount(&self.reserved_amounts, denom)
+    }
+
+    /// 某币种的可用资金：锁仓减去已预留的敞口
+    pub fn free(&self, denom: &str) -> u128 {
+        self.locked(denom).saturating_sub(self.reserved(denom))
+    }
+
+    /// 预留赔付敞口，可用资金不足时拒绝
+    pub fn reserve(&mut self, denom: &str, amount: u128) -> Result<(), ContractError> {
+        let free = self.free(denom);
+        if amount > free {
+            return Err(ContractError::InsufficientBankroll {
+                denom: denom.to_string(),
+                available: free,
+                required: amount,
+            });
+        }
+        add_coin(&mut self.reserved_amounts, denom, amount);
+        Ok(())
+    }
+
+    /// 释放赔付敞口（不足时归零）
+    pub fn release(&mut self, denom: &str, amount: u128) {
+        sub_coin(&mut self.reserved_amounts, denom, amount);
+    }
+
+    /// 主币种配置
+    pub fn primary_denom(&self) -> &DenomConfig {
+        &self.denoms[0]
+    }
+
+    /// 可下注币种的配置，不支持的币种返回错误
+    pub fn denom_config(&self, denom: &str) -> Result<&DenomConfig, ContractError> {
+        self.denoms
+            .iter()
+            .find(|d| d.denom == denom)
+            .ok_or_else(|| ContractError::UnsupportedDenom {
+                denom: denom.to_string(),
+            })
+    }
+
+    /// 可选币种参数：为空时使用主币种
+    pub fn denom_or_primary(&self, denom: Option<String>) -> Result<&DenomConfig, ContractError> {
+        match denom {
+            Some(denom) => self.denom_config(&denom),
+            None => Ok(self.primary_denom()),
+        }
+    }
+}
+
+/// 可下注币种（原生币、IBC 币或 CW20 代币）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomConfig {
+    /// 币种，如 "uatom"、"ibc/27394FB0..."；CW20 代币为代币合约地址
+    pub denom: String,
+    /// 下注限额倍数：各游戏以 uatom 计的下注上下限、最低锁仓要求 × bet_scale
+    pub bet_scale: Decimal,
+    /// 是否为 CW20 代币：经由代币合约 Send 下注，赔付通过 Cw20ExecuteMsg::Transfer
+    #[serde(default)]
+    pub cw20: bool,
+}
+
+impl DenomConfig {
+    /// 默认币种 uatom，限额不缩放
+    pub fn uatom() -> Self {
+        DenomConfig {
+            denom: DEFAULT_DENOM.to_string(),
+            bet_scale: Decimal::one(),
+            cw20: false,
+        }
+    }
+
+    /// 把以 uatom 计的限额换算为本币种金额
+    pub fn scale(&self, base_amount: u128) -> u128 {
+        Uint128::from(base_amount).mul_floor(self.bet_scale).u128()
+    }
+
+    /// 向 recipient 转出本币种：原生币为 BankMsg::Send，CW20 代币为 Transfer
+    pub fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        if self.cw20 {
+            let transfer = Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            };
+            return Ok(WasmMsg::Execute {
+                contract_addr: self.denom.clone(),
+                msg: to_json_binary(&transfer)?,
+                funds: vec![],
+            }
+            .into());
+        }
+        Ok(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: self.denom.clone(),
+                amount,
+            }],
+        }
+        .into())
+    }
+}
+
+/// 旧数据没有记录币种时按默认币种处理
+pub fn default_denom() -> String {
+    DEFAULT_DENOM.to_string()
+}
+
+/// 下注方：玩家、下注币种与资金来源，赔付和退款按同一币种、同一来源返还
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Bettor {
+    pub player: Addr,
+    pub denom: String,
+    pub source: FundsSource,
+}
+
+/// 管理角色（所有者拥有全部角色）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// 财务：注入流动性、提款（提款只能转给所有者）
+    Treasurer,
+    /// 风控：抽水、单注上限、玩法配置、调用方限制
+    RiskManager,
+    /// 暂停人：紧急暂停、关闭玩法（恢复只能由所有者操作）
+    Pauser,
+    /// 运维：提交 / 揭示服务端种子
+    Operator,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Treasurer, Role::RiskManager, Role::Pauser, Role::Operator];
+
+    /// 存储键（与 JSON 序列化名一致）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Treasurer => "treasurer",
+            Role::RiskManager => "risk_manager",
+            Role::Pauser => "pauser",
+            Role::Operator => "operator",
+        }
+    }
 }
 
+/// 下注资金来源（赔付按同一来源返还）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FundsSource {
+    /// 随消息附带的资金，赔付通过 BankMsg 发回钱包
+    #[default]
+    Attached,
+    /// 玩家在合约内的余额，赔付记回余额
+    Balance,
+}
+
+// 21 点牌局状态：牌靴不在链上保存，每次发牌按开局下注的种子重新洗出，已发牌数即下一张的位置
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BlackjackState {
-    pub user_cards: Vec<u32>,   // 用户的牌
-    pub dealer_cards: Vec<u32>, // 庄家牌
-    pub bet: Uint128,           // 投注金额
-    pub finished: bool,         // 是否结束
+    pub hands: Vec<BlackjackHand>,  // 用户的各手牌（分牌后多手）
+    pub active_hand: u8,            // 当前操作的手
+    pub dealer_cards: Vec<Card>,    // 庄家牌，第 1 张为暗牌
+    pub bet: Uint128,               // 开局投注金额
+    pub insurance: Uint128,         // 保险金额（未买为 0）
+    pub peeked: bool,               // 庄家是否已看暗牌（明牌为 A 时推迟到第一个操作）
+    pub finished: bool,             // 是否结束
+    #[serde(default)]
+    pub source: FundsSource,        // 开局下注的资金来源
+    #[serde(default = "default_denom")]
+    pub denom: String,              // 下注币种
+    #[serde(default)]
+    pub reserved: Uint128,          // 为本局预留的最坏情况赔付
+    #[serde(default)]
+    pub bet_id: u64,                // 开局下注的 ID（结算事件使用）
+    #[serde(default)]
+    pub start_height: u64,          // 开局区块高度（超时判断）
+    #[serde(default)]
+    pub start_time: u64,            // 开局时间（秒，超时判断）
+    #[serde(default)]
+    pub rules: BlackjackRules,      // 开局时的庄规
+    #[serde(default)]
+    pub side_bets: BlackjackSideBets, // 开局时的边注（发牌后即结算）
+    #[serde(default)]
+    pub side_payout: Uint128,       // 边注的实际赔付（抽水后）
 }
 
-// 目前将 21 点返回的 Response 设置为 BlackjackState 类型.
-pub type BlackjackStateResponse = BlackjackState;
+fn coin_amount(coins: &[Coin], denom: &str) -> u128 {
+    coins
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| c.amount.u128())
+        .unwrap_or(0)
+}
+
+fn add_coin(coins: &mut Vec<Coin>, denom: &str, amount: u128) {
+    match coins.iter_mut().find(|c| c.denom == denom) {
+        Some(coin) => coin.amount += Uint128::from(amount),
+        None => coins.push(Coin {
+            denom: denom.to_string(),
+            amount: Uint128::from(amount),
+        }),
+    }
+}
+
+fn sub_coin(coins: &mut [Coin], denom: &str, amount: u128) {
+    if let Some(coin) = coins.iter_mut().find(|c| c.denom == denom) {
+        coin.amount = coin.amount.saturating_sub(Uint128::from(amount));
+    }
+}
+
+// 21 点查询返回（不包含牌靴）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackStateResponse {
+    pub hands: Vec<BlackjackHand>,
+    pub hand_totals: Vec<u32>,
+    pub active_hand: u8,
+    pub dealer_cards: Vec<Card>, // 未结束时只含庄家明牌
+    pub bet: Uint128,
+    pub insurance: Uint128,
+    pub finished: bool,
+    pub rules: BlackjackRules,
+    pub side_bets: BlackjackSideBets,
+    pub side_payout: Uint128,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct LockedAmountResponse {
+    pub denom: String,
+    pub locked_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExposureResponse {
+    pub denom: String,
     pub locked_amount: Uint128,
+    pub reserved_amount: Uint128, // 未结下注预留的最坏情况赔付
+    pub free_amount: Uint128,     // 可用于新下注 / 加注和部署者提款的资金
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BetLimitsResponse {
+    pub denom: String,
+    pub min_bet: Uint128,
+    pub max_bet: Uint128,        // 静态上限与动态上限中的较小者
+    pub static_max_bet: Uint128, // 该玩法的静态上限（已按 bet_scale 换算）
+    pub max_multiplier: Uint128, // 该玩法最高赔率倍数（含本金）
+    pub max_payout: Uint128,     // 单注最多可赢取额 = 可用资金 × max_payout_bps / 10_000
+    pub max_payout_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameConfigResponse {
+    pub game: GameKind,
+    pub config: GameConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GameConfigsResponse {
+    pub configs: Vec<GameConfigResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -33,6 +319,61 @@ pub struct HouseEdgeResponse {
     pub house_edge_bps: u16,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CallerGuardResponse {
+    pub eoa_only: bool,               // 是否拒绝合约（非白名单）下注
+    pub allowed_contracts: Vec<Addr>, // 集成方合约白名单
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CircuitBreakerResponse {
+    pub paused: bool,
+    pub disabled_games: Vec<GameKind>, // 已关闭的玩法
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OwnershipResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>, // 已提议但尚未接受的新所有者
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RoleMembersResponse {
+    pub role: Role,
+    pub members: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AddressRolesResponse {
+    pub address: Addr,
+    pub is_owner: bool,
+    pub roles: Vec<Role>, // 单独授予的角色（所有者隐含拥有全部角色）
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingAdminActionsResponse {
+    pub actions: Vec<PendingAdminAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ContractVersionResponse {
+    pub contract: String,
+    pub version: String,
+    pub state_version: u32, // 存储结构版本
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerBalanceResponse {
+    pub address: Addr,
+    pub balances: Vec<Coin>, // 合约内各币种余额
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerNonceResponse {
+    pub address: Addr,
+    pub nonce: u64, // 该玩家已下注次数（下一笔下注为 nonce + 1）
+}
+
 // 锁仓状态
 pub const STATE: Item<State> = Item::new("state");
 
@@ -45,3 +386,65 @@ pub const OMAHA_STATE: Map<&Addr, OmahaState> = Map::new("omaha_state");
 // 德州扑克状态
 pub const TEXAS_STATE: Map<&Addr, TexasState> = Map::new("texas_state");
 
+// 服务端种子承诺
+pub const SEED_COMMITMENTS: Map<u64, SeedCommitment> = Map::new("seed_commitments");
+
+// 已提交的承诺数量（最新承诺 ID）
+pub const COMMITMENT_COUNT: Item<u64> = Item::new("commitment_count");
+
+// 当前接受下注的承诺 ID
+pub const ACTIVE_COMMITMENT: Item<u64> = Item::new("active_commitment");
+
+// 下注记录
+pub const BETS: Map<u64, BetRecord> = Map::new("bets");
+
+// 已下注数量（最新下注 ID）
+pub const BET_COUNT: Item<u64> = Item::new("bet_count");
+
+// 仅允许外部账户（EOA）下注：开启时拒绝来自合约的 Play* 消息（白名单除外）
+pub const EOA_ONLY: Item<bool> = Item::new("eoa_only");
+
+// 可以下注的集成方合约白名单
+pub const ALLOWED_CONTRACTS: Map<&Addr, bool> = Map::new("allowed_contracts");
+
+// 玩家在合约内的余额：不计入 locked_amount，庄家无法提取
+pub const PLAYER_BALANCES: Map<(&Addr, &str), Uint128> = Map::new("player_balances");
+
+// 每个币种全部玩家余额之和：合约真实余额中属于玩家的部分，不能用于赔付
+pub const PLAYER_BALANCE_TOTALS: Map<&str, Uint128> = Map::new("player_balance_totals");
+
+// 每个玩家的下注序号（已下注次数），每次下注加 1 并混入本局随机种子
+pub const PLAYER_NONCES: Map<&Addr, u64> = Map::new("player_nonces");
+
+// drand 信标配置（未配置时所有下注使用承诺-揭示）
+pub const DRAND_CONFIG: Item<DrandConfig> = Item::new("drand_config");
+
+// 已验证的 drand 轮次随机数
+pub const DRAND_BEACONS: Map<u64, HexBinary> = Map::new("drand_beacons");
+
+// 等待 drand 开奖的下注：(玩家, 轮次) -> 下注 ID 列表
+pub const PENDING_BEACON_BETS: Map<(&Addr, u64), Vec<u64>> = Map::new("pending_beacon_bets");
+
+// 各玩法配置（未保存的玩法使用 GameKind::default_config）
+pub const GAME_CONFIG: Map<GameKind, GameConfig> = Map::new("game_config");
+
+// 角色成员：(角色, 地址) -> true
+pub const ROLE_MEMBERS: Map<(&str, &Addr), bool> = Map::new("role_members");
+
+// 管理操作时间锁延迟（未设置时不延迟）
+pub const TIMELOCK: Item<TimelockConfig> = Item::new("timelock");
+
+// 待执行的管理操作：ID -> 操作
+pub const ADMIN_ACTIONS: Map<u64, PendingAdminAction> = Map::new("admin_actions");
+
+// 已提议的管理操作数（用于生成操作 ID）
+pub const ADMIN_ACTION_COUNT: Item<u64> = Item::new("admin_action_count");
+
+// 存储结构版本（未记录时为最初部署的版本 1）
+pub const STATE_VERSION: Item<u32> = Item::new("state_version");
+
+// 多步游戏超时（未设置时使用 GameTimeout::default）
+pub const GAME_TIMEOUT: Item<GameTimeout> = Item::new("game_timeout");
+
+// 21 点庄规（未设置时使用 BlackjackRules::default）
+pub const BLACKJACK_RULES: Item<BlackjackRules> = Item::new("blackjack_rules");
@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use cosmwasm_std::Uint128;
+use crate::state::{default_denom, FundsSource};
 
 // ─────────────────────────────────────────────────────────────
 // 德州扑克（Texas Hold'em）数据类型
@@ -68,9 +69,9 @@ pub enum TexasAction {
     Start,
     /// 加注：附带 funds 中追加注额
     Raise { amount: u128 },
-    /// 跟注：补齐当前最高注
+    /// 跟注：补齐当前最高注，附带的资金必须等于差额
     Call,
-    /// 过牌（Check）：当前差额为 0 时无需付款，直接推进阶段
+    /// 过牌（Check）：当前差额为 0 时无需付款（不能附带资金），直接推进阶段
     Check,
     /// 弃牌：放弃本局，损失已下注金额
     Fold,
@@ -84,6 +85,7 @@ pub enum TexasAction {
 // 链上游戏状态
 // ─────────────────────────────────────────────────────────────
 
+/// 牌序不在链上保存，揭示公共牌时按开局下注的种子重新洗出
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TexasState {
     /// 玩家手牌（2 张）
@@ -102,8 +104,24 @@ pub struct TexasState {
     pub finished: bool,
     /// 是否处于全押状态
     pub all_in: bool,
-    /// 洗牌后的 52 张牌 ID 序列
-    pub deck: Vec<u8>,
+    /// 开局下注的资金来源
+    #[serde(default)]
+    pub source: FundsSource,
+    /// 下注币种
+    #[serde(default = "default_denom")]
+    pub denom: String,
+    /// 为本局预留的最坏情况赔付
+    #[serde(default)]
+    pub reserved: Uint128,
+    /// 开局下注的 ID（结算事件使用）
+    #[serde(default)]
+    pub bet_id: u64,
+    /// 开局区块高度（超时判断）
+    #[serde(default)]
+    pub start_height: u64,
+    /// 开局时间（秒，超时判断）
+    #[serde(default)]
+    pub start_time: u64,
 }
 
 // ─────────────────────────────────────────────────────────────
@@ -0,0 +1,119 @@
+use cosmwasm_std::Addr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::blackjack::BlackjackRules;
+use crate::error::ContractError;
+use crate::expiry::GameTimeout;
+use crate::game_config::{GameConfig, GameKind};
+use crate::state::Role;
+
+// ─────────────────────────────────────────────────────────────
+// 管理操作时间锁
+//
+// 提款、修改抽水、修改玩法配置、修改多步游戏超时和修改时间锁本身不再立即生效，而是先进入待执行队列，
+// 经过配置的区块数与秒数后才能执行，期间玩家可以通过查询看到即将发生的变更，
+// 有权限的角色也可以取消。延迟为 0（默认）时立即执行。
+//
+// 降低风险的操作（暂停、关闭玩法、调低单注上限）不受时间锁限制。
+// ─────────────────────────────────────────────────────────────
+
+/// 时间锁延迟：区块数与秒数都满足后才能执行
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TimelockConfig {
+    pub delay_blocks: u64,
+    pub delay_seconds: u64,
+}
+
+impl TimelockConfig {
+    /// 没有延迟：管理操作立即执行
+    pub fn is_zero(&self) -> bool {
+        self.delay_blocks == 0 && self.delay_seconds == 0
+    }
+}
+
+/// 受时间锁约束的管理操作
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    /// 提款到所有者地址（denom 为空时使用主币种）
+    Withdraw { amount: u128, denom: Option<String> },
+    /// 修改全局抽水
+    UpdateHouseEdge { new_house_edge_bps: u16 },
+    /// 修改某玩法的配置
+    UpdateGameConfig { game: GameKind, config: GameConfig },
+    /// 修改时间锁延迟
+    UpdateTimelock { config: TimelockConfig },
+    /// 修改多步游戏超时
+    UpdateGameTimeout { timeout: GameTimeout },
+    /// 修改 21 点庄规
+    UpdateBlackjackRules { rules: BlackjackRules },
+}
+
+impl AdminAction {
+    /// 提议 / 执行该操作所需的角色（None 表示仅限所有者）
+    pub fn role(&self) -> Option<Role> {
+        match self {
+            AdminAction::Withdraw { .. } => Some(Role::Treasurer),
+            AdminAction::UpdateHouseEdge { .. }
+            | AdminAction::UpdateGameConfig { .. }
+            | AdminAction::UpdateGameTimeout { .. }
+            | AdminAction::UpdateBlackjackRules { .. } => Some(Role::RiskManager),
+            AdminAction::UpdateTimelock { .. } => None,
+        }
+    }
+
+    /// 提议时即校验参数，避免排队到期后才失败
+    pub fn validate(&self) -> Result<(), ContractError> {
+        match self {
+            AdminAction::Withdraw { amount, .. } => {
+                if *amount == 0 {
+                    return Err(ContractError::InvalidAmount);
+                }
+            }
+            AdminAction::UpdateHouseEdge { new_house_edge_bps } => {
+                if *new_house_edge_bps > 1000 {
+                    return Err(ContractError::InvalidHouseEdge);
+                }
+            }
+            AdminAction::UpdateGameConfig { config, .. } => config.validate()?,
+            AdminAction::UpdateBlackjackRules { rules } => rules.validate()?,
+            AdminAction::UpdateGameTimeout { timeout } => timeout.validate()?,
+            AdminAction::UpdateTimelock { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// 事件属性中的操作名
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdminAction::Withdraw { .. } => "withdraw",
+            AdminAction::UpdateHouseEdge { .. } => "update_house_edge",
+            AdminAction::UpdateGameConfig { .. } => "update_game_config",
+            AdminAction::UpdateTimelock { .. } => "update_timelock",
+            AdminAction::UpdateGameTimeout { .. } => "update_game_timeout",
+            AdminAction::UpdateBlackjackRules { .. } => "update_blackjack_rules",
+        }
+    }
+}
+
+/// 待执行的管理操作
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdminAction {
+    pub id: u64,
+    pub action: AdminAction,
+    pub proposer: Addr,
+    pub queued_height: u64,
+    pub queued_time: u64,
+    /// 可执行的最早区块高度
+    pub ready_height: u64,
+    /// 可执行的最早时间（秒）
+    pub ready_time: u64,
+}
+
+impl PendingAdminAction {
+    /// 是否已过时间锁
+    pub fn is_ready(&self, height: u64, time_secs: u64) -> bool {
+        height >= self.ready_height && time_secs >= self.ready_time
+    }
+}
@@ -1,25 +1,90 @@
-use cosmwasm_std::{Env, MessageInfo};
 use sha2::{Digest, Sha256};
 
-/// 生成一个随机数
+/// 基于 SHA-256 的确定性随机数流
 ///
-/// 使用盐来生成一个随机数，并返回一个1到100之间的整数
-pub fn generate_random_number(info: &MessageInfo, env: &Env, salt: &[u8]) -> u32 {
-    let mut hasher = Sha256::new();
-    // 用户地址
-    hasher.update(&info.sender.as_bytes());
-    // 区块高度
-    hasher.update(&env.block.height.to_be_bytes());
-    // 区块时间
-    hasher.update(&env.block.time.seconds().to_be_bytes());
-    if let Some(tx) = &env.transaction {
-        // 交易索引
-        hasher.update(tx.index.to_be_bytes());
-    }
-    // 加盐
-    hasher.update(salt);
-    let hash = hasher.finalize();
-    (u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]) % 100) + 1
+/// 同一 (seed, domain) 永远产生相同的序列，任何人都可以复算：
+///   key     = sha256(seed ‖ domain)
+///   block_i = sha256(key ‖ i 的大端 8 字节)
+/// 每个 32 字节块依次切成 4 个 u64 使用。
+///
+/// 区间、洗牌、加权选择都基于拒绝采样的 `below`，没有取模偏差。
+pub struct RngStream {
+    key: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    pos: usize,
+}
+
+impl RngStream {
+    /// 由本局随机种子和用途标识（domain）创建随机数流，不同用途互不相关
+    pub fn new(seed: &[u8], domain: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(domain);
+        RngStream {
+            key: hasher.finalize().into(),
+            counter: 0,
+            block: [0u8; 32],
+            pos: 32,
+        }
+    }
+
+    /// 下一个均匀分布的 u64
+    pub fn next_u64(&mut self) -> u64 {
+        if self.pos + 8 > self.block.len() {
+            let mut hasher = Sha256::new();
+            hasher.update(self.key);
+            hasher.update(self.counter.to_be_bytes());
+            self.block = hasher.finalize().into();
+            self.counter += 1;
+            self.pos = 0;
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.block[self.pos..self.pos + 8]);
+        self.pos += 8;
+        u64::from_be_bytes(bytes)
+    }
+
+    /// [0, n) 内的均匀整数（拒绝采样，n 必须大于 0）
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "RngStream::below requires n > 0");
+        // 2^64 mod n：小于该值的样本会让低位结果多出现一次，直接丢弃重抽
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return x % n;
+            }
+        }
+    }
+
+    /// [low, high] 内的均匀整数
+    pub fn range_inclusive(&mut self, low: u32, high: u32) -> u32 {
+        assert!(low <= high, "RngStream::range_inclusive requires low <= high");
+        low + self.below(u64::from(high - low) + 1) as u32
+    }
+
+    /// Fisher–Yates 均匀洗牌
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// 按权重选择下标：下标 i 被选中的概率 = weights[i] / sum(weights)
+    pub fn weighted_index(&mut self, weights: &[u32]) -> usize {
+        let total: u64 = weights.iter().map(|&w| u64::from(w)).sum();
+        assert!(total > 0, "RngStream::weighted_index requires a positive total weight");
+        let mut r = self.below(total);
+        for (i, &w) in weights.iter().enumerate() {
+            if r < u64::from(w) {
+                return i;
+            }
+            r -= u64::from(w);
+        }
+        unreachable!("r < total")
+    }
 }
 
 /// 计算 blackjack 的 total 值